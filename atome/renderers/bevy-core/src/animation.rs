@@ -0,0 +1,223 @@
+use bevy::{prelude::*, window::RequestRedraw};
+
+use crate::{
+    render_ops::{apply_style, apply_transform},
+    types::*,
+};
+
+#[derive(Clone, Debug)]
+pub struct AtomeRunningAnimation {
+    pub animation: AtomeAnimation,
+    // Clock reading of the first frame after the op landed, so time spent
+    // queued before a frame does not eat into the delay.
+    pub started_at: Option<f64>,
+}
+
+// Running animations in start order; a later one wins where two animate the
+// same property of a node.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct AtomeAnimations {
+    pub running: Vec<AtomeRunningAnimation>,
+}
+
+#[derive(Clone, Debug, Default, Resource)]
+pub struct AtomeAnimationEventQueue {
+    pub events: Vec<AtomeAnimationEvent>,
+}
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AtomeAnimations>()
+            .init_resource::<AtomeAnimationEventQueue>()
+            .add_systems(Update, advance_atome_animations);
+    }
+}
+
+fn queue_event(world: &mut World, animation: &AtomeAnimation, kind: AtomeAnimationEventKind) {
+    world.init_resource::<AtomeAnimationEventQueue>();
+    world
+        .resource_mut::<AtomeAnimationEventQueue>()
+        .events
+        .push(AtomeAnimationEvent {
+            animation_id: animation.id.clone(),
+            target_id: animation.target_id.clone(),
+            kind,
+        });
+}
+
+pub fn apply_animate(world: &mut World, animation: AtomeAnimation) -> Result<(), String> {
+    let animation = animation.normalized()?;
+    if !world
+        .resource::<AtomeEntityTable>()
+        .by_id
+        .contains_key(&animation.target_id)
+    {
+        return Err(format!(
+            "bevy_animation_target_missing:{}",
+            animation.target_id
+        ));
+    }
+    world.init_resource::<AtomeAnimations>();
+    let replaced = {
+        let mut animations = world.resource_mut::<AtomeAnimations>();
+        let replaced = animations
+            .running
+            .iter()
+            .position(|running| running.animation.id == animation.id)
+            .map(|index| animations.running.remove(index));
+        animations.running.push(AtomeRunningAnimation {
+            animation,
+            started_at: None,
+        });
+        replaced
+    };
+    if let Some(replaced) = replaced {
+        queue_event(
+            world,
+            &replaced.animation,
+            AtomeAnimationEventKind::Cancelled,
+        );
+    }
+    Ok(())
+}
+
+fn apply_sample(
+    world: &mut World,
+    target_id: &str,
+    sample: AtomeAnimationSample,
+) -> Result<(), String> {
+    let entity = world
+        .resource::<AtomeEntityTable>()
+        .by_id
+        .get(target_id)
+        .copied()
+        .ok_or_else(|| format!("bevy_atome_entity_missing:{target_id}"))?;
+    if sample.has_transform() {
+        let position = *world
+            .get::<AtomeLogicalPosition>(entity)
+            .ok_or_else(|| format!("bevy_animation_position_missing:{target_id}"))?;
+        let size = *world
+            .get::<AtomeLogicalSize>(entity)
+            .ok_or_else(|| format!("bevy_animation_size_missing:{target_id}"))?;
+        let local = world
+            .get::<AtomeLocalTransform>(entity)
+            .copied()
+            .unwrap_or_default();
+        let clip_rect = world.get::<AtomeClipRect>(entity).and_then(|clip| clip.0);
+        apply_transform(
+            world,
+            AtomeTransformPatch {
+                id: target_id.to_string(),
+                logical_position: sample.position.unwrap_or([position.x, position.y]),
+                logical_size: sample.size.unwrap_or([size.width, size.height]),
+                scale: sample.scale.unwrap_or(local.scale),
+                rotation: sample.rotation.unwrap_or(local.rotation),
+                origin: local.origin,
+                clip_rect,
+            },
+        )?;
+    }
+    if sample.has_style() {
+        apply_style(
+            world,
+            AtomeStylePatch {
+                id: target_id.to_string(),
+                color: sample.color,
+                shadow: None,
+                backdrop: None,
+                selected: None,
+                opacity: sample.opacity,
+                playback_progress: None,
+                filters: sample.filters,
+                transition: None,
+                procedural: sample.procedural,
+                fill: None,
+                blend_mode: None,
+                mask_id: None,
+                mask_mode: None,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Steps every running animation to clock time `now` (seconds), applying
+/// the sampled values through the regular transform and style ops. Finished
+/// animations, and those whose node was despawned, leave an event behind.
+pub fn advance_animations(world: &mut World, now: f64) -> Result<(), String> {
+    let Some(mut animations) = world.get_resource_mut::<AtomeAnimations>() else {
+        return Ok(());
+    };
+    let running = std::mem::take(&mut animations.running);
+    let mut still_running = Vec::with_capacity(running.len());
+    let mut last_error = None;
+    for mut running in running {
+        let started_at = *running.started_at.get_or_insert(now);
+        let animation = &running.animation;
+        if !world
+            .resource::<AtomeEntityTable>()
+            .by_id
+            .contains_key(&animation.target_id)
+        {
+            queue_event(world, animation, AtomeAnimationEventKind::Cancelled);
+            continue;
+        }
+        let elapsed_ms = ((now - started_at) * 1000.0) as f32;
+        let (progress, finished) = match animation.phase_at(elapsed_ms) {
+            AtomeAnimationPhase::Delayed => {
+                still_running.push(running);
+                continue;
+            }
+            AtomeAnimationPhase::Running(progress) => (progress, false),
+            AtomeAnimationPhase::Finished(progress) => (progress, true),
+        };
+        if let Err(error) = apply_sample(world, &animation.target_id, animation.sample(progress)) {
+            last_error = Some(error);
+        }
+        if finished {
+            queue_event(world, animation, AtomeAnimationEventKind::Completed);
+        } else {
+            still_running.push(running);
+        }
+    }
+    // Ops applied while sampling cannot start animations, so nothing was
+    // added behind our back.
+    world.resource_mut::<AtomeAnimations>().running = still_running;
+    last_error.map_or(Ok(()), Err)
+}
+
+pub fn animations_active(world: &World) -> bool {
+    world
+        .get_resource::<AtomeAnimations>()
+        .is_some_and(|animations| !animations.running.is_empty())
+}
+
+pub fn drain_animation_events(world: &mut World) -> Vec<AtomeAnimationEvent> {
+    world
+        .get_resource_mut::<AtomeAnimationEventQueue>()
+        .map(|mut queue| queue.events.drain(..).collect())
+        .unwrap_or_default()
+}
+
+// Keeps requesting frames only while something animates, so reactive update
+// modes fall back to sleeping once the last animation ends. The frame after
+// it ends still runs, letting hosts drain the completion event.
+fn advance_atome_animations(world: &mut World) {
+    if !animations_active(world) {
+        return;
+    }
+    let Some(now) = world
+        .get_resource::<Time>()
+        .map(|time| time.elapsed_secs_f64())
+    else {
+        return;
+    };
+    if let Err(error) = advance_animations(world, now) {
+        world.resource_mut::<AtomeRendererDiagnostics>().last_error = Some(error);
+    }
+    if world.contains_resource::<Messages<RequestRedraw>>() {
+        world.write_message(RequestRedraw);
+    }
+}
@@ -0,0 +1,274 @@
+use bevy::{image::Image, prelude::*};
+
+use crate::{
+    animation::{advance_animations, animations_active, drain_animation_events},
+    apply_despawn, apply_render_op, apply_spawn,
+    types::*,
+};
+
+fn shape_node(id: &str) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: "shape".to_string(),
+        parent_id: None,
+        logical_position: [10.0, 20.0],
+        logical_size: [100.0, 50.0],
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.5, 0.5],
+        layer: 2,
+        opacity: 1.0,
+        corner_radius: 0.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([0.2, 0.4, 0.6, 1.0]),
+        text: None,
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(AtomeEntityTable::default());
+    world.insert_resource(AtomeBevyRendererConfig::empty(640.0, 480.0));
+    world.insert_resource(AtomeRendererDiagnostics::default());
+    world.insert_resource(Assets::<Image>::default());
+    world
+}
+
+fn keyframe(offset: f32) -> AtomeKeyframe {
+    AtomeKeyframe {
+        offset,
+        ..AtomeKeyframe::default()
+    }
+}
+
+fn slide(id: &str, target_id: &str) -> AtomeAnimation {
+    AtomeAnimation {
+        id: id.to_string(),
+        target_id: target_id.to_string(),
+        keyframes: vec![
+            AtomeKeyframe {
+                position: Some([10.0, 20.0]),
+                opacity: Some(1.0),
+                ..keyframe(0.0)
+            },
+            AtomeKeyframe {
+                position: Some([110.0, 20.0]),
+                opacity: Some(0.0),
+                ..keyframe(1.0)
+            },
+        ],
+        duration_ms: 1000.0,
+        delay_ms: 0.0,
+        easing: AtomeEasing::Linear,
+        iterations: 1,
+        yoyo: false,
+    }
+}
+
+fn position(world: &World, entity: Entity) -> [f32; 2] {
+    let position = world.get::<AtomeLogicalPosition>(entity).unwrap();
+    [position.x, position.y]
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-3, "{actual} != {expected}");
+}
+
+#[test]
+fn easing_curves_match_css_endpoints_and_shape() {
+    for easing in [
+        AtomeEasing::Linear,
+        AtomeEasing::Ease,
+        AtomeEasing::EaseIn,
+        AtomeEasing::EaseOut,
+        AtomeEasing::EaseInOut,
+    ] {
+        assert_close(easing.apply(0.0), 0.0);
+        assert_close(easing.apply(1.0), 1.0);
+    }
+    assert_close(AtomeEasing::EaseInOut.apply(0.5), 0.5);
+    assert!(AtomeEasing::EaseIn.apply(0.25) < 0.25);
+    assert!(AtomeEasing::EaseOut.apply(0.25) > 0.25);
+    // CSS `ease-in-out` at 25% progress.
+    assert_close(AtomeEasing::EaseInOut.apply(0.25), 0.129);
+    let steps = AtomeEasing::Steps {
+        count: 4,
+        jump_start: false,
+    };
+    assert_eq!(steps.apply(0.3), 0.25);
+    assert_eq!(steps.apply(1.0), 1.0);
+    let jump_start = AtomeEasing::Steps {
+        count: 4,
+        jump_start: true,
+    };
+    assert_eq!(jump_start.apply(0.0), 0.25);
+}
+
+#[test]
+fn phases_cover_delay_repeats_and_yoyo() {
+    let animation = AtomeAnimation {
+        delay_ms: 100.0,
+        iterations: 2,
+        yoyo: true,
+        ..slide("bounce", "node")
+    };
+    assert_eq!(animation.phase_at(50.0), AtomeAnimationPhase::Delayed);
+    assert_eq!(
+        animation.phase_at(350.0),
+        AtomeAnimationPhase::Running(0.25)
+    );
+    // The second iteration plays backwards and ends where it began.
+    assert_eq!(
+        animation.phase_at(1350.0),
+        AtomeAnimationPhase::Running(0.75)
+    );
+    assert_eq!(
+        animation.phase_at(2100.0),
+        AtomeAnimationPhase::Finished(0.0)
+    );
+    let forever = AtomeAnimation {
+        iterations: 0,
+        ..slide("spin", "node")
+    };
+    assert_eq!(
+        forever.phase_at(1_000_250.0),
+        AtomeAnimationPhase::Running(0.25)
+    );
+    let instant = AtomeAnimation {
+        duration_ms: 0.0,
+        ..slide("snap", "node")
+    };
+    assert_eq!(instant.phase_at(0.0), AtomeAnimationPhase::Finished(1.0));
+}
+
+#[test]
+fn properties_interpolate_between_the_keyframes_that_set_them() {
+    let animation = AtomeAnimation {
+        keyframes: vec![
+            AtomeKeyframe {
+                rotation: Some(90.0),
+                ..keyframe(1.0)
+            },
+            AtomeKeyframe {
+                opacity: Some(0.0),
+                easing: Some(AtomeEasing::Steps {
+                    count: 2,
+                    jump_start: false,
+                }),
+                ..keyframe(0.0)
+            },
+            AtomeKeyframe {
+                opacity: Some(1.0),
+                rotation: Some(0.0),
+                ..keyframe(0.5)
+            },
+        ],
+        ..slide("mixed", "node")
+    }
+    .normalized()
+    .unwrap();
+    assert_eq!(
+        animation
+            .keyframes
+            .iter()
+            .map(|keyframe| keyframe.offset)
+            .collect::<Vec<_>>(),
+        vec![0.0, 0.5, 1.0]
+    );
+    let early = animation.sample(0.2);
+    assert_eq!(early.opacity, Some(0.0), "the segment easing steps");
+    assert_eq!(early.rotation, Some(0.0), "held before its first keyframe");
+    assert_eq!(early.position, None);
+    let late = animation.sample(0.75);
+    assert_eq!(late.opacity, Some(1.0), "held after its last keyframe");
+    assert_eq!(late.rotation, Some(45.0));
+
+    assert_eq!(
+        AtomeAnimation {
+            keyframes: Vec::new(),
+            ..slide("empty", "node")
+        }
+        .normalized()
+        .unwrap_err(),
+        "bevy_animation_invalid:empty"
+    );
+}
+
+#[test]
+fn animate_op_drives_the_node_and_reports_completion() {
+    let mut world = world();
+    let entity = apply_spawn(&mut world, shape_node("card")).unwrap();
+    apply_render_op(&mut world, AtomeRenderOp::Animate(slide("slide", "card"))).unwrap();
+    assert!(animations_active(&world));
+
+    // The first frame after the op starts the clock.
+    advance_animations(&mut world, 10.0).unwrap();
+    assert_eq!(position(&world, entity), [10.0, 20.0]);
+    advance_animations(&mut world, 10.25).unwrap();
+    assert_close(position(&world, entity)[0], 35.0);
+    assert_close(world.get::<AtomeVisualOpacity>(entity).unwrap().0, 0.75);
+    assert!(drain_animation_events(&mut world).is_empty());
+
+    advance_animations(&mut world, 11.5).unwrap();
+    assert_eq!(position(&world, entity), [110.0, 20.0]);
+    assert_eq!(world.get::<AtomeVisualOpacity>(entity).unwrap().0, 0.0);
+    assert!(!animations_active(&world));
+    assert_eq!(
+        drain_animation_events(&mut world),
+        vec![AtomeAnimationEvent {
+            animation_id: "slide".to_string(),
+            target_id: "card".to_string(),
+            kind: AtomeAnimationEventKind::Completed,
+        }]
+    );
+
+    assert_eq!(
+        apply_render_op(
+            &mut world,
+            AtomeRenderOp::Animate(slide("ghost", "missing"))
+        )
+        .unwrap_err(),
+        "bevy_animation_target_missing:missing"
+    );
+}
+
+#[test]
+fn replacing_or_despawning_cancels_running_animations() {
+    let mut world = world();
+    apply_spawn(&mut world, shape_node("card")).unwrap();
+    apply_render_op(&mut world, AtomeRenderOp::Animate(slide("slide", "card"))).unwrap();
+    apply_render_op(&mut world, AtomeRenderOp::Animate(slide("slide", "card"))).unwrap();
+    let cancelled = drain_animation_events(&mut world);
+    assert_eq!(cancelled.len(), 1);
+    assert_eq!(cancelled[0].kind, AtomeAnimationEventKind::Cancelled);
+
+    advance_animations(&mut world, 0.0).unwrap();
+    apply_despawn(&mut world, "card").unwrap();
+    advance_animations(&mut world, 0.5).unwrap();
+    assert!(!animations_active(&world));
+    assert_eq!(
+        drain_animation_events(&mut world)[0].kind,
+        AtomeAnimationEventKind::Cancelled
+    );
+}
@@ -1,4 +1,5 @@
 #import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import bevy_sprite::mesh2d_view_bindings::view
 
 struct BackdropSurfaceUniform {
     size_radius: vec4<f32>,
@@ -24,12 +25,12 @@ fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
     let point = mesh.uv * size;
     let distance = rounded_rect_distance(point, size, material.size_radius.z);
     let edge = 1.0 - smoothstep(-0.6, 0.6, distance);
-    let workspace_size = max(material.workspace_size.xy, vec2(1.0));
+    // The capture camera shares the presentation camera's view, so projecting
+    // the logical world position through it addresses the capture wherever
+    // the scene viewport looks.
+    let clip = view.clip_from_world * mesh.world_position;
     let screen_uv = clamp(
-        vec2(
-            mesh.world_position.x / workspace_size.x + 0.5,
-            0.5 - mesh.world_position.y / workspace_size.y
-        ),
+        vec2(clip.x / clip.w * 0.5 + 0.5, 0.5 - clip.y / clip.w * 0.5),
         vec2(0.0),
         vec2(1.0)
     );
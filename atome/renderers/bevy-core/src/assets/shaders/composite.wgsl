@@ -0,0 +1,68 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import bevy_sprite::mesh2d_view_bindings::view
+
+// mode.x: mask kind (0 none, 1 alpha, 2 luminance).
+// mode.y: output kind (0 premultiplied, 1 lifted for multiply/darken,
+//         2 overlay, 3 difference); see `blend_state_for` in composite.rs.
+struct CompositeUniform {
+    mode: vec4<f32>,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> material: CompositeUniform;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var content_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var content_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var mask_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(4) var mask_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(5) var backdrop_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(6) var backdrop_sampler: sampler;
+
+fn overlay_channel(backdrop: f32, source: f32) -> f32 {
+    if backdrop <= 0.5 {
+        return 2.0 * backdrop * source;
+    }
+    return 1.0 - 2.0 * (1.0 - backdrop) * (1.0 - source);
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    // Group targets are rendered by cameras posed like the one drawing this
+    // quad, so the clip-space position addresses them directly.
+    let clip = view.clip_from_world * mesh.world_position;
+    let screen_uv = clamp(
+        vec2(clip.x / clip.w * 0.5 + 0.5, 0.5 - clip.y / clip.w * 0.5),
+        vec2(0.0),
+        vec2(1.0)
+    );
+    // Targets are cleared to transparent and drawn with alpha blending, which
+    // leaves them premultiplied.
+    var color = textureSample(content_texture, content_sampler, screen_uv);
+    let mask = textureSample(mask_texture, mask_sampler, screen_uv);
+    var coverage = 1.0;
+    if material.mode.x > 1.5 {
+        coverage = clamp(dot(mask.rgb, vec3(0.2126, 0.7152, 0.0722)), 0.0, 1.0);
+    } else if material.mode.x > 0.5 {
+        coverage = mask.a;
+    }
+    color = color * coverage;
+    if color.a < 0.002 { discard; }
+
+    if material.mode.y > 1.5 {
+        let source = color.rgb / color.a;
+        let backdrop = textureSample(backdrop_texture, backdrop_sampler, screen_uv).rgb;
+        var blended = abs(backdrop - source);
+        if material.mode.y < 2.5 {
+            blended = vec3(
+                overlay_channel(backdrop.r, source.r),
+                overlay_channel(backdrop.g, source.g),
+                overlay_channel(backdrop.b, source.b)
+            );
+        }
+        return vec4(blended * color.a, color.a);
+    }
+    if material.mode.y > 0.5 {
+        // Multiply and darken act on the destination directly; lifting by the
+        // uncovered fraction keeps transparent texels neutral.
+        return vec4(color.rgb + vec3(1.0 - color.a), color.a);
+    }
+    return color;
+}
@@ -0,0 +1,95 @@
+#define_import_path atome::fill_gradient
+
+// Packed `AtomeFill` written by `fill::FillGradientUniform::from_fill`.
+// kind: [kind (0 solid, 1 linear, 2 radial, 3 conic), spread (0 pad,
+// 1 repeat, 2 reflect), stop count, 0]. geometry: linear [start, end],
+// radial [center, radius], conic [center, angle in degrees, 0], all in the
+// node's unit box. inverse_x/inverse_y are the rows of the inverse fill
+// transform. Stop colors are straight-alpha sRGB.
+struct FillGradient {
+    kind: vec4<f32>,
+    geometry: vec4<f32>,
+    inverse_x: vec4<f32>,
+    inverse_y: vec4<f32>,
+    offsets: array<vec4<f32>, 2>,
+    colors: array<vec4<f32>, 8>,
+}
+
+fn spread_position(t: f32, spread: f32) -> f32 {
+    if spread > 1.5 {
+        return 1.0 - abs(t - 2.0 * floor(t * 0.5) - 1.0);
+    }
+    if spread > 0.5 {
+        return t - floor(t);
+    }
+    return clamp(t, 0.0, 1.0);
+}
+
+fn premultiply(color: vec4<f32>) -> vec4<f32> {
+    return vec4(color.rgb * color.a, color.a);
+}
+
+// Mirrors `sample_stops` in `types_fill.rs`: stops are walked in order and
+// interpolated in premultiplied sRGB.
+fn sample_stops(gradient: FillGradient, t: f32) -> vec4<f32> {
+    var offsets = gradient.offsets;
+    var colors = gradient.colors;
+    let count = u32(gradient.kind.z);
+    var color = premultiply(colors[0]);
+    for (var index = 1u; index < count; index = index + 1u) {
+        let from_offset = offsets[(index - 1u) / 4u][(index - 1u) % 4u];
+        let to_offset = offsets[index / 4u][index % 4u];
+        if t >= to_offset {
+            color = premultiply(colors[index]);
+            continue;
+        }
+        if t > from_offset {
+            let amount = clamp((t - from_offset) / max(to_offset - from_offset, 0.000001), 0.0, 1.0);
+            color = mix(premultiply(colors[index - 1u]), premultiply(colors[index]), amount);
+        }
+        break;
+    }
+    if color.a <= 0.0 {
+        return vec4(0.0);
+    }
+    return vec4(color.rgb / color.a, color.a);
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + vec3(0.055)) / 1.055, vec3(2.4));
+    return select(high, low, color <= vec3(0.04045));
+}
+
+// Linear-space straight-alpha paint at `unit_point` in the node's unit box
+// for a node of `size` logical pixels. CPU twin: `AtomeFill::color_at`.
+fn fill_color(gradient: FillGradient, unit_point: vec2<f32>, size: vec2<f32>) -> vec4<f32> {
+    let point = vec2(
+        dot(gradient.inverse_x.xyz, vec3(unit_point, 1.0)),
+        dot(gradient.inverse_y.xyz, vec3(unit_point, 1.0)),
+    );
+    let kind = gradient.kind.x;
+    let geometry = gradient.geometry;
+    var t = 0.0;
+    if kind > 2.5 {
+        let delta = (point - geometry.xy) * max(size, vec2(1.0));
+        let turns = (degrees(atan2(delta.x, -delta.y)) - geometry.z) / 360.0;
+        t = turns - floor(turns);
+    } else if kind > 1.5 {
+        if min(geometry.z, geometry.w) <= 0.000001 {
+            t = 1.0;
+        } else {
+            t = spread_position(length((point - geometry.xy) / geometry.zw), gradient.kind.y);
+        }
+    } else if kind > 0.5 {
+        let axis = geometry.zw - geometry.xy;
+        let length_squared = dot(axis, axis);
+        if length_squared <= 1e-12 {
+            t = 1.0;
+        } else {
+            t = spread_position(dot(point - geometry.xy, axis) / length_squared, gradient.kind.y);
+        }
+    }
+    let color = sample_stops(gradient, t);
+    return vec4(srgb_to_linear(color.rgb), color.a);
+}
@@ -0,0 +1,31 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import atome::fill_gradient::{FillGradient, fill_color}
+
+struct PathUniform {
+    stroke: vec4<f32>,
+    // [opacity, node width, node height, 0].
+    geometry: vec4<f32>,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> material: PathUniform;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var<uniform> fill_paint: FillGradient;
+
+// Vertex data comes from `path::path_mesh`: `uv` is the node-local position
+// in logical pixels and `color` is [edge x, edge y, half extent, alpha], the
+// edge being the vertex offset from the solid part of the outline in device
+// pixels and the alpha negated on stroke vertices.
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+#ifdef VERTEX_COLORS
+    let paint = mesh.color;
+#else
+    let paint = vec4<f32>(0.0, 0.0, 1.0, 1.0);
+#endif
+    let coverage = clamp(paint.z - length(paint.xy), 0.0, 1.0) * abs(paint.w);
+    let size = max(material.geometry.yz, vec2(1.0));
+    var color = material.stroke;
+    if paint.w >= 0.0 {
+        color = fill_color(fill_paint, mesh.uv / size, size);
+    }
+    return vec4<f32>(color.rgb, color.a * coverage * material.geometry.x);
+}
@@ -0,0 +1,47 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import atome::fill_gradient::{FillGradient, fill_color}
+
+struct ShapeFillUniform {
+    // [width, height, opacity, 0] in logical pixels.
+    size: vec4<f32>,
+    // [top_left, top_right, bottom_right, bottom_left].
+    radii: vec4<f32>,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> gradient: FillGradient;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var<uniform> shape: ShapeFillUniform;
+
+// Same quadrant selection and half-extent clamp as `texture.rs`.
+fn corner_radius(point: vec2<f32>, size: vec2<f32>, radii: vec4<f32>) -> f32 {
+    let left = point.x < size.x * 0.5;
+    let top = point.y < size.y * 0.5;
+    var radius = radii.z;
+    if left && top {
+        radius = radii.x;
+    } else if top {
+        radius = radii.y;
+    } else if left {
+        radius = radii.w;
+    }
+    return min(max(radius, 0.0), min(size.x, size.y) * 0.5);
+}
+
+fn rounded_rect_distance(point: vec2<f32>, size: vec2<f32>, radius: f32) -> f32 {
+    let delta = abs(point - size * 0.5) - (size * 0.5 - vec2(radius));
+    return length(max(delta, vec2(0.0))) + min(max(delta.x, delta.y), 0.0) - radius;
+}
+
+// `uv` addresses the whole node even when the quad is clipped, so the
+// gradient and the rounded corners stay put while the visible part changes.
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let size = max(shape.size.xy, vec2(1.0));
+    let point = mesh.uv * size;
+    let distance = rounded_rect_distance(point, size, corner_radius(point, size, shape.radii));
+    // One device pixel of analytic coverage at any zoom.
+    let softness = max(fwidth(distance), 0.0001) * 0.5;
+    let coverage = 1.0 - smoothstep(-softness, softness, distance);
+    if coverage < 0.002 { discard; }
+    let color = fill_color(gradient, mesh.uv, size);
+    return vec4(color.rgb, color.a * coverage * shape.size.z);
+}
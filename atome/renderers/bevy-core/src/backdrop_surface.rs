@@ -195,5 +195,6 @@ pub fn refresh_workspace_backdrop_enabled(world: &mut World) -> Result<(), Strin
         .query::<&MeshMaterial2d<BackdropSurfaceMaterial>>()
         .iter(world)
         .count();
-    set_workspace_backdrop_enabled(world, assistant_count + surface_count > 0)
+    let composite_count = crate::composite::composite_backdrop_readers(world);
+    set_workspace_backdrop_enabled(world, assistant_count + surface_count + composite_count > 0)
 }
@@ -2,10 +2,14 @@ use bevy::{image::Image, prelude::*};
 
 use crate::{
     components::*,
+    fill::sync_shape_fill_quad,
+    path::sync_path_mesh,
     render_math::{atome_rect_transform_with_local, depth_for_layer},
+    text_layout::sync_text_layout,
+    viewport::{set_node_visibility, sync_node_culling},
 };
 
-fn intersection(rect: [f32; 4], clip: [f32; 4]) -> Option<[f32; 4]> {
+pub(crate) fn intersection(rect: [f32; 4], clip: [f32; 4]) -> Option<[f32; 4]> {
     let left = rect[0].max(clip[0]);
     let top = rect[1].max(clip[1]);
     let right = (rect[0] + rect[2]).min(clip[0] + clip[2]);
@@ -25,7 +29,10 @@ pub fn apply_entity_clip(world: &mut World, entity: Entity) -> Result<(), String
         .get::<AtomeLocalTransform>(entity)
         .copied()
         .unwrap_or_default();
-    let layer = world.get::<AtomeLayer>(entity).map(|value| value.0).unwrap_or(0);
+    let layer = world
+        .get::<AtomeLayer>(entity)
+        .map(|value| value.0)
+        .unwrap_or(0);
     let (surface_width, surface_height) = {
         let config = world.resource::<AtomeBevyRendererConfig>();
         (config.width, config.height)
@@ -35,15 +42,17 @@ pub fn apply_entity_clip(world: &mut World, entity: Entity) -> Result<(), String
     let clipped_out = clip.is_some() && intersection.is_none();
     let visible = intersection.unwrap_or(original);
 
-    if let Some(mut visibility) = world.get_mut::<Visibility>(entity) {
-        *visibility = if clipped_out {
+    set_node_visibility(
+        world,
+        entity,
+        if clipped_out {
             Visibility::Hidden
         } else {
             Visibility::Visible
-        };
-    }
+        },
+    );
     if clipped_out {
-        return Ok(());
+        return sync_node_culling(world, entity);
     }
 
     let transform = atome_rect_transform_with_local(
@@ -62,6 +71,15 @@ pub fn apply_entity_clip(world: &mut World, entity: Entity) -> Result<(), String
     world
         .entity_mut(entity)
         .insert(GlobalTransform::from(transform));
+    let local_visible = [
+        visible[0] - original[0],
+        visible[1] - original[1],
+        visible[2],
+        visible[3],
+    ];
+    sync_path_mesh(world, entity, local_visible)?;
+    sync_shape_fill_quad(world, entity, local_visible)?;
+    sync_text_layout(world, entity)?;
 
     let source_rect = world
         .get::<AtomeSpriteSourceRect>(entity)
@@ -92,5 +110,5 @@ pub fn apply_entity_clip(world: &mut World, entity: Entity) -> Result<(), String
             ));
         }
     }
-    Ok(())
+    sync_node_culling(world, entity)
 }
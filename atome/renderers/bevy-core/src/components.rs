@@ -1,5 +1,6 @@
 use bevy::{image::Image, prelude::*};
 use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
 
 use crate::types::{
     default_transform_origin, default_transform_scale, normalize_transform_origin,
@@ -31,6 +32,35 @@ pub struct AtomeClipRect(pub Option<[f32; 4]>);
 #[derive(Clone, Copy, Debug, Component)]
 pub struct AtomeSpriteSourceRect(pub Option<Rect>);
 
+/// The image uploaded from the texture the node was last given. Its
+/// `texture_rgba_hash` is only computed when readback first asks for it,
+/// then kept until the node gets another texture.
+#[derive(Clone, Debug, Component)]
+pub struct AtomeTextureHash {
+    pub image: AssetId<Image>,
+    hash: OnceLock<String>,
+}
+
+impl AtomeTextureHash {
+    pub fn new(image: AssetId<Image>) -> Self {
+        Self {
+            image,
+            hash: OnceLock::new(),
+        }
+    }
+
+    /// The content hash, or `None` when the image or its CPU copy is gone.
+    pub fn get(&self, images: &Assets<Image>) -> Option<&str> {
+        if self.hash.get().is_none() {
+            let image = images.get(self.image)?;
+            let rgba = image.data.as_deref()?;
+            let hash = crate::texture::texture_rgba_hash(image.width(), image.height(), rgba);
+            let _ = self.hash.set(hash);
+        }
+        self.hash.get().map(String::as_str)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Component, PartialEq)]
 pub struct AtomeLocalTransform {
     pub scale: [f32; 2],
@@ -61,6 +91,11 @@ impl AtomeLocalTransform {
 #[derive(Clone, Copy, Debug, Component)]
 pub struct AtomeLayer(pub i32);
 
+/// Position of the node in spawn order. Nodes sharing a layer draw in this
+/// order, which entity ids do not follow once they are recycled.
+#[derive(Clone, Copy, Debug, Component, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AtomeSpawnOrder(pub u64);
+
 #[derive(Clone, Copy, Debug, Component)]
 pub struct AtomeVisualColor(pub [f32; 4]);
 
@@ -202,6 +237,7 @@ pub struct AtomeBackdropBlurState {
 #[derive(Clone, Debug, Resource, Default)]
 pub struct AtomeEntityTable {
     pub by_id: HashMap<String, Entity>,
+    pub next_spawn_order: u64,
 }
 
 #[derive(Clone, Debug, Resource, Default)]
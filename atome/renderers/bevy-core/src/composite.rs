@@ -0,0 +1,710 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{load_internal_asset, uuid_handle},
+    camera::{visibility::RenderLayers, ClearColorConfig, RenderTarget},
+    mesh::{Mesh, Mesh2d, MeshVertexBufferLayoutRef},
+    prelude::*,
+    reflect::TypePath,
+    render::{
+        render_resource::{
+            AsBindGroup, BlendComponent, BlendFactor, BlendOperation, BlendState,
+            RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError,
+        },
+        RenderApp,
+    },
+    shader::{Shader, ShaderRef},
+    sprite_render::{AlphaMode2d, Material2d, Material2dKey, Material2dPlugin, MeshMaterial2d},
+};
+
+use crate::{
+    backdrop_surface::refresh_workspace_backdrop_enabled,
+    picking::node_scene_bounds,
+    render_math::{atome_rect_transform, depth_for_layer},
+    types::*,
+    video_external_texture::video_quad_mesh_handle_from_size,
+    viewport::{pose_scene_camera, viewport},
+    workspace_backdrop::{target_image, AtomeWorkspaceBackdrop, FLOWER_PRESENTATION_LAYER},
+};
+
+const COMPOSITE_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("3f6d1b2a-8c47-4e59-b0d3-6a2e9f1c7b84");
+
+/// Render layers from here up are handed out to composite groups, two per
+/// group: one for the members, one for the mask node.
+const COMPOSITE_FIRST_LAYER: usize = 8;
+/// Group cameras render before the workspace capture (order -3), so a
+/// composite quad on the capture layer samples a finished target. Nested
+/// groups step further down so they finish before the group holding them.
+const COMPOSITE_CAMERA_ORDER: isize = -8;
+/// Bounds on parent chains walked when resolving group membership, so a
+/// cyclic `parent_id` cannot hang the renderer.
+pub(crate) const MAX_PARENT_DEPTH: usize = 64;
+
+// What the fragment shader writes, paired with a GPU blend state in
+// `blend_state_for`.
+const OUTPUT_PREMULTIPLIED: f32 = 0.0;
+const OUTPUT_LIFTED: f32 = 1.0;
+const OUTPUT_OVERLAY: f32 = 2.0;
+const OUTPUT_DIFFERENCE: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, ShaderType)]
+pub struct CompositeUniform {
+    /// x: mask kind (0 none, 1 alpha, 2 luminance), y: output kind.
+    pub mode: Vec4,
+}
+
+/// Draws a composite group's offscreen content back into the scene, through
+/// its mask and blend mode.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[bind_group_data(CompositeMaterialKey)]
+pub struct CompositeMaterial {
+    #[uniform(0)]
+    pub uniform: CompositeUniform,
+    #[texture(1)]
+    #[sampler(2)]
+    pub content: Handle<Image>,
+    #[texture(3)]
+    #[sampler(4)]
+    pub mask: Handle<Image>,
+    #[texture(5)]
+    #[sampler(6)]
+    pub backdrop: Handle<Image>,
+    pub blend_mode: AtomeBlendMode,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CompositeMaterialKey {
+    blend_mode: AtomeBlendMode,
+}
+
+impl From<&CompositeMaterial> for CompositeMaterialKey {
+    fn from(material: &CompositeMaterial) -> Self {
+        Self {
+            blend_mode: material.blend_mode,
+        }
+    }
+}
+
+impl Material2d for CompositeMaterial {
+    fn fragment_shader() -> ShaderRef {
+        COMPOSITE_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if let Some(target) = descriptor
+            .fragment
+            .as_mut()
+            .and_then(|fragment| fragment.targets.first_mut())
+            .and_then(Option::as_mut)
+        {
+            target.blend = Some(blend_state_for(key.bind_group_data.blend_mode));
+        }
+        Ok(())
+    }
+}
+
+fn blend_color(
+    src_factor: BlendFactor,
+    dst_factor: BlendFactor,
+    operation: BlendOperation,
+) -> BlendState {
+    BlendState {
+        color: BlendComponent {
+            src_factor,
+            dst_factor,
+            operation,
+        },
+        alpha: BlendComponent::OVER,
+    }
+}
+
+/// The content target holds premultiplied color, so every mode but overlay
+/// and difference maps onto fixed-function blending. Multiply and darken
+/// receive `color + (1 - alpha)` from the shader so transparent texels leave
+/// the destination alone; darken and lighten are exact for opaque texels.
+pub(crate) fn blend_state_for(mode: AtomeBlendMode) -> BlendState {
+    match mode {
+        AtomeBlendMode::Normal | AtomeBlendMode::Overlay | AtomeBlendMode::Difference => {
+            BlendState::PREMULTIPLIED_ALPHA_BLENDING
+        }
+        AtomeBlendMode::Multiply => {
+            blend_color(BlendFactor::Zero, BlendFactor::Src, BlendOperation::Add)
+        }
+        AtomeBlendMode::Screen => blend_color(
+            BlendFactor::One,
+            BlendFactor::OneMinusSrc,
+            BlendOperation::Add,
+        ),
+        AtomeBlendMode::Darken => {
+            blend_color(BlendFactor::One, BlendFactor::One, BlendOperation::Min)
+        }
+        AtomeBlendMode::Lighten => {
+            blend_color(BlendFactor::One, BlendFactor::One, BlendOperation::Max)
+        }
+        AtomeBlendMode::Additive => {
+            blend_color(BlendFactor::One, BlendFactor::One, BlendOperation::Add)
+        }
+    }
+}
+
+fn output_kind(mode: AtomeBlendMode) -> f32 {
+    match mode {
+        AtomeBlendMode::Multiply | AtomeBlendMode::Darken => OUTPUT_LIFTED,
+        AtomeBlendMode::Overlay => OUTPUT_OVERLAY,
+        AtomeBlendMode::Difference => OUTPUT_DIFFERENCE,
+        _ => OUTPUT_PREMULTIPLIED,
+    }
+}
+
+pub struct CompositePlugin;
+
+impl Plugin for CompositePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Assets<Shader>>();
+        load_internal_asset!(
+            app,
+            COMPOSITE_SHADER_HANDLE,
+            "assets/shaders/composite.wgsl",
+            Shader::from_wgsl
+        );
+        app.init_resource::<Assets<CompositeMaterial>>();
+        if app.get_sub_app_mut(RenderApp).is_some() {
+            app.add_plugins(Material2dPlugin::<CompositeMaterial>::default());
+        }
+        // Animations move members without going through an op.
+        app.add_systems(PostUpdate, sync_atome_composites);
+    }
+}
+
+/// Blend mode and mask a node asked for. Only present while one of them
+/// differs from plain drawing.
+#[derive(Clone, Debug, Component, PartialEq)]
+pub struct AtomeNodeComposite {
+    pub blend_mode: AtomeBlendMode,
+    pub mask_id: Option<String>,
+    pub mask_mode: AtomeMaskMode,
+}
+
+impl AtomeNodeComposite {
+    fn is_plain(&self) -> bool {
+        self.blend_mode == AtomeBlendMode::Normal && self.mask_id.is_none()
+    }
+}
+
+/// Offscreen isolation group of a node with a blend mode or mask. The node
+/// and its descendants draw into `content` on `layer`; the mask node draws
+/// into `mask` on `layer + 1`; `quad` composites the result over the node's
+/// bounds.
+#[derive(Clone, Debug, Component)]
+pub struct AtomeCompositeGroup {
+    pub layer: usize,
+    pub content: Handle<Image>,
+    pub content_camera: Entity,
+    pub mask: Handle<Image>,
+    pub mask_camera: Entity,
+    pub quad: Entity,
+    pub material: Handle<CompositeMaterial>,
+    pub bounds: Option<[f32; 4]>,
+}
+
+/// A node moved onto a composite group's layer, either as content or as a
+/// mask, holding the render layers it gets back when it leaves.
+#[derive(Clone, Debug, Component)]
+pub struct AtomeCompositeMember {
+    pub restore: Option<RenderLayers>,
+}
+
+#[derive(Clone, Copy, Debug, Component)]
+pub struct AtomeCompositeCamera;
+
+#[derive(Clone, Copy, Debug, Component)]
+pub struct AtomeCompositeQuad;
+
+/// Group layer allocator. It only exists once a node asked for a blend mode
+/// or mask; until then syncing is free.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct AtomeCompositeLayers {
+    free: Vec<usize>,
+    next: usize,
+}
+
+impl AtomeCompositeLayers {
+    fn allocate(&mut self) -> usize {
+        self.free.pop().unwrap_or_else(|| {
+            let layer = COMPOSITE_FIRST_LAYER + self.next * 2;
+            self.next += 1;
+            layer
+        })
+    }
+}
+
+fn node_composite(node: &AtomeRenderNode) -> AtomeNodeComposite {
+    AtomeNodeComposite {
+        blend_mode: node.blend_mode,
+        mask_id: node.mask_id.clone().filter(|id| !id.trim().is_empty()),
+        mask_mode: node.mask_mode,
+    }
+}
+
+fn set_node_composite(world: &mut World, entity: Entity, composite: AtomeNodeComposite) {
+    if composite.is_plain() {
+        world.entity_mut(entity).remove::<AtomeNodeComposite>();
+        return;
+    }
+    world.init_resource::<AtomeCompositeLayers>();
+    world.entity_mut(entity).insert(composite);
+}
+
+pub fn insert_node_composite(world: &mut World, entity: Entity, node: &AtomeRenderNode) {
+    set_node_composite(world, entity, node_composite(node));
+}
+
+pub fn patch_node_composite(
+    world: &mut World,
+    entity: Entity,
+    blend_mode: Option<AtomeBlendMode>,
+    mask_id: Option<Option<String>>,
+    mask_mode: Option<AtomeMaskMode>,
+) {
+    let mut composite =
+        world
+            .get::<AtomeNodeComposite>(entity)
+            .cloned()
+            .unwrap_or(AtomeNodeComposite {
+                blend_mode: AtomeBlendMode::Normal,
+                mask_id: None,
+                mask_mode: AtomeMaskMode::Alpha,
+            });
+    if let Some(blend_mode) = blend_mode {
+        composite.blend_mode = blend_mode;
+    }
+    if let Some(mask_id) = mask_id {
+        composite.mask_id = mask_id.filter(|id| !id.trim().is_empty());
+    }
+    if let Some(mask_mode) = mask_mode {
+        composite.mask_mode = mask_mode;
+    }
+    set_node_composite(world, entity, composite);
+}
+
+fn spawn_group_camera(world: &mut World, image: Handle<Image>, layer: usize) -> Entity {
+    let surface = {
+        let config = world.resource::<AtomeBevyRendererConfig>();
+        [config.width, config.height]
+    };
+    let mut transform = Transform::default();
+    let projection = pose_scene_camera(viewport(world), surface, &mut transform);
+    world
+        .spawn((
+            Camera2d,
+            Camera {
+                order: COMPOSITE_CAMERA_ORDER,
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                ..default()
+            },
+            Msaa::Off,
+            RenderTarget::Image(image.into()),
+            projection,
+            transform,
+            RenderLayers::layer(layer),
+            AtomeCompositeCamera,
+        ))
+        .id()
+}
+
+fn create_composite_group(world: &mut World, entity: Entity) -> Result<(), String> {
+    let layer = world.resource_mut::<AtomeCompositeLayers>().allocate();
+    let pixel_size = {
+        let config = world.resource::<AtomeBevyRendererConfig>();
+        [config.pixel_width, config.pixel_height]
+    };
+    let (content, mask) = {
+        let mut images = world
+            .get_resource_mut::<Assets<Image>>()
+            .ok_or_else(|| "bevy_image_assets_required".to_string())?;
+        (
+            images.add(target_image(pixel_size[0], pixel_size[1])),
+            images.add(target_image(pixel_size[0], pixel_size[1])),
+        )
+    };
+    let content_camera = spawn_group_camera(world, content.clone(), layer);
+    let mask_camera = spawn_group_camera(world, mask.clone(), layer + 1);
+    let material = world
+        .get_resource_mut::<Assets<CompositeMaterial>>()
+        .ok_or_else(|| "bevy_composite_material_assets_required".to_string())?
+        .add(CompositeMaterial {
+            uniform: CompositeUniform { mode: Vec4::ZERO },
+            content: content.clone(),
+            mask: mask.clone(),
+            backdrop: content.clone(),
+            blend_mode: AtomeBlendMode::Normal,
+        });
+    let quad = world
+        .spawn((
+            Transform::default(),
+            Visibility::Hidden,
+            MeshMaterial2d(material.clone()),
+            AtomeCompositeQuad,
+        ))
+        .id();
+    world.entity_mut(entity).insert(AtomeCompositeGroup {
+        layer,
+        content,
+        content_camera,
+        mask,
+        mask_camera,
+        quad,
+        material,
+        bounds: None,
+    });
+    Ok(())
+}
+
+/// Tears down the group a node owns, if any. Its members go back to their
+/// own layers on the next sync.
+pub fn remove_composite_group(world: &mut World, entity: Entity) {
+    let Some(group) = world
+        .get_entity_mut(entity)
+        .ok()
+        .and_then(|mut entity_mut| entity_mut.take::<AtomeCompositeGroup>())
+    else {
+        return;
+    };
+    for helper in [group.content_camera, group.mask_camera, group.quad] {
+        if world.get_entity(helper).is_ok() {
+            world.despawn(helper);
+        }
+    }
+    if let Some(mut images) = world.get_resource_mut::<Assets<Image>>() {
+        images.remove(&group.content);
+        images.remove(&group.mask);
+    }
+    if let Some(mut materials) = world.get_resource_mut::<Assets<CompositeMaterial>>() {
+        materials.remove(&group.material);
+    }
+    if let Some(mut layers) = world.get_resource_mut::<AtomeCompositeLayers>() {
+        layers.free.push(group.layer);
+    }
+}
+
+// Nearest node at or above `entity` in the parent chain that owns a group.
+fn owning_group(
+    entity: Entity,
+    parents: &HashMap<Entity, Entity>,
+    owners: &HashMap<Entity, usize>,
+) -> Option<Entity> {
+    let mut current = entity;
+    for _ in 0..MAX_PARENT_DEPTH {
+        if owners.contains_key(&current) {
+            return Some(current);
+        }
+        current = *parents.get(&current)?;
+    }
+    None
+}
+
+fn set_member_layers(world: &mut World, entity: Entity, layers: Option<RenderLayers>) {
+    let current = world.get::<RenderLayers>(entity).cloned();
+    match layers {
+        Some(layers) => {
+            if world.get::<AtomeCompositeMember>(entity).is_none() {
+                world.entity_mut(entity).insert(AtomeCompositeMember {
+                    restore: current.clone(),
+                });
+            }
+            if current.as_ref() != Some(&layers) {
+                world.entity_mut(entity).insert(layers);
+            }
+        }
+        None => {
+            let Some(member) = world
+                .get_entity_mut(entity)
+                .ok()
+                .and_then(|mut entity_mut| entity_mut.take::<AtomeCompositeMember>())
+            else {
+                return;
+            };
+            match member.restore {
+                Some(restore) => {
+                    world.entity_mut(entity).insert(restore);
+                }
+                None => {
+                    world.entity_mut(entity).remove::<RenderLayers>();
+                }
+            }
+        }
+    }
+}
+
+/// Brings composite groups in line with the nodes' blend modes and masks:
+/// creates and tears down groups, moves members and mask nodes onto group
+/// layers, and fits each composite quad to its members' bounds.
+pub fn sync_composites(world: &mut World) -> Result<(), String> {
+    if !world.contains_resource::<AtomeCompositeLayers>() {
+        return Ok(());
+    }
+    let requested = world
+        .query::<(Entity, &AtomeNodeComposite)>()
+        .iter(world)
+        .map(|(entity, composite)| (entity, composite.clone()))
+        .collect::<HashMap<_, _>>();
+    let existing = world
+        .query_filtered::<Entity, With<AtomeCompositeGroup>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for entity in &existing {
+        if !requested.contains_key(entity) {
+            remove_composite_group(world, *entity);
+        }
+    }
+    for entity in requested.keys() {
+        if world.get::<AtomeCompositeGroup>(*entity).is_none() {
+            create_composite_group(world, *entity)?;
+        }
+    }
+
+    let (by_id, nodes) = {
+        let table = world.resource::<AtomeEntityTable>();
+        (
+            table.by_id.clone(),
+            table.by_id.values().copied().collect::<Vec<_>>(),
+        )
+    };
+    let parents = nodes
+        .iter()
+        .filter_map(|entity| {
+            let parent_id = world.get::<AtomeParentEntityId>(*entity)?.0.as_ref()?;
+            Some((*entity, *by_id.get(parent_id)?))
+        })
+        .collect::<HashMap<_, _>>();
+    let owners = requested
+        .keys()
+        .filter_map(|entity| Some((*entity, world.get::<AtomeCompositeGroup>(*entity)?.layer)))
+        .collect::<HashMap<_, _>>();
+    let masks = requested
+        .iter()
+        .filter_map(|(owner, composite)| {
+            let source = *by_id.get(composite.mask_id.as_ref()?)?;
+            (source != *owner).then_some((source, *owner))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut bounds = HashMap::<Entity, [f32; 4]>::new();
+    for entity in &nodes {
+        let layer = if let Some(owner) = masks.get(entity) {
+            Some(owners[owner] + 1)
+        } else if let Some(owner) = owning_group(*entity, &parents, &owners) {
+            if let Some(node_bounds) = node_scene_bounds(world, *entity) {
+                bounds
+                    .entry(owner)
+                    .and_modify(|union| *union = rect_union(*union, node_bounds))
+                    .or_insert(node_bounds);
+            }
+            Some(owners[&owner])
+        } else {
+            None
+        };
+        set_member_layers(world, *entity, layer.map(RenderLayers::layer));
+    }
+    for (owner, composite) in &requested {
+        let outer = parents
+            .get(owner)
+            .and_then(|parent| owning_group(*parent, &parents, &owners));
+        let depth = std::iter::successors(outer, |group| {
+            parents
+                .get(group)
+                .and_then(|parent| owning_group(*parent, &parents, &owners))
+        })
+        .take(MAX_PARENT_DEPTH)
+        .count();
+        let base_layers = match outer {
+            Some(outer) => RenderLayers::layer(owners[&outer]),
+            None => world
+                .get::<AtomeCompositeMember>(*owner)
+                .and_then(|member| member.restore.clone())
+                .unwrap_or_default(),
+        };
+        let masked = masks.values().any(|masked_owner| masked_owner == owner);
+        update_composite_group(
+            world,
+            *owner,
+            composite,
+            masked,
+            base_layers,
+            depth,
+            bounds.get(owner).copied(),
+        )?;
+    }
+    refresh_workspace_backdrop_enabled(world)
+}
+
+fn rect_union(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let left = a[0].min(b[0]);
+    let top = a[1].min(b[1]);
+    let right = (a[0] + a[2]).max(b[0] + b[2]);
+    let bottom = (a[1] + a[3]).max(b[1] + b[3]);
+    [left, top, right - left, bottom - top]
+}
+
+fn update_composite_group(
+    world: &mut World,
+    owner: Entity,
+    composite: &AtomeNodeComposite,
+    masked: bool,
+    base_layers: RenderLayers,
+    depth: usize,
+    bounds: Option<[f32; 4]>,
+) -> Result<(), String> {
+    let group = world
+        .get::<AtomeCompositeGroup>(owner)
+        .cloned()
+        .ok_or_else(|| "bevy_composite_group_missing".to_string())?;
+    let blend_mode = composite.blend_mode;
+    // Overlay and difference read what is beneath them from the workspace
+    // capture, so like backdrop surfaces they draw after it, on the
+    // presentation layer.
+    let (backdrop, quad_layers) = if blend_mode.reads_backdrop() {
+        let capture = world
+            .get_resource::<AtomeWorkspaceBackdrop>()
+            .map(|state| state.image.clone())
+            .ok_or_else(|| "bevy_workspace_backdrop_required".to_string())?;
+        (capture, RenderLayers::layer(FLOWER_PRESENTATION_LAYER))
+    } else {
+        (group.content.clone(), base_layers)
+    };
+    let mask_kind = match (masked, composite.mask_mode) {
+        (false, _) => 0.0,
+        (true, AtomeMaskMode::Alpha) => 1.0,
+        (true, AtomeMaskMode::Luminance) => 2.0,
+    };
+    {
+        let mut materials = world
+            .get_resource_mut::<Assets<CompositeMaterial>>()
+            .ok_or_else(|| "bevy_composite_material_assets_required".to_string())?;
+        let mode = Vec4::new(mask_kind, output_kind(blend_mode), 0.0, 0.0);
+        let current = materials
+            .get(&group.material)
+            .ok_or_else(|| "bevy_composite_material_missing".to_string())?;
+        // Fetching an asset mutably re-uploads it; only do so on change.
+        if current.uniform.mode != mode
+            || current.blend_mode != blend_mode
+            || current.backdrop != backdrop
+        {
+            let mut material = materials
+                .get_mut(&group.material)
+                .ok_or_else(|| "bevy_composite_material_missing".to_string())?;
+            material.uniform.mode = mode;
+            material.blend_mode = blend_mode;
+            material.backdrop = backdrop;
+        }
+    }
+    let order = COMPOSITE_CAMERA_ORDER - 2 * depth as isize;
+    for (camera, order, active) in [
+        (group.content_camera, order, true),
+        (group.mask_camera, order - 1, masked),
+    ] {
+        if let Some(mut camera) = world.get_mut::<Camera>(camera) {
+            if camera.order != order || camera.is_active != active {
+                camera.order = order;
+                camera.is_active = active;
+            }
+        }
+    }
+    if world.get::<RenderLayers>(group.quad) != Some(&quad_layers) {
+        world.entity_mut(group.quad).insert(quad_layers);
+    }
+    let Some(bounds) = bounds.filter(|rect| rect[2] > 0.0 && rect[3] > 0.0) else {
+        world.entity_mut(group.quad).insert(Visibility::Hidden);
+        return Ok(());
+    };
+    if group.bounds.map(|rect| [rect[2], rect[3]]) != Some([bounds[2], bounds[3]]) {
+        let mesh = {
+            let mut meshes = world
+                .get_resource_mut::<Assets<Mesh>>()
+                .ok_or_else(|| "bevy_mesh_assets_required".to_string())?;
+            video_quad_mesh_handle_from_size(
+                &mut meshes,
+                [bounds[2], bounds[3]],
+                [0.0, 0.0, 1.0, 1.0],
+            )
+        };
+        world.entity_mut(group.quad).insert(Mesh2d(mesh));
+    }
+    let (surface_width, surface_height) = {
+        let config = world.resource::<AtomeBevyRendererConfig>();
+        (config.width, config.height)
+    };
+    let layer = world
+        .get::<AtomeLayer>(owner)
+        .map(|value| value.0)
+        .unwrap_or(0);
+    let transform = atome_rect_transform(
+        bounds[0],
+        bounds[1],
+        bounds[2],
+        bounds[3],
+        surface_width,
+        surface_height,
+        depth_for_layer(layer),
+    );
+    if world.get::<Transform>(group.quad) != Some(&transform) {
+        world
+            .entity_mut(group.quad)
+            .insert((transform, GlobalTransform::from(transform)));
+    }
+    if group.bounds != Some(bounds) {
+        if let Some(mut stored) = world.get_mut::<AtomeCompositeGroup>(owner) {
+            stored.bounds = Some(bounds);
+        }
+    }
+    if world.get::<Visibility>(group.quad) != Some(&Visibility::Visible) {
+        world.entity_mut(group.quad).insert(Visibility::Visible);
+    }
+    Ok(())
+}
+
+/// Reallocates group targets after a surface resize.
+pub fn resize_composite_targets(world: &mut World, pixel_size: UVec2) -> Result<(), String> {
+    let targets = world
+        .query::<&AtomeCompositeGroup>()
+        .iter(world)
+        .flat_map(|group| [group.content.clone(), group.mask.clone()])
+        .collect::<Vec<_>>();
+    if targets.is_empty() {
+        return Ok(());
+    }
+    let mut images = world
+        .get_resource_mut::<Assets<Image>>()
+        .ok_or_else(|| "bevy_image_assets_required".to_string())?;
+    for image in targets {
+        images
+            .insert(image.id(), target_image(pixel_size.x, pixel_size.y))
+            .map_err(|error| format!("bevy_composite_target_resize_failed:{error}"))?;
+    }
+    Ok(())
+}
+
+/// Number of groups sampling the workspace capture, which keeps it enabled.
+pub fn composite_backdrop_readers(world: &mut World) -> usize {
+    world
+        .query::<&AtomeNodeComposite>()
+        .iter(world)
+        .filter(|composite| composite.blend_mode.reads_backdrop())
+        .count()
+}
+
+fn sync_atome_composites(world: &mut World) {
+    if let Err(error) = sync_composites(world) {
+        world.resource_mut::<AtomeRendererDiagnostics>().last_error = Some(error);
+    }
+}
@@ -0,0 +1,337 @@
+use bevy::{
+    camera::{visibility::RenderLayers, RenderTarget},
+    image::Image,
+    mesh::Mesh,
+    prelude::*,
+    render::render_resource::{BlendFactor, BlendState},
+};
+
+use crate::{
+    apply_render_op,
+    composite::{blend_state_for, AtomeCompositeGroup, AtomeCompositeMember, CompositeMaterial},
+    render_math::{atome_rect_transform, depth_for_layer},
+    types::*,
+};
+
+fn shape_node(id: &str, position: [f32; 2], size: [f32; 2]) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: "shape".to_string(),
+        parent_id: None,
+        logical_position: position,
+        logical_size: size,
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.5, 0.5],
+        layer: 2,
+        opacity: 1.0,
+        corner_radius: 0.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([0.8, 0.3, 0.1, 1.0]),
+        text: None,
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn style_patch(id: &str) -> AtomeStylePatch {
+    AtomeStylePatch {
+        id: id.to_string(),
+        color: None,
+        shadow: None,
+        backdrop: None,
+        selected: None,
+        opacity: None,
+        playback_progress: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
+    }
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(AtomeEntityTable::default());
+    world.insert_resource(AtomeBevyRendererConfig::empty(640.0, 480.0));
+    world.insert_resource(AtomeRendererDiagnostics::default());
+    world.insert_resource(Assets::<Image>::default());
+    world.insert_resource(Assets::<Mesh>::default());
+    world.insert_resource(Assets::<CompositeMaterial>::default());
+    world
+}
+
+fn spawn(world: &mut World, node: AtomeRenderNode) {
+    apply_render_op(world, AtomeRenderOp::Spawn(node)).unwrap();
+}
+
+fn entity(world: &World, id: &str) -> Entity {
+    world.resource::<AtomeEntityTable>().by_id[id]
+}
+
+fn layers(world: &World, id: &str) -> Option<RenderLayers> {
+    world.get::<RenderLayers>(entity(world, id)).cloned()
+}
+
+fn group(world: &World, id: &str) -> AtomeCompositeGroup {
+    world
+        .get::<AtomeCompositeGroup>(entity(world, id))
+        .cloned()
+        .unwrap()
+}
+
+fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+    assert!(
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| (actual - expected).abs() < 1e-5),
+        "{actual:?} != {expected:?}"
+    );
+}
+
+#[test]
+fn blend_modes_follow_the_css_formulas() {
+    let backdrop = [0.25, 0.5, 0.75];
+    let source = [0.5, 0.5, 0.5, 1.0];
+    assert_close(
+        AtomeBlendMode::Normal.composite(backdrop, source),
+        [0.5, 0.5, 0.5],
+    );
+    assert_close(
+        AtomeBlendMode::Multiply.composite(backdrop, source),
+        [0.125, 0.25, 0.375],
+    );
+    assert_close(
+        AtomeBlendMode::Screen.composite(backdrop, source),
+        [0.625, 0.75, 0.875],
+    );
+    assert_close(
+        AtomeBlendMode::Overlay.composite(backdrop, source),
+        [0.25, 0.5, 0.75],
+    );
+    assert_close(
+        AtomeBlendMode::Difference.composite(backdrop, source),
+        [0.25, 0.0, 0.25],
+    );
+    assert_close(
+        AtomeBlendMode::Darken.composite(backdrop, source),
+        [0.25, 0.5, 0.5],
+    );
+    assert_close(
+        AtomeBlendMode::Lighten.composite(backdrop, source),
+        [0.5, 0.5, 0.75],
+    );
+    assert_close(
+        AtomeBlendMode::Additive.composite(backdrop, source),
+        [0.75, 1.0, 1.0],
+    );
+    // Half coverage mixes halfway back to the backdrop.
+    assert_close(
+        AtomeBlendMode::Multiply.composite(backdrop, [0.5, 0.5, 0.5, 0.5]),
+        [0.1875, 0.375, 0.5625],
+    );
+
+    assert_eq!(AtomeMaskMode::Alpha.coverage([0.0, 0.0, 0.0, 0.5]), 0.5);
+    assert_eq!(AtomeMaskMode::Luminance.coverage([0.0, 0.0, 0.0, 1.0]), 0.0);
+    assert!((AtomeMaskMode::Luminance.coverage([1.0, 1.0, 1.0, 0.5]) - 0.5).abs() < 1e-5);
+
+    assert_eq!(
+        blend_state_for(AtomeBlendMode::Normal),
+        BlendState::PREMULTIPLIED_ALPHA_BLENDING
+    );
+    let multiply = blend_state_for(AtomeBlendMode::Multiply);
+    assert_eq!(multiply.color.src_factor, BlendFactor::Zero);
+    assert_eq!(multiply.color.dst_factor, BlendFactor::Src);
+}
+
+#[test]
+fn blend_and_mask_move_the_node_and_children_into_an_offscreen_group() {
+    let mut world = world();
+    spawn(
+        &mut world,
+        shape_node("card", [100.0, 100.0], [200.0, 100.0]),
+    );
+    spawn(
+        &mut world,
+        AtomeRenderNode {
+            parent_id: Some("card".to_string()),
+            ..shape_node("badge", [250.0, 80.0], [40.0, 40.0])
+        },
+    );
+    spawn(
+        &mut world,
+        shape_node("stencil", [120.0, 120.0], [50.0, 50.0]),
+    );
+    spawn(
+        &mut world,
+        shape_node("bystander", [0.0, 0.0], [10.0, 10.0]),
+    );
+
+    apply_render_op(
+        &mut world,
+        AtomeRenderOp::Style(AtomeStylePatch {
+            blend_mode: Some(AtomeBlendMode::Multiply),
+            mask_id: Some(Some("stencil".to_string())),
+            mask_mode: Some(AtomeMaskMode::Luminance),
+            ..style_patch("card")
+        }),
+    )
+    .unwrap();
+
+    let card = group(&world, "card");
+    assert_eq!(
+        layers(&world, "card"),
+        Some(RenderLayers::layer(card.layer))
+    );
+    assert_eq!(
+        layers(&world, "badge"),
+        Some(RenderLayers::layer(card.layer))
+    );
+    assert_eq!(
+        layers(&world, "stencil"),
+        Some(RenderLayers::layer(card.layer + 1))
+    );
+    assert_eq!(layers(&world, "bystander"), None);
+
+    for (camera, image) in [
+        (card.content_camera, &card.content),
+        (card.mask_camera, &card.mask),
+    ] {
+        let Some(RenderTarget::Image(target)) = world.get::<RenderTarget>(camera) else {
+            panic!("group cameras render offscreen");
+        };
+        assert_eq!(&target.handle, image);
+        assert!(world.get::<Camera>(camera).unwrap().is_active);
+        assert!(world.resource::<Assets<Image>>().contains(image.id()));
+    }
+    let material = world
+        .resource::<Assets<CompositeMaterial>>()
+        .get(&card.material)
+        .unwrap()
+        .clone();
+    assert_eq!(material.blend_mode, AtomeBlendMode::Multiply);
+    assert_eq!(material.uniform.mode, Vec4::new(2.0, 1.0, 0.0, 0.0));
+
+    // The quad covers the card and its badge, not the consumed mask.
+    assert_eq!(card.bounds, Some([100.0, 80.0, 200.0, 120.0]));
+    assert_eq!(
+        world.get::<Transform>(card.quad),
+        Some(&atome_rect_transform(
+            100.0,
+            80.0,
+            200.0,
+            120.0,
+            640.0,
+            480.0,
+            depth_for_layer(2)
+        ))
+    );
+    assert_eq!(
+        world.get::<Visibility>(card.quad),
+        Some(&Visibility::Visible)
+    );
+    assert_eq!(
+        world.get::<RenderLayers>(card.quad),
+        Some(&RenderLayers::default())
+    );
+
+    // Back to plain drawing restores every layer and frees the group.
+    apply_render_op(
+        &mut world,
+        AtomeRenderOp::Style(AtomeStylePatch {
+            blend_mode: Some(AtomeBlendMode::Normal),
+            mask_id: Some(None),
+            ..style_patch("card")
+        }),
+    )
+    .unwrap();
+    for id in ["card", "badge", "stencil"] {
+        assert_eq!(layers(&world, id), None, "{id}");
+        assert!(world
+            .get::<AtomeCompositeMember>(entity(&world, id))
+            .is_none());
+    }
+    assert!(world
+        .get::<AtomeCompositeGroup>(entity(&world, "card"))
+        .is_none());
+    for helper in [card.content_camera, card.mask_camera, card.quad] {
+        assert!(world.get_entity(helper).is_err());
+    }
+    assert!(!world
+        .resource::<Assets<Image>>()
+        .contains(card.content.id()));
+}
+
+#[test]
+fn despawning_a_group_owner_hands_its_children_back() {
+    let mut world = world();
+    spawn(
+        &mut world,
+        AtomeRenderNode {
+            blend_mode: AtomeBlendMode::Screen,
+            presentation: true,
+            ..shape_node("glow", [10.0, 10.0], [40.0, 40.0])
+        },
+    );
+    spawn(
+        &mut world,
+        AtomeRenderNode {
+            parent_id: Some("glow".to_string()),
+            ..shape_node("spark", [20.0, 20.0], [5.0, 5.0])
+        },
+    );
+    let glow = group(&world, "glow");
+    assert_eq!(
+        layers(&world, "spark"),
+        Some(RenderLayers::layer(glow.layer))
+    );
+    // The quad draws wherever the owner used to.
+    assert_eq!(
+        world.get::<RenderLayers>(glow.quad),
+        Some(&RenderLayers::layer(
+            crate::workspace_backdrop::FLOWER_PRESENTATION_LAYER
+        ))
+    );
+
+    apply_render_op(&mut world, AtomeRenderOp::Despawn("glow".to_string())).unwrap();
+    assert_eq!(layers(&world, "spark"), None);
+    assert!(world.get_entity(glow.quad).is_err());
+}
+
+#[test]
+fn overlay_needs_the_workspace_capture() {
+    let mut world = world();
+    spawn(&mut world, shape_node("tint", [0.0, 0.0], [50.0, 50.0]));
+    let error = apply_render_op(
+        &mut world,
+        AtomeRenderOp::Style(AtomeStylePatch {
+            blend_mode: Some(AtomeBlendMode::Overlay),
+            ..style_patch("tint")
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(error, "bevy_workspace_backdrop_required");
+}
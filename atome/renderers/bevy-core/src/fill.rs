@@ -0,0 +1,377 @@
+use bevy::{
+    asset::{load_internal_asset, uuid_handle},
+    prelude::*,
+    reflect::TypePath,
+    render::{
+        render_resource::{AsBindGroup, ShaderType},
+        RenderApp,
+    },
+    shader::{Shader, ShaderRef},
+    sprite_render::{AlphaMode2d, Material2d, Material2dPlugin},
+};
+
+use crate::{
+    render_math::color_from_rgba,
+    texture::{cached_image_handle_from_rounded_rect_mask, corner_radii_are_zero},
+    types::{
+        normalize_opacity, AtomeCornerRadius, AtomeEntityId, AtomeFill, AtomeGradientSpread,
+        AtomeLogicalSize, AtomeRenderKind, AtomeSpriteSourceRect, AtomeVisualColor,
+        AtomeVisualOpacity, MAX_FILL_STOPS,
+    },
+    video_external_texture::video_quad_mesh_handle_from_size,
+};
+
+const FILL_GRADIENT_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("5b0f7f8e-3c1a-4f0e-9d57-2a61c4e8b9d3");
+const SHAPE_FILL_SHADER_HANDLE: Handle<Shader> =
+    uuid_handle!("9e4c2d71-6a8b-4b35-a0f2-7c13d5e94a68");
+
+/// Packed `AtomeFill` shared by every material that paints one; see
+/// `assets/shaders/fill_gradient.wgsl` for the field layout.
+#[derive(Clone, Copy, Debug, ShaderType)]
+pub struct FillGradientUniform {
+    pub kind: Vec4,
+    pub geometry: Vec4,
+    pub inverse_x: Vec4,
+    pub inverse_y: Vec4,
+    pub offsets: [Vec4; 2],
+    pub colors: [Vec4; MAX_FILL_STOPS],
+}
+
+impl FillGradientUniform {
+    pub fn solid(color: [f32; 4]) -> Self {
+        Self::from_fill(&AtomeFill::Solid { color })
+    }
+
+    /// Expects a `normalized` fill. Colors stay in sRGB; the shader
+    /// interpolates them before converting to linear, as `color_at` does.
+    pub fn from_fill(fill: &AtomeFill) -> Self {
+        let (kind, spread, geometry) = match fill {
+            AtomeFill::Solid { .. } => (0.0, AtomeGradientSpread::Pad, Vec4::ZERO),
+            AtomeFill::Linear {
+                start, end, spread, ..
+            } => (1.0, *spread, Vec4::new(start[0], start[1], end[0], end[1])),
+            AtomeFill::Radial {
+                center,
+                radius,
+                spread,
+                ..
+            } => (
+                2.0,
+                *spread,
+                Vec4::new(center[0], center[1], radius[0], radius[1]),
+            ),
+            AtomeFill::Conic { center, angle, .. } => (
+                3.0,
+                AtomeGradientSpread::Pad,
+                Vec4::new(center[0], center[1], *angle, 0.0),
+            ),
+        };
+        let spread = match spread {
+            AtomeGradientSpread::Pad => 0.0,
+            AtomeGradientSpread::Repeat => 1.0,
+            AtomeGradientSpread::Reflect => 2.0,
+        };
+        let mut offsets = [0.0; MAX_FILL_STOPS];
+        let mut colors = [Vec4::ZERO; MAX_FILL_STOPS];
+        let stop_count = match fill {
+            AtomeFill::Solid { color } => {
+                colors[0] = Vec4::from_array(*color);
+                1
+            }
+            _ => {
+                for (index, stop) in fill.stops().iter().take(MAX_FILL_STOPS).enumerate() {
+                    offsets[index] = stop.offset;
+                    colors[index] = Vec4::from_array(stop.color);
+                }
+                fill.stops().len().min(MAX_FILL_STOPS)
+            }
+        };
+        let [a, b, c, d, e, f] = fill
+            .transform()
+            .and_then(crate::types::invert_fill_transform)
+            .unwrap_or([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        Self {
+            kind: Vec4::new(kind, spread, stop_count as f32, 0.0),
+            geometry,
+            inverse_x: Vec4::new(a, c, e, 0.0),
+            inverse_y: Vec4::new(b, d, f, 0.0),
+            offsets: [
+                Vec4::new(offsets[0], offsets[1], offsets[2], offsets[3]),
+                Vec4::new(offsets[4], offsets[5], offsets[6], offsets[7]),
+            ],
+            colors,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ShaderType)]
+pub struct ShapeFillUniform {
+    pub size: Vec4,
+    pub radii: Vec4,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct ShapeFillMaterial {
+    #[uniform(0)]
+    pub gradient: FillGradientUniform,
+    #[uniform(1)]
+    pub shape: ShapeFillUniform,
+}
+
+impl Material2d for ShapeFillMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SHAPE_FILL_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+/// Loads the shared `atome::fill_gradient` shader module, so it must be added
+/// before any material importing it (`PathPlugin`).
+pub struct FillPlugin;
+
+impl Plugin for FillPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Assets<Shader>>();
+        load_internal_asset!(
+            app,
+            FILL_GRADIENT_SHADER_HANDLE,
+            "assets/shaders/fill_gradient.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SHAPE_FILL_SHADER_HANDLE,
+            "assets/shaders/shape_fill.wgsl",
+            Shader::from_wgsl
+        );
+        app.init_resource::<Assets<ShapeFillMaterial>>();
+        if app.get_sub_app_mut(RenderApp).is_some() {
+            app.add_plugins(Material2dPlugin::<ShapeFillMaterial>::default());
+        }
+    }
+}
+
+/// Normalized interior paint of a `shape` or `path` entity.
+#[derive(Clone, Debug, Component)]
+pub struct AtomeNodeFill(pub AtomeFill);
+
+/// Node size and node-local visible rect the shape fill quad was built for.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct AtomeShapeFillQuad {
+    pub size: [f32; 2],
+    pub visible_rect: [f32; 4],
+}
+
+pub fn normalized_fill_for(fill: &AtomeFill, id: &str) -> Result<AtomeFill, String> {
+    fill.normalized()
+        .ok_or_else(|| format!("bevy_fill_invalid:{id}"))
+}
+
+fn shape_fill_uniform(size: [f32; 2], radii: [f32; 4], opacity: f32) -> ShapeFillUniform {
+    ShapeFillUniform {
+        size: Vec4::new(
+            size[0].max(1.0),
+            size[1].max(1.0),
+            normalize_opacity(opacity),
+            0.0,
+        ),
+        radii: Vec4::from_array(radii.map(|radius| radius.max(0.0))),
+    }
+}
+
+fn entity_size(world: &World, entity: Entity) -> Result<[f32; 2], String> {
+    world
+        .get::<AtomeLogicalSize>(entity)
+        .map(|size| [size.width, size.height])
+        .ok_or_else(|| "bevy_fill_size_missing".to_string())
+}
+
+fn entity_opacity(world: &World, entity: Entity) -> f32 {
+    world
+        .get::<AtomeVisualOpacity>(entity)
+        .map(|value| value.0)
+        .unwrap_or(1.0)
+}
+
+fn entity_radii(world: &World, entity: Entity) -> [f32; 4] {
+    world
+        .get::<AtomeCornerRadius>(entity)
+        .map(|value| value.0)
+        .unwrap_or([0.0; 4])
+}
+
+/// Draws a `shape` entity with a fill material instead of its sprite. The
+/// quad itself is built by the caller's next `apply_entity_clip`.
+pub fn insert_shape_fill(
+    world: &mut World,
+    entity: Entity,
+    fill: AtomeFill,
+    radii: [f32; 4],
+    opacity: f32,
+) -> Result<(), String> {
+    let size = entity_size(world, entity)?;
+    let material = {
+        let mut materials = world
+            .get_resource_mut::<Assets<ShapeFillMaterial>>()
+            .ok_or_else(|| "bevy_fill_assets_required".to_string())?;
+        materials.add(ShapeFillMaterial {
+            gradient: FillGradientUniform::from_fill(&fill),
+            shape: shape_fill_uniform(size, radii, opacity),
+        })
+    };
+    world.entity_mut(entity).remove::<Sprite>().insert((
+        AtomeNodeFill(fill),
+        AtomeShapeFillQuad {
+            size: [0.0, 0.0],
+            visible_rect: [0.0; 4],
+        },
+        MeshMaterial2d(material),
+    ));
+    Ok(())
+}
+
+/// Rebuilds the shape fill quad when the node size or its visible (clipped)
+/// part changed. `visible_rect` is node-local; the quad's UVs keep addressing
+/// the whole node so the gradient does not shift under a clip.
+pub fn sync_shape_fill_quad(
+    world: &mut World,
+    entity: Entity,
+    visible_rect: [f32; 4],
+) -> Result<(), String> {
+    let Some(quad) = world.get::<AtomeShapeFillQuad>(entity).copied() else {
+        return Ok(());
+    };
+    let size = entity_size(world, entity)?;
+    if quad.size == size && quad.visible_rect == visible_rect {
+        return Ok(());
+    }
+    let width = size[0].max(1.0);
+    let height = size[1].max(1.0);
+    let uv_rect = [
+        visible_rect[0] / width,
+        visible_rect[1] / height,
+        visible_rect[2] / width,
+        visible_rect[3] / height,
+    ];
+    let mesh = {
+        let mut meshes = world
+            .get_resource_mut::<Assets<Mesh>>()
+            .ok_or_else(|| "bevy_mesh_assets_required".to_string())?;
+        video_quad_mesh_handle_from_size(&mut meshes, [visible_rect[2], visible_rect[3]], uv_rect)
+    };
+    world
+        .entity_mut(entity)
+        .insert((Mesh2d(mesh), AtomeShapeFillQuad { size, visible_rect }));
+    sync_shape_fill_material(world, entity)
+}
+
+/// Pushes the fill, node size, corner radii and opacity into the material.
+pub fn sync_shape_fill_material(world: &mut World, entity: Entity) -> Result<(), String> {
+    let Some(handle) = world
+        .get::<MeshMaterial2d<ShapeFillMaterial>>(entity)
+        .map(|material| material.0.clone())
+    else {
+        return Ok(());
+    };
+    let fill = world
+        .get::<AtomeNodeFill>(entity)
+        .map(|value| value.0.clone())
+        .ok_or_else(|| "bevy_fill_component_missing".to_string())?;
+    let uniform = shape_fill_uniform(
+        entity_size(world, entity)?,
+        entity_radii(world, entity),
+        entity_opacity(world, entity),
+    );
+    let mut materials = world
+        .get_resource_mut::<Assets<ShapeFillMaterial>>()
+        .ok_or_else(|| "bevy_fill_assets_required".to_string())?;
+    let mut material = materials
+        .get_mut(&handle)
+        .ok_or_else(|| "bevy_fill_material_missing".to_string())?;
+    material.gradient = FillGradientUniform::from_fill(&fill);
+    material.shape = uniform;
+    Ok(())
+}
+
+/// Returns a filled `shape` entity to the flat-color sprite it spawns with
+/// when no fill is set.
+fn remove_shape_fill(world: &mut World, entity: Entity) -> Result<(), String> {
+    let size = entity_size(world, entity)?;
+    let radii = entity_radii(world, entity);
+    let color = world
+        .get::<AtomeVisualColor>(entity)
+        .map(|value| value.0)
+        .unwrap_or([1.0, 1.0, 1.0, 1.0]);
+    let mut visible_color = color;
+    visible_color[3] =
+        visible_color[3].clamp(0.0, 1.0) * normalize_opacity(entity_opacity(world, entity));
+    let id = world
+        .get::<AtomeEntityId>(entity)
+        .map(|id| id.0.clone())
+        .unwrap_or_default();
+    let sprite = if corner_radii_are_zero(radii) {
+        Sprite::from_color(color_from_rgba(visible_color), Vec2::new(size[0], size[1]))
+    } else {
+        let handle =
+            cached_image_handle_from_rounded_rect_mask(world, size[0], size[1], radii, &id)?;
+        let mut sprite = Sprite::from_image(handle);
+        sprite.custom_size = Some(Vec2::new(size[0], size[1]));
+        sprite.color = color_from_rgba(visible_color);
+        sprite
+    };
+    world
+        .entity_mut(entity)
+        .remove::<(
+            AtomeNodeFill,
+            AtomeShapeFillQuad,
+            Mesh2d,
+            MeshMaterial2d<ShapeFillMaterial>,
+        )>()
+        .insert((sprite, AtomeSpriteSourceRect(None)));
+    Ok(())
+}
+
+/// Applies a style `fill` patch. Shapes switch between the sprite and the
+/// fill material; paths keep their mesh and only repaint. The caller
+/// re-applies the clip so a new fill quad gets built.
+pub fn patch_node_fill(
+    world: &mut World,
+    entity: Entity,
+    id: &str,
+    fill: Option<AtomeFill>,
+) -> Result<(), String> {
+    let fill = fill
+        .map(|value| normalized_fill_for(&value, id))
+        .transpose()?;
+    let is_shape = world
+        .get::<AtomeRenderKind>(entity)
+        .is_some_and(|kind| kind.0 == "shape");
+    let has_shape_fill = world
+        .get::<MeshMaterial2d<ShapeFillMaterial>>(entity)
+        .is_some();
+    match fill {
+        Some(fill) if has_shape_fill => {
+            world.entity_mut(entity).insert(AtomeNodeFill(fill));
+            sync_shape_fill_material(world, entity)
+        }
+        // Backdrop shapes keep their own material.
+        Some(fill) if is_shape && world.get::<Sprite>(entity).is_some() => {
+            let radii = entity_radii(world, entity);
+            let opacity = entity_opacity(world, entity);
+            insert_shape_fill(world, entity, fill, radii, opacity)
+        }
+        Some(fill) => {
+            world.entity_mut(entity).insert(AtomeNodeFill(fill));
+            Ok(())
+        }
+        None if has_shape_fill => remove_shape_fill(world, entity),
+        None => {
+            world.entity_mut(entity).remove::<AtomeNodeFill>();
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use bevy::{image::Image, mesh::Mesh, prelude::*, sprite_render::MeshMaterial2d};
+
+use crate::{
+    apply_spawn, apply_style, apply_transform,
+    fill::{AtomeNodeFill, FillGradientUniform, ShapeFillMaterial},
+    path::PathMaterial,
+    types::*,
+};
+
+const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+fn stops(colors: &[(f32, [f32; 4])]) -> Vec<AtomeColorStop> {
+    colors
+        .iter()
+        .map(|(offset, color)| AtomeColorStop {
+            offset: *offset,
+            color: *color,
+        })
+        .collect()
+}
+
+fn horizontal(
+    end: f32,
+    stops: Vec<AtomeColorStop>,
+    spread: AtomeGradientSpread,
+    transform: Option<[f32; 6]>,
+) -> AtomeFill {
+    AtomeFill::Linear {
+        start: [0.0, 0.0],
+        end: [end, 0.0],
+        stops,
+        spread,
+        transform,
+    }
+}
+
+fn red_to_blue() -> AtomeFill {
+    horizontal(
+        1.0,
+        stops(&[(0.0, RED), (1.0, BLUE)]),
+        AtomeGradientSpread::Pad,
+        None,
+    )
+}
+
+fn assert_color(actual: [f32; 4], expected: [f32; 4]) {
+    assert!(
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| (actual - expected).abs() < 1e-4),
+        "{actual:?} != {expected:?}"
+    );
+}
+
+fn shape_node(id: &str, fill: Option<AtomeFill>) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: "shape".to_string(),
+        parent_id: None,
+        logical_position: [10.0, 20.0],
+        logical_size: [100.0, 50.0],
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.0, 0.0],
+        layer: 2,
+        opacity: 1.0,
+        corner_radius: 12.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([0.2, 0.4, 0.6, 1.0]),
+        text: None,
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: None,
+        fill,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn style_patch(id: &str) -> AtomeStylePatch {
+    AtomeStylePatch {
+        id: id.to_string(),
+        color: None,
+        shadow: None,
+        backdrop: None,
+        selected: None,
+        opacity: None,
+        playback_progress: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
+    }
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(AtomeEntityTable::default());
+    world.insert_resource(AtomeBevyRendererConfig::empty(640.0, 480.0));
+    world.insert_resource(AtomeRendererDiagnostics::default());
+    world.insert_resource(Assets::<Image>::default());
+    world.insert_resource(Assets::<Mesh>::default());
+    world.insert_resource(Assets::<ShapeFillMaterial>::default());
+    world.insert_resource(Assets::<PathMaterial>::default());
+    world
+}
+
+fn shape_material(world: &World, entity: Entity) -> ShapeFillMaterial {
+    let handle = world
+        .get::<MeshMaterial2d<ShapeFillMaterial>>(entity)
+        .unwrap()
+        .0
+        .clone();
+    world
+        .resource::<Assets<ShapeFillMaterial>>()
+        .get(&handle)
+        .unwrap()
+        .clone()
+}
+
+fn mesh_uvs(world: &World, entity: Entity) -> Vec<[f32; 2]> {
+    let handle = world.get::<Mesh2d>(entity).unwrap().0.clone();
+    match world
+        .resource::<Assets<Mesh>>()
+        .get(&handle)
+        .unwrap()
+        .attribute(Mesh::ATTRIBUTE_UV_0)
+        .unwrap()
+    {
+        bevy::mesh::VertexAttributeValues::Float32x2(uvs) => uvs.clone(),
+        other => panic!("unexpected uv format {other:?}"),
+    }
+}
+
+#[test]
+fn gradients_sample_stops_in_premultiplied_srgb_along_their_geometry() {
+    let linear = red_to_blue().normalized().unwrap();
+    assert_color(linear.color_at([0.0, 0.5], [100.0, 50.0]), RED);
+    assert_color(
+        linear.color_at([0.5, 0.9], [100.0, 50.0]),
+        [0.5, 0.0, 0.5, 1.0],
+    );
+    assert_color(linear.color_at([2.0, 0.0], [100.0, 50.0]), BLUE);
+
+    // Fading to transparent keeps the hue instead of darkening through black.
+    let fade = AtomeFill::Linear {
+        start: [0.0, 0.0],
+        end: [1.0, 0.0],
+        stops: stops(&[(0.0, RED), (1.0, [0.0, 0.0, 0.0, 0.0])]),
+        spread: AtomeGradientSpread::Pad,
+        transform: None,
+    };
+    assert_color(fade.color_at([0.5, 0.0], [1.0, 1.0]), [1.0, 0.0, 0.0, 0.5]);
+
+    let reflected = horizontal(
+        0.5,
+        stops(&[(0.0, RED), (1.0, BLUE)]),
+        AtomeGradientSpread::Reflect,
+        None,
+    );
+    assert_color(
+        reflected.color_at([0.75, 0.0], [1.0, 1.0]),
+        [0.5, 0.0, 0.5, 1.0],
+    );
+    assert_color(reflected.color_at([1.0, 0.0], [1.0, 1.0]), RED);
+    let repeated = horizontal(
+        0.5,
+        stops(&[(0.0, RED), (1.0, BLUE)]),
+        AtomeGradientSpread::Repeat,
+        None,
+    );
+    assert_color(
+        repeated.color_at([0.625, 0.0], [1.0, 1.0]),
+        [0.75, 0.0, 0.25, 1.0],
+    );
+
+    let radial = AtomeFill::Radial {
+        center: [0.5, 0.5],
+        radius: [0.5, 0.5],
+        stops: stops(&[(0.0, RED), (1.0, BLUE)]),
+        spread: AtomeGradientSpread::Pad,
+        transform: None,
+    };
+    assert_color(radial.color_at([0.5, 0.5], [80.0, 40.0]), RED);
+    assert_color(
+        radial.color_at([0.75, 0.5], [80.0, 40.0]),
+        [0.5, 0.0, 0.5, 1.0],
+    );
+    assert_color(radial.color_at([0.5, 0.0], [80.0, 40.0]), BLUE);
+
+    // Conic angles are measured in pixels, so the diagonal of a 2:1 node is
+    // not 45 degrees; directly right of the center is a quarter turn.
+    let conic = AtomeFill::Conic {
+        center: [0.5, 0.5],
+        angle: 0.0,
+        stops: stops(&[(0.0, RED), (1.0, BLUE)]),
+        transform: None,
+    };
+    assert_color(
+        conic.color_at([1.0, 0.5], [80.0, 40.0]),
+        [0.75, 0.0, 0.25, 1.0],
+    );
+    assert_color(
+        conic.color_at([0.5, 1.0], [80.0, 40.0]),
+        [0.5, 0.0, 0.5, 1.0],
+    );
+    let turned = AtomeFill::Conic {
+        center: [0.5, 0.5],
+        angle: 90.0,
+        stops: stops(&[(0.0, RED), (1.0, BLUE)]),
+        transform: None,
+    };
+    assert_color(
+        turned.color_at([0.5, 1.0], [80.0, 40.0]),
+        [0.75, 0.0, 0.25, 1.0],
+    );
+
+    // Hard stops switch exactly at the shared offset.
+    let hard = AtomeFill::Linear {
+        start: [0.0, 0.0],
+        end: [1.0, 0.0],
+        stops: stops(&[(0.0, RED), (0.5, RED), (0.5, BLUE), (1.0, BLUE)]),
+        spread: AtomeGradientSpread::Pad,
+        transform: None,
+    };
+    assert_color(hard.color_at([0.499, 0.0], [1.0, 1.0]), RED);
+    assert_color(hard.color_at([0.5, 0.0], [1.0, 1.0]), BLUE);
+}
+
+#[test]
+fn fill_transforms_map_the_gradient_inside_the_unit_box() {
+    // Rotating a left-to-right gradient a quarter turn about the center makes
+    // it run top-to-bottom.
+    let rotated = horizontal(
+        1.0,
+        stops(&[(0.0, RED), (1.0, BLUE)]),
+        AtomeGradientSpread::Pad,
+        Some([0.0, 1.0, -1.0, 0.0, 1.0, 0.0]),
+    )
+    .normalized()
+    .unwrap();
+    assert_color(rotated.color_at([0.3, 0.0], [10.0, 10.0]), RED);
+    assert_color(rotated.color_at([0.3, 1.0], [10.0, 10.0]), BLUE);
+
+    let uniform = FillGradientUniform::from_fill(&rotated);
+    assert_eq!(uniform.kind, Vec4::new(1.0, 0.0, 2.0, 0.0));
+    assert_eq!(uniform.inverse_x, Vec4::new(0.0, 1.0, 0.0, 0.0));
+    assert_eq!(uniform.inverse_y, Vec4::new(-1.0, 0.0, 1.0, 0.0));
+    assert_eq!(uniform.colors[1], Vec4::from_array(BLUE));
+    assert_eq!(uniform.offsets[0], Vec4::new(0.0, 1.0, 0.0, 0.0));
+
+    assert!(horizontal(
+        1.0,
+        stops(&[(0.0, RED), (1.0, BLUE)]),
+        AtomeGradientSpread::Pad,
+        Some([1.0, 2.0, 2.0, 4.0, 0.0, 0.0]),
+    )
+    .normalized()
+    .is_none());
+}
+
+#[test]
+fn fill_contract_fixes_up_stops_and_rejects_what_the_uniform_cannot_hold() {
+    let fill: AtomeFill = serde_json::from_value(serde_json::json!({
+        "type": "radial",
+        "center": [0.5, 0.5],
+        "radius": [0.5, -1.0],
+        "stops": [
+            { "offset": 0.6, "color": [2.0, 0.0, 0.0, 1.0] },
+            { "offset": 0.2, "color": [0.0, 0.0, 1.0, 1.0] },
+            { "offset": 1.4, "color": [0.0, 1.0, 0.0, 1.0] }
+        ],
+        "spread": "repeat"
+    }))
+    .unwrap();
+    let AtomeFill::Radial {
+        radius,
+        stops,
+        spread,
+        ..
+    } = fill.normalized().unwrap()
+    else {
+        panic!("radial fill expected");
+    };
+    assert_eq!(radius, [0.5, 0.0]);
+    assert_eq!(spread, AtomeGradientSpread::Repeat);
+    assert_eq!(
+        stops.iter().map(|stop| stop.offset).collect::<Vec<_>>(),
+        vec![0.6, 0.6, 1.0]
+    );
+    assert_eq!(stops[0].color, RED);
+
+    let too_many = AtomeFill::Conic {
+        center: [0.5, 0.5],
+        angle: 0.0,
+        stops: stops_of(MAX_FILL_STOPS + 1),
+        transform: None,
+    };
+    assert!(too_many.normalized().is_none());
+    assert!(horizontal(1.0, Vec::new(), AtomeGradientSpread::Pad, None)
+        .normalized()
+        .is_none());
+    assert!(AtomeFill::Solid {
+        color: [f32::NAN, 0.0, 0.0, 1.0]
+    }
+    .normalized()
+    .is_none());
+
+    let patch: AtomeStylePatch = serde_json::from_value(
+        serde_json::json!({ "id": "card", "color": null, "selected": null, "fill": null }),
+    )
+    .unwrap();
+    assert_eq!(patch.fill, Some(None));
+    let patch: AtomeStylePatch = serde_json::from_value(
+        serde_json::json!({ "id": "card", "color": null, "selected": null }),
+    )
+    .unwrap();
+    assert_eq!(patch.fill, None);
+}
+
+fn stops_of(count: usize) -> Vec<AtomeColorStop> {
+    (0..count)
+        .map(|index| AtomeColorStop {
+            offset: index as f32 / count as f32,
+            color: RED,
+        })
+        .collect()
+}
+
+#[test]
+fn filled_shapes_swap_their_sprite_for_a_clipped_fill_quad_and_back() {
+    let mut world = world();
+    let entity = apply_spawn(&mut world, shape_node("card", Some(red_to_blue()))).unwrap();
+    assert!(world.get::<Sprite>(entity).is_none());
+    let material = shape_material(&world, entity);
+    assert_eq!(material.gradient.kind.x, 1.0);
+    assert_eq!(material.shape.size, Vec4::new(100.0, 50.0, 1.0, 0.0));
+    assert_eq!(material.shape.radii, Vec4::splat(12.0));
+    assert_eq!(
+        mesh_uvs(&world, entity),
+        vec![[0.0, 1.0], [1.0, 1.0], [0.0, 0.0], [1.0, 0.0]]
+    );
+
+    // A clip keeps the UVs addressing the whole node, so the gradient holds still.
+    apply_transform(
+        &mut world,
+        AtomeTransformPatch {
+            id: "card".to_string(),
+            logical_position: [10.0, 20.0],
+            logical_size: [100.0, 50.0],
+            scale: [1.0, 1.0],
+            rotation: 0.0,
+            origin: [0.0, 0.0],
+            clip_rect: Some([60.0, 0.0, 200.0, 200.0]),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        mesh_uvs(&world, entity),
+        vec![[0.5, 1.0], [1.0, 1.0], [0.5, 0.0], [1.0, 0.0]]
+    );
+
+    apply_style(
+        &mut world,
+        AtomeStylePatch {
+            opacity: Some(0.5),
+            ..style_patch("card")
+        },
+    )
+    .unwrap();
+    assert_eq!(shape_material(&world, entity).shape.size.z, 0.5);
+
+    apply_style(
+        &mut world,
+        AtomeStylePatch {
+            fill: Some(None),
+            ..style_patch("card")
+        },
+    )
+    .unwrap();
+    assert!(world
+        .get::<MeshMaterial2d<ShapeFillMaterial>>(entity)
+        .is_none());
+    assert!(world.get::<AtomeNodeFill>(entity).is_none());
+    let sprite = world.get::<Sprite>(entity).unwrap();
+    assert_eq!(sprite.color, Color::srgba(0.2, 0.4, 0.6, 0.5));
+    assert_eq!(sprite.custom_size, Some(Vec2::new(50.0, 50.0)));
+
+    apply_style(
+        &mut world,
+        AtomeStylePatch {
+            fill: Some(Some(AtomeFill::Solid { color: BLUE })),
+            ..style_patch("card")
+        },
+    )
+    .unwrap();
+    assert!(world.get::<Sprite>(entity).is_none());
+    assert_eq!(
+        shape_material(&world, entity).gradient.colors[0],
+        Vec4::from_array(BLUE)
+    );
+    assert_eq!(
+        mesh_uvs(&world, entity),
+        vec![[0.5, 1.0], [1.0, 1.0], [0.5, 0.0], [1.0, 0.0]]
+    );
+
+    let error = apply_style(
+        &mut world,
+        AtomeStylePatch {
+            fill: Some(Some(horizontal(
+                1.0,
+                Vec::new(),
+                AtomeGradientSpread::Pad,
+                None,
+            ))),
+            ..style_patch("card")
+        },
+    )
+    .unwrap_err();
+    assert_eq!(error, "bevy_fill_invalid:card");
+}
+
+#[test]
+fn path_fill_replaces_the_interior_paint_but_not_the_stroke() {
+    let mut world = world();
+    let entity = apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            kind: "path".to_string(),
+            path: Some(AtomePath {
+                d: Some("M0 0 H100 V50 Z".to_string()),
+                stroke: Some(RED),
+                stroke_width: 2.0,
+                fill: Some(RED),
+                ..AtomePath::default()
+            }),
+            ..shape_node("wedge", Some(red_to_blue()))
+        },
+    )
+    .unwrap();
+    let material = |world: &World| {
+        let handle = world
+            .get::<MeshMaterial2d<PathMaterial>>(entity)
+            .unwrap()
+            .0
+            .clone();
+        world
+            .resource::<Assets<PathMaterial>>()
+            .get(&handle)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(material(&world).fill.kind.x, 1.0);
+    assert_eq!(
+        material(&world).uniform.geometry,
+        Vec4::new(1.0, 100.0, 50.0, 0.0)
+    );
+    assert_eq!(
+        material(&world).uniform.stroke,
+        Vec4::new(1.0, 0.0, 0.0, 1.0)
+    );
+
+    apply_style(
+        &mut world,
+        AtomeStylePatch {
+            fill: Some(None),
+            ..style_patch("wedge")
+        },
+    )
+    .unwrap();
+    assert_eq!(material(&world).fill.kind.x, 0.0);
+    assert_eq!(material(&world).fill.colors[0], Vec4::from_array(RED));
+}
@@ -1,10 +1,20 @@
+pub mod animation;
 pub mod backdrop_blur;
 pub mod backdrop_surface;
 pub mod background;
 pub mod clip;
 pub mod components;
+pub mod composite;
+pub mod fill;
+pub mod op_stream;
+pub mod path;
+pub mod path_geometry;
+pub mod picking;
 pub mod plugin;
+pub mod png;
 pub mod procedural_sdf;
+pub mod raster;
+pub mod readback;
 pub mod render_math;
 pub mod render_ops;
 pub mod resource_ops;
@@ -12,15 +22,26 @@ pub mod selection_overlay;
 pub mod shadow_texture;
 pub mod shape_shadow_overlay;
 pub mod spawn;
+pub mod text_layout;
 pub mod texture;
 pub mod types;
-mod types_procedural;
+mod types_animation;
+mod types_composite;
+mod types_fill;
 mod types_ops;
+mod types_path;
+mod types_pick;
+mod types_procedural;
+mod types_raster;
+mod types_readback;
+mod types_text;
+mod types_viewport;
 pub mod ui;
 pub mod video_diagnostics;
 pub mod video_external_texture;
 #[cfg(target_arch = "wasm32")]
 pub mod video_external_web;
+pub mod viewport;
 pub mod waveform_playback_overlay;
 pub mod workspace_backdrop;
 pub mod workspace_blur;
@@ -32,17 +53,37 @@ pub use types::*;
 pub use ui::*;
 pub use video_diagnostics::*;
 
+#[cfg(test)]
+mod animation_tests;
 #[cfg(test)]
 mod backdrop_blur_tests;
 #[cfg(test)]
+mod composite_tests;
+#[cfg(test)]
+mod fill_tests;
+#[cfg(test)]
+mod op_stream_tests;
+#[cfg(test)]
+mod path_tests;
+#[cfg(test)]
+mod picking_tests;
+#[cfg(test)]
 mod procedural_sdf_tests;
 #[cfg(test)]
+mod raster_tests;
+#[cfg(test)]
+mod readback_tests;
+#[cfg(test)]
 mod shape_shadow_overlay_tests;
 #[cfg(test)]
 mod tests;
 #[cfg(test)]
+mod text_layout_tests;
+#[cfg(test)]
 mod texture_sprite_color_tests;
 #[cfg(test)]
 mod video_external_texture_tests;
 #[cfg(test)]
+mod viewport_tests;
+#[cfg(test)]
 mod workspace_blur_tests;
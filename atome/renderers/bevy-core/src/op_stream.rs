@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+
+use crate::types::{
+    AtomeBackdropStyle, AtomeBlendMode, AtomeLayerPatch, AtomeMaskMode, AtomeParentPatch,
+    AtomeRenderOp, AtomeShadowStyle, AtomeStylePatch, AtomeTransformPatch, AtomeVisibilityPatch,
+};
+
+// Compact binary encoding of the ops a drag or scrub emits per frame, so the
+// web module can take a whole batch as one byte slice instead of walking a
+// `JsValue` tree per field. Layout (v1, every number little-endian):
+//
+//   "AOPS" u8 version
+//   u32 string count, then per string: u32 byte length, UTF-8 bytes
+//   u32 op count, then per op: u8 tag, payload
+//
+// Ids and other strings are u32 indices into the string table. `opt<T>` is a
+// u8 0 (absent / null) or 1 followed by `T`. Payloads by tag:
+//
+//   1 despawn     id
+//   2 transform   id, f32x2 position, f32x2 size, f32x2 scale, f32 rotation,
+//                 f32x2 origin, opt<f32x4> clip_rect
+//   3 style       id, u16 field mask, then the masked fields in bit order
+//   4 reparent    id, opt<string> parent_id
+//   5 layer       id, i32 layer
+//   6 visibility  id, u8 visible
+//
+// Style field bits: 0 color f32x4, 1 opacity f32, 2 selected u8,
+// 3 playback_progress opt<f32>, 4 shadow opt<f32x4 color, f32 blur,
+// f32 offset_x, f32 offset_y, f32 spread>, 5 backdrop opt<f32 blur_px,
+// f32x4 tint>, 6 blend_mode u8, 7 mask_id opt<string>, 8 mask_mode u8.
+// Blend and mask modes are indices in declaration order. Other ops and style
+// fields keep going through the JSON-shaped exports.
+
+pub const OP_STREAM_MAGIC: [u8; 4] = *b"AOPS";
+pub const OP_STREAM_VERSION: u8 = 1;
+
+const TAG_DESPAWN: u8 = 1;
+const TAG_TRANSFORM: u8 = 2;
+const TAG_STYLE: u8 = 3;
+const TAG_REPARENT: u8 = 4;
+const TAG_LAYER: u8 = 5;
+const TAG_VISIBILITY: u8 = 6;
+
+const STYLE_COLOR: u16 = 1 << 0;
+const STYLE_OPACITY: u16 = 1 << 1;
+const STYLE_SELECTED: u16 = 1 << 2;
+const STYLE_PLAYBACK_PROGRESS: u16 = 1 << 3;
+const STYLE_SHADOW: u16 = 1 << 4;
+const STYLE_BACKDROP: u16 = 1 << 5;
+const STYLE_BLEND_MODE: u16 = 1 << 6;
+const STYLE_MASK_ID: u16 = 1 << 7;
+const STYLE_MASK_MODE: u16 = 1 << 8;
+const STYLE_FIELDS: u16 = (1 << 9) - 1;
+
+const BLEND_MODES: [AtomeBlendMode; 8] = [
+    AtomeBlendMode::Normal,
+    AtomeBlendMode::Multiply,
+    AtomeBlendMode::Screen,
+    AtomeBlendMode::Overlay,
+    AtomeBlendMode::Darken,
+    AtomeBlendMode::Lighten,
+    AtomeBlendMode::Difference,
+    AtomeBlendMode::Additive,
+];
+const MASK_MODES: [AtomeMaskMode; 2] = [AtomeMaskMode::Alpha, AtomeMaskMode::Luminance];
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| format!("bevy_op_stream_truncated:{}", self.offset))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        self.array().map(i32::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        self.array().map(f32::from_le_bytes)
+    }
+
+    fn f32s<const N: usize>(&mut self) -> Result<[f32; N], String> {
+        let mut values = [0.0; N];
+        for value in &mut values {
+            *value = self.f32()?;
+        }
+        Ok(values)
+    }
+
+    fn flag(&mut self) -> Result<bool, String> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(format!("bevy_op_stream_flag_invalid:{value}")),
+        }
+    }
+
+    fn opt<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<Option<T>, String> {
+        if self.flag()? {
+            read(self).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn string(&mut self, strings: &[String]) -> Result<String, String> {
+        let index = self.u32()?;
+        strings
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| format!("bevy_op_stream_string_missing:{index}"))
+    }
+
+    fn variant<T: Copy>(&mut self, variants: &[T]) -> Result<T, String> {
+        let index = self.u8()?;
+        variants
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| format!("bevy_op_stream_variant_invalid:{index}"))
+    }
+}
+
+fn read_count(reader: &mut Reader, min_item_len: usize) -> Result<usize, String> {
+    let count = reader.u32()? as usize;
+    // Reject counts the remaining bytes cannot hold before reserving for them.
+    if count > reader.remaining() / min_item_len {
+        return Err(format!("bevy_op_stream_count_invalid:{count}"));
+    }
+    Ok(count)
+}
+
+fn read_strings(reader: &mut Reader) -> Result<Vec<String>, String> {
+    let count = read_count(reader, 4)?;
+    let mut strings = Vec::with_capacity(count);
+    for index in 0..count {
+        let len = reader.u32()? as usize;
+        let string = std::str::from_utf8(reader.take(len)?)
+            .map_err(|_| format!("bevy_op_stream_string_invalid:{index}"))?;
+        strings.push(string.to_string());
+    }
+    Ok(strings)
+}
+
+fn read_style(reader: &mut Reader, strings: &[String]) -> Result<AtomeStylePatch, String> {
+    let id = reader.string(strings)?;
+    let fields = reader.u16()?;
+    if fields & !STYLE_FIELDS != 0 {
+        return Err(format!("bevy_op_stream_style_fields_invalid:{fields}"));
+    }
+    let has = |field: u16| fields & field != 0;
+    let mut patch = AtomeStylePatch {
+        id,
+        color: None,
+        shadow: None,
+        backdrop: None,
+        selected: None,
+        opacity: None,
+        playback_progress: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
+    };
+    if has(STYLE_COLOR) {
+        patch.color = Some(reader.f32s()?);
+    }
+    if has(STYLE_OPACITY) {
+        patch.opacity = Some(reader.f32()?);
+    }
+    if has(STYLE_SELECTED) {
+        patch.selected = Some(reader.flag()?);
+    }
+    if has(STYLE_PLAYBACK_PROGRESS) {
+        patch.playback_progress = Some(reader.opt(Reader::f32)?);
+    }
+    if has(STYLE_SHADOW) {
+        patch.shadow = Some(reader.opt(|reader| {
+            Ok(AtomeShadowStyle {
+                color: reader.f32s()?,
+                blur: reader.f32()?,
+                offset_x: reader.f32()?,
+                offset_y: reader.f32()?,
+                spread: reader.f32()?,
+            })
+        })?);
+    }
+    if has(STYLE_BACKDROP) {
+        patch.backdrop = Some(reader.opt(|reader| {
+            Ok(AtomeBackdropStyle {
+                blur_px: reader.f32()?,
+                tint: reader.f32s()?,
+            })
+        })?);
+    }
+    if has(STYLE_BLEND_MODE) {
+        patch.blend_mode = Some(reader.variant(&BLEND_MODES)?);
+    }
+    if has(STYLE_MASK_ID) {
+        patch.mask_id = Some(reader.opt(|reader| reader.string(strings))?);
+    }
+    if has(STYLE_MASK_MODE) {
+        patch.mask_mode = Some(reader.variant(&MASK_MODES)?);
+    }
+    Ok(patch)
+}
+
+fn read_op(reader: &mut Reader, strings: &[String]) -> Result<AtomeRenderOp, String> {
+    Ok(match reader.u8()? {
+        TAG_DESPAWN => AtomeRenderOp::Despawn(reader.string(strings)?),
+        TAG_TRANSFORM => AtomeRenderOp::Transform(AtomeTransformPatch {
+            id: reader.string(strings)?,
+            logical_position: reader.f32s()?,
+            logical_size: reader.f32s()?,
+            scale: reader.f32s()?,
+            rotation: reader.f32()?,
+            origin: reader.f32s()?,
+            clip_rect: reader.opt(Reader::f32s)?,
+        }),
+        TAG_STYLE => AtomeRenderOp::Style(read_style(reader, strings)?),
+        TAG_REPARENT => AtomeRenderOp::Reparent(AtomeParentPatch {
+            id: reader.string(strings)?,
+            parent_id: reader.opt(|reader| reader.string(strings))?,
+        }),
+        TAG_LAYER => AtomeRenderOp::Layer(AtomeLayerPatch {
+            id: reader.string(strings)?,
+            layer: reader.i32()?,
+        }),
+        TAG_VISIBILITY => AtomeRenderOp::Visibility(AtomeVisibilityPatch {
+            id: reader.string(strings)?,
+            visible: reader.flag()?,
+        }),
+        tag => return Err(format!("bevy_op_stream_op_unknown:{tag}")),
+    })
+}
+
+/// Decodes a v1 op stream. Never panics on malformed input; the error names
+/// the first problem and, for truncation, the byte offset.
+pub fn decode_op_stream(bytes: &[u8]) -> Result<Vec<AtomeRenderOp>, String> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.take(4).ok() != Some(&OP_STREAM_MAGIC[..]) {
+        return Err("bevy_op_stream_magic_invalid".to_string());
+    }
+    let version = reader.u8()?;
+    if version != OP_STREAM_VERSION {
+        return Err(format!("bevy_op_stream_version_unsupported:{version}"));
+    }
+    let strings = read_strings(&mut reader)?;
+    // The shortest op is a despawn: tag plus id index.
+    let count = read_count(&mut reader, 5)?;
+    let mut ops = Vec::with_capacity(count);
+    for _ in 0..count {
+        ops.push(read_op(&mut reader, &strings)?);
+    }
+    if reader.remaining() != 0 {
+        return Err(format!("bevy_op_stream_trailing_bytes:{}", reader.offset));
+    }
+    Ok(ops)
+}
+
+#[derive(Default)]
+struct Writer<'a> {
+    body: Vec<u8>,
+    strings: Vec<&'a str>,
+    indices: HashMap<&'a str, u32>,
+}
+
+impl<'a> Writer<'a> {
+    fn u8(&mut self, value: u8) {
+        self.body.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.body.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.body.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32s(&mut self, values: &[f32]) {
+        for value in values {
+            self.body.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn opt<T>(&mut self, value: Option<T>, write: impl FnOnce(&mut Self, T)) {
+        self.u8(u8::from(value.is_some()));
+        if let Some(value) = value {
+            write(self, value);
+        }
+    }
+
+    fn string(&mut self, value: &'a str) {
+        let next = self.strings.len() as u32;
+        let index = *self.indices.entry(value).or_insert(next);
+        if index == next {
+            self.strings.push(value);
+        }
+        self.body.extend_from_slice(&index.to_le_bytes());
+    }
+
+    fn style(&mut self, patch: &'a AtomeStylePatch) -> Result<(), String> {
+        if patch.filters.is_some()
+            || patch.transition.is_some()
+            || patch.procedural.is_some()
+            || patch.fill.is_some()
+        {
+            return Err(format!("bevy_op_stream_style_unsupported:{}", patch.id));
+        }
+        self.string(&patch.id);
+        let fields = [
+            (patch.color.is_some(), STYLE_COLOR),
+            (patch.opacity.is_some(), STYLE_OPACITY),
+            (patch.selected.is_some(), STYLE_SELECTED),
+            (patch.playback_progress.is_some(), STYLE_PLAYBACK_PROGRESS),
+            (patch.shadow.is_some(), STYLE_SHADOW),
+            (patch.backdrop.is_some(), STYLE_BACKDROP),
+            (patch.blend_mode.is_some(), STYLE_BLEND_MODE),
+            (patch.mask_id.is_some(), STYLE_MASK_ID),
+            (patch.mask_mode.is_some(), STYLE_MASK_MODE),
+        ]
+        .into_iter()
+        .filter(|(present, _)| *present)
+        .fold(0, |fields, (_, field)| fields | field);
+        self.u16(fields);
+        if let Some(color) = patch.color {
+            self.f32s(&color);
+        }
+        if let Some(opacity) = patch.opacity {
+            self.f32s(&[opacity]);
+        }
+        if let Some(selected) = patch.selected {
+            self.u8(u8::from(selected));
+        }
+        if let Some(progress) = patch.playback_progress {
+            self.opt(progress, |writer, progress| writer.f32s(&[progress]));
+        }
+        if let Some(shadow) = patch.shadow {
+            self.opt(shadow, |writer, shadow| {
+                writer.f32s(&shadow.color);
+                writer.f32s(&[shadow.blur, shadow.offset_x, shadow.offset_y, shadow.spread]);
+            });
+        }
+        if let Some(backdrop) = patch.backdrop {
+            self.opt(backdrop, |writer, backdrop| {
+                writer.f32s(&[backdrop.blur_px]);
+                writer.f32s(&backdrop.tint);
+            });
+        }
+        if let Some(blend_mode) = patch.blend_mode {
+            self.u8(variant_index(&BLEND_MODES, blend_mode));
+        }
+        if let Some(mask_id) = &patch.mask_id {
+            self.opt(mask_id.as_deref(), Self::string);
+        }
+        if let Some(mask_mode) = patch.mask_mode {
+            self.u8(variant_index(&MASK_MODES, mask_mode));
+        }
+        Ok(())
+    }
+
+    fn op(&mut self, op: &'a AtomeRenderOp) -> Result<(), String> {
+        match op {
+            AtomeRenderOp::Despawn(id) => {
+                self.u8(TAG_DESPAWN);
+                self.string(id);
+            }
+            AtomeRenderOp::Transform(patch) => {
+                self.u8(TAG_TRANSFORM);
+                self.string(&patch.id);
+                self.f32s(&patch.logical_position);
+                self.f32s(&patch.logical_size);
+                self.f32s(&patch.scale);
+                self.f32s(&[patch.rotation]);
+                self.f32s(&patch.origin);
+                self.opt(patch.clip_rect, |writer, clip| writer.f32s(&clip));
+            }
+            AtomeRenderOp::Style(patch) => {
+                self.u8(TAG_STYLE);
+                self.style(patch)?;
+            }
+            AtomeRenderOp::Reparent(patch) => {
+                self.u8(TAG_REPARENT);
+                self.string(&patch.id);
+                self.opt(patch.parent_id.as_deref(), Self::string);
+            }
+            AtomeRenderOp::Layer(patch) => {
+                self.u8(TAG_LAYER);
+                self.string(&patch.id);
+                self.i32(patch.layer);
+            }
+            AtomeRenderOp::Visibility(patch) => {
+                self.u8(TAG_VISIBILITY);
+                self.string(&patch.id);
+                self.u8(u8::from(patch.visible));
+            }
+            _ => return Err("bevy_op_stream_op_unsupported".to_string()),
+        }
+        Ok(())
+    }
+}
+
+fn variant_index<T: PartialEq>(variants: &[T], value: T) -> u8 {
+    variants
+        .iter()
+        .position(|variant| *variant == value)
+        .unwrap_or_default() as u8
+}
+
+/// Encodes `ops` as a v1 op stream, interning ids in first-use order. Fails on
+/// ops and style fields the stream does not carry.
+pub fn encode_op_stream(ops: &[AtomeRenderOp]) -> Result<Vec<u8>, String> {
+    let mut writer = Writer::default();
+    for op in ops {
+        writer.op(op)?;
+    }
+    let mut bytes = Vec::with_capacity(writer.body.len() + 16);
+    bytes.extend_from_slice(&OP_STREAM_MAGIC);
+    bytes.push(OP_STREAM_VERSION);
+    bytes.extend_from_slice(&(writer.strings.len() as u32).to_le_bytes());
+    for string in &writer.strings {
+        bytes.extend_from_slice(&(string.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(string.as_bytes());
+    }
+    bytes.extend_from_slice(&(ops.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&writer.body);
+    Ok(bytes)
+}
@@ -0,0 +1,288 @@
+use crate::{
+    op_stream::{decode_op_stream, encode_op_stream, OP_STREAM_MAGIC, OP_STREAM_VERSION},
+    types::*,
+};
+
+// Deterministic xorshift so fuzz failures reproduce from the seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn chance(&mut self) -> bool {
+        self.below(2) == 0
+    }
+
+    fn f32(&mut self) -> f32 {
+        self.below(20_000) as f32 / 8.0 - 1_250.0
+    }
+
+    fn f32s<const N: usize>(&mut self) -> [f32; N] {
+        std::array::from_fn(|_| self.f32())
+    }
+
+    fn id(&mut self) -> String {
+        // A small id pool so interning gets exercised.
+        ["card", "badge", "clip-é", "", "track_7"][self.below(5) as usize].to_string()
+    }
+
+    fn maybe<T>(&mut self, value: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        self.chance().then(|| value(self))
+    }
+}
+
+fn empty_style(id: &str) -> AtomeStylePatch {
+    AtomeStylePatch {
+        id: id.to_string(),
+        color: None,
+        shadow: None,
+        backdrop: None,
+        selected: None,
+        opacity: None,
+        playback_progress: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
+    }
+}
+
+fn random_style(rng: &mut Rng) -> AtomeStylePatch {
+    AtomeStylePatch {
+        color: rng.maybe(Rng::f32s),
+        shadow: rng.maybe(|rng| {
+            rng.maybe(|rng| AtomeShadowStyle {
+                color: rng.f32s(),
+                blur: rng.f32(),
+                offset_x: rng.f32(),
+                offset_y: rng.f32(),
+                spread: rng.f32(),
+            })
+        }),
+        backdrop: rng.maybe(|rng| {
+            rng.maybe(|rng| AtomeBackdropStyle {
+                blur_px: rng.f32(),
+                tint: rng.f32s(),
+            })
+        }),
+        selected: rng.maybe(Rng::chance),
+        opacity: rng.maybe(Rng::f32),
+        playback_progress: rng.maybe(|rng| rng.maybe(Rng::f32)),
+        blend_mode: rng.maybe(|rng| {
+            [
+                AtomeBlendMode::Normal,
+                AtomeBlendMode::Multiply,
+                AtomeBlendMode::Overlay,
+                AtomeBlendMode::Additive,
+            ][rng.below(4) as usize]
+        }),
+        mask_id: rng.maybe(|rng| rng.maybe(Rng::id)),
+        mask_mode: rng.maybe(|rng| {
+            if rng.chance() {
+                AtomeMaskMode::Alpha
+            } else {
+                AtomeMaskMode::Luminance
+            }
+        }),
+        ..empty_style(&rng.id())
+    }
+}
+
+fn random_op(rng: &mut Rng) -> AtomeRenderOp {
+    match rng.below(6) {
+        0 => AtomeRenderOp::Despawn(rng.id()),
+        1 => AtomeRenderOp::Transform(AtomeTransformPatch {
+            id: rng.id(),
+            logical_position: rng.f32s(),
+            logical_size: rng.f32s(),
+            scale: rng.f32s(),
+            rotation: rng.f32(),
+            origin: rng.f32s(),
+            clip_rect: rng.maybe(Rng::f32s),
+        }),
+        2 => AtomeRenderOp::Style(random_style(rng)),
+        3 => AtomeRenderOp::Reparent(AtomeParentPatch {
+            id: rng.id(),
+            parent_id: rng.maybe(Rng::id),
+        }),
+        4 => AtomeRenderOp::Layer(AtomeLayerPatch {
+            id: rng.id(),
+            layer: rng.next() as i32,
+        }),
+        _ => AtomeRenderOp::Visibility(AtomeVisibilityPatch {
+            id: rng.id(),
+            visible: rng.chance(),
+        }),
+    }
+}
+
+fn random_ops(rng: &mut Rng) -> Vec<AtomeRenderOp> {
+    (0..rng.below(12)).map(|_| random_op(rng)).collect()
+}
+
+// The patches do not implement `PartialEq`; their `Debug` output covers
+// every field.
+fn assert_same_ops(actual: &[AtomeRenderOp], expected: &[AtomeRenderOp]) {
+    assert_eq!(format!("{actual:?}"), format!("{expected:?}"));
+}
+
+#[test]
+fn encoded_ops_decode_to_the_same_ops() {
+    let mut rng = Rng(0x5eed_fa70);
+    for _ in 0..500 {
+        let ops = random_ops(&mut rng);
+        let bytes = encode_op_stream(&ops).unwrap();
+        assert_same_ops(&decode_op_stream(&bytes).unwrap(), &ops);
+    }
+}
+
+#[test]
+fn encoding_interns_repeated_ids_once() {
+    let ops = (0..100)
+        .map(|index| {
+            AtomeRenderOp::Layer(AtomeLayerPatch {
+                id: if index % 2 == 0 { "card" } else { "badge" }.to_string(),
+                layer: index,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let bytes = encode_op_stream(&ops).unwrap();
+
+    // Header, two table entries, op count, then tag + index + layer per op.
+    assert_eq!(bytes.len(), 5 + 4 + (4 + 4) + (4 + 5) + 4 + 100 * 9);
+    assert_eq!(&bytes[..4], &OP_STREAM_MAGIC);
+    assert_eq!(bytes[4], OP_STREAM_VERSION);
+    assert_same_ops(&decode_op_stream(&bytes).unwrap(), &ops);
+}
+
+#[test]
+fn style_null_fields_survive_the_round_trip() {
+    let ops = vec![AtomeRenderOp::Style(AtomeStylePatch {
+        shadow: Some(None),
+        backdrop: Some(None),
+        playback_progress: Some(None),
+        mask_id: Some(None),
+        ..empty_style("card")
+    })];
+
+    let decoded = decode_op_stream(&encode_op_stream(&ops).unwrap()).unwrap();
+
+    let [AtomeRenderOp::Style(patch)] = decoded.as_slice() else {
+        panic!("expected one style op");
+    };
+    assert_eq!(patch.shadow, Some(None));
+    assert_eq!(patch.backdrop, Some(None));
+    assert_eq!(patch.playback_progress, Some(None));
+    assert_eq!(patch.mask_id, Some(None));
+    assert_eq!(patch.color, None);
+}
+
+#[test]
+fn encoding_rejects_ops_the_stream_does_not_carry() {
+    let fill = AtomeRenderOp::Style(AtomeStylePatch {
+        fill: Some(None),
+        ..empty_style("card")
+    });
+    let surface = AtomeRenderOp::Surface(AtomeSurfacePatch::logical(10.0, 10.0));
+
+    assert_eq!(
+        encode_op_stream(&[fill]),
+        Err("bevy_op_stream_style_unsupported:card".to_string())
+    );
+    assert_eq!(
+        encode_op_stream(&[surface]),
+        Err("bevy_op_stream_op_unsupported".to_string())
+    );
+}
+
+#[test]
+fn decoding_reports_malformed_streams() {
+    let valid = encode_op_stream(&[AtomeRenderOp::Despawn("card".to_string())]).unwrap();
+    let with = |edit: &dyn Fn(&mut Vec<u8>)| {
+        let mut bytes = valid.clone();
+        edit(&mut bytes);
+        decode_op_stream(&bytes).unwrap_err()
+    };
+
+    assert_eq!(
+        with(&|bytes| bytes[0] = b'X'),
+        "bevy_op_stream_magic_invalid"
+    );
+    assert_eq!(
+        with(&|bytes| bytes[4] = 9),
+        "bevy_op_stream_version_unsupported:9"
+    );
+    // String table: count at 5, length at 9, "card" at 13; op count at 17.
+    assert_eq!(
+        with(&|bytes| bytes[5..9].copy_from_slice(&u32::MAX.to_le_bytes())),
+        format!("bevy_op_stream_count_invalid:{}", u32::MAX)
+    );
+    assert_eq!(
+        with(&|bytes| bytes[13] = 0xff),
+        "bevy_op_stream_string_invalid:0"
+    );
+    assert_eq!(
+        with(&|bytes| bytes[21] = 42),
+        "bevy_op_stream_op_unknown:42"
+    );
+    assert_eq!(
+        with(&|bytes| bytes[22] = 3),
+        "bevy_op_stream_string_missing:3"
+    );
+    assert_eq!(
+        with(&|bytes| bytes.truncate(19)),
+        "bevy_op_stream_truncated:17"
+    );
+    // A count the remaining bytes cannot hold fails before decoding ops.
+    assert_eq!(
+        with(&|bytes| bytes.truncate(24)),
+        "bevy_op_stream_count_invalid:1"
+    );
+    assert_eq!(
+        with(&|bytes| bytes.push(0)),
+        format!("bevy_op_stream_trailing_bytes:{}", valid.len())
+    );
+}
+
+#[test]
+fn decoding_never_panics_on_fuzzed_input() {
+    let mut rng = Rng(0xf022_5eed);
+    for _ in 0..2_000 {
+        let mut bytes = encode_op_stream(&random_ops(&mut rng)).unwrap();
+        match rng.below(4) {
+            0 => bytes.truncate(rng.below(bytes.len() as u64 + 1) as usize),
+            1 => {
+                for _ in 0..=rng.below(4) {
+                    let index = rng.below(bytes.len() as u64) as usize;
+                    bytes[index] = rng.next() as u8;
+                }
+            }
+            2 => {
+                let len = 5 + rng.below(64) as usize;
+                bytes = (0..len).map(|_| rng.next() as u8).collect();
+                bytes[..4].copy_from_slice(&OP_STREAM_MAGIC);
+                bytes[4] = OP_STREAM_VERSION;
+            }
+            _ => bytes = (0..rng.below(64)).map(|_| rng.next() as u8).collect(),
+        }
+        // Malformed input may decode or fail, but a decoded stream must
+        // re-encode to itself.
+        if let Ok(ops) = decode_op_stream(&bytes) {
+            let again = encode_op_stream(&ops).unwrap();
+            assert_same_ops(&decode_op_stream(&again).unwrap(), &ops);
+        }
+    }
+}
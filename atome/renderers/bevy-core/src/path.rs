@@ -0,0 +1,319 @@
+use bevy::{
+    asset::{load_internal_asset, uuid_handle, RenderAssetUsages},
+    color::ColorToComponents,
+    mesh::{Indices, Mesh, Mesh2d},
+    prelude::*,
+    reflect::TypePath,
+    render::{
+        render_resource::{AsBindGroup, PrimitiveTopology, ShaderType},
+        RenderApp,
+    },
+    shader::{Shader, ShaderRef},
+    sprite_render::{AlphaMode2d, Material2d, Material2dPlugin, MeshMaterial2d},
+};
+
+use crate::{
+    fill::{AtomeNodeFill, FillGradientUniform},
+    path_geometry::{resolve_path_commands, tessellate_path, PathTessellation},
+    render_math::color_from_rgba,
+    types::{
+        normalize_opacity, AtomeBevyRendererConfig, AtomeEntityId, AtomeFill, AtomeLocalTransform,
+        AtomeLogicalSize, AtomePath, AtomePathCommand, AtomeVisualColor, AtomeVisualOpacity,
+    },
+    viewport::viewport_zoom,
+};
+
+const PATH_SHADER_HANDLE: Handle<Shader> = uuid_handle!("ce68c3da-88dd-4bcf-9231-75b809c82fef");
+
+/// Relative device-scale change that triggers re-tessellation. Smaller zoom
+/// steps reuse the mesh; the fringe is then off by at most this fraction.
+const RETESSELLATE_SCALE_RATIO: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug, ShaderType)]
+pub struct PathUniform {
+    pub stroke: Vec4,
+    // [opacity, node width, node height, 0]; the size maps mesh positions
+    // into the fill's unit box.
+    pub geometry: Vec4,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct PathMaterial {
+    #[uniform(0)]
+    pub uniform: PathUniform,
+    #[uniform(1)]
+    pub fill: FillGradientUniform,
+}
+
+impl Material2d for PathMaterial {
+    fn fragment_shader() -> ShaderRef {
+        PATH_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+pub struct PathPlugin;
+
+impl Plugin for PathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Assets<Shader>>();
+        load_internal_asset!(
+            app,
+            PATH_SHADER_HANDLE,
+            "assets/shaders/path.wgsl",
+            Shader::from_wgsl
+        );
+        app.init_resource::<Assets<PathMaterial>>();
+        if app.get_sub_app_mut(RenderApp).is_some() {
+            app.add_plugins(Material2dPlugin::<PathMaterial>::default());
+        }
+    }
+}
+
+/// The path a `path` entity draws and the inputs its current mesh was
+/// tessellated for, so transforms that do not change them skip the work.
+#[derive(Clone, Debug, Component)]
+pub struct AtomePathVisual {
+    pub path: AtomePath,
+    pub commands: Vec<AtomePathCommand>,
+    pub size: [f32; 2],
+    pub visible_rect: [f32; 4],
+    pub device_scale: f32,
+}
+
+pub fn path_commands_for(path: &AtomePath, id: &str) -> Result<Vec<AtomePathCommand>, String> {
+    resolve_path_commands(path).ok_or_else(|| format!("bevy_path_data_invalid:{id}"))
+}
+
+fn linear(color: [f32; 4]) -> Vec4 {
+    color_from_rgba(color.map(|value| value.clamp(0.0, 1.0)))
+        .to_linear()
+        .to_vec4()
+}
+
+fn path_uniform(path: &AtomePath, opacity: f32, size: [f32; 2]) -> PathUniform {
+    PathUniform {
+        stroke: path.stroke.map_or(Vec4::ZERO, linear),
+        geometry: Vec4::new(normalize_opacity(opacity), size[0], size[1], 0.0),
+    }
+}
+
+/// A node `fill` replaces the interior paint; paths that only stroke keep no
+/// interior.
+fn path_fill_uniform(
+    path: &AtomePath,
+    node_color: [f32; 4],
+    fill: Option<&AtomeFill>,
+) -> FillGradientUniform {
+    match (path.fill_color(node_color), fill) {
+        (Some(_), Some(fill)) => FillGradientUniform::from_fill(fill),
+        (Some(color), None) => FillGradientUniform::solid(color.map(|value| value.clamp(0.0, 1.0))),
+        (None, _) => FillGradientUniform::solid([0.0; 4]),
+    }
+}
+
+fn path_mesh(tessellation: &PathTessellation, visible_rect: [f32; 4]) -> Mesh {
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    );
+    if tessellation.is_empty() {
+        // Keep a valid zero-coverage triangle so the entity still owns a mesh.
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0]; 3]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; 3]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![[0.0, 0.0, 0.0, 0.0]; 3]);
+        mesh.insert_indices(Indices::U32(vec![0, 1, 2]));
+        return mesh;
+    }
+    // `uv` carries the node-local position for the fill paint, so the edge
+    // vector moves into `color`: [edge x, edge y, half extent, alpha scale],
+    // with the alpha negated on stroke vertices. Paint is constant per
+    // triangle, so the sign survives interpolation.
+    let paints: Vec<[f32; 4]> = tessellation
+        .edges
+        .iter()
+        .zip(&tessellation.paints)
+        .map(|(edge, paint)| {
+            let alpha = if paint[0] >= 0.5 { -paint[1] } else { paint[1] };
+            [edge[0], edge[1], paint[2], alpha]
+        })
+        .collect();
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        tessellation.mesh_positions(visible_rect),
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, tessellation.positions.clone());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, paints);
+    mesh.insert_indices(Indices::U32(tessellation.indices.clone()));
+    mesh
+}
+
+fn device_scale(world: &World, entity: Entity) -> f32 {
+    let device_pixel_ratio = world
+        .get_resource::<AtomeBevyRendererConfig>()
+        .map(|config| config.device_pixel_ratio)
+        .unwrap_or(1.0);
+    let scale = world
+        .get::<AtomeLocalTransform>(entity)
+        .map(|local| local.scale[0].abs().max(local.scale[1].abs()))
+        .unwrap_or(1.0);
+    (device_pixel_ratio.max(0.01) * scale * viewport_zoom(world)).max(0.01)
+}
+
+pub fn insert_path(
+    world: &mut World,
+    entity: Entity,
+    path: AtomePath,
+    commands: Vec<AtomePathCommand>,
+    color: [f32; 4],
+    opacity: f32,
+    fill: Option<AtomeFill>,
+) -> Result<(), String> {
+    let size = world
+        .get::<AtomeLogicalSize>(entity)
+        .map(|size| [size.width, size.height])
+        .ok_or_else(|| "bevy_path_size_missing".to_string())?;
+    let material = {
+        let mut materials = world
+            .get_resource_mut::<Assets<PathMaterial>>()
+            .ok_or_else(|| "bevy_path_assets_required".to_string())?;
+        materials.add(PathMaterial {
+            uniform: path_uniform(&path, opacity, size),
+            fill: path_fill_uniform(&path, color, fill.as_ref()),
+        })
+    };
+    if let Some(fill) = fill {
+        world.entity_mut(entity).insert(AtomeNodeFill(fill));
+    }
+    world.entity_mut(entity).insert((
+        AtomePathVisual {
+            path,
+            commands,
+            size,
+            visible_rect: [0.0, 0.0, size[0], size[1]],
+            device_scale: 0.0,
+        },
+        MeshMaterial2d(material),
+    ));
+    sync_path_mesh(world, entity, [0.0, 0.0, size[0], size[1]])
+}
+
+/// Re-tessellates when the node size, its visible (clipped) part, or the
+/// device pixels per logical pixel changed. `visible_rect` is node-local.
+pub fn sync_path_mesh(
+    world: &mut World,
+    entity: Entity,
+    visible_rect: [f32; 4],
+) -> Result<(), String> {
+    let Some(visual) = world.get::<AtomePathVisual>(entity) else {
+        return Ok(());
+    };
+    let size = world
+        .get::<AtomeLogicalSize>(entity)
+        .map(|size| [size.width, size.height])
+        .unwrap_or(visual.size);
+    let device_scale = device_scale(world, entity);
+    if visual.size == size
+        && visual.visible_rect == visible_rect
+        && (visual.device_scale - device_scale).abs() <= device_scale * RETESSELLATE_SCALE_RATIO
+    {
+        return Ok(());
+    }
+    let size_changed = visual.size != size;
+    let id = world
+        .get::<AtomeEntityId>(entity)
+        .map(|id| id.0.clone())
+        .unwrap_or_default();
+    let tessellation = tessellate_path(&visual.path, &visual.commands, size, device_scale)
+        .map_err(|_| format!("bevy_path_tessellation_failed:{id}"))?
+        .clipped_to(visible_rect);
+    let mesh = {
+        let mut meshes = world
+            .get_resource_mut::<Assets<Mesh>>()
+            .ok_or_else(|| "bevy_mesh_assets_required".to_string())?;
+        meshes.add(path_mesh(&tessellation, visible_rect))
+    };
+    if size_changed {
+        set_path_material_size(world, entity, size)?;
+    }
+    let mut entity_mut = world.entity_mut(entity);
+    entity_mut.insert(Mesh2d(mesh));
+    if let Some(mut visual) = entity_mut.get_mut::<AtomePathVisual>() {
+        visual.size = size;
+        visual.visible_rect = visible_rect;
+        visual.device_scale = device_scale;
+    }
+    Ok(())
+}
+
+fn path_material_handle(world: &World, entity: Entity) -> Result<Handle<PathMaterial>, String> {
+    world
+        .get::<MeshMaterial2d<PathMaterial>>(entity)
+        .map(|material| material.0.clone())
+        .ok_or_else(|| "bevy_path_component_missing".to_string())
+}
+
+fn set_path_material_size(world: &mut World, entity: Entity, size: [f32; 2]) -> Result<(), String> {
+    let handle = path_material_handle(world, entity)?;
+    let mut materials = world
+        .get_resource_mut::<Assets<PathMaterial>>()
+        .ok_or_else(|| "bevy_path_assets_required".to_string())?;
+    let mut material = materials
+        .get_mut(&handle)
+        .ok_or_else(|| "bevy_path_material_missing".to_string())?;
+    material.uniform.geometry.y = size[0];
+    material.uniform.geometry.z = size[1];
+    Ok(())
+}
+
+/// Pushes the node color or fill (the default interior paint) and opacity
+/// into the material.
+pub fn sync_path_material(world: &mut World, entity: Entity) -> Result<(), String> {
+    let Some(visual) = world.get::<AtomePathVisual>(entity) else {
+        return Ok(());
+    };
+    let path = visual.path.clone();
+    let size = visual.size;
+    let color = world
+        .get::<AtomeVisualColor>(entity)
+        .map(|value| value.0)
+        .unwrap_or([1.0, 1.0, 1.0, 1.0]);
+    let opacity = world
+        .get::<AtomeVisualOpacity>(entity)
+        .map(|value| value.0)
+        .unwrap_or(1.0);
+    let fill = world
+        .get::<AtomeNodeFill>(entity)
+        .map(|value| value.0.clone());
+    let handle = path_material_handle(world, entity)?;
+    let mut materials = world
+        .get_resource_mut::<Assets<PathMaterial>>()
+        .ok_or_else(|| "bevy_path_assets_required".to_string())?;
+    let mut material = materials
+        .get_mut(&handle)
+        .ok_or_else(|| "bevy_path_material_missing".to_string())?;
+    material.uniform = path_uniform(&path, opacity, size);
+    material.fill = path_fill_uniform(&path, color, fill.as_ref());
+    Ok(())
+}
+
+/// Replaces the geometry and paint of a `path` entity. The mesh is rebuilt
+/// by the caller's next `apply_entity_clip`.
+pub fn patch_path(
+    world: &mut World,
+    entity: Entity,
+    id: &str,
+    path: AtomePath,
+) -> Result<(), String> {
+    let commands = path_commands_for(&path, id)?;
+    let mut visual = world
+        .get_mut::<AtomePathVisual>(entity)
+        .ok_or_else(|| format!("bevy_path_component_missing:{id}"))?;
+    visual.path = path;
+    visual.commands = commands;
+    visual.device_scale = 0.0;
+    sync_path_material(world, entity)
+}
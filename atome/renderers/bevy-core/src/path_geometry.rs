@@ -0,0 +1,756 @@
+use lyon_tessellation::{
+    geom::ArcFlags,
+    math::{point, vector, Angle, Point, Vector},
+    path::{builder::SvgPathBuilder, iterator::PathIterator, Path, PathEvent},
+    BuffersBuilder, FillOptions, FillRule, FillTessellator, FillVertex, LineCap, LineJoin,
+    StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers,
+};
+
+use crate::types::{AtomeFillRule, AtomeLineCap, AtomeLineJoin, AtomePath, AtomePathCommand};
+
+/// Curve flattening error, in device pixels.
+const DEVICE_TOLERANCE: f32 = 0.25;
+const PAINT_FILL: f32 = 0.0;
+const PAINT_STROKE: f32 = 1.0;
+
+/// Triangles for one path, in node-local logical pixels (y down).
+///
+/// Edges are antialiased in the shader rather than by MSAA: every vertex
+/// carries, in `edges`, its offset from the nearest point of the outline's
+/// interior measured in fringe units (one device pixel), and `paints` holds
+/// `[paint, alpha scale, half extent, 0]` where paint 0 is the fill and 1 the
+/// stroke. Coverage is `half extent - |edge|` clamped to [0, 1], so only the
+/// outermost device pixel fades out.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PathTessellation {
+    pub positions: Vec<[f32; 2]>,
+    pub edges: Vec<[f32; 2]>,
+    pub paints: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+#[derive(Clone, Copy)]
+struct PathVertex {
+    position: [f32; 2],
+    edge: [f32; 2],
+    paint: [f32; 4],
+}
+
+impl PathTessellation {
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    fn push(&mut self, vertex: PathVertex) -> u32 {
+        self.positions.push(vertex.position);
+        self.edges.push(vertex.edge);
+        self.paints.push(vertex.paint);
+        (self.positions.len() - 1) as u32
+    }
+
+    fn vertex(&self, index: u32) -> PathVertex {
+        let index = index as usize;
+        PathVertex {
+            position: self.positions[index],
+            edge: self.edges[index],
+            paint: self.paints[index],
+        }
+    }
+
+    /// Keeps the parts of every triangle inside `rect` ([x, y, width, height]
+    /// in node-local pixels), interpolating the coverage attributes along the
+    /// cut so clipped edges stay hard like clipped sprites.
+    pub fn clipped_to(self, rect: [f32; 4]) -> Self {
+        let [left, top, width, height] = rect;
+        let (right, bottom) = (left + width, top + height);
+        let inside = |position: [f32; 2]| {
+            position[0] >= left
+                && position[0] <= right
+                && position[1] >= top
+                && position[1] <= bottom
+        };
+        if self.positions.iter().all(|position| inside(*position)) {
+            return self;
+        }
+        let mut clipped = Self::default();
+        for triangle in self.indices.chunks_exact(3) {
+            let mut polygon: Vec<PathVertex> =
+                triangle.iter().map(|index| self.vertex(*index)).collect();
+            for (axis, bound, keep_below) in [
+                (0, left, false),
+                (0, right, true),
+                (1, top, false),
+                (1, bottom, true),
+            ] {
+                polygon = clip_polygon(&polygon, axis, bound, keep_below);
+                if polygon.len() < 3 {
+                    break;
+                }
+            }
+            if polygon.len() < 3 {
+                continue;
+            }
+            let first = clipped.push(polygon[0]);
+            let mut previous = clipped.push(polygon[1]);
+            for vertex in &polygon[2..] {
+                let next = clipped.push(*vertex);
+                clipped.indices.extend_from_slice(&[first, previous, next]);
+                previous = next;
+            }
+        }
+        clipped
+    }
+
+    /// Positions relative to the centre of `rect`, y up, as expected by a
+    /// mesh whose transform is the rect transform of `rect`.
+    pub fn mesh_positions(&self, rect: [f32; 4]) -> Vec<[f32; 3]> {
+        let center = [rect[0] + rect[2] / 2.0, rect[1] + rect[3] / 2.0];
+        self.positions
+            .iter()
+            .map(|position| [position[0] - center[0], center[1] - position[1], 0.0])
+            .collect()
+    }
+}
+
+fn clip_polygon(
+    polygon: &[PathVertex],
+    axis: usize,
+    bound: f32,
+    keep_below: bool,
+) -> Vec<PathVertex> {
+    let inside = |vertex: &PathVertex| {
+        if keep_below {
+            vertex.position[axis] <= bound
+        } else {
+            vertex.position[axis] >= bound
+        }
+    };
+    let mut output = Vec::with_capacity(polygon.len() + 2);
+    for (index, current) in polygon.iter().enumerate() {
+        let previous = &polygon[(index + polygon.len() - 1) % polygon.len()];
+        if inside(current) != inside(previous) {
+            let span = current.position[axis] - previous.position[axis];
+            let t = if span.abs() > f32::EPSILON {
+                (bound - previous.position[axis]) / span
+            } else {
+                0.0
+            };
+            output.push(lerp_vertex(previous, current, t));
+        }
+        if inside(current) {
+            output.push(*current);
+        }
+    }
+    output
+}
+
+fn lerp_vertex(from: &PathVertex, to: &PathVertex, t: f32) -> PathVertex {
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    PathVertex {
+        position: [
+            lerp(from.position[0], to.position[0]),
+            lerp(from.position[1], to.position[1]),
+        ],
+        edge: [
+            lerp(from.edge[0], to.edge[0]),
+            lerp(from.edge[1], to.edge[1]),
+        ],
+        paint: [
+            lerp(from.paint[0], to.paint[0]),
+            lerp(from.paint[1], to.paint[1]),
+            lerp(from.paint[2], to.paint[2]),
+            lerp(from.paint[3], to.paint[3]),
+        ],
+    }
+}
+
+struct PathDataLexer<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl PathDataLexer<'_> {
+    fn skip_separators(&mut self) {
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| byte.is_ascii_whitespace() || *byte == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.bytes.get(self.position).copied()
+    }
+
+    fn at_number(&mut self) -> bool {
+        self.peek()
+            .is_some_and(|byte| byte.is_ascii_digit() || matches!(byte, b'-' | b'+' | b'.'))
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |lexer: &mut Self| {
+            let from = lexer.position;
+            while lexer
+                .bytes
+                .get(lexer.position)
+                .is_some_and(u8::is_ascii_digit)
+            {
+                lexer.position += 1;
+            }
+            lexer.position > from
+        };
+        if matches!(self.bytes.get(self.position), Some(b'-' | b'+')) {
+            self.position += 1;
+        }
+        let mut has_digits = digits(self);
+        if self.bytes.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            has_digits |= digits(self);
+        }
+        if !has_digits {
+            return None;
+        }
+        if matches!(self.bytes.get(self.position), Some(b'e' | b'E')) {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if matches!(self.bytes.get(self.position), Some(b'-' | b'+')) {
+                self.position += 1;
+            }
+            if !digits(self) {
+                self.position = mantissa_end;
+            }
+        }
+        std::str::from_utf8(&self.bytes[start..self.position])
+            .ok()?
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+    }
+
+    fn point(&mut self) -> Option<[f32; 2]> {
+        Some([self.number()?, self.number()?])
+    }
+
+    // Arc flags may be written without separators ("a5 5 0 015 5").
+    fn flag(&mut self) -> Option<bool> {
+        let flag = match self.peek()? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.position += 1;
+        Some(flag)
+    }
+}
+
+fn offset(point: [f32; 2], base: [f32; 2]) -> [f32; 2] {
+    [point[0] + base[0], point[1] + base[1]]
+}
+
+fn reflect(control: Option<[f32; 2]>, current: [f32; 2]) -> [f32; 2] {
+    control.map_or(current, |control| {
+        [2.0 * current[0] - control[0], 2.0 * current[1] - control[1]]
+    })
+}
+
+/// Parses SVG path data (`M`, `L`, `H`, `V`, `C`, `S`, `Q`, `T`, `A`, `Z`
+/// and their relative forms) into absolute commands. Returns `None` on the
+/// first malformed token instead of rendering a truncated path.
+pub fn parse_path_data(data: &str) -> Option<Vec<AtomePathCommand>> {
+    let mut lexer = PathDataLexer {
+        bytes: data.as_bytes(),
+        position: 0,
+    };
+    let mut commands = Vec::new();
+    let mut current = [0.0, 0.0];
+    let mut start = [0.0, 0.0];
+    let mut last_cubic_ctrl = None;
+    let mut last_quad_ctrl = None;
+    let mut pending: Option<u8> = None;
+    while let Some(byte) = lexer.peek() {
+        let letter = if byte.is_ascii_alphabetic() {
+            lexer.position += 1;
+            byte
+        } else {
+            pending?
+        };
+        let base = if letter.is_ascii_lowercase() {
+            current
+        } else {
+            [0.0, 0.0]
+        };
+        let mut cubic_ctrl = None;
+        let mut quad_ctrl = None;
+        let command = letter.to_ascii_uppercase();
+        match command {
+            b'M' => {
+                current = offset(lexer.point()?, base);
+                start = current;
+                commands.push(AtomePathCommand::MoveTo { to: current });
+                pending = Some(if letter == b'm' { b'l' } else { b'L' });
+            }
+            b'L' => {
+                current = offset(lexer.point()?, base);
+                commands.push(AtomePathCommand::LineTo { to: current });
+            }
+            b'H' => {
+                current = [lexer.number()? + base[0], current[1]];
+                commands.push(AtomePathCommand::LineTo { to: current });
+            }
+            b'V' => {
+                current = [current[0], lexer.number()? + base[1]];
+                commands.push(AtomePathCommand::LineTo { to: current });
+            }
+            b'C' | b'S' => {
+                let ctrl1 = if command == b'C' {
+                    offset(lexer.point()?, base)
+                } else {
+                    reflect(last_cubic_ctrl, current)
+                };
+                let ctrl2 = offset(lexer.point()?, base);
+                current = offset(lexer.point()?, base);
+                commands.push(AtomePathCommand::CubicTo {
+                    ctrl1,
+                    ctrl2,
+                    to: current,
+                });
+                cubic_ctrl = Some(ctrl2);
+            }
+            b'Q' | b'T' => {
+                let ctrl = if command == b'Q' {
+                    offset(lexer.point()?, base)
+                } else {
+                    reflect(last_quad_ctrl, current)
+                };
+                current = offset(lexer.point()?, base);
+                commands.push(AtomePathCommand::QuadTo { ctrl, to: current });
+                quad_ctrl = Some(ctrl);
+            }
+            b'A' => {
+                let radii = lexer.point()?;
+                let x_rotation = lexer.number()?;
+                let large_arc = lexer.flag()?;
+                let sweep = lexer.flag()?;
+                current = offset(lexer.point()?, base);
+                commands.push(AtomePathCommand::ArcTo {
+                    radii,
+                    x_rotation,
+                    large_arc,
+                    sweep,
+                    to: current,
+                });
+            }
+            b'Z' => {
+                current = start;
+                commands.push(AtomePathCommand::Close);
+                pending = None;
+            }
+            _ => return None,
+        }
+        if command != b'M' && command != b'Z' {
+            pending = Some(letter);
+        }
+        last_cubic_ctrl = cubic_ctrl;
+        last_quad_ctrl = quad_ctrl;
+        if pending.is_none() && lexer.at_number() {
+            return None;
+        }
+    }
+    Some(commands)
+}
+
+/// The outline commands of `path`, or `None` when they are malformed.
+pub fn resolve_path_commands(path: &AtomePath) -> Option<Vec<AtomePathCommand>> {
+    let commands = if path.commands.is_empty() {
+        match path.d.as_deref() {
+            Some(data) => parse_path_data(data)?,
+            None => Vec::new(),
+        }
+    } else {
+        path.commands.clone()
+    };
+    commands
+        .iter()
+        .all(AtomePathCommand::is_finite)
+        .then_some(commands)
+}
+
+/// Uniform scale and offset fitting `view_box` into `size` (`xMidYMid meet`).
+fn view_box_fit(view_box: Option<[f32; 4]>, size: [f32; 2]) -> (f32, Vector) {
+    let Some([x, y, width, height]) = view_box.filter(|view_box| {
+        view_box.iter().all(|value| value.is_finite()) && view_box[2] > 0.0 && view_box[3] > 0.0
+    }) else {
+        return (1.0, vector(0.0, 0.0));
+    };
+    let scale = (size[0] / width).min(size[1] / height);
+    (
+        scale,
+        vector(
+            (size[0] - width * scale) / 2.0 - x * scale,
+            (size[1] - height * scale) / 2.0 - y * scale,
+        ),
+    )
+}
+
+fn build_outline(commands: &[AtomePathCommand], scale: f32, shift: Vector) -> Path {
+    let map = |value: [f32; 2]| point(value[0] * scale, value[1] * scale) + shift;
+    let mut builder = Path::builder().with_svg();
+    for command in commands {
+        match *command {
+            AtomePathCommand::MoveTo { to } => {
+                builder.move_to(map(to));
+            }
+            AtomePathCommand::LineTo { to } => {
+                builder.line_to(map(to));
+            }
+            AtomePathCommand::QuadTo { ctrl, to } => {
+                builder.quadratic_bezier_to(map(ctrl), map(to));
+            }
+            AtomePathCommand::CubicTo { ctrl1, ctrl2, to } => {
+                builder.cubic_bezier_to(map(ctrl1), map(ctrl2), map(to));
+            }
+            AtomePathCommand::ArcTo {
+                radii,
+                x_rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                builder.arc_to(
+                    vector(radii[0].abs() * scale, radii[1].abs() * scale),
+                    Angle::degrees(x_rotation),
+                    ArcFlags { large_arc, sweep },
+                    map(to),
+                );
+            }
+            AtomePathCommand::Close => builder.close(),
+        }
+    }
+    builder.build()
+}
+
+struct Polyline {
+    points: Vec<Point>,
+    closed: bool,
+}
+
+fn flatten(outline: &Path, tolerance: f32) -> Vec<Polyline> {
+    let mut polylines = Vec::new();
+    let mut points = Vec::new();
+    for event in outline.iter().flattened(tolerance) {
+        match event {
+            PathEvent::Begin { at } => points = vec![at],
+            PathEvent::Line { to, .. } => points.push(to),
+            PathEvent::End { close, .. } => polylines.push(Polyline {
+                points: std::mem::take(&mut points),
+                closed: close,
+            }),
+            PathEvent::Quadratic { to, .. } | PathEvent::Cubic { to, .. } => points.push(to),
+        }
+    }
+    polylines
+}
+
+fn is_inside(sample: Point, polylines: &[Polyline], rule: AtomeFillRule) -> bool {
+    let mut winding = 0;
+    for polyline in polylines {
+        let count = polyline.points.len();
+        for index in 0..count {
+            let from = polyline.points[index];
+            let to = polyline.points[(index + 1) % count];
+            let side = (to - from).cross(sample - from);
+            if from.y <= sample.y {
+                if to.y > sample.y && side > 0.0 {
+                    winding += 1;
+                }
+            } else if to.y <= sample.y && side < 0.0 {
+                winding -= 1;
+            }
+        }
+    }
+    match rule {
+        AtomeFillRule::NonZero => winding != 0,
+        AtomeFillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+fn miter_offset(previous: Option<Vector>, normal: Vector) -> Vector {
+    match previous {
+        // Turns sharper than 120° keep the edge normal; the gap that leaves is
+        // narrower than the fringe.
+        Some(previous) if 1.0 + previous.dot(normal) > 0.5 => {
+            (previous + normal) / (1.0 + previous.dot(normal))
+        }
+        _ => normal,
+    }
+}
+
+/// Outward-facing fringe quads along every fill edge that separates inside
+/// from outside, judged per edge so holes and self-overlaps fringe correctly.
+/// The fill itself stays solid up to the outline, so the fringe starts at half
+/// coverage on the edge and fades out over the next half device pixel.
+fn push_fill_fringe(
+    output: &mut PathTessellation,
+    polylines: &[Polyline],
+    rule: AtomeFillRule,
+    fringe: f32,
+    alpha: f32,
+) {
+    let probe = fringe * 0.05;
+    for polyline in polylines {
+        let mut points = polyline.points.clone();
+        if points.len() > 2 && (points[0] - points[points.len() - 1]).length() < 1e-6 {
+            points.pop();
+        }
+        let count = points.len();
+        if count < 3 {
+            continue;
+        }
+        let normals: Vec<Option<Vector>> = (0..count)
+            .map(|index| {
+                let from = points[index];
+                let to = points[(index + 1) % count];
+                let direction = to - from;
+                let length = direction.length();
+                if length < 1e-6 {
+                    return None;
+                }
+                let left = vector(-direction.y, direction.x) / length;
+                let middle = from.lerp(to, 0.5);
+                let left_inside = is_inside(middle + left * probe, polylines, rule);
+                let right_inside = is_inside(middle - left * probe, polylines, rule);
+                match (left_inside, right_inside) {
+                    (true, false) => Some(-left),
+                    (false, true) => Some(left),
+                    _ => None,
+                }
+            })
+            .collect();
+        for index in 0..count {
+            let Some(normal) = normals[index] else {
+                continue;
+            };
+            let previous = normals[(index + count - 1) % count];
+            let next = normals[(index + 1) % count];
+            let start_offset = miter_offset(previous, normal) * fringe * 0.5;
+            let end_offset = miter_offset(next, normal) * fringe * 0.5;
+            let from = points[index];
+            let to = points[(index + 1) % count];
+            let paint = [PAINT_FILL, alpha, 1.0, 0.0];
+            let corners = [
+                (from, [0.5, 0.0]),
+                (to, [0.5, 0.0]),
+                (from + start_offset, [1.0, 0.0]),
+                (to + end_offset, [1.0, 0.0]),
+            ];
+            let base = output.positions.len() as u32;
+            for (position, edge) in corners {
+                output.push(PathVertex {
+                    position: position.to_array(),
+                    edge,
+                    paint,
+                });
+            }
+            output.indices.extend_from_slice(&[
+                base,
+                base + 1,
+                base + 2,
+                base + 1,
+                base + 3,
+                base + 2,
+            ]);
+        }
+    }
+}
+
+fn dash_polylines(polylines: &[Polyline], pattern: &[f32], dash_offset: f32) -> Vec<Vec<Point>> {
+    let total: f32 = pattern.iter().sum();
+    let mut dashes = Vec::new();
+    for polyline in polylines {
+        let Some(first) = polyline.points.first().copied() else {
+            continue;
+        };
+        let mut phase = if dash_offset.is_finite() {
+            dash_offset.rem_euclid(total)
+        } else {
+            0.0
+        };
+        let mut index = 0;
+        while phase >= pattern[index] {
+            phase -= pattern[index];
+            index = (index + 1) % pattern.len();
+        }
+        let mut remaining = pattern[index] - phase;
+        let mut current = if index % 2 == 0 {
+            vec![first]
+        } else {
+            Vec::new()
+        };
+        let mut points = polyline.points.clone();
+        if polyline.closed {
+            points.push(first);
+        }
+        for segment in points.windows(2) {
+            let (from, to) = (segment[0], segment[1]);
+            let length = (to - from).length();
+            let mut travelled = 0.0;
+            while length - travelled > remaining {
+                travelled += remaining;
+                let split = from.lerp(to, travelled / length);
+                if index % 2 == 0 {
+                    current.push(split);
+                    dashes.push(std::mem::take(&mut current));
+                } else {
+                    current = vec![split];
+                }
+                index = (index + 1) % pattern.len();
+                remaining = pattern[index];
+            }
+            remaining -= length - travelled;
+            if index % 2 == 0 {
+                current.push(to);
+            }
+        }
+        if index % 2 == 0 && current.len() >= 2 {
+            dashes.push(current);
+        }
+    }
+    dashes
+}
+
+fn stroke_options(path: &AtomePath, width: f32, tolerance: f32) -> StrokeOptions {
+    let cap = match path.line_cap {
+        AtomeLineCap::Butt => LineCap::Butt,
+        AtomeLineCap::Round => LineCap::Round,
+        AtomeLineCap::Square => LineCap::Square,
+    };
+    let join = match path.line_join {
+        AtomeLineJoin::Miter => LineJoin::Miter,
+        AtomeLineJoin::Round => LineJoin::Round,
+        AtomeLineJoin::Bevel => LineJoin::Bevel,
+    };
+    let miter_limit = if path.miter_limit.is_finite() {
+        path.miter_limit.max(1.0)
+    } else {
+        4.0
+    };
+    StrokeOptions::tolerance(tolerance)
+        .with_line_width(width)
+        .with_line_join(join)
+        .with_line_cap(cap)
+        .with_miter_limit(miter_limit)
+}
+
+/// Tessellates `path` for a node of `size` logical pixels drawn at
+/// `device_scale` device pixels per logical pixel (device pixel ratio times
+/// node scale). Flattening tolerance and the antialiasing fringe both follow
+/// the device scale, so callers re-tessellate when it changes to stay crisp.
+pub fn tessellate_path(
+    path: &AtomePath,
+    commands: &[AtomePathCommand],
+    size: [f32; 2],
+    device_scale: f32,
+) -> Result<PathTessellation, String> {
+    let device_scale = if device_scale.is_finite() && device_scale > 0.0 {
+        device_scale
+    } else {
+        1.0
+    };
+    let tolerance = DEVICE_TOLERANCE / device_scale;
+    let fringe = 1.0 / device_scale;
+    let (scale, shift) = view_box_fit(path.view_box, size);
+    let outline = build_outline(commands, scale, shift);
+    let mut output = PathTessellation::default();
+
+    if path.fill.is_some() || !path.has_stroke() {
+        let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+        let rule = match path.fill_rule {
+            AtomeFillRule::NonZero => FillRule::NonZero,
+            AtomeFillRule::EvenOdd => FillRule::EvenOdd,
+        };
+        FillTessellator::new()
+            .tessellate_path(
+                &outline,
+                &FillOptions::tolerance(tolerance).with_fill_rule(rule),
+                &mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| {
+                    vertex.position().to_array()
+                }),
+            )
+            .map_err(|error| format!("{error:?}"))?;
+        for position in buffers.vertices {
+            output.push(PathVertex {
+                position,
+                edge: [0.0, 0.0],
+                paint: [PAINT_FILL, 1.0, 1.0, 0.0],
+            });
+        }
+        output.indices.extend(buffers.indices);
+        push_fill_fringe(
+            &mut output,
+            &flatten(&outline, tolerance),
+            path.fill_rule,
+            fringe,
+            1.0,
+        );
+    }
+
+    if path.has_stroke() {
+        let width = path.stroke_width();
+        // Strokes thinner than a device pixel are drawn one pixel wide at
+        // proportionally lower alpha rather than dropping out.
+        let drawn_width = width.max(fringe);
+        let alpha = (width / drawn_width).min(1.0);
+        let half_extent = (drawn_width + fringe) / 2.0;
+        let options = stroke_options(path, drawn_width + fringe, tolerance);
+        let stroked = match path.dash_pattern() {
+            Some(pattern) => {
+                let mut builder = Path::builder();
+                for dash in
+                    dash_polylines(&flatten(&outline, tolerance), &pattern, path.dash_offset)
+                {
+                    builder.begin(dash[0]);
+                    for point in &dash[1..] {
+                        builder.line_to(*point);
+                    }
+                    builder.end(false);
+                }
+                builder.build()
+            }
+            None => outline,
+        };
+        let mut buffers: VertexBuffers<PathVertex, u32> = VertexBuffers::new();
+        StrokeTessellator::new()
+            .tessellate_path(
+                &stroked,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |vertex: StrokeVertex| {
+                    let position = vertex.position();
+                    // Capping the offset at the half extent keeps miter tips
+                    // solid; round caps and joins get exact radial coverage.
+                    let mut edge = position - vertex.position_on_path();
+                    if edge.length() > half_extent {
+                        edge = edge.normalize() * half_extent;
+                    }
+                    PathVertex {
+                        position: position.to_array(),
+                        edge: (edge / fringe).to_array(),
+                        paint: [PAINT_STROKE, alpha, half_extent / fringe, 0.0],
+                    }
+                }),
+            )
+            .map_err(|error| format!("{error:?}"))?;
+        let base = output.positions.len() as u32;
+        for vertex in buffers.vertices {
+            output.push(vertex);
+        }
+        output
+            .indices
+            .extend(buffers.indices.into_iter().map(|index| index + base));
+    }
+    Ok(output)
+}
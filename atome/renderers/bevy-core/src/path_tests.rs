@@ -0,0 +1,359 @@
+use bevy::{image::Image, mesh::Mesh, prelude::*, sprite_render::MeshMaterial2d};
+
+use crate::{
+    apply_path, apply_spawn, apply_style, apply_transform,
+    path::{AtomePathVisual, PathMaterial},
+    path_geometry::{parse_path_data, resolve_path_commands, tessellate_path, PathTessellation},
+    types::*,
+};
+
+const CIRCLE: &str = "M10 2 A8 8 0 1 1 10 18 A8 8 0 1 1 10 2 Z";
+
+fn tessellate(path: &AtomePath, size: [f32; 2], device_scale: f32) -> PathTessellation {
+    let commands = resolve_path_commands(path).unwrap();
+    tessellate_path(path, &commands, size, device_scale).unwrap()
+}
+
+fn bounds(tessellation: &PathTessellation) -> [f32; 4] {
+    tessellation.positions.iter().fold(
+        [f32::MAX, f32::MAX, f32::MIN, f32::MIN],
+        |bounds, position| {
+            [
+                bounds[0].min(position[0]),
+                bounds[1].min(position[1]),
+                bounds[2].max(position[0]),
+                bounds[3].max(position[1]),
+            ]
+        },
+    )
+}
+
+fn path_node(id: &str, path: AtomePath) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: "path".to_string(),
+        parent_id: None,
+        logical_position: [40.0, 30.0],
+        logical_size: [20.0, 20.0],
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.0, 0.0],
+        layer: 2,
+        opacity: 1.0,
+        corner_radius: 0.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([1.0, 0.0, 0.0, 1.0]),
+        text: None,
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: Some(path),
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(AtomeEntityTable::default());
+    world.insert_resource(AtomeBevyRendererConfig::empty(640.0, 480.0));
+    world.insert_resource(AtomeRendererDiagnostics::default());
+    world.insert_resource(Assets::<Image>::default());
+    world.insert_resource(Assets::<Mesh>::default());
+    world.insert_resource(Assets::<PathMaterial>::default());
+    world
+}
+
+fn mesh_handle(world: &World, entity: Entity) -> Handle<Mesh> {
+    world.get::<Mesh2d>(entity).unwrap().0.clone()
+}
+
+fn fill_uniform(world: &World, entity: Entity) -> Vec4 {
+    let handle = world
+        .get::<MeshMaterial2d<PathMaterial>>(entity)
+        .unwrap()
+        .0
+        .clone();
+    world
+        .resource::<Assets<PathMaterial>>()
+        .get(&handle)
+        .unwrap()
+        .fill
+        .colors[0]
+}
+
+#[test]
+fn path_data_parses_relative_shorthand_and_compact_arc_flags() {
+    let commands =
+        parse_path_data("m10 10h5v5H10z M0,0 c1 1 2 2 3 3 s1 1 2 2 q1-1 2 0t2 0 a5 5 0 015 5")
+            .unwrap();
+    assert_eq!(
+        commands,
+        vec![
+            AtomePathCommand::MoveTo { to: [10.0, 10.0] },
+            AtomePathCommand::LineTo { to: [15.0, 10.0] },
+            AtomePathCommand::LineTo { to: [15.0, 15.0] },
+            AtomePathCommand::LineTo { to: [10.0, 15.0] },
+            AtomePathCommand::Close,
+            AtomePathCommand::MoveTo { to: [0.0, 0.0] },
+            AtomePathCommand::CubicTo {
+                ctrl1: [1.0, 1.0],
+                ctrl2: [2.0, 2.0],
+                to: [3.0, 3.0],
+            },
+            AtomePathCommand::CubicTo {
+                ctrl1: [4.0, 4.0],
+                ctrl2: [4.0, 4.0],
+                to: [5.0, 5.0],
+            },
+            AtomePathCommand::QuadTo {
+                ctrl: [6.0, 4.0],
+                to: [7.0, 5.0],
+            },
+            AtomePathCommand::QuadTo {
+                ctrl: [8.0, 6.0],
+                to: [9.0, 5.0],
+            },
+            AtomePathCommand::ArcTo {
+                radii: [5.0, 5.0],
+                x_rotation: 0.0,
+                large_arc: false,
+                sweep: true,
+                to: [14.0, 10.0],
+            },
+        ]
+    );
+    assert_eq!(
+        parse_path_data("M1.5.5-2e1 3").unwrap(),
+        vec![
+            AtomePathCommand::MoveTo { to: [1.5, 0.5] },
+            AtomePathCommand::LineTo { to: [-20.0, 3.0] },
+        ]
+    );
+    assert!(parse_path_data("10 10").is_none());
+    assert!(parse_path_data("M1 1 L").is_none());
+    assert!(parse_path_data("M1 1 Z 2 2").is_none());
+    assert!(parse_path_data("M1 1 X 2").is_none());
+}
+
+#[test]
+fn fill_fringe_is_half_a_device_pixel_at_every_scale() {
+    let square = AtomePath {
+        d: Some("M5 5 H15 V15 H5 Z".to_string()),
+        ..AtomePath::default()
+    };
+    let at_one = tessellate(&square, [20.0, 20.0], 1.0);
+    let at_four = tessellate(&square, [20.0, 20.0], 4.0);
+    for (tessellation, fringe) in [(&at_one, 0.5), (&at_four, 0.125)] {
+        let [left, top, right, bottom] = bounds(tessellation);
+        for (actual, expected) in [
+            (left, 5.0 - fringe),
+            (top, 5.0 - fringe),
+            (right, 15.0 + fringe),
+            (bottom, 15.0 + fringe),
+        ] {
+            assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+        }
+        assert!(tessellation.paints.iter().all(|paint| paint[0] == 0.0));
+    }
+
+    let circle = AtomePath {
+        d: Some(CIRCLE.to_string()),
+        ..AtomePath::default()
+    };
+    assert!(
+        tessellate(&circle, [20.0, 20.0], 4.0).positions.len()
+            > tessellate(&circle, [20.0, 20.0], 1.0).positions.len(),
+        "zooming in must flatten curves more finely"
+    );
+}
+
+#[test]
+fn strokes_cover_their_width_and_thin_strokes_fade_instead_of_vanishing() {
+    let mut line = AtomePath {
+        d: Some("M2 10 H22".to_string()),
+        stroke: Some([0.0, 0.0, 1.0, 1.0]),
+        stroke_width: 4.0,
+        ..AtomePath::default()
+    };
+    let thick = tessellate(&line, [24.0, 20.0], 1.0);
+    assert!(thick.paints.iter().all(|paint| paint[0] == 1.0));
+    // Drawn half a device pixel wider on each side, then faded by coverage.
+    let [left, top, right, bottom] = bounds(&thick);
+    assert_eq!([left, right], [2.0, 22.0]);
+    assert!((top - 7.5).abs() < 1e-4 && (bottom - 12.5).abs() < 1e-4);
+    assert!(thick
+        .paints
+        .iter()
+        .all(|paint| paint[1] == 1.0 && (paint[2] - 2.5).abs() < 1e-4));
+
+    line.stroke_width = 0.25;
+    let hairline = tessellate(&line, [24.0, 20.0], 1.0);
+    assert!(!hairline.is_empty());
+    assert!(hairline
+        .paints
+        .iter()
+        .all(|paint| (paint[1] - 0.25).abs() < 1e-4));
+
+    line.stroke_width = 2.0;
+    line.dash = vec![4.0];
+    let solid_triangles = tessellate(
+        &AtomePath {
+            dash: Vec::new(),
+            ..line.clone()
+        },
+        [24.0, 20.0],
+        1.0,
+    )
+    .indices
+    .len();
+    // 20px at a 4px on/off pattern leaves three dashes shaped like the solid run.
+    assert_eq!(
+        tessellate(&line, [24.0, 20.0], 1.0).indices.len(),
+        solid_triangles * 3
+    );
+}
+
+#[test]
+fn clipping_keeps_triangles_inside_the_visible_rect() {
+    let square = AtomePath {
+        d: Some("M0 0 H20 V20 H0 Z".to_string()),
+        fill: Some([1.0, 1.0, 1.0, 1.0]),
+        stroke: Some([0.0, 0.0, 0.0, 1.0]),
+        stroke_width: 2.0,
+        ..AtomePath::default()
+    };
+    let clipped = tessellate(&square, [20.0, 20.0], 1.0).clipped_to([5.0, 0.0, 10.0, 20.0]);
+    assert!(!clipped.is_empty());
+    let [left, top, right, bottom] = bounds(&clipped);
+    assert!(left >= 5.0 && right <= 15.0 && top >= 0.0 && bottom <= 20.0);
+    let centred = clipped.mesh_positions([5.0, 0.0, 10.0, 20.0]);
+    assert!(centred
+        .iter()
+        .all(|position| position[0].abs() <= 5.0 && position[1].abs() <= 10.0));
+}
+
+#[test]
+fn path_node_spawns_a_mesh_and_follows_style_scale_and_path_patches() {
+    let mut world = world();
+    let entity = apply_spawn(
+        &mut world,
+        path_node(
+            "arrow",
+            AtomePath {
+                d: Some(CIRCLE.to_string()),
+                ..AtomePath::default()
+            },
+        ),
+    )
+    .unwrap();
+    assert!(world.get::<AtomePathVisual>(entity).is_some());
+    let spawned_mesh = mesh_handle(&world, entity);
+    // With no fill or stroke the node color is the fill.
+    assert_eq!(fill_uniform(&world, entity), Vec4::new(1.0, 0.0, 0.0, 1.0));
+
+    apply_style(
+        &mut world,
+        AtomeStylePatch {
+            id: "arrow".to_string(),
+            color: Some([0.0, 1.0, 0.0, 1.0]),
+            shadow: None,
+            backdrop: None,
+            selected: None,
+            opacity: None,
+            playback_progress: None,
+            filters: None,
+            transition: None,
+            procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(fill_uniform(&world, entity), Vec4::new(0.0, 1.0, 0.0, 1.0));
+    assert_eq!(
+        mesh_handle(&world, entity),
+        spawned_mesh,
+        "recoloring must not re-tessellate"
+    );
+
+    apply_transform(
+        &mut world,
+        AtomeTransformPatch {
+            id: "arrow".to_string(),
+            logical_position: [40.0, 30.0],
+            logical_size: [20.0, 20.0],
+            scale: [4.0, 4.0],
+            rotation: 0.0,
+            origin: [0.5, 0.5],
+            clip_rect: None,
+        },
+    )
+    .unwrap();
+    let zoomed_mesh = mesh_handle(&world, entity);
+    assert_ne!(
+        zoomed_mesh, spawned_mesh,
+        "zooming must re-tessellate at the new scale"
+    );
+    assert_eq!(
+        world.get::<AtomePathVisual>(entity).unwrap().device_scale,
+        4.0
+    );
+
+    apply_path(
+        &mut world,
+        AtomePathPatch {
+            id: "arrow".to_string(),
+            path: AtomePath {
+                d: Some("M0 10 L20 10".to_string()),
+                stroke: Some([0.0, 0.0, 1.0, 1.0]),
+                stroke_width: 2.0,
+                ..AtomePath::default()
+            },
+        },
+    )
+    .unwrap();
+    assert_ne!(mesh_handle(&world, entity), zoomed_mesh);
+    assert_eq!(fill_uniform(&world, entity), Vec4::ZERO);
+
+    let error = apply_path(
+        &mut world,
+        AtomePathPatch {
+            id: "arrow".to_string(),
+            path: AtomePath {
+                d: Some("M0 0 L".to_string()),
+                ..AtomePath::default()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(error, "bevy_path_data_invalid:arrow");
+    assert_eq!(
+        apply_spawn(
+            &mut world,
+            AtomeRenderNode {
+                path: None,
+                ..path_node("bare", AtomePath::default())
+            }
+        )
+        .unwrap_err(),
+        "bevy_path_contract_required:bare"
+    );
+}
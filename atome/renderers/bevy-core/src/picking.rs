@@ -0,0 +1,330 @@
+use bevy::{image::Image, prelude::*};
+
+use crate::{
+    clip::intersection,
+    render_math::depth_for_layer,
+    text_layout::AtomeTextLayout,
+    texture::{corner_radii_are_zero, rounded_rect_signed_distance, AtomeCornerRadii},
+    types::*,
+    viewport::viewport,
+};
+
+/// Segments approximating each rounded corner when a rect query tests a node
+/// shape; fine enough that a marquee grazing a corner agrees with the pixels.
+const PICK_CORNER_SEGMENTS: usize = 6;
+
+// A pickable node in its pre-transform logical space. `visible` is the node
+// rect cropped by its clip rect, which is what the renderer transforms, so
+// the pivot is derived from it exactly as `apply_entity_clip` does.
+struct PickTarget {
+    entity: Entity,
+    id: String,
+    layer: i32,
+    spawn_order: u64,
+    original: [f32; 4],
+    visible: [f32; 4],
+    radii: AtomeCornerRadii,
+    pivot: Vec2,
+    scale: Vec2,
+    rotation: f32,
+}
+
+impl PickTarget {
+    /// Maps a surface point (y down) into the node's pre-transform space by
+    /// undoing its rotation and scale about the pivot.
+    fn to_node(&self, point: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let offset = point - self.pivot;
+        let unrotated = Vec2::new(
+            offset.x * cos + offset.y * sin,
+            -offset.x * sin + offset.y * cos,
+        );
+        self.pivot + unrotated / self.scale
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        let [x, y, width, height] = self.visible;
+        if point.x < x || point.y < y || point.x >= x + width || point.y >= y + height {
+            return false;
+        }
+        if corner_radii_are_zero(self.radii) {
+            return true;
+        }
+        let [left, top, width, height] = self.original;
+        rounded_rect_signed_distance(point.x - left, point.y - top, width, height, self.radii)
+            <= 0.0
+    }
+
+    fn outline(&self) -> Vec<Vec2> {
+        let [left, top, width, height] = self.original;
+        let [right, bottom] = [left + width, top + height];
+        let limit = (width / 2.0).min(height / 2.0).max(0.0);
+        let radii = self.radii.map(|radius| radius.max(0.0).min(limit));
+        // Corner centers clockwise from the top left, each with the angle its
+        // quarter arc starts at in y-down space.
+        let corners = [
+            (
+                Vec2::new(left + radii[0], top + radii[0]),
+                radii[0],
+                180.0_f32,
+            ),
+            (Vec2::new(right - radii[1], top + radii[1]), radii[1], 270.0),
+            (
+                Vec2::new(right - radii[2], bottom - radii[2]),
+                radii[2],
+                0.0,
+            ),
+            (
+                Vec2::new(left + radii[3], bottom - radii[3]),
+                radii[3],
+                90.0,
+            ),
+        ];
+        let mut outline = Vec::with_capacity(4 * (PICK_CORNER_SEGMENTS + 1));
+        for (center, radius, start) in corners {
+            if radius <= 0.0 {
+                outline.push(center);
+                continue;
+            }
+            for step in 0..=PICK_CORNER_SEGMENTS {
+                let angle = (start + 90.0 * step as f32 / PICK_CORNER_SEGMENTS as f32).to_radians();
+                outline.push(center + Vec2::new(angle.cos(), angle.sin()) * radius);
+            }
+        }
+        outline
+    }
+}
+
+fn node_target(world: &World, id: &str, entity: Entity) -> Option<PickTarget> {
+    let position = world.get::<AtomeLogicalPosition>(entity)?;
+    let size = world.get::<AtomeLogicalSize>(entity)?;
+    let local = world
+        .get::<AtomeLocalTransform>(entity)
+        .copied()
+        .unwrap_or_default();
+    if local.scale[0] == 0.0 || local.scale[1] == 0.0 {
+        return None;
+    }
+    let original = [position.x, position.y, size.width, size.height];
+    let clip = world.get::<AtomeClipRect>(entity).and_then(|value| value.0);
+    // Laid-out text is never cropped, matching `sync_text_layout`.
+    let visible = match clip {
+        Some(_) if world.get::<AtomeTextLayout>(entity).is_some() => original,
+        Some(clip) => intersection(original, clip)?,
+        None => original,
+    };
+    let pivot = Vec2::new(
+        visible[0] + visible[2] / 2.0 + (local.origin[0] - 0.5) * visible[2].max(1.0),
+        visible[1] + visible[3] / 2.0 + (local.origin[1] - 0.5) * visible[3].max(1.0),
+    );
+    Some(PickTarget {
+        entity,
+        id: id.to_string(),
+        layer: world
+            .get::<AtomeLayer>(entity)
+            .map(|value| value.0)
+            .unwrap_or(0),
+        spawn_order: world
+            .get::<AtomeSpawnOrder>(entity)
+            .map_or(0, |value| value.0),
+        original,
+        visible,
+        radii: world
+            .get::<AtomeCornerRadius>(entity)
+            .map(|value| value.0)
+            .unwrap_or_default(),
+        pivot,
+        scale: Vec2::new(local.scale[0], local.scale[1]),
+        rotation: local.rotation.to_radians(),
+    })
+}
+
+fn pick_target(world: &World, id: &str, entity: Entity) -> Option<PickTarget> {
+    if world.get::<Visibility>(entity) == Some(&Visibility::Hidden) {
+        return None;
+    }
+    node_target(world, id, entity)
+}
+
+/// Axis-aligned logical scene bounds `[x, y, width, height]` of what the node
+/// draws, after its local transform and clip rect.
+pub(crate) fn node_scene_bounds(world: &World, entity: Entity) -> Option<[f32; 4]> {
+    let target = node_target(world, "", entity)?;
+    let [x, y, width, height] = target.visible;
+    let (sin, cos) = target.rotation.sin_cos();
+    let (mut min, mut max) = (Vec2::INFINITY, Vec2::NEG_INFINITY);
+    for corner in [
+        Vec2::new(x, y),
+        Vec2::new(x + width, y),
+        Vec2::new(x + width, y + height),
+        Vec2::new(x, y + height),
+    ] {
+        let scaled = (corner - target.pivot) * target.scale;
+        let point = target.pivot
+            + Vec2::new(
+                scaled.x * cos - scaled.y * sin,
+                scaled.x * sin + scaled.y * cos,
+            );
+        min = min.min(point);
+        max = max.max(point);
+    }
+    Some([min.x, min.y, max.x - min.x, max.y - min.y])
+}
+
+// The scene point under a logical surface point, through the viewport.
+fn scene_point(world: &World, point: Vec2) -> Vec2 {
+    let surface = world
+        .get_resource::<AtomeBevyRendererConfig>()
+        .map_or([1.0, 1.0], |config| [config.width, config.height]);
+    Vec2::from(viewport(world).surface_to_scene(point.into(), surface))
+}
+
+// Visible scene nodes, topmost first. Nodes sharing a layer draw in spawn
+// order, so the most recently spawned one is on top.
+fn pick_targets(world: &World) -> Vec<PickTarget> {
+    let Some(table) = world.get_resource::<AtomeEntityTable>() else {
+        return Vec::new();
+    };
+    let mut targets = table
+        .by_id
+        .iter()
+        .filter_map(|(id, entity)| pick_target(world, id, *entity))
+        .collect::<Vec<_>>();
+    targets.sort_by(|a, b| {
+        depth_for_layer(b.layer)
+            .total_cmp(&depth_for_layer(a.layer))
+            .then_with(|| b.spawn_order.cmp(&a.spawn_order))
+    });
+    targets
+}
+
+fn texture_alpha_at(world: &World, target: &PickTarget, point: Vec2) -> Option<f32> {
+    let sprite = world.get::<Sprite>(target.entity)?;
+    let image = world.get_resource::<Assets<Image>>()?.get(&sprite.image)?;
+    let (width, height) = (image.width(), image.height());
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let base = world
+        .get::<AtomeSpriteSourceRect>(target.entity)
+        .and_then(|value| value.0)
+        .unwrap_or(Rect::from_corners(
+            Vec2::ZERO,
+            Vec2::new(width as f32, height as f32),
+        ));
+    let [left, top, node_width, node_height] = target.original;
+    let uv = Vec2::new(
+        (point.x - left) / node_width.max(f32::EPSILON),
+        (point.y - top) / node_height.max(f32::EPSILON),
+    );
+    let texel = base.min + base.size() * uv;
+    let x = (texel.x.max(0.0) as u32).min(width - 1);
+    let y = (texel.y.max(0.0) as u32).min(height - 1);
+    image.get_color_at(x, y).ok().map(|color| color.alpha())
+}
+
+/// Ids of the visible nodes under the logical surface point, topmost first.
+/// The point is taken through the current viewport, so pointer coordinates
+/// can be passed as they are.
+/// Hit shapes follow each node's rotation, scale and origin, its clip rect
+/// and its rounded corners. Textures without CPU-side pixels, such as video
+/// frames, count as opaque.
+pub fn pick_at(world: &World, x: f32, y: f32, options: AtomePickOptions) -> Vec<String> {
+    let surface_point = Vec2::new(x, y);
+    if !surface_point.is_finite() {
+        return Vec::new();
+    }
+    let scene = scene_point(world, surface_point);
+    pick_targets(world)
+        .into_iter()
+        .filter(|target| {
+            let point = target.to_node(scene);
+            target.contains(point)
+                && (!options.texture_alpha
+                    || texture_alpha_at(world, target, point)
+                        .is_none_or(|alpha| alpha >= options.alpha_threshold))
+        })
+        .map(|target| target.id)
+        .collect()
+}
+
+/// Ids of the visible nodes whose shape intersects the logical surface rect
+/// `[x, y, width, height]`, topmost first. Marquee selection tests shapes
+/// only; texture alpha applies to point picks.
+pub fn pick_in_rect(world: &World, rect: [f32; 4]) -> Vec<String> {
+    if rect.iter().any(|value| !value.is_finite()) || rect[2] < 0.0 || rect[3] < 0.0 {
+        return Vec::new();
+    }
+    let [x, y, width, height] = rect;
+    let corners = [
+        Vec2::new(x, y),
+        Vec2::new(x + width, y),
+        Vec2::new(x + width, y + height),
+        Vec2::new(x, y + height),
+    ]
+    .map(|corner| scene_point(world, corner));
+    pick_targets(world)
+        .into_iter()
+        .filter(|target| {
+            let query = corners.map(|corner| target.to_node(corner));
+            let cropped = clip_polygon(&query, target.visible);
+            !cropped.is_empty()
+                && (corner_radii_are_zero(target.radii)
+                    || convex_polygons_overlap(&cropped, &target.outline()))
+        })
+        .map(|target| target.id)
+        .collect()
+}
+
+// Sutherland-Hodgman against the four edges of an axis-aligned rect. The
+// result is convex whenever the input is.
+fn clip_polygon(polygon: &[Vec2], rect: [f32; 4]) -> Vec<Vec2> {
+    let [left, top, width, height] = rect;
+    let edges: [(Vec2, f32); 4] = [
+        (Vec2::X, left),
+        (Vec2::NEG_X, -(left + width)),
+        (Vec2::Y, top),
+        (Vec2::NEG_Y, -(top + height)),
+    ];
+    let mut output = polygon.to_vec();
+    for (normal, offset) in edges {
+        let input = std::mem::take(&mut output);
+        let inside = |point: Vec2| normal.dot(point) >= offset;
+        for (index, &current) in input.iter().enumerate() {
+            let previous = input[(index + input.len() - 1) % input.len()];
+            if inside(current) != inside(previous) {
+                let from = normal.dot(previous) - offset;
+                let to = normal.dot(current) - offset;
+                output.push(previous + (current - previous) * (from / (from - to)));
+            }
+            if inside(current) {
+                output.push(current);
+            }
+        }
+    }
+    output
+}
+
+// Separating axis test; touching polygons count as overlapping.
+fn convex_polygons_overlap(a: &[Vec2], b: &[Vec2]) -> bool {
+    let project = |polygon: &[Vec2], axis: Vec2| {
+        polygon
+            .iter()
+            .map(|point| axis.dot(*point))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            })
+    };
+    [a, b].into_iter().all(|polygon| {
+        (0..polygon.len()).all(|index| {
+            let edge = polygon[(index + 1) % polygon.len()] - polygon[index];
+            if edge.length_squared() <= f32::EPSILON {
+                return true;
+            }
+            let axis = edge.perp();
+            let (a_min, a_max) = project(a, axis);
+            let (b_min, b_max) = project(b, axis);
+            a_max >= b_min && b_max >= a_min
+        })
+    })
+}
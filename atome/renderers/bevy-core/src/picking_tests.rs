@@ -0,0 +1,251 @@
+use bevy::{image::Image, prelude::*};
+
+use crate::{
+    apply_render_op, apply_spawn,
+    picking::{pick_at, pick_in_rect},
+    types::*,
+};
+
+fn shape_node(id: &str, position: [f32; 2], size: [f32; 2], layer: i32) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: "shape".to_string(),
+        parent_id: None,
+        logical_position: position,
+        logical_size: size,
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.5, 0.5],
+        layer,
+        opacity: 1.0,
+        corner_radius: 0.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([0.2, 0.4, 0.6, 1.0]),
+        text: None,
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(AtomeEntityTable::default());
+    world.insert_resource(AtomeBevyRendererConfig::empty(640.0, 480.0));
+    world.insert_resource(AtomeRendererDiagnostics::default());
+    world.insert_resource(Assets::<Image>::default());
+    world
+}
+
+fn picked(world: &World, x: f32, y: f32) -> Vec<String> {
+    pick_at(world, x, y, AtomePickOptions::default())
+}
+
+#[test]
+fn pick_at_orders_hits_by_layer_and_skips_hidden_nodes() {
+    let mut world = world();
+    apply_spawn(
+        &mut world,
+        shape_node("bottom", [0.0, 0.0], [100.0, 100.0], 1),
+    )
+    .unwrap();
+    apply_spawn(
+        &mut world,
+        shape_node("top", [50.0, 50.0], [100.0, 100.0], 3),
+    )
+    .unwrap();
+    apply_spawn(
+        &mut world,
+        shape_node("middle", [40.0, 40.0], [20.0, 20.0], 2),
+    )
+    .unwrap();
+
+    assert_eq!(picked(&world, 55.0, 55.0), vec!["top", "middle", "bottom"]);
+    assert_eq!(picked(&world, 10.0, 10.0), vec!["bottom"]);
+    assert!(picked(&world, 300.0, 300.0).is_empty());
+
+    apply_render_op(
+        &mut world,
+        AtomeRenderOp::Visibility(AtomeVisibilityPatch {
+            id: "top".to_string(),
+            visible: false,
+        }),
+    )
+    .unwrap();
+    assert_eq!(picked(&world, 55.0, 55.0), vec!["middle", "bottom"]);
+}
+
+#[test]
+fn pick_at_puts_the_latest_spawn_on_top_within_a_layer() {
+    let mut world = world();
+    for id in ["back", "front"] {
+        apply_spawn(&mut world, shape_node(id, [0.0, 0.0], [100.0, 100.0], 1)).unwrap();
+    }
+    assert_eq!(picked(&world, 50.0, 50.0), vec!["front", "back"]);
+
+    // A respawned node takes a fresh spawn order, whatever entity it reuses.
+    apply_render_op(&mut world, AtomeRenderOp::Despawn("back".to_string())).unwrap();
+    apply_spawn(
+        &mut world,
+        shape_node("back", [0.0, 0.0], [100.0, 100.0], 1),
+    )
+    .unwrap();
+
+    let back = world.resource::<AtomeEntityTable>().by_id["back"];
+    assert_eq!(
+        world.get::<AtomeSpawnOrder>(back),
+        Some(&AtomeSpawnOrder(2))
+    );
+    assert_eq!(picked(&world, 50.0, 50.0), vec!["back", "front"]);
+}
+
+#[test]
+fn pick_at_follows_rotation_scale_and_origin() {
+    let mut world = world();
+    // A 100x20 bar turned upright about its center spans y 60..160.
+    apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            rotation: 90.0,
+            ..shape_node("bar", [100.0, 100.0], [100.0, 20.0], 1)
+        },
+    )
+    .unwrap();
+    assert_eq!(picked(&world, 150.0, 65.0), vec!["bar"]);
+    assert!(picked(&world, 110.0, 110.0).is_empty());
+
+    // Rotation is clockwise on screen, about the top-left corner here.
+    apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            rotation: 90.0,
+            origin: [0.0, 0.0],
+            ..shape_node("hand", [300.0, 0.0], [100.0, 10.0], 1)
+        },
+    )
+    .unwrap();
+    assert_eq!(picked(&world, 295.0, 50.0), vec!["hand"]);
+    assert!(picked(&world, 350.0, 5.0).is_empty());
+
+    apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            scale: [2.0, 2.0],
+            origin: [0.0, 0.0],
+            ..shape_node("grown", [400.0, 300.0], [10.0, 10.0], 1)
+        },
+    )
+    .unwrap();
+    assert_eq!(picked(&world, 415.0, 315.0), vec!["grown"]);
+    assert!(picked(&world, 421.0, 305.0).is_empty());
+}
+
+#[test]
+fn picks_respect_clip_rects_and_rounded_corners() {
+    let mut world = world();
+    apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            clip_rect: Some([0.0, 0.0, 20.0, 40.0]),
+            ..shape_node("clipped", [0.0, 0.0], [40.0, 40.0], 1)
+        },
+    )
+    .unwrap();
+    apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            corner_radius: 10.0,
+            ..shape_node("pill", [100.0, 0.0], [40.0, 40.0], 1)
+        },
+    )
+    .unwrap();
+
+    assert_eq!(picked(&world, 10.0, 10.0), vec!["clipped"]);
+    assert!(picked(&world, 30.0, 10.0).is_empty());
+    assert!(picked(&world, 102.0, 2.0).is_empty());
+    assert_eq!(picked(&world, 105.0, 5.0), vec!["pill"]);
+
+    assert!(pick_in_rect(&world, [25.0, 0.0, 10.0, 10.0]).is_empty());
+    assert_eq!(
+        pick_in_rect(&world, [15.0, 0.0, 10.0, 10.0]),
+        vec!["clipped"]
+    );
+    // A marquee over the cut-away corner misses; one reaching past it hits.
+    assert!(pick_in_rect(&world, [95.0, -5.0, 6.0, 6.0]).is_empty());
+    assert_eq!(pick_in_rect(&world, [95.0, -5.0, 10.0, 10.0]), vec!["pill"]);
+}
+
+#[test]
+fn pick_in_rect_intersects_rotated_nodes_topmost_first() {
+    let mut world = world();
+    apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            rotation: 90.0,
+            ..shape_node("bar", [100.0, 100.0], [100.0, 20.0], 1)
+        },
+    )
+    .unwrap();
+    apply_spawn(
+        &mut world,
+        shape_node("tile", [140.0, 40.0], [30.0, 30.0], 4),
+    )
+    .unwrap();
+
+    assert!(pick_in_rect(&world, [100.0, 100.0, 20.0, 20.0]).is_empty());
+    assert_eq!(pick_in_rect(&world, [145.0, 150.0, 2.0, 4.0]), vec!["bar"]);
+    assert_eq!(
+        pick_in_rect(&world, [145.0, 58.0, 2.0, 4.0]),
+        vec!["tile", "bar"]
+    );
+    assert!(pick_in_rect(&world, [0.0, 0.0, f32::NAN, 1.0]).is_empty());
+}
+
+#[test]
+fn pick_at_can_ignore_transparent_texels() {
+    let mut world = world();
+    // Left column transparent, right column opaque.
+    let transparent = [0, 0, 0, 0];
+    let opaque = [255, 255, 255, 255];
+    apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            kind: "image".to_string(),
+            color: None,
+            source: Some("data:image/png;base64,fixture".to_string()),
+            texture: Some(AtomeTexture {
+                width: 2,
+                height: 2,
+                rgba: [transparent, opaque, transparent, opaque].concat(),
+            }),
+            ..shape_node("icon", [0.0, 0.0], [32.0, 32.0], 1)
+        },
+    )
+    .unwrap();
+    let alpha = AtomePickOptions {
+        texture_alpha: true,
+        ..AtomePickOptions::default()
+    };
+
+    assert_eq!(picked(&world, 8.0, 16.0), vec!["icon"]);
+    assert!(pick_at(&world, 8.0, 16.0, alpha).is_empty());
+    assert_eq!(pick_at(&world, 24.0, 16.0, alpha), vec!["icon"]);
+}
@@ -1,19 +1,25 @@
 use bevy::{image::Image, mesh::Mesh, prelude::*, ui::IsDefaultUiCamera};
 
 use crate::{
+    animation::AnimationPlugin,
     backdrop_surface::BackdropSurfacePlugin,
     backdrop_blur::{apply_scene_effects, refresh_scene_effects},
+    composite::CompositePlugin,
+    fill::FillPlugin,
+    path::PathPlugin,
     procedural_sdf::ProceduralSdfPlugin,
     render_math::atome_camera_projection,
     render_ops::apply_render_op,
     selection_overlay::rebuild_selection_overlay,
     shape_shadow_overlay::rebuild_shape_shadow_overlay,
-    spawn::{spawn_node_with_texture_handle, texture_handle_for_node},
+    spawn::{register_node_entity, spawn_node_with_texture_handle, texture_handle_for_node},
+    text_layout::{sync_text_layout, AtomeTextFontTable},
     types::*,
     ui::AtomeBevyUiPlugin,
     video_external_texture::{
         insert_video_external_texture_component_for_node, AtomeVideoExternalTexturePlugin,
     },
+    viewport::ViewportPlugin,
     waveform_playback_overlay::rebuild_waveform_playback_overlay,
     workspace_backdrop::{
         spawn_workspace_backdrop, AtomePresentationCamera, FLOWER_PRESENTATION_LAYER,
@@ -37,12 +43,18 @@ impl Plugin for AtomeBevyRendererPlugin {
         app.insert_resource(self.config.clone())
             .add_plugins(AtomeVideoExternalTexturePlugin)
             .add_plugins(ProceduralSdfPlugin)
+            .add_plugins(FillPlugin)
+            .add_plugins(PathPlugin)
+            .add_plugins(CompositePlugin)
+            .add_plugins(AnimationPlugin)
+            .add_plugins(ViewportPlugin)
             .add_plugins(BackdropSurfacePlugin)
             .add_plugins(WorkspaceBlurPlugin)
             .add_plugins(AtomeBevyUiPlugin)
             .init_resource::<AtomeEntityTable>()
             .init_resource::<AtomeBackdropBlurState>()
             .init_resource::<AtomeRendererDiagnostics>()
+            .init_resource::<AtomeTextFontTable>()
             .init_resource::<Assets<Image>>()
             .init_resource::<Assets<Mesh>>()
             .add_systems(Startup, spawn_atome_bevy_scene);
@@ -103,10 +115,7 @@ fn spawn_atome_bevy_scene(
                         entity,
                         &node_for_world,
                     );
-                    world
-                        .resource_mut::<AtomeEntityTable>()
-                        .by_id
-                        .insert(node_id, entity);
+                    register_node_entity(world, node_id, entity);
                     if let Err(error) = rebuild_selection_overlay(world, entity) {
                         world.resource_mut::<AtomeRendererDiagnostics>().last_error = Some(error);
                     }
@@ -116,6 +125,9 @@ fn spawn_atome_bevy_scene(
                     if let Err(error) = rebuild_waveform_playback_overlay(world, entity) {
                         world.resource_mut::<AtomeRendererDiagnostics>().last_error = Some(error);
                     }
+                    if let Err(error) = sync_text_layout(world, entity) {
+                        world.resource_mut::<AtomeRendererDiagnostics>().last_error = Some(error);
+                    }
                 }
                 Err(error) => {
                     world.resource_mut::<AtomeRendererDiagnostics>().last_error = Some(error);
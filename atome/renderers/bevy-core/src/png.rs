@@ -0,0 +1,18 @@
+// PNG writer for renderer snapshots: 8-bit RGBA through the `png` crate
+// that Bevy's `png` image feature already builds.
+
+/// Encodes straight-alpha sRGB `rgba` rows as a PNG file.
+pub fn encode_rgba_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, String> {
+    if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+        return Err("bevy_png_dimensions_invalid".to_string());
+    }
+    let encode_failed = |error: ::png::EncodingError| format!("bevy_png_encode_failed:{error}");
+    let mut out = Vec::new();
+    let mut encoder = ::png::Encoder::new(&mut out, width, height);
+    encoder.set_color(::png::ColorType::Rgba);
+    encoder.set_depth(::png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(encode_failed)?;
+    writer.write_image_data(rgba).map_err(encode_failed)?;
+    writer.finish().map_err(encode_failed)?;
+    Ok(out)
+}
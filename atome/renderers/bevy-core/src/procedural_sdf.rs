@@ -48,6 +48,11 @@ pub struct ProceduralSdfMaterial {
     pub blurred_backdrop: Handle<Image>,
 }
 
+/// The normalized contract the node's material was last built from, kept for
+/// scene readback since the uniform does not map back to it.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct AtomeProceduralSdfContract(pub AtomeProceduralSdf);
+
 impl Material2d for ProceduralSdfMaterial {
     fn fragment_shader() -> ShaderRef {
         PROCEDURAL_SDF_SHADER_HANDLE.into()
@@ -191,6 +196,7 @@ pub fn insert_procedural_sdf(
     world.entity_mut(entity).insert((
         Mesh2d(mesh),
         MeshMaterial2d(material),
+        AtomeProceduralSdfContract(normalized),
         bevy::camera::visibility::RenderLayers::layer(FLOWER_PRESENTATION_LAYER),
     ));
     set_workspace_backdrop_enabled(world, true)?;
@@ -231,6 +237,9 @@ pub fn patch_procedural_sdf(
     let device_pixel_ratio = world.get_resource::<crate::types::AtomeBevyRendererConfig>().map(|config| config.device_pixel_ratio).unwrap_or(1.0);
     let backdrop = world.get_resource::<AtomeWorkspaceBackdrop>().cloned().ok_or_else(|| "bevy_workspace_backdrop_required".to_string())?;
     set_workspace_blur_radius(world, &backdrop.blur, normalized.background_blur_px)?;
+    world
+        .entity_mut(entity)
+        .insert(AtomeProceduralSdfContract(normalized));
     let mut materials = world
         .get_resource_mut::<Assets<ProceduralSdfMaterial>>()
         .ok_or_else(|| "bevy_procedural_sdf_assets_required".to_string())?;
@@ -132,12 +132,19 @@ fn procedural_sdf_spawns_and_patches_one_full_surface_material_quad() {
             texture_size: None,
             uv_rect: None,
             texture: None,
+            texture_hash: None,
             peaks: None,
             playback_progress: None,
             selected: None,
             filters: None,
             transition: None,
             procedural: Some(contract()),
+            path: None,
+            fill: None,
+            text_style: None,
+            blend_mode: AtomeBlendMode::Normal,
+            mask_id: None,
+            mask_mode: AtomeMaskMode::Alpha,
         },
     )
     .unwrap();
@@ -217,6 +224,10 @@ fn procedural_sdf_spawns_and_patches_one_full_surface_material_quad() {
             filters: None,
             transition: None,
             procedural: Some(patched),
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
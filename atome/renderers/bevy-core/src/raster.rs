@@ -0,0 +1,1304 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use bevy::math::Vec2;
+
+use crate::{
+    clip::intersection,
+    composite::MAX_PARENT_DEPTH,
+    fill::normalized_fill_for,
+    path::path_commands_for,
+    path_geometry::tessellate_path,
+    png::encode_rgba_png,
+    render_math::depth_for_layer,
+    selection_overlay::{self, build_shadow_texture_rgba, selection_outline_rects},
+    shadow_texture::{build_gaussian_outer_shadow_texture_rgba, shadow_padding},
+    shape_shadow_overlay,
+    spawn::effective_corner_radii,
+    texture::{corner_radii_are_zero, rounded_rect_mask_texture, rounded_rect_signed_distance},
+    types::*,
+    viewport::detail_zoom_for,
+    waveform_playback_overlay::{playback_depth_for_layer, PLAYHEAD_COLOR, PLAYHEAD_WIDTH},
+};
+
+// CPU twin of the GPU renderer: draws an `AtomeRenderScene` into an RGBA
+// buffer the way the Bevy pipeline would, so image-diff tests and server
+// previews need no GPU. Blending happens in linear light on premultiplied
+// color like the sRGB render targets. `procedural_sdf` nodes and glyph text
+// (`text_style` or plain `text` without a texture) have no CPU path and scene
+// effects are not drawn either; they are left out of the image and listed in
+// `AtomeRasterOutput` so callers can tell a partial raster from a complete
+// one. Backdrop surfaces and overlay/difference
+// groups read what is already drawn beneath them instead of the workspace
+// capture, and the glass blur is a plain Gaussian.
+
+const MAX_RASTER_DIMENSION: f32 = 16_384.0;
+const DEFAULT_NODE_COLOR: [f32; 4] = [0.24, 0.55, 0.92, 1.0];
+/// Fragments below this alpha are discarded by the fill, glass and composite
+/// shaders.
+const DISCARD_ALPHA: f32 = 0.002;
+const LUMINANCE: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// A rasterized scene: `rgba` holds straight-alpha sRGB rows, top row first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtomeRasterImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// What `rasterize_scene` drew, and the ids of the nodes and scene effects it
+/// had to leave out because they have no CPU path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtomeRasterOutput {
+    pub image: AtomeRasterImage,
+    pub skipped_nodes: Vec<String>,
+    pub skipped_effects: Vec<String>,
+}
+
+impl AtomeRasterOutput {
+    /// True when every node and effect of the scene made it into the image.
+    pub fn is_complete(&self) -> bool {
+        self.skipped_nodes.is_empty() && self.skipped_effects.is_empty()
+    }
+}
+
+/// How far two rasters of the same size are apart.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AtomeRasterDiff {
+    pub differing_pixels: usize,
+    pub max_channel_delta: u8,
+}
+
+impl AtomeRasterImage {
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.rgba[offset],
+            self.rgba[offset + 1],
+            self.rgba[offset + 2],
+            self.rgba[offset + 3],
+        ]
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        encode_rgba_png(self.width, self.height, &self.rgba)
+    }
+
+    /// Compares against `other`, typically a GPU readback of the same scene.
+    /// A pixel differs when any channel is more than `tolerance` apart.
+    pub fn diff(&self, other: &Self, tolerance: u8) -> Result<AtomeRasterDiff, String> {
+        if self.width != other.width || self.height != other.height {
+            return Err("bevy_raster_size_mismatch".to_string());
+        }
+        let mut diff = AtomeRasterDiff::default();
+        for (left, right) in self.rgba.chunks_exact(4).zip(other.rgba.chunks_exact(4)) {
+            let delta = left
+                .iter()
+                .zip(right)
+                .map(|(left, right)| left.abs_diff(*right))
+                .max()
+                .unwrap_or(0);
+            diff.max_channel_delta = diff.max_channel_delta.max(delta);
+            if delta > tolerance {
+                diff.differing_pixels += 1;
+            }
+        }
+        Ok(diff)
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Straight-alpha sRGB to straight-alpha linear, clamped like a color
+/// uniform.
+fn linear_color(color: [f32; 4]) -> [f32; 4] {
+    let [r, g, b, a] = color.map(|value| value.clamp(0.0, 1.0));
+    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+}
+
+fn smoothstep(edge0: f32, edge1: f32, value: f32) -> f32 {
+    let t = ((value - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn color_for_node(node: &AtomeRenderNode) -> [f32; 4] {
+    node.color.unwrap_or(DEFAULT_NODE_COLOR)
+}
+
+/// The node color with its alpha scaled by the node opacity, as sprites
+/// are tinted.
+fn visible_color(node: &AtomeRenderNode) -> [f32; 4] {
+    let mut color = color_for_node(node);
+    color[3] = color[3].clamp(0.0, 1.0) * normalize_opacity(node.opacity);
+    color
+}
+
+fn white_with_opacity(opacity: f32) -> [f32; 4] {
+    [1.0, 1.0, 1.0, normalize_opacity(opacity)]
+}
+
+// Render target: linear, premultiplied, clamped to [0, 1] after every draw
+// like an 8-bit target.
+#[derive(Clone)]
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize, clear: [f32; 4]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![clear; width * height],
+        }
+    }
+
+    /// Alpha-blends a straight-alpha linear color over pixel `index`.
+    fn blend(&mut self, index: usize, color: [f32; 4]) {
+        let alpha = color[3].clamp(0.0, 1.0);
+        if alpha <= 0.0 {
+            return;
+        }
+        let pixel = &mut self.pixels[index];
+        for channel in 0..3 {
+            pixel[channel] =
+                (color[channel] * alpha + pixel[channel] * (1.0 - alpha)).clamp(0.0, 1.0);
+        }
+        pixel[3] = (alpha + pixel[3] * (1.0 - alpha)).clamp(0.0, 1.0);
+    }
+
+    fn to_image(&self) -> AtomeRasterImage {
+        let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in &self.pixels {
+            let alpha = pixel[3];
+            for value in &pixel[..3] {
+                let straight = if alpha > 0.0 {
+                    (value / alpha).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                rgba.push((linear_to_srgb(straight) * 255.0).round() as u8);
+            }
+            rgba.push((alpha * 255.0).round() as u8);
+        }
+        AtomeRasterImage {
+            width: self.width as u32,
+            height: self.height as u32,
+            rgba,
+        }
+    }
+}
+
+// The device pixel grid and the camera looking at the scene.
+struct Frame {
+    width: usize,
+    height: usize,
+    pixel_ratio: f32,
+    surface: [f32; 2],
+    viewport: AtomeViewport,
+    srgb_table: [f32; 256],
+}
+
+impl Frame {
+    fn new(options: AtomeRasterOptions) -> Result<Self, String> {
+        let pixel_ratio = if options.pixel_ratio.is_finite() && options.pixel_ratio > 0.0 {
+            options.pixel_ratio
+        } else {
+            1.0
+        };
+        let valid = |value: f32| value.is_finite() && value > 0.0;
+        if !valid(options.width) || !valid(options.height) {
+            return Err("bevy_raster_size_invalid".to_string());
+        }
+        let width = (options.width * pixel_ratio).round().max(1.0);
+        let height = (options.height * pixel_ratio).round().max(1.0);
+        if width > MAX_RASTER_DIMENSION || height > MAX_RASTER_DIMENSION {
+            return Err("bevy_raster_size_invalid".to_string());
+        }
+        Ok(Self {
+            width: width as usize,
+            height: height as usize,
+            pixel_ratio,
+            surface: [options.width, options.height],
+            viewport: options.viewport.normalized(),
+            srgb_table: std::array::from_fn(|value| srgb_to_linear(value as f32 / 255.0)),
+        })
+    }
+
+    /// Scene point under a device pixel position.
+    fn scene_at(&self, device: Vec2) -> Vec2 {
+        let surface = device / self.pixel_ratio;
+        Vec2::from(self.viewport.surface_to_scene(surface.into(), self.surface))
+    }
+
+    fn device_at(&self, scene: Vec2) -> Vec2 {
+        Vec2::from(self.viewport.scene_to_surface(scene.into(), self.surface)) * self.pixel_ratio
+    }
+
+    /// Pixel range `[x0, y0, x1, y1)` whose centers may fall inside the
+    /// scene points' hull.
+    fn pixel_span(&self, points: impl IntoIterator<Item = Vec2>) -> Option<[usize; 4]> {
+        let (mut min, mut max) = (Vec2::INFINITY, Vec2::NEG_INFINITY);
+        for point in points {
+            let device = self.device_at(point);
+            min = min.min(device);
+            max = max.max(device);
+        }
+        if !min.is_finite() || !max.is_finite() {
+            return None;
+        }
+        let x0 = min.x.floor().max(0.0) as usize;
+        let y0 = min.y.floor().max(0.0) as usize;
+        let x1 = (max.x.ceil().max(0.0) as usize).min(self.width);
+        let y1 = (max.y.ceil().max(0.0) as usize).min(self.height);
+        (x1 > x0 && y1 > y0).then_some([x0, y0, x1, y1])
+    }
+
+    /// Bilinear sample of a texture in linear light, straight alpha,
+    /// clamped to the edge.
+    fn sample(&self, texels: &Texels, uv: Vec2) -> [f32; 4] {
+        texels.sample(uv, |value| self.srgb_table[value as usize])
+    }
+}
+
+// Where a node draws: its rect, the part its clip rect leaves visible, and
+// the local transform about the pivot, as `apply_entity_clip` and
+// `picking::node_target` derive them.
+#[derive(Clone, Copy)]
+struct Placement {
+    original: [f32; 4],
+    visible: [f32; 4],
+    pivot: Vec2,
+    scale: Vec2,
+    rotation: f32,
+}
+
+impl Placement {
+    fn for_node(node: &AtomeRenderNode) -> Option<Self> {
+        if node.scale[0] == 0.0 || node.scale[1] == 0.0 {
+            return None;
+        }
+        let original = [
+            node.logical_position[0],
+            node.logical_position[1],
+            node.logical_size[0].max(1.0),
+            node.logical_size[1].max(1.0),
+        ];
+        let visible = match node.clip_rect {
+            Some(clip) => intersection(original, clip)?,
+            None => original,
+        };
+        let pivot = Vec2::new(
+            visible[0] + visible[2] / 2.0 + (node.origin[0] - 0.5) * visible[2].max(1.0),
+            visible[1] + visible[3] / 2.0 + (node.origin[1] - 0.5) * visible[3].max(1.0),
+        );
+        Some(Self {
+            original,
+            visible,
+            pivot,
+            scale: Vec2::from(node.scale),
+            rotation: node.rotation.to_radians(),
+        })
+    }
+
+    /// An untransformed rect, as overlay sprites are placed.
+    fn rect(rect: [f32; 4], clip: Option<[f32; 4]>) -> Option<Self> {
+        let visible = match clip {
+            Some(clip) => intersection(rect, clip)?,
+            None => rect,
+        };
+        Some(Self {
+            original: rect,
+            visible,
+            pivot: Vec2::ZERO,
+            scale: Vec2::ONE,
+            rotation: 0.0,
+        })
+    }
+
+    fn size(&self) -> Vec2 {
+        Vec2::new(self.original[2], self.original[3])
+    }
+
+    fn to_node(self, point: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let offset = point - self.pivot;
+        let unrotated = Vec2::new(
+            offset.x * cos + offset.y * sin,
+            -offset.x * sin + offset.y * cos,
+        );
+        self.pivot + unrotated / self.scale
+    }
+
+    fn to_scene(self, point: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let scaled = (point - self.pivot) * self.scale;
+        self.pivot
+            + Vec2::new(
+                scaled.x * cos - scaled.y * sin,
+                scaled.x * sin + scaled.y * cos,
+            )
+    }
+
+    /// Scene positions of the visible rect's corners.
+    fn corners(&self) -> [Vec2; 4] {
+        let [x, y, width, height] = self.visible;
+        [
+            Vec2::new(x, y),
+            Vec2::new(x + width, y),
+            Vec2::new(x + width, y + height),
+            Vec2::new(x, y + height),
+        ]
+        .map(|corner| self.to_scene(corner))
+    }
+}
+
+// One fragment of a quad: `point` is the node-local position in logical
+// pixels from the node's top-left corner, `dx`/`dy` how far it moves per
+// device pixel, for derivatives.
+struct Fragment {
+    index: usize,
+    point: Vec2,
+    dx: Vec2,
+    dy: Vec2,
+}
+
+struct Texels<'a> {
+    width: usize,
+    height: usize,
+    rgba: &'a [u8],
+}
+
+impl<'a> Texels<'a> {
+    fn new(texture: &'a AtomeTexture, id: &str) -> Result<Self, String> {
+        if texture.width == 0 || texture.height == 0 {
+            return Err(format!("bevy_texture_dimension_required:{id}"));
+        }
+        if texture.rgba.len() != texture.width as usize * texture.height as usize * 4 {
+            return Err(format!("bevy_texture_rgba_length_invalid:{id}"));
+        }
+        Ok(Self {
+            width: texture.width as usize,
+            height: texture.height as usize,
+            rgba: &texture.rgba,
+        })
+    }
+
+    /// Bilinear sample with `decode` applied to the color channels of each
+    /// texel before filtering.
+    fn sample(&self, uv: Vec2, decode: impl Fn(u8) -> f32) -> [f32; 4] {
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = uv.y * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let texel = |column: f32, row: f32| {
+            let column = (column.max(0.0) as usize).min(self.width - 1);
+            let row = (row.max(0.0) as usize).min(self.height - 1);
+            let offset = (row * self.width + column) * 4;
+            [
+                decode(self.rgba[offset]),
+                decode(self.rgba[offset + 1]),
+                decode(self.rgba[offset + 2]),
+                self.rgba[offset + 3] as f32 / 255.0,
+            ]
+        };
+        let [top_left, top_right, bottom_left, bottom_right] = [
+            texel(x0, y0),
+            texel(x0 + 1.0, y0),
+            texel(x0, y0 + 1.0),
+            texel(x0 + 1.0, y0 + 1.0),
+        ];
+        std::array::from_fn(|channel| {
+            let top = top_left[channel] + (top_right[channel] - top_left[channel]) * fx;
+            let bottom = bottom_left[channel] + (bottom_right[channel] - bottom_left[channel]) * fx;
+            top + (bottom - top) * fy
+        })
+    }
+}
+
+/// Visits every device pixel whose center falls inside the placement's
+/// visible rect and blends what `shade` returns there.
+fn paint_quad(
+    canvas: &mut Canvas,
+    frame: &Frame,
+    placement: &Placement,
+    mut shade: impl FnMut(&Fragment) -> Option<[f32; 4]>,
+) {
+    let Some([x0, y0, x1, y1]) = frame.pixel_span(placement.corners()) else {
+        return;
+    };
+    let at = |x: f32, y: f32| placement.to_node(frame.scene_at(Vec2::new(x, y)));
+    let base = at(0.0, 0.0);
+    let dx = at(1.0, 0.0) - base;
+    let dy = at(0.0, 1.0) - base;
+    let origin = Vec2::new(placement.original[0], placement.original[1]);
+    let [left, top, width, height] = placement.visible;
+    for py in y0..y1 {
+        for px in x0..x1 {
+            let local = base + dx * (px as f32 + 0.5) + dy * (py as f32 + 0.5);
+            if local.x < left || local.y < top || local.x >= left + width || local.y >= top + height
+            {
+                continue;
+            }
+            let index = py * canvas.width + px;
+            let fragment = Fragment {
+                index,
+                point: local - origin,
+                dx,
+                dy,
+            };
+            if let Some(color) = shade(&fragment) {
+                canvas.blend(index, color);
+            }
+        }
+    }
+}
+
+/// A sprite: the texture stretched over the rect, or a flat rect, times the
+/// sRGB `tint`.
+fn paint_sprite(
+    canvas: &mut Canvas,
+    frame: &Frame,
+    placement: &Placement,
+    texels: Option<&Texels>,
+    tint: [f32; 4],
+) {
+    let tint = linear_color(tint);
+    let size = placement.size();
+    paint_quad(canvas, frame, placement, |fragment| {
+        let Some(texels) = texels else {
+            return Some(tint);
+        };
+        let texel = frame.sample(texels, fragment.point / size);
+        Some(std::array::from_fn(|channel| {
+            texel[channel] * tint[channel]
+        }))
+    });
+}
+
+fn edge_function(a: Vec2, b: Vec2, point: Vec2) -> f32 {
+    (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x)
+}
+
+// Ties on a shared edge go to exactly one of the two triangles, which walk
+// it in opposite directions once both are wound the same way.
+fn owns_edge(a: Vec2, b: Vec2) -> bool {
+    let delta = b - a;
+    delta.y > 0.0 || (delta.y == 0.0 && delta.x < 0.0)
+}
+
+/// Visits the device pixels whose centers fall inside the triangle and
+/// blends what `shade` returns for their barycentric weights.
+fn paint_triangle(
+    canvas: &mut Canvas,
+    points: [Vec2; 3],
+    mut shade: impl FnMut([f32; 3]) -> Option<[f32; 4]>,
+) {
+    let area = edge_function(points[0], points[1], points[2]);
+    if !area.is_finite() || area.abs() <= f32::EPSILON {
+        return;
+    }
+    // Wind every triangle the same way; `order` maps back to the caller's
+    // vertex order for the weights.
+    let order = if area > 0.0 { [0, 1, 2] } else { [0, 2, 1] };
+    let [a, b, c] = order.map(|index| points[index]);
+    let area = area.abs();
+    let min = a.min(b).min(c);
+    let max = a.max(b).max(c);
+    let x0 = min.x.floor().max(0.0) as usize;
+    let y0 = min.y.floor().max(0.0) as usize;
+    let x1 = (max.x.ceil().max(0.0) as usize).min(canvas.width);
+    let y1 = (max.y.ceil().max(0.0) as usize).min(canvas.height);
+    let edges = [(b, c), (c, a), (a, b)];
+    let owned = edges.map(|(from, to)| owns_edge(from, to));
+    for py in y0..y1 {
+        for px in x0..x1 {
+            let center = Vec2::new(px as f32 + 0.5, py as f32 + 0.5);
+            let weights = edges.map(|(from, to)| edge_function(from, to, center));
+            let inside = weights
+                .iter()
+                .zip(owned)
+                .all(|(weight, owned)| *weight > 0.0 || (*weight == 0.0 && owned));
+            if !inside {
+                continue;
+            }
+            let mut barycentric = [0.0; 3];
+            for (slot, index) in order.iter().enumerate() {
+                barycentric[*index] = weights[slot] / area;
+            }
+            if let Some(color) = shade(barycentric) {
+                canvas.blend(py * canvas.width + px, color);
+            }
+        }
+    }
+}
+
+fn gaussian_blur(canvas: &Canvas, sigma: f32) -> Vec<[f32; 4]> {
+    if sigma < 0.5 {
+        return canvas.pixels.clone();
+    }
+    let radius = (sigma * 3.0).ceil() as isize;
+    let mut kernel: Vec<f32> = (-radius..=radius)
+        .map(|offset| (-0.5 * (offset as f32 / sigma).powi(2)).exp())
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|weight| *weight /= total);
+    let (width, height) = (canvas.width as isize, canvas.height as isize);
+    let pass = |source: &[[f32; 4]], horizontal: bool| {
+        let mut result = vec![[0.0; 4]; source.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 4];
+                for (index, weight) in kernel.iter().enumerate() {
+                    let offset = index as isize - radius;
+                    // Clamp to the edge like the capture sampler.
+                    let (sx, sy) = if horizontal {
+                        ((x + offset).clamp(0, width - 1), y)
+                    } else {
+                        (x, (y + offset).clamp(0, height - 1))
+                    };
+                    let texel = source[(sy * width + sx) as usize];
+                    for channel in 0..4 {
+                        sum[channel] += texel[channel] * weight;
+                    }
+                }
+                result[(y * width + x) as usize] = sum;
+            }
+        }
+        result
+    };
+    pass(&pass(&canvas.pixels, true), false)
+}
+
+// `apply_color_filters` in `video_external.wgsl`, on display-encoded color.
+fn apply_color_filters(color: [f32; 3], filters: AtomeColorFilters) -> [f32; 3] {
+    let dot = |a: [f32; 3], b: [f32; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    let mix = |a: [f32; 3], b: [f32; 3], amount: f32| -> [f32; 3] {
+        std::array::from_fn(|channel| a[channel] + (b[channel] - a[channel]) * amount)
+    };
+    let mut c = color.map(|value| (value * filters.brightness - 0.5) * filters.contrast + 0.5);
+    if filters.hue != 0.0 {
+        let (sn, cs) = filters.hue.sin_cos();
+        c = [
+            dot(
+                c,
+                [
+                    0.213 + cs * 0.787 - sn * 0.213,
+                    0.715 - cs * 0.715 - sn * 0.715,
+                    0.072 - cs * 0.072 + sn * 0.928,
+                ],
+            ),
+            dot(
+                c,
+                [
+                    0.213 - cs * 0.213 + sn * 0.143,
+                    0.715 + cs * 0.285 + sn * 0.140,
+                    0.072 - cs * 0.072 - sn * 0.283,
+                ],
+            ),
+            dot(
+                c,
+                [
+                    0.213 - cs * 0.213 - sn * 0.787,
+                    0.715 - cs * 0.715 + sn * 0.715,
+                    0.072 + cs * 0.928 + sn * 0.072,
+                ],
+            ),
+        ];
+    }
+    c = mix([dot(c, LUMINANCE); 3], c, filters.saturate);
+    let sepia = [
+        dot(c, [0.393, 0.769, 0.189]),
+        dot(c, [0.349, 0.686, 0.168]),
+        dot(c, [0.272, 0.534, 0.131]),
+    ];
+    c = mix(c, sepia, filters.sepia);
+    c = mix(c, [dot(c, LUMINANCE); 3], filters.grayscale);
+    c = mix(c, c.map(|value| 1.0 - value), filters.invert);
+    c.map(|value| value.clamp(0.0, 1.0))
+}
+
+// `apply_transition` in `video_external.wgsl`: the uv to sample and an
+// alpha factor.
+fn apply_transition(uv: Vec2, transition: AtomeTransition) -> (Vec2, f32) {
+    let progress = transition.progress.clamp(0.0, 1.0);
+    let incoming = transition.role < 0.5;
+    match transition.kind as u8 {
+        1 => (uv, if incoming { progress } else { 1.0 - progress }),
+        2 if incoming => {
+            let edge = transition.softness.max(0.0001);
+            (uv, ((progress - uv.x) / edge + 0.5).clamp(0.0, 1.0))
+        }
+        3 if incoming => {
+            let shifted = Vec2::new(uv.x - (1.0 - progress), uv.y);
+            let alpha = if (0.0..=1.0).contains(&shifted.x) {
+                1.0
+            } else {
+                0.0
+            };
+            (shifted, alpha)
+        }
+        _ => (uv, 1.0),
+    }
+}
+
+/// Composites a group's premultiplied `content` onto `canvas` the way the
+/// composite shader and `composite::blend_state_for` do.
+fn composite_onto(
+    canvas: &mut Canvas,
+    content: &Canvas,
+    mask: Option<(&Canvas, AtomeMaskMode)>,
+    mode: AtomeBlendMode,
+) {
+    for (index, source) in content.pixels.iter().enumerate() {
+        let coverage = match mask {
+            Some((mask, AtomeMaskMode::Luminance)) => {
+                let texel = mask.pixels[index];
+                (texel[0] * LUMINANCE[0] + texel[1] * LUMINANCE[1] + texel[2] * LUMINANCE[2])
+                    .clamp(0.0, 1.0)
+            }
+            Some((mask, AtomeMaskMode::Alpha)) => mask.pixels[index][3],
+            None => 1.0,
+        };
+        let source = source.map(|value| value * coverage);
+        let alpha = source[3];
+        if alpha < DISCARD_ALPHA {
+            continue;
+        }
+        let destination = &mut canvas.pixels[index];
+        let lifted = |channel: usize| source[channel] + 1.0 - alpha;
+        for channel in 0..3 {
+            let backdrop = destination[channel];
+            destination[channel] = match mode {
+                AtomeBlendMode::Normal => source[channel] + backdrop * (1.0 - alpha),
+                AtomeBlendMode::Multiply => backdrop * lifted(channel),
+                AtomeBlendMode::Screen => source[channel] + backdrop * (1.0 - source[channel]),
+                AtomeBlendMode::Darken => backdrop.min(lifted(channel)),
+                AtomeBlendMode::Lighten => backdrop.max(source[channel]),
+                AtomeBlendMode::Additive => source[channel] + backdrop,
+                AtomeBlendMode::Overlay | AtomeBlendMode::Difference => {
+                    let blended = mode.blend_channel(backdrop, source[channel] / alpha);
+                    blended * alpha + backdrop * (1.0 - alpha)
+                }
+            }
+            .clamp(0.0, 1.0);
+        }
+        destination[3] = (alpha + destination[3] * (1.0 - alpha)).clamp(0.0, 1.0);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pass {
+    Node,
+    Group,
+    Shadow,
+    SelectionShadow,
+    SelectionOutline,
+    Playhead,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct DrawItem {
+    depth: f32,
+    node: usize,
+    pass: Pass,
+}
+
+struct Rasterizer<'a> {
+    scene: &'a AtomeRenderScene,
+    frame: Frame,
+    selection_style: SelectionVisualStyle,
+    detail_zoom: f32,
+    /// Draw lists of composite groups by owner node.
+    groups: HashMap<usize, Vec<DrawItem>>,
+    /// Mask node of each masked group owner.
+    masks: HashMap<usize, usize>,
+    /// Ids of nodes left out for lack of a CPU path, in draw order.
+    skipped: RefCell<Vec<String>>,
+}
+
+fn sort_items(items: &mut [DrawItem]) {
+    // Stable, so equal depths keep scene order.
+    items.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+}
+
+impl<'a> Rasterizer<'a> {
+    fn new(scene: &'a AtomeRenderScene, frame: Frame) -> (Self, Vec<DrawItem>) {
+        let detail_zoom = detail_zoom_for(frame.viewport.zoom);
+        let nodes = &scene.nodes;
+        let by_id = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id.as_str(), index))
+            .collect::<HashMap<_, _>>();
+        let parents = nodes
+            .iter()
+            .map(|node| {
+                node.parent_id
+                    .as_deref()
+                    .and_then(|parent| by_id.get(parent).copied())
+            })
+            .collect::<Vec<_>>();
+        let mask_id = |node: &AtomeRenderNode| {
+            node.mask_id
+                .as_deref()
+                .filter(|id| !id.trim().is_empty())
+                .map(str::to_string)
+        };
+        // Same membership rules as `composite::sync_composites`.
+        let owners = nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| {
+                node.blend_mode != AtomeBlendMode::Normal || mask_id(node).is_some()
+            })
+            .map(|(index, _)| index)
+            .collect::<HashSet<_>>();
+        let mask_sources = owners
+            .iter()
+            .filter_map(|owner| {
+                let source = *by_id.get(mask_id(&nodes[*owner])?.as_str())?;
+                (source != *owner).then_some((source, *owner))
+            })
+            .collect::<HashMap<_, _>>();
+        let owning_group = |mut current: usize| {
+            for _ in 0..MAX_PARENT_DEPTH {
+                if owners.contains(&current) {
+                    return Some(current);
+                }
+                current = parents[current]?;
+            }
+            None
+        };
+
+        let mut root = Vec::new();
+        let mut groups = owners
+            .iter()
+            .map(|owner| (*owner, Vec::new()))
+            .collect::<HashMap<_, _>>();
+        let mut masks = HashMap::new();
+        for (index, node) in nodes.iter().enumerate() {
+            let depth = depth_for_layer(node.layer);
+            let item = |depth: f32, pass: Pass| DrawItem {
+                depth,
+                node: index,
+                pass,
+            };
+            if let Some(owner) = mask_sources.get(&index) {
+                masks.insert(*owner, index);
+            } else {
+                match owning_group(index) {
+                    Some(owner) => groups.get_mut(&owner),
+                    None => Some(&mut root),
+                }
+                .into_iter()
+                .for_each(|list| list.push(item(depth, Pass::Node)));
+            }
+            if owners.contains(&index) {
+                match parents[index].and_then(owning_group) {
+                    Some(outer) => groups.get_mut(&outer),
+                    None => Some(&mut root),
+                }
+                .into_iter()
+                .for_each(|list| list.push(item(depth, Pass::Group)));
+            }
+            // Overlays stay on the default layer whatever group the node is in.
+            if node.shadow.and_then(|shadow| shadow.normalized()).is_some() {
+                root.push(item(
+                    shape_shadow_overlay::shadow_depth_for_layer(node.layer),
+                    Pass::Shadow,
+                ));
+            }
+            if node.selected == Some(true) {
+                root.push(item(
+                    selection_overlay::shadow_depth_for_layer(node.layer),
+                    Pass::SelectionShadow,
+                ));
+                root.push(item(
+                    selection_overlay::outline_depth_for_layer(node.layer),
+                    Pass::SelectionOutline,
+                ));
+            }
+            if node.kind == "audio_waveform"
+                && node
+                    .playback_progress
+                    .is_some_and(|value| value.is_finite())
+            {
+                root.push(item(playback_depth_for_layer(node.layer), Pass::Playhead));
+            }
+        }
+        sort_items(&mut root);
+        groups.values_mut().for_each(|list| sort_items(list));
+        (
+            Self {
+                scene,
+                frame,
+                selection_style: scene.selection_style().at_zoom(detail_zoom),
+                detail_zoom,
+                groups,
+                masks,
+                skipped: RefCell::default(),
+            },
+            root,
+        )
+    }
+
+    fn skip(&self, node: &AtomeRenderNode) {
+        let mut skipped = self.skipped.borrow_mut();
+        if !skipped.contains(&node.id) {
+            skipped.push(node.id.clone());
+        }
+    }
+
+    fn canvas(&self, clear: [f32; 4]) -> Canvas {
+        Canvas::new(self.frame.width, self.frame.height, clear)
+    }
+
+    fn draw(&self, canvas: &mut Canvas, items: &[DrawItem], nesting: usize) -> Result<(), String> {
+        for item in items {
+            let node = &self.scene.nodes[item.node];
+            match item.pass {
+                Pass::Node => self.paint_node(canvas, node)?,
+                Pass::Group => self.draw_group(canvas, item.node, nesting)?,
+                Pass::Shadow => self.paint_shadow(canvas, node),
+                Pass::SelectionShadow => self.paint_selection_shadow(canvas, node),
+                Pass::SelectionOutline => self.paint_selection_outline(canvas, node),
+                Pass::Playhead => self.paint_playhead(canvas, node),
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_group(&self, canvas: &mut Canvas, owner: usize, nesting: usize) -> Result<(), String> {
+        // A cyclic parent chain could otherwise nest a group in itself.
+        if nesting >= MAX_PARENT_DEPTH {
+            return Ok(());
+        }
+        let mut content = self.canvas([0.0; 4]);
+        self.draw(&mut content, &self.groups[&owner], nesting + 1)?;
+        let node = &self.scene.nodes[owner];
+        let mask = match self.masks.get(&owner) {
+            Some(source) => {
+                let mut mask = self.canvas([0.0; 4]);
+                self.paint_node(&mut mask, &self.scene.nodes[*source])?;
+                Some(mask)
+            }
+            None => None,
+        };
+        composite_onto(
+            canvas,
+            &content,
+            mask.as_ref().map(|mask| (mask, node.mask_mode)),
+            node.blend_mode,
+        );
+        Ok(())
+    }
+
+    fn paint_node(&self, canvas: &mut Canvas, node: &AtomeRenderNode) -> Result<(), String> {
+        let Some(placement) = Placement::for_node(node) else {
+            return Ok(());
+        };
+        let frame = &self.frame;
+        let texels = node
+            .texture
+            .as_ref()
+            .map(|texture| Texels::new(texture, &node.id))
+            .transpose()?;
+        match node.kind.as_str() {
+            "shape" => {
+                if let Some(backdrop) = node.backdrop {
+                    self.paint_backdrop(canvas, node, &placement, backdrop)?;
+                } else if let Some(fill) = &node.fill {
+                    let fill = normalized_fill_for(fill, &node.id)?;
+                    self.paint_fill(canvas, node, &placement, &fill);
+                } else if texels.is_some() {
+                    paint_sprite(
+                        canvas,
+                        frame,
+                        &placement,
+                        texels.as_ref(),
+                        visible_color(node),
+                    );
+                } else {
+                    let radii = effective_corner_radii(node);
+                    let mask = (!corner_radii_are_zero(radii)).then(|| {
+                        rounded_rect_mask_texture(node.logical_size[0], node.logical_size[1], radii)
+                    });
+                    let mask = mask
+                        .as_ref()
+                        .map(|mask| Texels::new(mask, &node.id))
+                        .transpose()?;
+                    paint_sprite(
+                        canvas,
+                        frame,
+                        &placement,
+                        mask.as_ref(),
+                        visible_color(node),
+                    );
+                }
+            }
+            "text" => {
+                if texels.is_some() {
+                    paint_sprite(
+                        canvas,
+                        frame,
+                        &placement,
+                        texels.as_ref(),
+                        white_with_opacity(node.opacity),
+                    );
+                } else if node
+                    .text
+                    .as_deref()
+                    .is_some_and(|text| !text.trim().is_empty())
+                {
+                    // Glyph runs come from bevy_text.
+                    self.skip(node);
+                }
+            }
+            "image" | "audio_waveform" => {
+                let tint = if texels.is_some() {
+                    white_with_opacity(node.opacity)
+                } else {
+                    visible_color(node)
+                };
+                paint_sprite(canvas, frame, &placement, texels.as_ref(), tint);
+            }
+            "video" => {
+                if let Some(texels) = &texels {
+                    self.paint_video(canvas, node, &placement, texels);
+                }
+            }
+            "path" => self.paint_path(canvas, node, &placement)?,
+            "procedural_sdf" => self.skip(node),
+            other => return Err(format!("bevy_render_kind_unsupported:{other}")),
+        }
+        Ok(())
+    }
+
+    // `shape_fill.wgsl`: the fill inside a rounded rect with one device
+    // pixel of analytic coverage.
+    fn paint_fill(
+        &self,
+        canvas: &mut Canvas,
+        node: &AtomeRenderNode,
+        placement: &Placement,
+        fill: &AtomeFill,
+    ) {
+        let size = placement.size();
+        let radii = effective_corner_radii(node);
+        let opacity = normalize_opacity(node.opacity);
+        let distance =
+            |point: Vec2| rounded_rect_signed_distance(point.x, point.y, size.x, size.y, radii);
+        paint_quad(canvas, &self.frame, placement, |fragment| {
+            let center = distance(fragment.point);
+            let width = (distance(fragment.point + fragment.dx) - center).abs()
+                + (distance(fragment.point + fragment.dy) - center).abs();
+            let softness = width.max(0.0001) * 0.5;
+            let coverage = 1.0 - smoothstep(-softness, softness, center);
+            if coverage < DISCARD_ALPHA {
+                return None;
+            }
+            let unit = fragment.point / size;
+            let mut color = linear_color(fill.color_at(unit.into(), size.into()));
+            color[3] *= coverage * opacity;
+            Some(color)
+        });
+    }
+
+    // `backdrop_surface.wgsl`: blurred backdrop mixed with the tint inside
+    // a rounded rect.
+    fn paint_backdrop(
+        &self,
+        canvas: &mut Canvas,
+        node: &AtomeRenderNode,
+        placement: &Placement,
+        backdrop: AtomeBackdropStyle,
+    ) -> Result<(), String> {
+        let style = backdrop
+            .normalized()
+            .ok_or_else(|| "bevy_backdrop_style_invalid".to_string())?;
+        let glass = gaussian_blur(canvas, style.blur_px * 0.5 * self.frame.pixel_ratio);
+        let size = placement.size();
+        let radii = [node.corner_radius.max(0.0); 4];
+        let tint = style.tint;
+        paint_quad(canvas, &self.frame, placement, |fragment| {
+            let distance = rounded_rect_signed_distance(
+                fragment.point.x,
+                fragment.point.y,
+                size.x,
+                size.y,
+                radii,
+            );
+            let edge = 1.0 - smoothstep(-0.6, 0.6, distance);
+            if edge < DISCARD_ALPHA {
+                return None;
+            }
+            let under = glass[fragment.index];
+            Some([
+                under[0] + (tint[0] - under[0]) * tint[3],
+                under[1] + (tint[1] - under[1]) * tint[3],
+                under[2] + (tint[2] - under[2]) * tint[3],
+                edge,
+            ])
+        });
+        Ok(())
+    }
+
+    // `video_external.wgsl`, with the node texture standing in for the
+    // current frame.
+    fn paint_video(
+        &self,
+        canvas: &mut Canvas,
+        node: &AtomeRenderNode,
+        placement: &Placement,
+        texels: &Texels,
+    ) {
+        let filters = node
+            .filters
+            .unwrap_or_else(AtomeColorFilters::identity)
+            .normalized();
+        let transition = node
+            .transition
+            .unwrap_or_else(AtomeTransition::none)
+            .normalized();
+        let [u, v, width, height] = normalize_uv_rect(node.uv_rect);
+        let opacity = normalize_opacity(node.opacity);
+        let size = placement.size();
+        paint_quad(canvas, &self.frame, placement, |fragment| {
+            let unit = fragment.point / size;
+            let uv = Vec2::new(u + unit.x * width, v + unit.y * height);
+            let (uv, alpha) = apply_transition(uv, transition);
+            let texel = texels.sample(uv, |value| value as f32 / 255.0);
+            let [r, g, b] = apply_color_filters([texel[0], texel[1], texel[2]], filters);
+            Some([
+                srgb_to_linear(r),
+                srgb_to_linear(g),
+                srgb_to_linear(b),
+                opacity * alpha,
+            ])
+        });
+    }
+
+    // `path.wgsl` over the same tessellation `path::sync_path_mesh` uploads.
+    fn paint_path(
+        &self,
+        canvas: &mut Canvas,
+        node: &AtomeRenderNode,
+        placement: &Placement,
+    ) -> Result<(), String> {
+        let path = node
+            .path
+            .as_ref()
+            .ok_or_else(|| format!("bevy_path_contract_required:{}", node.id))?;
+        let commands = path_commands_for(path, &node.id)?;
+        let fill = node
+            .fill
+            .as_ref()
+            .map(|fill| normalized_fill_for(fill, &node.id))
+            .transpose()?;
+        let size = placement.size();
+        let device_scale = (self.frame.pixel_ratio.max(0.01)
+            * placement.scale.x.abs().max(placement.scale.y.abs())
+            * self.frame.viewport.zoom)
+            .max(0.01);
+        let origin = Vec2::new(placement.original[0], placement.original[1]);
+        let local_visible = [
+            placement.visible[0] - origin.x,
+            placement.visible[1] - origin.y,
+            placement.visible[2],
+            placement.visible[3],
+        ];
+        let tessellation = tessellate_path(path, &commands, size.into(), device_scale)
+            .map_err(|_| format!("bevy_path_tessellation_failed:{}", node.id))?
+            .clipped_to(local_visible);
+        let stroke = linear_color(path.stroke.unwrap_or([0.0; 4]));
+        let interior = path
+            .fill_color(color_for_node(node))
+            .map(|color| color.map(|value| value.clamp(0.0, 1.0)));
+        let opacity = normalize_opacity(node.opacity);
+        for triangle in tessellation.indices.chunks_exact(3) {
+            let vertices = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+            let points = vertices.map(|index| {
+                let local = Vec2::from(tessellation.positions[index]);
+                self.frame.device_at(placement.to_scene(origin + local))
+            });
+            // Paint is constant per triangle.
+            let is_stroke = tessellation.paints[vertices[0]][0] >= 0.5;
+            paint_triangle(canvas, points, |weights| {
+                let lerp = |attribute: &dyn Fn(usize) -> Vec2| {
+                    attribute(vertices[0]) * weights[0]
+                        + attribute(vertices[1]) * weights[1]
+                        + attribute(vertices[2]) * weights[2]
+                };
+                let edge = lerp(&|index| Vec2::from(tessellation.edges[index]));
+                let paint = lerp(&|index| {
+                    Vec2::new(tessellation.paints[index][1], tessellation.paints[index][2])
+                });
+                let coverage = (paint.y - edge.length()).clamp(0.0, 1.0) * paint.x.abs();
+                let mut color = if is_stroke {
+                    stroke
+                } else {
+                    let interior = interior?;
+                    match &fill {
+                        Some(fill) => {
+                            let uv = lerp(&|index| Vec2::from(tessellation.positions[index]));
+                            linear_color(fill.color_at((uv / size).into(), size.into()))
+                        }
+                        None => linear_color(interior),
+                    }
+                };
+                color[3] *= coverage * opacity;
+                Some(color)
+            });
+        }
+        Ok(())
+    }
+
+    // `shape_shadow_overlay::rebuild_shape_shadow_overlay`: the exterior
+    // halo texture below the unrotated node rect, cropped by the clip rect.
+    fn paint_shadow(&self, canvas: &mut Canvas, node: &AtomeRenderNode) {
+        let Some(shadow) = node.shadow.and_then(|shadow| shadow.normalized()) else {
+            return;
+        };
+        let size = node.logical_size.map(|value| value.max(1.0));
+        let radii = effective_corner_radii(node).map(|radius| {
+            if radius > 0.0 {
+                radius + shadow.spread
+            } else {
+                0.0
+            }
+        });
+        let Some((width, height, rgba)) = build_gaussian_outer_shadow_texture_rgba(
+            shadow.color,
+            size[0] + shadow.spread * 2.0,
+            size[1] + shadow.spread * 2.0,
+            radii,
+            shadow.blur,
+        ) else {
+            return;
+        };
+        let padding = shadow_padding(shadow.blur) as f32;
+        let rect = [
+            node.logical_position[0] + shadow.offset_x - shadow.spread - padding,
+            node.logical_position[1] + shadow.offset_y - shadow.spread - padding,
+            width as f32,
+            height as f32,
+        ];
+        let Some(placement) = Placement::rect(rect, node.clip_rect) else {
+            return;
+        };
+        let texture = AtomeTexture {
+            width,
+            height,
+            rgba,
+        };
+        let texels = Texels::new(&texture, &node.id).ok();
+        paint_sprite(
+            canvas,
+            &self.frame,
+            &placement,
+            texels.as_ref(),
+            white_with_opacity(node.opacity),
+        );
+    }
+
+    fn paint_selection_shadow(&self, canvas: &mut Canvas, node: &AtomeRenderNode) {
+        let style = self.selection_style;
+        let size = node.logical_size.map(|value| value.max(1.0));
+        let Some((width, height, rgba)) = build_shadow_texture_rgba(style, size[0], size[1]) else {
+            return;
+        };
+        let padding = shadow_padding(style.shadow_size) as f32;
+        let rect = [
+            node.logical_position[0] - padding,
+            node.logical_position[1] - padding,
+            width as f32,
+            height as f32,
+        ];
+        let Some(placement) = Placement::rect(rect, None) else {
+            return;
+        };
+        let texture = AtomeTexture {
+            width,
+            height,
+            rgba,
+        };
+        let texels = Texels::new(&texture, &node.id).ok();
+        paint_sprite(canvas, &self.frame, &placement, texels.as_ref(), [1.0; 4]);
+    }
+
+    fn paint_selection_outline(&self, canvas: &mut Canvas, node: &AtomeRenderNode) {
+        let style = self.selection_style;
+        let bounds = (
+            node.logical_position[0],
+            node.logical_position[1],
+            node.logical_size[0].max(1.0),
+            node.logical_size[1].max(1.0),
+        );
+        // One surface pixel at the current zoom, as `spawn_overlay_rect`.
+        let min_extent = 1.0 / self.detail_zoom;
+        for [x, y, width, height] in selection_outline_rects(style, bounds) {
+            let rect = [x, y, width.max(min_extent), height.max(min_extent)];
+            if let Some(placement) = Placement::rect(rect, None) {
+                paint_sprite(canvas, &self.frame, &placement, None, style.border_color);
+            }
+        }
+    }
+
+    fn paint_playhead(&self, canvas: &mut Canvas, node: &AtomeRenderNode) {
+        let Some(progress) = node.playback_progress.filter(|value| value.is_finite()) else {
+            return;
+        };
+        let width = node.logical_size[0].max(1.0);
+        let rect = [
+            node.logical_position[0] + width * progress.clamp(0.0, 1.0) - PLAYHEAD_WIDTH / 2.0,
+            node.logical_position[1],
+            PLAYHEAD_WIDTH,
+            node.logical_size[1].max(1.0),
+        ];
+        if let Some(placement) = Placement::rect(rect, None) {
+            paint_sprite(canvas, &self.frame, &placement, None, PLAYHEAD_COLOR);
+        }
+    }
+}
+
+/// Draws `scene` on the CPU as the GPU renderer would show it through
+/// `options.viewport`. Node errors surface with the codes spawning the same
+/// node would raise; nodes and scene effects the CPU cannot draw are left
+/// out and reported in the output.
+pub fn rasterize_scene(
+    scene: &AtomeRenderScene,
+    options: AtomeRasterOptions,
+) -> Result<AtomeRasterOutput, String> {
+    let frame = Frame::new(options)?;
+    let [r, g, b, a] = linear_color(options.background);
+    let (rasterizer, root) = Rasterizer::new(scene, frame);
+    let mut canvas = rasterizer.canvas([r * a, g * a, b * a, a]);
+    rasterizer.draw(&mut canvas, &root, 0)?;
+    Ok(AtomeRasterOutput {
+        image: canvas.to_image(),
+        skipped_nodes: rasterizer.skipped.into_inner(),
+        skipped_effects: scene
+            .effects
+            .iter()
+            .map(|effect| effect.id.clone())
+            .collect(),
+    })
+}
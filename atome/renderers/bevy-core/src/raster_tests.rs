@@ -0,0 +1,536 @@
+use std::path::PathBuf;
+
+use crate::{
+    raster::{rasterize_scene, AtomeRasterDiff, AtomeRasterImage},
+    types::*,
+};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+fn node(id: &str, kind: &str, position: [f32; 2], size: [f32; 2]) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: kind.to_string(),
+        parent_id: None,
+        logical_position: position,
+        logical_size: size,
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.5, 0.5],
+        layer: 1,
+        opacity: 1.0,
+        corner_radius: 0.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([1.0, 0.0, 0.0, 1.0]),
+        text: None,
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn shape(id: &str, position: [f32; 2], size: [f32; 2], color: [f32; 4]) -> AtomeRenderNode {
+    AtomeRenderNode {
+        color: Some(color),
+        ..node(id, "shape", position, size)
+    }
+}
+
+fn rasterize(nodes: Vec<AtomeRenderNode>, options: AtomeRasterOptions) -> AtomeRasterImage {
+    let scene = AtomeRenderScene {
+        nodes,
+        effects: Vec::new(),
+        selection_style: None,
+    };
+    let output = rasterize_scene(&scene, options).unwrap();
+    assert!(output.is_complete(), "{output:?}");
+    output.image
+}
+
+fn on_background(width: f32, height: f32, background: [f32; 4]) -> AtomeRasterOptions {
+    AtomeRasterOptions {
+        background,
+        ..AtomeRasterOptions::new(width, height)
+    }
+}
+
+#[test]
+fn shapes_stack_by_layer_then_scene_order_and_blend_in_linear_light() {
+    let image = rasterize(
+        vec![
+            shape("a", [10.0, 10.0], [20.0, 20.0], [1.0, 0.0, 0.0, 1.0]),
+            AtomeRenderNode {
+                layer: 0,
+                ..shape("b", [20.0, 20.0], [20.0, 20.0], [0.0, 0.0, 1.0, 1.0])
+            },
+            shape("c", [25.0, 25.0], [10.0, 10.0], [0.0, 1.0, 0.0, 1.0]),
+            AtomeRenderNode {
+                opacity: 0.5,
+                ..shape("d", [50.0, 0.0], [10.0, 10.0], [1.0, 1.0, 1.0, 1.0])
+            },
+        ],
+        on_background(64.0, 48.0, [0.0, 0.0, 0.0, 1.0]),
+    );
+    assert_eq!((image.width, image.height), (64, 48));
+    assert_eq!(image.pixel(15, 15), RED);
+    // The higher layer wins, and a later node wins within a layer.
+    assert_eq!(image.pixel(22, 22), RED);
+    assert_eq!(image.pixel(27, 27), [0, 255, 0, 255]);
+    assert_eq!(image.pixel(36, 36), [0, 0, 255, 255]);
+    assert_eq!(image.pixel(5, 5), [0, 0, 0, 255]);
+    // Half-transparent white over black is half the light, not half the code.
+    assert_eq!(image.pixel(55, 5), [188, 188, 188, 255]);
+
+    let doubled = rasterize(
+        vec![shape("a", [0.0, 0.0], [4.0, 4.0], [1.0, 0.0, 0.0, 1.0])],
+        AtomeRasterOptions {
+            pixel_ratio: 2.0,
+            ..AtomeRasterOptions::new(8.0, 8.0)
+        },
+    );
+    assert_eq!((doubled.width, doubled.height), (16, 16));
+    assert_eq!(doubled.pixel(7, 7), RED);
+    assert_eq!(doubled.pixel(8, 8), CLEAR);
+}
+
+#[test]
+fn rounded_corners_and_shadows_follow_the_silhouette() {
+    let image = rasterize(
+        vec![AtomeRenderNode {
+            corner_radius: 10.0,
+            ..shape("card", [0.0, 0.0], [40.0, 40.0], [1.0, 1.0, 1.0, 1.0])
+        }],
+        AtomeRasterOptions::new(40.0, 40.0),
+    );
+    assert_eq!(image.pixel(0, 0), CLEAR);
+    assert_eq!(image.pixel(3, 3), [255, 255, 255, 255]);
+    assert_eq!(image.pixel(20, 20), [255, 255, 255, 255]);
+    let edge = image.pixel(2, 3);
+    assert!(edge[3] > 0 && edge[3] < 255, "{edge:?}");
+
+    let shadowed = rasterize(
+        vec![AtomeRenderNode {
+            shadow: Some(AtomeShadowStyle {
+                color: [0.0, 0.0, 0.0, 1.0],
+                blur: 8.0,
+                offset_x: 0.0,
+                offset_y: 0.0,
+                spread: 0.0,
+            }),
+            ..shape("card", [40.0, 40.0], [20.0, 20.0], [1.0, 0.0, 0.0, 1.0])
+        }],
+        on_background(100.0, 100.0, [1.0, 1.0, 1.0, 1.0]),
+    );
+    assert_eq!(shadowed.pixel(50, 50), RED);
+    let near = shadowed.pixel(61, 50);
+    let farther = shadowed.pixel(64, 50);
+    assert!(
+        near[0] < farther[0] && farther[0] < 255,
+        "{near:?} {farther:?}"
+    );
+    assert_eq!(shadowed.pixel(90, 50), [255, 255, 255, 255]);
+}
+
+#[test]
+fn textures_stretch_over_the_node_and_filter_bilinearly() {
+    let image = rasterize(
+        vec![AtomeRenderNode {
+            source: Some("strip.png".to_string()),
+            texture: Some(AtomeTexture {
+                width: 2,
+                height: 1,
+                rgba: vec![255, 0, 0, 255, 0, 0, 255, 255],
+            }),
+            ..node("strip", "image", [0.0, 0.0], [20.0, 10.0])
+        }],
+        AtomeRasterOptions::new(20.0, 10.0),
+    );
+    assert_eq!(image.pixel(2, 5), RED);
+    assert_eq!(image.pixel(17, 5), [0, 0, 255, 255]);
+    let middle = image.pixel(10, 5);
+    assert!(
+        middle[0] > 0 && middle[2] > 0 && middle[3] == 255,
+        "{middle:?}"
+    );
+}
+
+#[test]
+fn blend_modes_and_masks_composite_their_group() {
+    let gray = on_background(40.0, 20.0, [0.5, 0.5, 0.5, 1.0]);
+    let image = rasterize(
+        vec![AtomeRenderNode {
+            blend_mode: AtomeBlendMode::Multiply,
+            ..shape("tint", [0.0, 0.0], [20.0, 20.0], [1.0, 0.0, 0.0, 1.0])
+        }],
+        gray,
+    );
+    assert_eq!(image.pixel(10, 10), [128, 0, 0, 255]);
+    assert_eq!(image.pixel(30, 10), [128, 128, 128, 255]);
+
+    let masked = rasterize(
+        vec![
+            AtomeRenderNode {
+                mask_id: Some("hole".to_string()),
+                ..shape("card", [0.0, 0.0], [40.0, 20.0], [0.0, 1.0, 0.0, 1.0])
+            },
+            AtomeRenderNode {
+                parent_id: Some("card".to_string()),
+                layer: 2,
+                ..shape("badge", [5.0, 5.0], [30.0, 10.0], [1.0, 0.0, 0.0, 1.0])
+            },
+            shape("hole", [0.0, 0.0], [20.0, 20.0], [1.0, 1.0, 1.0, 1.0]),
+        ],
+        AtomeRasterOptions::new(40.0, 20.0),
+    );
+    // The mask node gates the card and its child instead of drawing.
+    assert_eq!(masked.pixel(2, 2), [0, 255, 0, 255]);
+    assert_eq!(masked.pixel(10, 10), RED);
+    assert_eq!(masked.pixel(30, 10), CLEAR);
+    assert_eq!(masked.pixel(30, 2), CLEAR);
+}
+
+#[test]
+fn viewport_and_local_transforms_move_the_pixels() {
+    let zoomed = rasterize(
+        vec![shape(
+            "tile",
+            [40.0, 40.0],
+            [10.0, 10.0],
+            [1.0, 0.0, 0.0, 1.0],
+        )],
+        AtomeRasterOptions {
+            viewport: AtomeViewport {
+                zoom: 2.0,
+                ..AtomeViewport::default()
+            },
+            ..AtomeRasterOptions::new(100.0, 100.0)
+        },
+    );
+    assert_eq!(zoomed.pixel(31, 31), RED);
+    assert_eq!(zoomed.pixel(49, 49), RED);
+    assert_eq!(zoomed.pixel(28, 28), CLEAR);
+
+    let turned = rasterize(
+        vec![AtomeRenderNode {
+            rotation: 90.0,
+            ..shape("bar", [0.0, 20.0], [40.0, 10.0], [1.0, 0.0, 0.0, 1.0])
+        }],
+        AtomeRasterOptions::new(40.0, 60.0),
+    );
+    // A quarter turn about the center stands the bar upright.
+    assert_eq!(turned.pixel(20, 7), RED);
+    assert_eq!(turned.pixel(20, 42), RED);
+    assert_eq!(turned.pixel(5, 25), CLEAR);
+}
+
+#[test]
+fn paths_and_video_frames_shade_like_their_materials() {
+    let image = rasterize(
+        vec![
+            AtomeRenderNode {
+                path: Some(AtomePath {
+                    d: Some("M0 0 H20 V20 H0 Z".to_string()),
+                    ..AtomePath::default()
+                }),
+                ..node("square", "path", [0.0, 0.0], [20.0, 20.0])
+            },
+            AtomeRenderNode {
+                source: Some("clip.mp4".to_string()),
+                texture: Some(AtomeTexture {
+                    width: 1,
+                    height: 1,
+                    rgba: vec![64, 64, 64, 0],
+                }),
+                filters: Some(AtomeColorFilters {
+                    invert: 1.0,
+                    ..AtomeColorFilters::identity()
+                }),
+                ..node("clip", "video", [30.0, 0.0], [10.0, 10.0])
+            },
+        ],
+        AtomeRasterOptions::new(40.0, 30.0),
+    );
+    assert_eq!(image.pixel(10, 10), RED);
+    assert_eq!(image.pixel(25, 10), CLEAR);
+    // Filters run on the decoded frame, whose alpha the video ignores.
+    assert_eq!(image.pixel(35, 5), [191, 191, 191, 255]);
+}
+
+#[test]
+fn png_output_round_trips_and_diff_counts_pixels() {
+    let image = AtomeRasterImage {
+        width: 3,
+        height: 2,
+        rgba: (0..24).collect(),
+    };
+    let png = image.to_png().unwrap();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(decode_png(&png), image);
+    assert!(AtomeRasterImage {
+        width: 4,
+        ..image.clone()
+    }
+    .to_png()
+    .is_err());
+
+    let mut other = image.clone();
+    other.rgba[5] += 3;
+    other.rgba[22] += 10;
+    assert_eq!(
+        image.diff(&other, 2).unwrap(),
+        AtomeRasterDiff {
+            differing_pixels: 2,
+            max_channel_delta: 10,
+        }
+    );
+    assert_eq!(image.diff(&other, 3).unwrap().differing_pixels, 1);
+    assert!(image
+        .diff(&AtomeRasterImage { width: 2, ..other }, 0)
+        .is_err());
+}
+
+#[test]
+fn nodes_and_effects_without_a_cpu_path_are_skipped_and_reported() {
+    let options = AtomeRasterOptions::new(20.0, 20.0);
+    let glyphs = AtomeRenderNode {
+        text: Some("Hello".to_string()),
+        ..node("label", "text", [0.0, 0.0], [20.0, 20.0])
+    };
+    let scene = AtomeRenderScene {
+        nodes: vec![
+            shape("card", [0.0, 0.0], [10.0, 10.0], [1.0, 0.0, 0.0, 1.0]),
+            glyphs.clone(),
+            node("flower", "procedural_sdf", [0.0, 0.0], [20.0, 20.0]),
+        ],
+        effects: vec![AtomeSceneEffect {
+            id: "frost".to_string(),
+            kind: "backdrop_blur".to_string(),
+            bounds: [0.0, 0.0, 20.0, 20.0],
+            source_layer_max: 0,
+            target_layer: 1,
+            radius: 4.0,
+            downsample: 1.0,
+            tint: [1.0; 4],
+        }],
+        selection_style: None,
+    };
+
+    // The rest of the scene still draws.
+    let output = rasterize_scene(&scene, options).unwrap();
+    assert!(!output.is_complete());
+    assert_eq!(output.skipped_nodes, ["label", "flower"]);
+    assert_eq!(output.skipped_effects, ["frost"]);
+    assert_eq!(output.image.pixel(5, 5), RED);
+    assert_eq!(output.image.pixel(15, 15), CLEAR);
+
+    // Pre-rasterized and empty text have nothing left to lay out.
+    let textured = AtomeRenderNode {
+        texture: Some(AtomeTexture {
+            width: 1,
+            height: 1,
+            rgba: vec![255, 0, 0, 255],
+        }),
+        ..glyphs.clone()
+    };
+    assert_eq!(rasterize(vec![textured], options).pixel(10, 10), RED);
+    let blank = AtomeRenderNode {
+        text: Some("  ".to_string()),
+        ..glyphs
+    };
+    assert_eq!(rasterize(vec![blank], options).pixel(10, 10), CLEAR);
+}
+
+// Golden images are the PNGs `to_png` writes. They were produced by this
+// rasterizer, not read back from the GPU, so they only catch regressions in
+// the CPU path; agreement with the GPU is checked by diffing against a
+// readback. After an intended change, rerun with
+// ATOME_UPDATE_RASTER_FIXTURES=1 and review the new images.
+const FIXTURE_TOLERANCE: u8 = 2;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/raster")
+        .join(format!("{name}.png"))
+}
+
+fn decode_png(png: &[u8]) -> AtomeRasterImage {
+    let decoder = ::png::Decoder::new(std::io::Cursor::new(png));
+    let mut reader = decoder.read_info().unwrap();
+    let mut rgba = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut rgba).unwrap();
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (::png::ColorType::Rgba, ::png::BitDepth::Eight)
+    );
+    rgba.truncate(info.buffer_size());
+    AtomeRasterImage {
+        width: info.width,
+        height: info.height,
+        rgba,
+    }
+}
+
+fn assert_matches_fixture(name: &str, image: &AtomeRasterImage) {
+    let path = fixture_path(name);
+    if std::env::var_os("ATOME_UPDATE_RASTER_FIXTURES").is_some() {
+        std::fs::write(&path, image.to_png().unwrap()).unwrap();
+    }
+    let png = std::fs::read(&path).unwrap_or_else(|error| panic!("{}: {error}", path.display()));
+    let diff = image.diff(&decode_png(&png), FIXTURE_TOLERANCE).unwrap();
+    assert_eq!(diff.differing_pixels, 0, "{name}: {diff:?}");
+}
+
+fn gradient(from: [f32; 4], to: [f32; 4]) -> AtomeFill {
+    AtomeFill::Linear {
+        start: [0.0, 0.0],
+        end: [1.0, 1.0],
+        stops: vec![
+            AtomeColorStop {
+                offset: 0.0,
+                color: from,
+            },
+            AtomeColorStop {
+                offset: 1.0,
+                color: to,
+            },
+        ],
+        spread: AtomeGradientSpread::default(),
+        transform: None,
+    }
+}
+
+#[test]
+fn card_scene_matches_its_golden_image() {
+    let image = rasterize(
+        vec![
+            AtomeRenderNode {
+                corner_radius: 12.0,
+                shadow: Some(AtomeShadowStyle {
+                    color: [0.0, 0.0, 0.0, 0.6],
+                    blur: 6.0,
+                    offset_x: 2.0,
+                    offset_y: 3.0,
+                    spread: 0.0,
+                }),
+                selected: Some(true),
+                ..shape("card", [10.0, 10.0], [60.0, 40.0], [1.0, 1.0, 1.0, 1.0])
+            },
+            AtomeRenderNode {
+                layer: 2,
+                corner_radii: Some([8.0, 0.0, 8.0, 0.0]),
+                fill: Some(gradient([0.1, 0.4, 0.9, 1.0], [0.9, 0.2, 0.5, 1.0])),
+                ..shape("badge", [20.0, 20.0], [40.0, 20.0], [0.0; 4])
+            },
+            AtomeRenderNode {
+                layer: 3,
+                opacity: 0.5,
+                rotation: 30.0,
+                ..shape("chip", [70.0, 40.0], [16.0, 16.0], [0.2, 0.8, 0.3, 1.0])
+            },
+        ],
+        on_background(96.0, 64.0, [0.93, 0.94, 0.96, 1.0]),
+    );
+    assert_matches_fixture("card_scene", &image);
+}
+
+#[test]
+fn media_scene_matches_its_golden_image() {
+    let image = rasterize(
+        vec![
+            AtomeRenderNode {
+                source: Some("checker.png".to_string()),
+                texture: Some(AtomeTexture {
+                    width: 2,
+                    height: 2,
+                    rgba: vec![
+                        255, 255, 255, 255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255, 255,
+                    ],
+                }),
+                ..node("photo", "image", [4.0, 4.0], [24.0, 24.0])
+            },
+            AtomeRenderNode {
+                path: Some(AtomePath {
+                    d: Some("M0 20 L10 0 L20 20 Z".to_string()),
+                    fill: Some([1.0, 0.8, 0.0, 1.0]),
+                    stroke: Some([0.9, 0.9, 0.9, 1.0]),
+                    stroke_width: 2.0,
+                    ..AtomePath::default()
+                }),
+                ..node("peak", "path", [36.0, 6.0], [20.0, 20.0])
+            },
+            AtomeRenderNode {
+                source: Some("clip.mp4".to_string()),
+                texture: Some(AtomeTexture {
+                    width: 2,
+                    height: 1,
+                    rgba: vec![200, 40, 40, 255, 40, 40, 200, 255],
+                }),
+                filters: Some(AtomeColorFilters {
+                    grayscale: 0.5,
+                    ..AtomeColorFilters::identity()
+                }),
+                ..node("clip", "video", [64.0, 4.0], [28.0, 24.0])
+            },
+            AtomeRenderNode {
+                source: Some("voice.m4a".to_string()),
+                playback_progress: Some(0.4),
+                color: Some([0.3, 0.5, 0.9, 1.0]),
+                ..node("voice", "audio_waveform", [4.0, 36.0], [88.0, 20.0])
+            },
+        ],
+        on_background(96.0, 64.0, [0.1, 0.1, 0.12, 1.0]),
+    );
+    assert_matches_fixture("media_scene", &image);
+}
+
+#[test]
+fn composite_scene_matches_its_golden_image() {
+    let image = rasterize(
+        vec![
+            shape("floor", [0.0, 0.0], [96.0, 64.0], [0.5, 0.6, 0.7, 1.0]),
+            AtomeRenderNode {
+                layer: 2,
+                blend_mode: AtomeBlendMode::Multiply,
+                ..shape("shade", [8.0, 8.0], [40.0, 40.0], [1.0, 0.5, 0.2, 1.0])
+            },
+            AtomeRenderNode {
+                layer: 2,
+                mask_id: Some("window".to_string()),
+                corner_radius: 6.0,
+                ..shape("panel", [44.0, 12.0], [44.0, 40.0], [0.9, 0.9, 0.2, 1.0])
+            },
+            AtomeRenderNode {
+                corner_radius: 14.0,
+                ..shape("window", [50.0, 18.0], [28.0, 28.0], [1.0, 1.0, 1.0, 1.0])
+            },
+        ],
+        AtomeRasterOptions {
+            pixel_ratio: 1.5,
+            viewport: AtomeViewport {
+                zoom: 1.25,
+                ..AtomeViewport::default()
+            },
+            ..AtomeRasterOptions::new(96.0, 64.0)
+        },
+    );
+    assert_matches_fixture("composite_scene", &image);
+}
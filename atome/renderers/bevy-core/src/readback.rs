@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{camera::visibility::RenderLayers, prelude::*, sprite_render::MeshMaterial2d};
+
+use crate::{
+    backdrop_surface::BackdropSurfaceMaterial,
+    composite::{AtomeCompositeMember, AtomeNodeComposite},
+    fill::AtomeNodeFill,
+    path::AtomePathVisual,
+    procedural_sdf::AtomeProceduralSdfContract,
+    spawn::{effective_corner_radii, visual_color_for_node},
+    text_layout::AtomeTextLayout,
+    texture::texture_content_hash,
+    types::*,
+    video_external_texture::{
+        video_external_texture_component_from_node, AtomeVideoExternalTexture,
+    },
+    workspace_backdrop::FLOWER_PRESENTATION_LAYER,
+};
+
+// Scene readback: rebuilds the `AtomeRenderScene` the renderer holds from
+// its components, so JS can check it against its own model after a dropped
+// or failed op and resync. Textures live in GPU-side image assets and are
+// not read back; `texture` and `texture_size` are always `None`, and
+// `texture_hash` stands in for the pixels.
+
+/// Uniform radii read back as `corner_radius`, others as `corner_radii`.
+fn split_corner_radii(radii: [f32; 4]) -> (f32, Option<[f32; 4]>) {
+    if radii.iter().all(|radius| *radius == radii[0]) {
+        (radii[0], None)
+    } else {
+        (0.0, Some(radii))
+    }
+}
+
+// Composite groups move members onto private render layers and keep the
+// layers to restore.
+fn reads_presentation(world: &World, entity: Entity) -> bool {
+    let layers = match world.get::<AtomeCompositeMember>(entity) {
+        Some(member) => member.restore.clone(),
+        None => world.get::<RenderLayers>(entity).cloned(),
+    };
+    let presentation = RenderLayers::layer(FLOWER_PRESENTATION_LAYER);
+    layers.is_some_and(|layers| layers.intersects(&presentation))
+}
+
+fn read_backdrop(world: &World, entity: Entity) -> Option<AtomeBackdropStyle> {
+    let handle = world.get::<MeshMaterial2d<BackdropSurfaceMaterial>>(entity)?;
+    let material = world
+        .get_resource::<Assets<BackdropSurfaceMaterial>>()?
+        .get(&handle.0)?;
+    Some(AtomeBackdropStyle {
+        blur_px: material.uniform.size_radius.w,
+        tint: material.uniform.tint.to_array(),
+    })
+}
+
+fn read_node(world: &World, entity: Entity) -> Option<AtomeRenderNode> {
+    let position = world.get::<AtomeLogicalPosition>(entity)?;
+    let size = world.get::<AtomeLogicalSize>(entity)?;
+    let local = world
+        .get::<AtomeLocalTransform>(entity)
+        .copied()
+        .unwrap_or_default();
+    let (corner_radius, corner_radii) = split_corner_radii(
+        world
+            .get::<AtomeCornerRadius>(entity)
+            .map_or([0.0; 4], |radii| radii.0),
+    );
+    let video = world.get::<AtomeVideoExternalTexture>(entity);
+    let composite = world.get::<AtomeNodeComposite>(entity);
+    Some(AtomeRenderNode {
+        id: world.get::<AtomeEntityId>(entity)?.0.clone(),
+        kind: world.get::<AtomeRenderKind>(entity)?.0.clone(),
+        parent_id: world
+            .get::<AtomeParentEntityId>(entity)
+            .and_then(|parent| parent.0.clone()),
+        logical_position: [position.x, position.y],
+        logical_size: [size.width, size.height],
+        clip_rect: world.get::<AtomeClipRect>(entity).and_then(|clip| clip.0),
+        scale: local.scale,
+        rotation: local.rotation,
+        origin: local.origin,
+        layer: world.get::<AtomeLayer>(entity).map_or(0, |layer| layer.0),
+        opacity: world
+            .get::<AtomeVisualOpacity>(entity)
+            .map_or_else(default_opacity, |opacity| opacity.0),
+        corner_radius,
+        corner_radii,
+        shadow: world
+            .get::<AtomeShapeShadow>(entity)
+            .and_then(|shadow| shadow.0)
+            .and_then(AtomeShadowStyle::normalized),
+        backdrop: read_backdrop(world, entity),
+        presentation: reads_presentation(world, entity),
+        color: world.get::<AtomeVisualColor>(entity).map(|color| color.0),
+        text: world
+            .get::<AtomeTextMetadata>(entity)
+            .and_then(|text| text.0.clone()),
+        source: world
+            .get::<AtomeMediaSource>(entity)
+            .and_then(|source| source.0.clone()),
+        texture_size: None,
+        uv_rect: video.map(|video| video.uv_rect),
+        texture: None,
+        texture_hash: world
+            .get::<AtomeTextureHash>(entity)
+            .zip(world.get_resource::<Assets<Image>>())
+            .and_then(|(hash, images)| hash.get(images))
+            .map(str::to_string),
+        peaks: world
+            .get::<AtomeWaveformPeaks>(entity)
+            .map(|peaks| peaks.0.clone())
+            .filter(|peaks| !peaks.is_empty()),
+        playback_progress: world
+            .get::<AtomeWaveformPlaybackProgress>(entity)
+            .and_then(|progress| progress.0),
+        selected: world
+            .get::<AtomeSelected>(entity)
+            .map(|selected| selected.0),
+        filters: video.map(|video| video.filters),
+        transition: video.map(|video| video.transition),
+        procedural: world
+            .get::<AtomeProceduralSdfContract>(entity)
+            .map(|contract| contract.0),
+        path: world
+            .get::<AtomePathVisual>(entity)
+            .map(|visual| visual.path.clone()),
+        fill: world
+            .get::<AtomeNodeFill>(entity)
+            .map(|fill| fill.0.clone()),
+        text_style: world
+            .get::<AtomeTextLayout>(entity)
+            .map(|layout| layout.style.clone()),
+        blend_mode: composite
+            .map(|composite| composite.blend_mode)
+            .unwrap_or_default(),
+        mask_id: composite.and_then(|composite| composite.mask_id.clone()),
+        mask_mode: composite
+            .map(|composite| composite.mask_mode)
+            .unwrap_or_default(),
+    })
+}
+
+/// The scene the renderer holds as of the last applied op, nodes sorted by
+/// id since the entity table keeps no scene order. Fields come back the way
+/// spawning stores them; `stored_node` maps a scene node to the same form.
+pub fn read_scene(world: &World) -> AtomeRenderScene {
+    let mut nodes: Vec<AtomeRenderNode> = world
+        .get_resource::<AtomeEntityTable>()
+        .map(|table| {
+            table
+                .by_id
+                .values()
+                .filter_map(|entity| read_node(world, *entity))
+                .collect()
+        })
+        .unwrap_or_default();
+    nodes.sort_by(|left, right| left.id.cmp(&right.id));
+    AtomeRenderScene {
+        nodes,
+        effects: world
+            .get_resource::<AtomeBackdropBlurState>()
+            .map(|state| state.effects.clone())
+            .unwrap_or_default(),
+        selection_style: world
+            .get_resource::<AtomeBevyRendererConfig>()
+            .map(|config| config.selection_style),
+    }
+}
+
+/// `node` as the renderer would read it back right after spawning it:
+/// normalized values, effective radii, the sprite tint as color, and only
+/// the contracts its kind consumes.
+pub fn stored_node(node: &AtomeRenderNode) -> AtomeRenderNode {
+    let local = AtomeLocalTransform::new(node.scale, node.rotation, node.origin);
+    let (corner_radius, corner_radii) = split_corner_radii(effective_corner_radii(node));
+    let backdrop = node
+        .backdrop
+        .filter(|_| node.kind == "shape")
+        .and_then(AtomeBackdropStyle::normalized);
+    let procedural = node
+        .procedural
+        .filter(|_| node.kind == "procedural_sdf")
+        .map(AtomeProceduralSdf::normalized);
+    let painted = (node.kind == "shape" && backdrop.is_none()) || node.kind == "path";
+    let video = video_external_texture_component_from_node(node);
+    AtomeRenderNode {
+        id: node.id.clone(),
+        kind: node.kind.clone(),
+        parent_id: node.parent_id.clone(),
+        logical_position: node.logical_position,
+        logical_size: node.logical_size.map(|value| value.max(1.0)),
+        clip_rect: node.clip_rect,
+        scale: local.scale,
+        rotation: local.rotation,
+        origin: local.origin,
+        layer: node.layer,
+        opacity: normalize_opacity(node.opacity),
+        corner_radius,
+        corner_radii,
+        shadow: node.shadow.and_then(AtomeShadowStyle::normalized),
+        backdrop,
+        // Glass surfaces and procedural SDFs always draw in presentation.
+        presentation: node.presentation || backdrop.is_some() || procedural.is_some(),
+        color: Some(visual_color_for_node(node, node.texture.is_some())),
+        text: node.text.clone(),
+        source: node.source.clone(),
+        texture_size: None,
+        uv_rect: video.as_ref().map(|video| video.uv_rect),
+        texture: None,
+        texture_hash: node.texture.as_ref().map(texture_content_hash),
+        peaks: node.peaks.clone().filter(|peaks| !peaks.is_empty()),
+        playback_progress: node.playback_progress.map(|value| value.clamp(0.0, 1.0)),
+        selected: Some(node.selected.unwrap_or(false)),
+        filters: video.as_ref().map(|video| video.filters),
+        transition: video.as_ref().map(|video| video.transition),
+        procedural,
+        path: node.path.clone().filter(|_| node.kind == "path"),
+        fill: node
+            .fill
+            .as_ref()
+            .filter(|_| painted)
+            .and_then(AtomeFill::normalized),
+        text_style: node
+            .text_style
+            .clone()
+            .filter(|_| node.kind == "text" && node.texture.is_none())
+            .map(AtomeTextStyle::normalized),
+        blend_mode: node.blend_mode,
+        mask_id: node.mask_id.clone().filter(|id| !id.trim().is_empty()),
+        mask_mode: node.mask_mode,
+    }
+}
+
+macro_rules! changed_fields {
+    ($expected:expr, $actual:expr, $($field:ident),+ $(,)?) => {{
+        let mut fields = Vec::new();
+        $(
+            if $expected.$field != $actual.$field {
+                fields.push(stringify!($field).to_string());
+            }
+        )+
+        fields
+    }};
+}
+
+fn node_changes(expected: &AtomeRenderNode, actual: &AtomeRenderNode) -> Vec<String> {
+    let mut fields = changed_fields!(
+        expected,
+        actual,
+        kind,
+        parent_id,
+        logical_position,
+        logical_size,
+        clip_rect,
+        scale,
+        rotation,
+        origin,
+        layer,
+        opacity,
+        corner_radius,
+        corner_radii,
+        shadow,
+        backdrop,
+        presentation,
+        color,
+        text,
+        source,
+        uv_rect,
+        peaks,
+        playback_progress,
+        selected,
+        filters,
+        transition,
+        procedural,
+        path,
+        fill,
+        text_style,
+        blend_mode,
+        mask_id,
+        mask_mode,
+    );
+    // A scene sent without pixels says nothing about the texture.
+    if expected.texture_hash.is_some() && expected.texture_hash != actual.texture_hash {
+        fields.push("texture".to_string());
+    }
+    fields
+}
+
+/// Compares the scene JS expects against `actual`, typically `read_scene`.
+/// Expected nodes go through `stored_node` first, so fields the renderer
+/// normalizes or drops do not count as differences.
+pub fn diff_scene(expected: &AtomeRenderScene, actual: &AtomeRenderScene) -> AtomeSceneDiff {
+    let actual_nodes: HashMap<&str, &AtomeRenderNode> = actual
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect();
+    let expected_ids: HashSet<&str> = expected.nodes.iter().map(|node| node.id.as_str()).collect();
+    let mut diff = AtomeSceneDiff::default();
+    for node in &expected.nodes {
+        let Some(actual) = actual_nodes.get(node.id.as_str()) else {
+            diff.missing.push(node.id.clone());
+            continue;
+        };
+        let fields = node_changes(&stored_node(node), actual);
+        if !fields.is_empty() {
+            diff.changed.push(AtomeNodeDiff {
+                id: node.id.clone(),
+                fields,
+            });
+        }
+    }
+    diff.unexpected = actual
+        .nodes
+        .iter()
+        .filter(|node| !expected_ids.contains(node.id.as_str()))
+        .map(|node| node.id.clone())
+        .collect();
+    diff.effects_changed = expected.effects != actual.effects;
+    diff.selection_style_changed = actual
+        .selection_style
+        .is_some_and(|style| style != expected.selection_style());
+    diff
+}
+
+/// Ops that bring the renderer back to `expected` given their `diff`:
+/// unexpected and changed nodes are despawned, then missing and changed
+/// nodes are spawned again in scene order, so a node holding a stale
+/// texture comes back with the expected one. The selection style is fixed
+/// at startup and has no op.
+pub fn resync_ops(expected: &AtomeRenderScene, diff: &AtomeSceneDiff) -> Vec<AtomeRenderOp> {
+    let changed: HashSet<&str> = diff.changed.iter().map(|node| node.id.as_str()).collect();
+    let missing: HashSet<&str> = diff.missing.iter().map(String::as_str).collect();
+    let mut ops: Vec<AtomeRenderOp> = diff
+        .unexpected
+        .iter()
+        .chain(diff.changed.iter().map(|node| &node.id))
+        .cloned()
+        .map(AtomeRenderOp::Despawn)
+        .collect();
+    ops.extend(
+        expected
+            .nodes
+            .iter()
+            .filter(|node| changed.contains(node.id.as_str()) || missing.contains(node.id.as_str()))
+            .cloned()
+            .map(AtomeRenderOp::Spawn),
+    );
+    if diff.effects_changed {
+        ops.push(AtomeRenderOp::SceneEffects(AtomeSceneEffectsPatch {
+            effects: expected.effects.clone(),
+        }));
+    }
+    ops
+}
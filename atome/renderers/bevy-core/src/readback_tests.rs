@@ -0,0 +1,283 @@
+use bevy::{image::Image, mesh::Mesh, prelude::*};
+
+use crate::{
+    apply_render_op,
+    composite::CompositeMaterial,
+    fill::ShapeFillMaterial,
+    path::PathMaterial,
+    readback::{diff_scene, read_scene, resync_ops, stored_node},
+    types::*,
+};
+
+fn shape_node(id: &str, position: [f32; 2]) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: "shape".to_string(),
+        parent_id: None,
+        logical_position: position,
+        logical_size: [40.0, 30.0],
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.5, 0.5],
+        layer: 2,
+        opacity: 1.0,
+        corner_radius: 0.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([0.8, 0.3, 0.1, 1.0]),
+        text: None,
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn scene(nodes: Vec<AtomeRenderNode>) -> AtomeRenderScene {
+    AtomeRenderScene {
+        nodes,
+        ..AtomeRenderScene::default()
+    }
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(AtomeEntityTable::default());
+    world.insert_resource(AtomeBevyRendererConfig::empty(640.0, 480.0));
+    world.insert_resource(AtomeRendererDiagnostics::default());
+    world.insert_resource(Assets::<Image>::default());
+    world.insert_resource(Assets::<Mesh>::default());
+    world.insert_resource(Assets::<ShapeFillMaterial>::default());
+    world.insert_resource(Assets::<PathMaterial>::default());
+    world.insert_resource(Assets::<CompositeMaterial>::default());
+    world
+}
+
+fn apply(world: &mut World, ops: Vec<AtomeRenderOp>) {
+    for op in ops {
+        apply_render_op(world, op).unwrap();
+    }
+}
+
+fn styled_scene() -> AtomeRenderScene {
+    let mut card = shape_node("card", [10.0, 20.0]);
+    card.rotation = 30.0;
+    card.scale = [2.0, 0.5];
+    card.corner_radii = Some([4.0, 8.0, 0.0, 2.0]);
+    card.opacity = 0.5;
+    card.fill = Some(AtomeFill::Linear {
+        start: [0.0, 0.0],
+        end: [1.0, 0.0],
+        stops: vec![
+            AtomeColorStop {
+                offset: 0.0,
+                color: [1.0, 0.0, 0.0, 1.0],
+            },
+            AtomeColorStop {
+                offset: 1.0,
+                color: [0.0, 0.0, 1.0, 1.0],
+            },
+        ],
+        spread: AtomeGradientSpread::Pad,
+        transform: None,
+    });
+    let mut badge = shape_node("badge", [4.0, 4.0]);
+    badge.parent_id = Some("card".to_string());
+    badge.corner_radius = 6.0;
+    badge.selected = Some(true);
+    badge.blend_mode = AtomeBlendMode::Multiply;
+    scene(vec![card, badge])
+}
+
+#[test]
+fn read_scene_returns_spawned_nodes_as_stored() {
+    let expected = styled_scene();
+    let mut world = world();
+    apply(
+        &mut world,
+        expected
+            .nodes
+            .iter()
+            .cloned()
+            .map(AtomeRenderOp::Spawn)
+            .collect(),
+    );
+
+    let actual = read_scene(&world);
+
+    let ids: Vec<&str> = actual.nodes.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(ids, ["badge", "card"]);
+    let badge = &actual.nodes[0];
+    assert_eq!(badge.parent_id.as_deref(), Some("card"));
+    assert_eq!(badge.corner_radius, 6.0);
+    assert_eq!(badge.corner_radii, None);
+    assert_eq!(badge.selected, Some(true));
+    assert_eq!(badge.blend_mode, AtomeBlendMode::Multiply);
+    let card = &actual.nodes[1];
+    assert_eq!(card.corner_radii, Some([4.0, 8.0, 0.0, 2.0]));
+    assert_eq!(card.rotation, 30.0);
+    assert_eq!(card.scale, [2.0, 0.5]);
+    assert_eq!(card.fill, expected.nodes[0].fill);
+    assert!(card.texture.is_none());
+    assert!(diff_scene(&expected, &actual).is_empty());
+}
+
+#[test]
+fn stored_node_drops_contracts_the_kind_does_not_consume() {
+    let mut node = shape_node("plain", [0.0, 0.0]);
+    node.logical_size = [0.0, 12.0];
+    node.opacity = 4.0;
+    node.text_style = Some(AtomeTextStyle::default());
+    node.mask_id = Some("  ".to_string());
+
+    let stored = stored_node(&node);
+
+    assert_eq!(stored.logical_size, [1.0, 12.0]);
+    assert_eq!(stored.opacity, 1.0);
+    assert_eq!(stored.text_style, None);
+    assert_eq!(stored.mask_id, None);
+    assert_eq!(stored.selected, Some(false));
+}
+
+#[test]
+fn diff_scene_reports_missing_unexpected_and_changed_fields() {
+    let mut world = world();
+    let mut stray = shape_node("stray", [0.0, 0.0]);
+    stray.layer = 1;
+    apply(
+        &mut world,
+        vec![
+            AtomeRenderOp::Spawn(shape_node("card", [10.0, 20.0])),
+            AtomeRenderOp::Spawn(stray),
+        ],
+    );
+    let mut card = shape_node("card", [12.0, 20.0]);
+    card.color = Some([0.0, 1.0, 0.0, 1.0]);
+    let expected = scene(vec![card, shape_node("lost", [0.0, 0.0])]);
+
+    let diff = diff_scene(&expected, &read_scene(&world));
+
+    assert_eq!(diff.missing, ["lost"]);
+    assert_eq!(diff.unexpected, ["stray"]);
+    assert_eq!(
+        diff.changed,
+        [AtomeNodeDiff {
+            id: "card".to_string(),
+            fields: vec!["logical_position".to_string(), "color".to_string()],
+        }]
+    );
+    assert!(!diff.effects_changed);
+    assert!(!diff.selection_style_changed);
+}
+
+#[test]
+fn resync_ops_bring_the_world_back_to_the_expected_scene() {
+    let mut world = world();
+    apply(
+        &mut world,
+        vec![
+            AtomeRenderOp::Spawn(shape_node("card", [10.0, 20.0])),
+            AtomeRenderOp::Spawn(shape_node("stray", [0.0, 0.0])),
+        ],
+    );
+    let expected = styled_scene();
+
+    let diff = diff_scene(&expected, &read_scene(&world));
+    apply(&mut world, resync_ops(&expected, &diff));
+
+    assert!(diff_scene(&expected, &read_scene(&world)).is_empty());
+}
+
+#[test]
+fn resync_ops_respawn_nodes_holding_a_stale_texture() {
+    let texture = |rgba: [u8; 4]| AtomeTexture {
+        width: 1,
+        height: 1,
+        rgba: rgba.to_vec(),
+    };
+    let photo = |pixel: Option<[u8; 4]>| AtomeRenderNode {
+        kind: "image".to_string(),
+        source: Some("photo.png".to_string()),
+        texture: pixel.map(texture),
+        ..shape_node("photo", [0.0, 0.0])
+    };
+    let mut world = world();
+    apply(
+        &mut world,
+        vec![AtomeRenderOp::Spawn(photo(Some([255, 0, 0, 255])))],
+    );
+    // FNV-1a 64 of the little-endian size and the pixels, hashed from the
+    // uploaded image the first time readback asks.
+    assert_eq!(
+        read_scene(&world).nodes[0].texture_hash.as_deref(),
+        Some("9b04381cfa76bee3")
+    );
+    // The resource update that replaced the pixels was dropped.
+    let expected = scene(vec![photo(Some([0, 0, 255, 255]))]);
+
+    let diff = diff_scene(&expected, &read_scene(&world));
+
+    assert_eq!(
+        diff.changed,
+        [AtomeNodeDiff {
+            id: "photo".to_string(),
+            fields: vec!["texture".to_string()],
+        }]
+    );
+    // Without pixels the expected scene makes no claim about the texture.
+    assert!(diff_scene(&scene(vec![photo(None)]), &read_scene(&world))
+        .changed
+        .iter()
+        .all(|node| !node.fields.contains(&"texture".to_string())));
+
+    apply(&mut world, resync_ops(&expected, &diff));
+    let actual = read_scene(&world);
+    assert_eq!(
+        actual.nodes[0].texture_hash,
+        stored_node(&expected.nodes[0]).texture_hash
+    );
+    assert!(diff_scene(&expected, &actual).is_empty());
+}
+
+#[test]
+fn resync_ops_reapply_changed_scene_effects() {
+    let effect = AtomeSceneEffect {
+        id: "blur".to_string(),
+        kind: "backdrop_blur".to_string(),
+        bounds: [0.0, 0.0, 100.0, 100.0],
+        source_layer_max: 1,
+        target_layer: 2,
+        radius: 8.0,
+        downsample: 2.0,
+        tint: [1.0, 1.0, 1.0, 0.2],
+    };
+    let expected = AtomeRenderScene {
+        effects: vec![effect.clone()],
+        ..AtomeRenderScene::default()
+    };
+
+    let diff = diff_scene(&expected, &AtomeRenderScene::default());
+    let ops = resync_ops(&expected, &diff);
+
+    assert!(diff.effects_changed);
+    assert!(matches!(
+        ops.as_slice(),
+        [AtomeRenderOp::SceneEffects(patch)] if patch.effects == [effect]
+    ));
+}
@@ -26,6 +26,16 @@ pub fn atome_camera_projection(surface_width: f32, surface_height: f32) -> Proje
     Projection::Orthographic(projection)
 }
 
+/// Scene camera projection at viewport `zoom`; zooming in shows less of the
+/// scene on the same surface.
+pub fn atome_viewport_projection(surface_width: f32, surface_height: f32, zoom: f32) -> Projection {
+    let mut projection = atome_camera_projection(surface_width, surface_height);
+    if let Projection::Orthographic(orthographic) = &mut projection {
+        orthographic.scale = 1.0 / zoom;
+    }
+    projection
+}
+
 pub fn atome_rect_transform(
     x: f32,
     y: f32,
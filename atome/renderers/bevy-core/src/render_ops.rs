@@ -2,17 +2,23 @@ use bevy::{image::Image, prelude::*, text::TextBounds};
 
 use crate::workspace_backdrop::resize_workspace_backdrop;
 use crate::{
+    animation::apply_animate,
     backdrop_blur::apply_scene_effects,
     backdrop_surface::{
         patch_backdrop_surface, refresh_workspace_backdrop_enabled, resize_backdrop_surface,
     },
     background::{apply_surface_background, resize_surface_background},
     clip::apply_entity_clip,
+    composite::{
+        patch_node_composite, remove_composite_group, resize_composite_targets, sync_composites,
+    },
+    fill::{patch_node_fill, sync_shape_fill_material},
+    path::{patch_path, sync_path_material},
     procedural_sdf::{patch_procedural_sdf, resize_procedural_sdf},
     render_math::{
         atome_camera_projection, atome_rect_transform_with_local, color_from_rgba, depth_for_layer,
     },
-    resource_ops::texture_sprite_color,
+    resource_ops::{record_texture_hash, texture_sprite_color},
     selection_overlay::{
         rebuild_selection_overlay, remove_selection_overlay, translate_selection_overlay,
     },
@@ -21,9 +27,11 @@ use crate::{
         sync_shape_shadow_overlay_opacity, sync_shape_shadow_overlay_transform,
     },
     spawn::spawn_node_in_world,
+    text_layout::patch_text_style,
     texture::image_handle_from_texture,
     types::*,
     video_external_texture::insert_video_quad_mesh,
+    viewport::{apply_viewport, forget_node_culling, set_node_visibility, sync_viewport},
     waveform_playback_overlay::{
         rebuild_waveform_playback_overlay, remove_waveform_playback_overlay,
     },
@@ -93,6 +101,8 @@ pub fn apply_despawn(world: &mut World, id: &str) -> Result<(), String> {
     remove_selection_overlay(world, entity);
     remove_shape_shadow_overlay(world, entity);
     remove_waveform_playback_overlay(world, entity);
+    forget_node_culling(world, entity);
+    remove_composite_group(world, entity);
     world.despawn(entity);
     refresh_workspace_backdrop_enabled(world)?;
     Ok(())
@@ -238,6 +248,8 @@ pub fn apply_surface(world: &mut World, patch: AtomeSurfacePatch) -> Result<(),
     {
         *projection = atome_camera_projection(width, height);
     }
+    sync_viewport(world)?;
+    resize_composite_targets(world, UVec2::new(pixel_width, pixel_height))?;
     resize_workspace_backdrop(
         world,
         Vec2::new(width, height),
@@ -374,6 +386,25 @@ pub fn apply_style(world: &mut World, patch: AtomeStylePatch) -> Result<(), Stri
         }
         rebuild_waveform_playback_overlay(world, entity)?;
     }
+    if patch.blend_mode.is_some() || patch.mask_id.is_some() || patch.mask_mode.is_some() {
+        patch_node_composite(
+            world,
+            entity,
+            patch.blend_mode,
+            patch.mask_id.clone(),
+            patch.mask_mode,
+        );
+    }
+    if let Some(fill) = patch.fill.clone() {
+        patch_node_fill(world, entity, &patch.id, fill)?;
+        apply_entity_clip(world, entity)?;
+    }
+    if patch.color.is_some() || patch.opacity.is_some() || patch.fill.is_some() {
+        sync_path_material(world, entity)?;
+    }
+    if patch.opacity.is_some() {
+        sync_shape_fill_material(world, entity)?;
+    }
     Ok(())
 }
 
@@ -415,14 +446,18 @@ pub fn apply_layer(world: &mut World, patch: AtomeLayerPatch) -> Result<(), Stri
 
 pub fn apply_visibility(world: &mut World, patch: AtomeVisibilityPatch) -> Result<(), String> {
     let entity = entity_for(world, &patch.id)?;
-    *world
-        .get_mut::<Visibility>(entity)
-        .ok_or_else(|| format!("bevy_visibility_component_missing:{}", patch.id))? =
+    if world.get::<Visibility>(entity).is_none() {
+        return Err(format!("bevy_visibility_component_missing:{}", patch.id));
+    }
+    set_node_visibility(
+        world,
+        entity,
         if patch.visible {
             Visibility::Visible
         } else {
             Visibility::Hidden
-        };
+        },
+    );
     if patch.visible {
         rebuild_selection_overlay(world, entity)?;
         rebuild_shape_shadow_overlay(world, entity)?;
@@ -451,16 +486,27 @@ pub fn apply_text(world: &mut World, patch: AtomeTextPatch) -> Result<(), String
                 .ok_or_else(|| "bevy_image_assets_required".to_string())?;
             image_handle_from_texture(&mut images, &patch.texture, &patch.id)?
         };
+        let image = handle.id();
         let color = texture_sprite_color(world, entity);
         if let Some(mut sprite) = world.get_mut::<Sprite>(entity) {
             sprite.image = handle;
             sprite.color = color;
         }
+        record_texture_hash(world, entity, image);
+        apply_entity_clip(world, entity)?;
+    } else if let Some(style) = patch.style {
+        patch_text_style(world, entity, style)?;
         apply_entity_clip(world, entity)?;
     }
     Ok(())
 }
 
+pub fn apply_path(world: &mut World, patch: AtomePathPatch) -> Result<(), String> {
+    let entity = entity_for(world, &patch.id)?;
+    patch_path(world, entity, &patch.id, patch.path)?;
+    apply_entity_clip(world, entity)
+}
+
 pub fn apply_render_op(world: &mut World, op: AtomeRenderOp) -> Result<(), String> {
     match op {
         AtomeRenderOp::Spawn(node) => apply_spawn(world, node).map(|_| ()),
@@ -477,5 +523,10 @@ pub fn apply_render_op(world: &mut World, op: AtomeRenderOp) -> Result<(), Strin
             apply_surface_background(world, patch).map(|_| ())
         }
         AtomeRenderOp::SceneEffects(patch) => apply_scene_effects(world, patch),
-    }
+        AtomeRenderOp::Path(patch) => apply_path(world, patch),
+        AtomeRenderOp::Animate(animation) => apply_animate(world, animation),
+        AtomeRenderOp::Viewport(patch) => apply_viewport(world, patch),
+    }?;
+    // Membership, masks and bounds can change with any op.
+    sync_composites(world)
 }
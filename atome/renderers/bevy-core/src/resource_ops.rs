@@ -30,6 +30,13 @@ pub(crate) fn texture_sprite_color(world: &mut World, entity: Entity) -> Color {
     color_from_rgba([1.0, 1.0, 1.0, normalize_opacity(opacity)])
 }
 
+/// Points `AtomeTextureHash` at the image a patch just uploaded.
+pub(crate) fn record_texture_hash(world: &mut World, entity: Entity, image: AssetId<Image>) {
+    world
+        .entity_mut(entity)
+        .insert(AtomeTextureHash::new(image));
+}
+
 pub fn apply_resource(world: &mut World, patch: AtomeResourcePatch) -> Result<(), String> {
     let entity = entity_for(world, &patch.id)?;
     let source = patch
@@ -105,12 +112,19 @@ pub fn apply_resource(world: &mut World, patch: AtomeResourcePatch) -> Result<()
             texture_size: patch.texture_size,
             uv_rect: Some(uv_rect),
             texture: None,
+            texture_hash: None,
             peaks: None,
             playback_progress: None,
             selected: None,
             filters: current_filters,
             transition: current_transition,
             procedural: None,
+            path: None,
+            fill: None,
+            text_style: None,
+            blend_mode: AtomeBlendMode::Normal,
+            mask_id: None,
+            mask_mode: AtomeMaskMode::Alpha,
         };
         insert_video_external_texture_component_for_node(world, entity, &node);
         insert_video_quad_mesh(world, entity, size, uv_rect)?;
@@ -127,6 +141,7 @@ pub fn apply_resource(world: &mut World, patch: AtomeResourcePatch) -> Result<()
                 .ok_or_else(|| "bevy_image_assets_required".to_string())?;
             image_handle_from_texture(&mut images, &patch.texture, &patch.id)?
         };
+        let image = handle.id();
         let color = texture_sprite_color(world, entity);
         let mut sprite = world
             .get_mut::<Sprite>(entity)
@@ -134,6 +149,7 @@ pub fn apply_resource(world: &mut World, patch: AtomeResourcePatch) -> Result<()
         sprite.image = handle;
         sprite.color = color;
         drop(sprite);
+        record_texture_hash(world, entity, image);
         apply_entity_clip(world, entity)?;
     }
     Ok(())
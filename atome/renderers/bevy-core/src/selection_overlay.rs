@@ -12,9 +12,14 @@ use crate::{
         AtomeBevyRendererConfig, AtomeLayer, AtomeLogicalPosition, AtomeLogicalSize, AtomeSelected,
         AtomeSelectionOverlay, SelectionVisualStyle,
     },
+    viewport::viewport_detail_zoom,
     workspace_backdrop::FLOWER_PRESENTATION_LAYER,
 };
 
+/// Edges longer than this many dashes on screen are drawn solid instead, so
+/// a node zoomed to fill many screens does not spawn thousands of sprites.
+const MAX_DASHES_PER_EDGE: f32 = 512.0;
+
 fn inherit_presentation_layer(world: &mut World, source: Entity, entities: &[Entity]) {
     let is_presentation = world
         .get::<bevy::camera::visibility::RenderLayers>(source)
@@ -33,11 +38,11 @@ fn inherit_presentation_layer(world: &mut World, source: Entity, entities: &[Ent
     }
 }
 
-fn shadow_depth_for_layer(layer: i32) -> f32 {
+pub(crate) fn shadow_depth_for_layer(layer: i32) -> f32 {
     depth_for_layer(layer) - 0.5
 }
 
-fn outline_depth_for_layer(layer: i32) -> f32 {
+pub(crate) fn outline_depth_for_layer(layer: i32) -> f32 {
     depth_for_layer(layer) + 0.5
 }
 
@@ -54,8 +59,10 @@ fn spawn_overlay_rect(
         let config = world.resource::<AtomeBevyRendererConfig>();
         (config.width, config.height)
     };
-    let width = width.max(1.0);
-    let height = height.max(1.0);
+    // One surface pixel at the current zoom.
+    let min_extent = 1.0 / viewport_detail_zoom(world);
+    let width = width.max(min_extent);
+    let height = height.max(min_extent);
     world
         .spawn((
             Sprite::from_color(color_from_rgba(color), Vec2::new(width, height)),
@@ -64,16 +71,18 @@ fn spawn_overlay_rect(
         .id()
 }
 
-fn spawn_dashed_axis(
-    world: &mut World,
-    entities: &mut Vec<Entity>,
+fn dashed_axis_rects(
+    rects: &mut Vec<[f32; 4]>,
     style: SelectionVisualStyle,
     start_x: f32,
     start_y: f32,
     length: f32,
     horizontal: bool,
-    z: f32,
 ) {
+    let mut style = style;
+    if length / (style.dash_length + style.dash_gap) > MAX_DASHES_PER_EDGE {
+        style.dash_length = length;
+    }
     let mut offset = 0.0;
     while offset < length.max(0.0) {
         let dash = (length - offset).min(style.dash_length).max(0.0);
@@ -90,20 +99,41 @@ fn spawn_dashed_axis(
             };
             let x = start_x + if horizontal { offset } else { 0.0 };
             let y = start_y + if horizontal { 0.0 } else { offset };
-            entities.push(spawn_overlay_rect(
-                world,
-                x,
-                y,
-                width,
-                height,
-                style.border_color,
-                z,
-            ));
+            rects.push([x, y, width, height]);
         }
         offset += style.dash_length + style.dash_gap;
     }
 }
 
+/// Dash rects `[x, y, width, height]` of the outline around a selected
+/// node's bounds: top, bottom, left, then right edge.
+pub(crate) fn selection_outline_rects(
+    style: SelectionVisualStyle,
+    bounds: (f32, f32, f32, f32),
+) -> Vec<[f32; 4]> {
+    let (x, y, width, height) = bounds;
+    let mut rects = Vec::new();
+    dashed_axis_rects(&mut rects, style, x, y, width, true);
+    dashed_axis_rects(
+        &mut rects,
+        style,
+        x,
+        y + height - style.border_thickness,
+        width,
+        true,
+    );
+    dashed_axis_rects(&mut rects, style, x, y, height, false);
+    dashed_axis_rects(
+        &mut rects,
+        style,
+        x + width - style.border_thickness,
+        y,
+        height,
+        false,
+    );
+    rects
+}
+
 pub(crate) fn build_shadow_texture_rgba(
     style: SelectionVisualStyle,
     width: f32,
@@ -229,7 +259,10 @@ pub fn rebuild_selection_overlay(world: &mut World, entity: Entity) -> Result<()
         .get::<AtomeLayer>(entity)
         .map(|value| value.0)
         .unwrap_or(0);
-    let style = world.resource::<AtomeBevyRendererConfig>().selection_style;
+    let style = world
+        .resource::<AtomeBevyRendererConfig>()
+        .selection_style
+        .at_zoom(viewport_detail_zoom(world));
     let x = position.x;
     let y = position.y;
     let width = size.width.max(1.0);
@@ -246,28 +279,17 @@ pub fn rebuild_selection_overlay(world: &mut World, entity: Entity) -> Result<()
         (x, y, width, height),
         shadow_z,
     )?;
-    spawn_dashed_axis(world, &mut entities, style, x, y, width, true, outline_z);
-    spawn_dashed_axis(
-        world,
-        &mut entities,
-        style,
-        x,
-        y + height - style.border_thickness,
-        width,
-        true,
-        outline_z,
-    );
-    spawn_dashed_axis(world, &mut entities, style, x, y, height, false, outline_z);
-    spawn_dashed_axis(
-        world,
-        &mut entities,
-        style,
-        x + width - style.border_thickness,
-        y,
-        height,
-        false,
-        outline_z,
-    );
+    for [x, y, width, height] in selection_outline_rects(style, (x, y, width, height)) {
+        entities.push(spawn_overlay_rect(
+            world,
+            x,
+            y,
+            width,
+            height,
+            style.border_color,
+            outline_z,
+        ));
+    }
     inherit_presentation_layer(world, entity, &entities);
     world.entity_mut(entity).insert(AtomeSelectionOverlay {
         entities,
@@ -18,9 +18,10 @@ use crate::{
         normalize_opacity, AtomeBevyRendererConfig, AtomeLayer, AtomeLogicalPosition,
         AtomeLogicalSize, AtomeShadowStyle,
     },
+    viewport::node_hidden,
 };
 
-fn shadow_depth_for_layer(layer: i32) -> f32 {
+pub(crate) fn shadow_depth_for_layer(layer: i32) -> f32 {
     depth_for_layer(layer) - 0.25
 }
 
@@ -330,11 +331,7 @@ pub fn sync_shape_shadow_overlay_opacity(world: &mut World, entity: Entity, opac
 
 pub fn rebuild_shape_shadow_overlay(world: &mut World, entity: Entity) -> Result<(), String> {
     remove_shape_shadow_overlay(world, entity);
-    if world
-        .get::<Visibility>(entity)
-        .map(|visibility| matches!(visibility, Visibility::Hidden))
-        .unwrap_or(false)
-    {
+    if node_hidden(world, entity) {
         return Ok(());
     }
     let Some(shadow) = world
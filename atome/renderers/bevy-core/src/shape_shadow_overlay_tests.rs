@@ -29,12 +29,19 @@ fn shape_node(id: &str) -> AtomeRenderNode {
         texture_size: None,
         uv_rect: None,
         texture: None,
+        texture_hash: None,
         peaks: None,
         playback_progress: None,
         selected: None,
         filters: None,
         transition: None,
         procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
     }
 }
 
@@ -138,6 +145,10 @@ fn shape_shadow_uses_bevy_overlay_without_changing_logical_size() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
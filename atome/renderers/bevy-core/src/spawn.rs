@@ -8,10 +8,14 @@ use bevy::{
 use crate::{
     backdrop_surface::insert_backdrop_surface,
     clip::apply_entity_clip,
+    composite::insert_node_composite,
+    fill::{insert_shape_fill, normalized_fill_for},
+    path::{insert_path, path_commands_for},
     procedural_sdf::insert_procedural_sdf,
     render_math::{atome_rect_transform_with_local, color_from_rgba, depth_for_layer},
     selection_overlay::rebuild_selection_overlay,
     shape_shadow_overlay::rebuild_shape_shadow_overlay,
+    text_layout::text_layout_components,
     texture::{
         cached_image_handle_from_rounded_rect_mask, corner_radii_are_zero,
         image_handle_from_rounded_rect_mask, image_handle_from_texture, uniform_corner_radii,
@@ -41,7 +45,7 @@ fn texture_owns_sprite_color(kind: &str, has_texture: bool) -> bool {
     has_texture && matches!(kind, "image" | "text" | "audio_waveform")
 }
 
-fn visual_color_for_node(node: &AtomeRenderNode, has_texture: bool) -> [f32; 4] {
+pub(crate) fn visual_color_for_node(node: &AtomeRenderNode, has_texture: bool) -> [f32; 4] {
     if texture_owns_sprite_color(&node.kind, has_texture) {
         [1.0, 1.0, 1.0, 1.0]
     } else {
@@ -144,6 +148,9 @@ pub(crate) fn texture_handle_for_node_in_world(
     world: &mut World,
     node: &AtomeRenderNode,
 ) -> Result<Option<Handle<Image>>, String> {
+    if node.kind == "shape" && node.fill.is_some() && node.backdrop.is_none() {
+        return Ok(None);
+    }
     let radii = effective_corner_radii(node);
     if node.kind == "shape" && node.texture.is_none() && !corner_radii_are_zero(radii) {
         return Ok(Some(cached_image_handle_from_rounded_rect_mask(
@@ -160,6 +167,15 @@ pub(crate) fn texture_handle_for_node_in_world(
     texture_handle_for_node(&mut images, node)
 }
 
+/// Records a spawned node under its id and stamps its spawn order.
+pub(crate) fn register_node_entity(world: &mut World, id: String, entity: Entity) {
+    let mut table = world.resource_mut::<AtomeEntityTable>();
+    let order = table.next_spawn_order;
+    table.next_spawn_order += 1;
+    table.by_id.insert(id, entity);
+    world.entity_mut(entity).insert(AtomeSpawnOrder(order));
+}
+
 pub fn spawn_node_in_world(world: &mut World, node: AtomeRenderNode) -> Result<Entity, String> {
     let entity = {
         let texture_handle = texture_handle_for_node_in_world(world, &node)?;
@@ -177,10 +193,7 @@ pub fn spawn_node_in_world(world: &mut World, node: AtomeRenderNode) -> Result<E
         insert_video_external_texture_component_for_node(world, entity, &node);
         entity
     };
-    world
-        .resource_mut::<AtomeEntityTable>()
-        .by_id
-        .insert(node.id, entity);
+    register_node_entity(world, node.id, entity);
     rebuild_selection_overlay(world, entity)?;
     rebuild_shape_shadow_overlay(world, entity)?;
     rebuild_waveform_playback_overlay(world, entity)?;
@@ -199,32 +212,71 @@ pub fn spawn_node_with_texture_handle(
     let height = node.logical_size[1].max(1.0);
     let color = color_for_node(&node);
     let has_texture = texture_handle.is_some();
+    let texture_image = texture_handle
+        .as_ref()
+        .filter(|_| node.texture.is_some())
+        .map(Handle::id);
     let visual_color = visual_color_for_node(&node, has_texture);
     let visible_color = color_with_opacity(color, node.opacity);
     let size = Vec2::new(width, height);
+    let fill = node
+        .fill
+        .as_ref()
+        .map(|fill| normalized_fill_for(fill, &node.id))
+        .transpose()?;
     let entity = match node.kind.as_str() {
         "shape" => {
             if let Some(backdrop) = node.backdrop {
                 let entity = world
-                    .spawn(node_base_components(&node, width, height, surface_width, surface_height))
+                    .spawn(node_base_components(
+                        &node,
+                        width,
+                        height,
+                        surface_width,
+                        surface_height,
+                    ))
                     .id();
-                insert_backdrop_surface(world, entity, [width, height], node.corner_radius, backdrop)?;
+                insert_backdrop_surface(
+                    world,
+                    entity,
+                    [width, height],
+                    node.corner_radius,
+                    backdrop,
+                )?;
+                entity
+            } else if let Some(fill) = fill {
+                let entity = world
+                    .spawn(node_base_components(
+                        &node,
+                        width,
+                        height,
+                        surface_width,
+                        surface_height,
+                    ))
+                    .id();
+                insert_shape_fill(
+                    world,
+                    entity,
+                    fill,
+                    effective_corner_radii(&node),
+                    node.opacity,
+                )?;
                 entity
             } else {
-            let sprite = if let Some(handle) = texture_handle {
-                let mut sprite = Sprite::from_image(handle);
-                sprite.custom_size = Some(size);
-                sprite.color = color_from_rgba(visible_color);
-                sprite
-            } else {
-                Sprite::from_color(color_from_rgba(visible_color), size)
-            };
-            world
-                .spawn((
-                    node_base_components(&node, width, height, surface_width, surface_height),
-                    sprite,
-                ))
-                .id()
+                let sprite = if let Some(handle) = texture_handle {
+                    let mut sprite = Sprite::from_image(handle);
+                    sprite.custom_size = Some(size);
+                    sprite.color = color_from_rgba(visible_color);
+                    sprite
+                } else {
+                    Sprite::from_color(color_from_rgba(visible_color), size)
+                };
+                world
+                    .spawn((
+                        node_base_components(&node, width, height, surface_width, surface_height),
+                        sprite,
+                    ))
+                    .id()
             }
         }
         "text" => {
@@ -238,6 +290,17 @@ pub fn spawn_node_with_texture_handle(
                         sprite,
                     ))
                     .id()
+            } else if let Some(style) = node.text_style.clone() {
+                world
+                    .spawn((
+                        node_base_components(&node, width, height, surface_width, surface_height),
+                        text_layout_components(
+                            node.text.clone(),
+                            style.normalized(),
+                            visible_color,
+                        ),
+                    ))
+                    .id()
             } else {
                 world
                     .spawn((
@@ -332,6 +395,24 @@ pub fn spawn_node_with_texture_handle(
             insert_procedural_sdf(world, entity, [width, height], contract)?;
             entity
         }
+        "path" => {
+            let path = node
+                .path
+                .clone()
+                .ok_or_else(|| format!("bevy_path_contract_required:{}", node.id))?;
+            let commands = path_commands_for(&path, &node.id)?;
+            let entity = world
+                .spawn(node_base_components(
+                    &node,
+                    width,
+                    height,
+                    surface_width,
+                    surface_height,
+                ))
+                .id();
+            insert_path(world, entity, path, commands, color, node.opacity, fill)?;
+            entity
+        }
         other => return Err(format!("bevy_render_kind_unsupported:{other}")),
     };
     world.entity_mut(entity).insert((
@@ -340,13 +421,23 @@ pub fn spawn_node_with_texture_handle(
         AtomeCornerRadius(effective_corner_radii(&node)),
         AtomeClipRect(node.clip_rect),
     ));
+    if let Some(image) = texture_image {
+        world
+            .entity_mut(entity)
+            .insert(AtomeTextureHash::new(image));
+    }
     if let Some(source_rect) = world.get::<Sprite>(entity).map(|sprite| sprite.rect) {
-        world.entity_mut(entity).insert(AtomeSpriteSourceRect(source_rect));
+        world
+            .entity_mut(entity)
+            .insert(AtomeSpriteSourceRect(source_rect));
     }
     if node.presentation {
-        world.entity_mut(entity).insert(bevy::camera::visibility::RenderLayers::layer(
-            crate::workspace_backdrop::FLOWER_PRESENTATION_LAYER,
-        ));
+        world
+            .entity_mut(entity)
+            .insert(bevy::camera::visibility::RenderLayers::layer(
+                crate::workspace_backdrop::FLOWER_PRESENTATION_LAYER,
+            ));
     }
+    insert_node_composite(world, entity, &node);
     Ok(entity)
 }
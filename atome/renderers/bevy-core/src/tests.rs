@@ -27,12 +27,19 @@ fn shape_node(id: &str) -> AtomeRenderNode {
         texture_size: None,
         uv_rect: None,
         texture: None,
+        texture_hash: None,
         peaks: None,
         playback_progress: None,
         selected: None,
         filters: None,
         transition: None,
         procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
     }
 }
 
@@ -64,12 +71,19 @@ fn text_node_with_texture(id: &str) -> AtomeRenderNode {
             height: 60,
             rgba: vec![255; 160 * 60 * 4],
         }),
+        texture_hash: None,
         peaks: None,
         playback_progress: None,
         selected: None,
         filters: None,
         transition: None,
         procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
     }
 }
 
@@ -173,12 +187,19 @@ fn backdrop_fixture_keeps_text_and_image_in_capture_and_large_glass_circle_in_pr
             height: 4,
             rgba: vec![255; 4 * 4 * 4],
         }),
+        texture_hash: None,
         peaks: None,
         playback_progress: None,
         selected: None,
         filters: None,
         transition: None,
         procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
     };
     let text = AtomeRenderNode {
         logical_position: [130.0, 160.0],
@@ -307,6 +328,10 @@ fn backdrop_style_patch_updates_the_resident_material_without_reallocation() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -460,6 +485,10 @@ fn style_opacity_patch_updates_shape_sprite_without_cumulative_alpha() {
                 filters: None,
                 transition: None,
                 procedural: None,
+                fill: None,
+                blend_mode: None,
+                mask_id: None,
+                mask_mode: None,
             },
         )
         .unwrap();
@@ -499,6 +528,10 @@ fn text_texture_spawn_and_opacity_patch_update_sprite_alpha() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -538,6 +571,10 @@ fn text_color_spawn_and_opacity_patch_update_text_alpha() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -587,6 +624,10 @@ fn shape_shadow_overlay_follows_owner_opacity() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -851,6 +892,10 @@ fn selected_nodes_create_overlay_from_configured_visual_style() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -914,12 +959,19 @@ fn audio_waveform_progress_spawns_and_moves_bevy_playhead_overlay() {
             texture_size: None,
             uv_rect: None,
             texture: None,
+            texture_hash: None,
             peaks: Some(vec![0.1, 0.5, -0.2]),
             playback_progress: Some(0.25),
             selected: None,
             filters: None,
             transition: None,
             procedural: None,
+            path: None,
+            fill: None,
+            text_style: None,
+            blend_mode: AtomeBlendMode::Normal,
+            mask_id: None,
+            mask_mode: AtomeMaskMode::Alpha,
         },
     )
     .unwrap();
@@ -947,6 +999,10 @@ fn audio_waveform_progress_spawns_and_moves_bevy_playhead_overlay() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -974,6 +1030,10 @@ fn audio_waveform_progress_spawns_and_moves_bevy_playhead_overlay() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
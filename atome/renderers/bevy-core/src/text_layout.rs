@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    text::{Font, FontSize, FontSource, FontWeight, LineBreak, LineHeight, TextBounds},
+};
+
+use crate::{
+    render_math::{atome_rect_transform_with_local, color_from_rgba, depth_for_layer},
+    types::*,
+    ui::AtomeUiFontTable,
+    viewport::viewport_zoom,
+};
+
+/// Raster scales snap to quarter octaves so a zoom gesture re-rasterizes
+/// glyphs a handful of times instead of every frame.
+const TEXT_RASTER_STEPS_PER_OCTAVE: f32 = 4.0;
+const TEXT_RASTER_SCALE_MIN: f32 = 0.25;
+const TEXT_RASTER_SCALE_MAX: f32 = 8.0;
+
+/// Renderer-laid-out text node. `raster_scale` is the factor glyphs are
+/// rasterized at on top of the window scale factor; the transform is divided
+/// by it so the node keeps its logical size.
+#[derive(Clone, Debug, Component)]
+pub struct AtomeTextLayout {
+    pub style: AtomeTextStyle,
+    pub raster_scale: f32,
+}
+
+// Faces registered per lowercase family name, each a weight table like the
+// UI's. Families without an entry fall back to the UI font table.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct AtomeTextFontTable {
+    pub families: HashMap<String, AtomeUiFontTable>,
+}
+
+impl AtomeTextFontTable {
+    pub fn handle_for(&self, family: &str, weight: u16) -> Option<Handle<Font>> {
+        self.families
+            .get(&family.to_ascii_lowercase())
+            .and_then(|table| table.handle_for_weight(weight))
+    }
+}
+
+// Registers a TTF as `family` at `weight` for scene text nodes.
+pub fn register_text_font(
+    world: &mut World,
+    family: &str,
+    weight: u16,
+    bytes: Vec<u8>,
+) -> Result<(), String> {
+    let family = family.trim().to_ascii_lowercase();
+    if family.is_empty() {
+        return Err("bevy_text_font_family_required".to_string());
+    }
+    let font = Font::from_bytes(bytes);
+    world.init_resource::<AtomeTextFontTable>();
+    let handle = {
+        let mut fonts = world
+            .get_resource_mut::<Assets<Font>>()
+            .ok_or_else(|| "bevy_text_font_assets_required".to_string())?;
+        fonts.add(font)
+    };
+    world
+        .resource_mut::<AtomeTextFontTable>()
+        .families
+        .entry(family)
+        .or_default()
+        .insert(weight, handle);
+    Ok(())
+}
+
+/// Glyph raster factor for a node drawn at `scale`, quantized to quarter
+/// octaves and clamped so extreme zooms cannot exhaust the glyph atlas.
+pub fn text_raster_scale(scale: [f32; 2]) -> f32 {
+    let magnitude = scale[0].abs().max(scale[1].abs());
+    if !magnitude.is_finite() || magnitude <= 0.0 {
+        return 1.0;
+    }
+    let steps = (magnitude.log2() * TEXT_RASTER_STEPS_PER_OCTAVE).round();
+    (steps / TEXT_RASTER_STEPS_PER_OCTAVE)
+        .exp2()
+        .clamp(TEXT_RASTER_SCALE_MIN, TEXT_RASTER_SCALE_MAX)
+}
+
+fn text_font(world: &World, style: &AtomeTextStyle, raster_scale: f32) -> TextFont {
+    let weight = style.font_weight as u16;
+    let handle = style
+        .font_family
+        .as_deref()
+        .and_then(|family| {
+            world
+                .get_resource::<AtomeTextFontTable>()?
+                .handle_for(family, weight)
+        })
+        .or_else(|| {
+            world
+                .get_resource::<AtomeUiFontTable>()?
+                .handle_for_weight(weight)
+        });
+    let mut font = TextFont {
+        font_size: FontSize::Px(style.font_size * raster_scale),
+        ..default()
+    };
+    // As in the UI, the weight attribute must accompany the handle so
+    // cosmic-text resolves the registered face rather than a sibling weight.
+    if let Some(handle) = handle {
+        font.font = FontSource::Handle(handle);
+        font.weight = FontWeight(weight);
+    }
+    font
+}
+
+fn text_layout(style: &AtomeTextStyle) -> TextLayout {
+    TextLayout {
+        justify: match style.align {
+            AtomeTextAlign::Left => Justify::Left,
+            AtomeTextAlign::Center => Justify::Center,
+            AtomeTextAlign::Right => Justify::Right,
+            AtomeTextAlign::Justified => Justify::Justified,
+        },
+        linebreak: match style.wrap {
+            AtomeTextWrap::Word => LineBreak::WordBoundary,
+            AtomeTextWrap::Character => LineBreak::AnyCharacter,
+            AtomeTextWrap::None => LineBreak::NoWrap,
+        },
+    }
+}
+
+/// Components of a laid-out text node; the font and bounds are filled in by
+/// `sync_text_layout` once the node's transform is known.
+pub fn text_layout_components(
+    text: Option<String>,
+    style: AtomeTextStyle,
+    color: [f32; 4],
+) -> impl Bundle {
+    (
+        Text2d::new(text.unwrap_or_default()),
+        TextColor(color_from_rgba(color)),
+        text_layout(&style),
+        AtomeTextLayout {
+            style,
+            raster_scale: 0.0,
+        },
+    )
+}
+
+/// Switches an existing text node to renderer layout with `style`, dropping
+/// any JS-rasterized sprite.
+pub fn patch_text_style(
+    world: &mut World,
+    entity: Entity,
+    style: AtomeTextStyle,
+) -> Result<(), String> {
+    let style = style.normalized();
+    if world.get::<AtomeTextLayout>(entity).is_some() {
+        world.entity_mut(entity).insert((
+            text_layout(&style),
+            AtomeTextLayout {
+                style,
+                raster_scale: 0.0,
+            },
+        ));
+        return Ok(());
+    }
+    let text = world
+        .get::<AtomeTextMetadata>(entity)
+        .and_then(|metadata| metadata.0.clone());
+    let color = world
+        .get::<AtomeVisualColor>(entity)
+        .map(|value| value.0)
+        .unwrap_or([1.0, 1.0, 1.0, 1.0]);
+    let opacity = world
+        .get::<AtomeVisualOpacity>(entity)
+        .map(|value| value.0)
+        .unwrap_or(1.0);
+    let mut visible_color = color;
+    visible_color[3] *= normalize_opacity(opacity);
+    world
+        .entity_mut(entity)
+        .remove::<(Sprite, TextFont, TextBounds)>()
+        .insert(text_layout_components(text, style, visible_color));
+    Ok(())
+}
+
+/// Places a laid-out text node and re-rasterizes its glyphs when the node's
+/// scale crosses a raster step or its box changes. Text is never cropped by
+/// the clip rect, so the transform covers the whole node.
+pub fn sync_text_layout(world: &mut World, entity: Entity) -> Result<(), String> {
+    let Some(layout) = world.get::<AtomeTextLayout>(entity).cloned() else {
+        return Ok(());
+    };
+    let position = *world
+        .get::<AtomeLogicalPosition>(entity)
+        .ok_or_else(|| "bevy_text_position_missing".to_string())?;
+    let size = *world
+        .get::<AtomeLogicalSize>(entity)
+        .ok_or_else(|| "bevy_text_size_missing".to_string())?;
+    let local = world
+        .get::<AtomeLocalTransform>(entity)
+        .copied()
+        .unwrap_or_default();
+    let layer = world
+        .get::<AtomeLayer>(entity)
+        .map(|value| value.0)
+        .unwrap_or(0);
+    let (surface_width, surface_height) = {
+        let config = world.resource::<AtomeBevyRendererConfig>();
+        (config.width, config.height)
+    };
+    // Glyphs raster for their on-screen size, viewport zoom included.
+    let zoom = viewport_zoom(world);
+    let raster_scale = text_raster_scale([local.scale[0] * zoom, local.scale[1] * zoom]);
+    let box_width = layout.style.box_width(size.width);
+    let box_height = size.height.max(1.0);
+
+    let mut transform = atome_rect_transform_with_local(
+        position.x,
+        position.y,
+        size.width,
+        size.height,
+        surface_width,
+        surface_height,
+        depth_for_layer(layer),
+        local.scale,
+        local.rotation,
+        local.origin,
+    );
+    // A wrap width narrower than the node keeps the box on the node's left edge.
+    let inset = (box_width - size.width.max(1.0)) / 2.0;
+    transform.translation += transform.rotation * Vec3::new(inset * transform.scale.x, 0.0, 0.0);
+    transform.scale.x /= raster_scale;
+    transform.scale.y /= raster_scale;
+    world
+        .entity_mut(entity)
+        .insert((transform, GlobalTransform::from(transform)));
+
+    let bounds = TextBounds::new(box_width * raster_scale, box_height * raster_scale);
+    let current_bounds = world.get::<TextBounds>(entity).copied();
+    if layout.raster_scale == raster_scale
+        && current_bounds
+            .is_some_and(|current| current.width == bounds.width && current.height == bounds.height)
+    {
+        return Ok(());
+    }
+    let font = text_font(world, &layout.style, raster_scale);
+    let line_height = layout
+        .style
+        .line_height
+        .map_or(LineHeight::default(), |line_height| {
+            LineHeight::Px(line_height * raster_scale)
+        });
+    world.entity_mut(entity).insert((font, bounds, line_height));
+    if let Some(mut layout) = world.get_mut::<AtomeTextLayout>(entity) {
+        layout.raster_scale = raster_scale;
+    }
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use bevy::{
+    image::Image,
+    prelude::*,
+    text::{FontSize, LineBreak, LineHeight, TextBounds},
+};
+
+use crate::{
+    apply_spawn, apply_text, apply_transform,
+    text_layout::{text_raster_scale, AtomeTextLayout},
+    types::*,
+};
+
+fn text_node(id: &str, text_style: Option<AtomeTextStyle>) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: "text".to_string(),
+        parent_id: None,
+        logical_position: [40.0, 30.0],
+        logical_size: [100.0, 40.0],
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.0, 0.0],
+        layer: 2,
+        opacity: 1.0,
+        corner_radius: 0.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([0.1, 0.1, 0.1, 1.0]),
+        text: Some("Hello layout".to_string()),
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: None,
+        fill: None,
+        text_style,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn style() -> AtomeTextStyle {
+    AtomeTextStyle {
+        font_size: 16.0,
+        line_height: Some(20.0),
+        align: AtomeTextAlign::Center,
+        wrap_width: Some(60.0),
+        ..AtomeTextStyle::default()
+    }
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(AtomeEntityTable::default());
+    world.insert_resource(AtomeBevyRendererConfig::empty(640.0, 480.0));
+    world.insert_resource(AtomeRendererDiagnostics::default());
+    world.insert_resource(Assets::<Image>::default());
+    world
+}
+
+fn font_size(world: &World, entity: Entity) -> f32 {
+    match world.get::<TextFont>(entity).unwrap().font_size {
+        FontSize::Px(size) => size,
+        other => panic!("unexpected font size {other:?}"),
+    }
+}
+
+fn bounds(world: &World, entity: Entity) -> [Option<f32>; 2] {
+    let bounds = world.get::<TextBounds>(entity).unwrap();
+    [bounds.width, bounds.height]
+}
+
+#[test]
+fn raster_scale_snaps_to_quarter_octaves_within_limits() {
+    assert_eq!(text_raster_scale([1.0, 1.0]), 1.0);
+    assert_eq!(text_raster_scale([4.0, 0.5]), 4.0);
+    assert_eq!(text_raster_scale([-2.0, 1.0]), 2.0);
+    assert!((text_raster_scale([3.0, 3.0]) - 2.0_f32.powf(1.5)).abs() < 1e-5);
+    assert_eq!(
+        text_raster_scale([1.05, 1.0]),
+        1.0,
+        "small zoom steps reuse the cached glyphs"
+    );
+    assert_eq!(text_raster_scale([100.0, 100.0]), 8.0);
+    assert_eq!(text_raster_scale([0.01, 0.01]), 0.25);
+    assert_eq!(text_raster_scale([0.0, 0.0]), 1.0);
+    assert_eq!(text_raster_scale([f32::NAN, 1.0]), 1.0);
+}
+
+#[test]
+fn styled_text_is_laid_out_in_its_wrap_box_and_rasterized_at_the_zoom_scale() {
+    let mut world = world();
+    let entity = apply_spawn(&mut world, text_node("label", Some(style()))).unwrap();
+    assert!(world.get::<Sprite>(entity).is_none());
+    assert_eq!(world.get::<Text2d>(entity).unwrap().0, "Hello layout");
+    let layout = world.get::<TextLayout>(entity).unwrap();
+    assert!(matches!(layout.justify, Justify::Center));
+    assert!(matches!(layout.linebreak, LineBreak::WordBoundary));
+    assert_eq!(font_size(&world, entity), 16.0);
+    assert_eq!(bounds(&world, entity), [Some(60.0), Some(40.0)]);
+    assert!(matches!(
+        world.get::<LineHeight>(entity),
+        Some(LineHeight::Px(height)) if *height == 20.0
+    ));
+    // The 60px box sits on the node's left edge: 40 + 30 - 320.
+    let transform = *world.get::<Transform>(entity).unwrap();
+    assert_eq!(transform.translation.truncate(), Vec2::new(-250.0, 190.0));
+    assert_eq!(transform.scale, Vec3::ONE);
+
+    apply_transform(
+        &mut world,
+        AtomeTransformPatch {
+            id: "label".to_string(),
+            logical_position: [40.0, 30.0],
+            logical_size: [100.0, 40.0],
+            scale: [4.0, 4.0],
+            rotation: 0.0,
+            origin: [0.0, 0.0],
+            clip_rect: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        world.get::<AtomeTextLayout>(entity).unwrap().raster_scale,
+        4.0
+    );
+    assert_eq!(font_size(&world, entity), 64.0);
+    assert_eq!(bounds(&world, entity), [Some(240.0), Some(160.0)]);
+    assert!(matches!(
+        world.get::<LineHeight>(entity),
+        Some(LineHeight::Px(height)) if *height == 80.0
+    ));
+    // Glyphs are rasterized 4x larger, so the transform no longer magnifies.
+    let transform = *world.get::<Transform>(entity).unwrap();
+    assert_eq!(transform.scale, Vec3::ONE);
+    assert_eq!(transform.translation.truncate(), Vec2::new(-160.0, 130.0));
+}
+
+#[test]
+fn style_patch_moves_a_legacy_text_node_to_renderer_layout() {
+    let mut world = world();
+    let entity = apply_spawn(&mut world, text_node("legacy", None)).unwrap();
+    assert!(world.get::<AtomeTextLayout>(entity).is_none());
+
+    apply_text(
+        &mut world,
+        AtomeTextPatch {
+            id: "legacy".to_string(),
+            text: Some("Wrapped by character".to_string()),
+            texture: None,
+            style: Some(AtomeTextStyle {
+                wrap: AtomeTextWrap::Character,
+                font_size: f32::NAN,
+                ..AtomeTextStyle::default()
+            }),
+        },
+    )
+    .unwrap();
+    let layout = world.get::<AtomeTextLayout>(entity).unwrap();
+    assert_eq!(layout.style.font_size, 14.0);
+    assert_eq!(layout.raster_scale, 1.0);
+    assert_eq!(
+        world.get::<Text2d>(entity).unwrap().0,
+        "Wrapped by character"
+    );
+    assert!(matches!(
+        world.get::<TextLayout>(entity).unwrap().linebreak,
+        LineBreak::AnyCharacter
+    ));
+    assert_eq!(font_size(&world, entity), 14.0);
+    assert_eq!(bounds(&world, entity), [Some(100.0), Some(40.0)]);
+}
@@ -42,6 +42,30 @@ pub fn image_handle_from_texture(
     Ok(images.add(image_from_texture(texture, id)?))
 }
 
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hash of a texture's size and pixels, so readback can tell which texture a
+/// node holds without copying it back: 64-bit FNV-1a over the width and
+/// height (little-endian `u32`) followed by the RGBA bytes, as 16 lowercase
+/// hex digits. Stable across builds and platforms, so hashes read from one
+/// renderer compare with hashes computed anywhere else.
+pub fn texture_rgba_hash(width: u32, height: u32, rgba: &[u8]) -> String {
+    let hash = width
+        .to_le_bytes()
+        .iter()
+        .chain(&height.to_le_bytes())
+        .chain(rgba)
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+        });
+    format!("{hash:016x}")
+}
+
+pub fn texture_content_hash(texture: &AtomeTexture) -> String {
+    texture_rgba_hash(texture.width, texture.height, &texture.rgba)
+}
+
 /// Corner radii in `[top_left, top_right, bottom_right, bottom_left]` order,
 /// matching the `radius_corners` style field emitted by the shared UI tree.
 pub type AtomeCornerRadii = [f32; 4];
@@ -187,6 +211,19 @@ pub fn image_handle_from_rounded_rect_mask(
     radii: AtomeCornerRadii,
     id: &str,
 ) -> Result<Handle<Image>, String> {
+    Ok(images.add(image_from_texture(
+        &rounded_rect_mask_texture(width, height, radii),
+        id,
+    )?))
+}
+
+/// White texels whose alpha is the rounded rect coverage, one texel per
+/// logical pixel.
+pub(crate) fn rounded_rect_mask_texture(
+    width: f32,
+    height: f32,
+    radii: AtomeCornerRadii,
+) -> AtomeTexture {
     let width = width.ceil().max(1.0) as u32;
     let height = height.ceil().max(1.0) as u32;
     let mut rgba = vec![255; width as usize * height as usize * 4];
@@ -196,12 +233,9 @@ pub fn image_handle_from_rounded_rect_mask(
             rgba[(y as usize * width as usize + x as usize) * 4 + 3] = alpha;
         }
     }
-    Ok(images.add(image_from_texture(
-        &AtomeTexture {
-            width,
-            height,
-            rgba,
-        },
-        id,
-    )?))
+    AtomeTexture {
+        width,
+        height,
+        rgba,
+    }
 }
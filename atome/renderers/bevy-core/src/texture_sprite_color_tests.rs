@@ -30,12 +30,19 @@ fn textured_image_node(id: &str) -> AtomeRenderNode {
             height: 2,
             rgba: vec![255; 2 * 2 * 4],
         }),
+        texture_hash: None,
         peaks: None,
         playback_progress: None,
         selected: None,
         filters: None,
         transition: None,
         procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
     }
 }
 
@@ -82,6 +89,10 @@ fn textured_image_opacity_patch_keeps_white_sprite_modulation() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
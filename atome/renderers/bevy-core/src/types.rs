@@ -1,8 +1,17 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub use crate::components::*;
-pub use crate::types_procedural::AtomeProceduralSdf;
+pub use crate::types_animation::*;
+pub use crate::types_composite::*;
+pub use crate::types_fill::*;
 pub use crate::types_ops::*;
+pub use crate::types_path::*;
+pub use crate::types_pick::*;
+pub use crate::types_procedural::AtomeProceduralSdf;
+pub use crate::types_raster::*;
+pub use crate::types_readback::*;
+pub use crate::types_text::*;
+pub use crate::types_viewport::*;
 
 pub fn default_opacity() -> f32 {
     1.0
@@ -16,7 +25,7 @@ pub fn normalize_opacity(opacity: f32) -> f32 {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
 /// Canonical GPU drop shadow for a shape silhouette.
 ///
 /// The renderer derives the alpha mask from the owner's rounded geometry,
@@ -73,7 +82,7 @@ fn finite_or(value: f32, fallback: f32) -> f32 {
 /// from the node/style payload and copied into the Bevy video material uniform —
 /// see `assets/shaders/video_external.wgsl`. Missing fields default to identity,
 /// so a partial `{ "brightness": 1.2 }` leaves the rest untouched.
-#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct AtomeColorFilters {
     #[serde(default = "default_filter_unit")]
     pub brightness: f32,
@@ -123,7 +132,7 @@ impl AtomeColorFilters {
 /// `apply_transition` in `assets/shaders/video_external.wgsl`. All-f32 so it
 /// copies straight into the uniform. kind: 0 none, 1 fade, 2 wipe, 3 slide;
 /// role: 0 incoming, 1 outgoing. `none()` = no transition (identity).
-#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub struct AtomeTransition {
     #[serde(default)]
     pub kind: f32,
@@ -271,7 +280,7 @@ pub fn validate_uv_rect(
     Ok(())
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
 pub struct SelectionVisualStyle {
     pub shadow_size: f32,
     pub border_thickness: f32,
@@ -294,7 +303,26 @@ impl Default for SelectionVisualStyle {
     }
 }
 
-#[derive(Clone, Debug, Default, Deserialize)]
+impl SelectionVisualStyle {
+    /// The style in scene units for a viewport `zoom`, so the outline keeps
+    /// its on-screen thickness and dash rhythm. The shadow scales with the
+    /// node it sits under.
+    pub fn at_zoom(self, zoom: f32) -> Self {
+        let zoom = if zoom.is_finite() && zoom > 0.0 {
+            zoom
+        } else {
+            1.0
+        };
+        Self {
+            border_thickness: self.border_thickness / zoom,
+            dash_length: self.dash_length / zoom,
+            dash_gap: self.dash_gap / zoom,
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct AtomeRenderScene {
     #[serde(default)]
     pub nodes: Vec<AtomeRenderNode>,
@@ -310,7 +338,7 @@ impl AtomeRenderScene {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct AtomeSceneEffect {
     pub id: String,
     pub kind: String,
@@ -329,7 +357,7 @@ pub struct AtomeSceneEffectsPatch {
     pub effects: Vec<AtomeSceneEffect>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AtomeRenderNode {
     pub id: String,
     pub kind: String,
@@ -365,6 +393,10 @@ pub struct AtomeRenderNode {
     pub texture_size: Option<[u32; 2]>,
     pub uv_rect: Option<[f32; 4]>,
     pub texture: Option<AtomeTexture>,
+    // Content hash of `texture` (`texture_rgba_hash`), reported by scene
+    // readback in place of the pixels. Spawning ignores this field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture_hash: Option<String>,
     pub peaks: Option<Vec<f32>>,
     pub playback_progress: Option<f32>,
     pub selected: Option<bool>,
@@ -374,9 +406,27 @@ pub struct AtomeRenderNode {
     pub transition: Option<AtomeTransition>,
     #[serde(default)]
     pub procedural: Option<AtomeProceduralSdf>,
+    #[serde(default)]
+    pub path: Option<AtomePath>,
+    // Gradient or solid interior paint for `shape` and `path` nodes; takes
+    // precedence over `color` (and a shape's texture) when present.
+    #[serde(default)]
+    pub fill: Option<AtomeFill>,
+    // Renderer-side layout for `text` nodes; without it (or with a texture)
+    // the node keeps drawing the JS-rasterized bitmap.
+    #[serde(default)]
+    pub text_style: Option<AtomeTextStyle>,
+    #[serde(default)]
+    pub blend_mode: AtomeBlendMode,
+    // Another node whose alpha or luminance masks this node and its
+    // children. The mask node is consumed: it no longer draws on its own.
+    #[serde(default)]
+    pub mask_id: Option<String>,
+    #[serde(default)]
+    pub mask_mode: AtomeMaskMode,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AtomeTexture {
     pub width: u32,
     pub height: u32,
@@ -384,7 +434,7 @@ pub struct AtomeTexture {
     pub rgba: Vec<u8>,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
 pub struct AtomeBackdropStyle {
     pub blur_px: f32,
     pub tint: [f32; 4],
@@ -392,7 +442,10 @@ pub struct AtomeBackdropStyle {
 
 impl AtomeBackdropStyle {
     pub fn normalized(self) -> Option<Self> {
-        if !self.blur_px.is_finite() || self.blur_px <= 0.0 || self.tint.iter().any(|value| !value.is_finite()) {
+        if !self.blur_px.is_finite()
+            || self.blur_px <= 0.0
+            || self.tint.iter().any(|value| !value.is_finite())
+        {
             return None;
         }
         Some(Self {
@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::{AtomeColorFilters, AtomeProceduralSdf};
+
+fn default_iterations() -> u32 {
+    1
+}
+
+/// Timing curve, matching the CSS easing functions of the same names.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AtomeEasing {
+    #[default]
+    Linear,
+    Ease,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    CubicBezier {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+    },
+    Steps {
+        count: u32,
+        #[serde(default)]
+        jump_start: bool,
+    },
+}
+
+/// y of the cubic bezier through (0,0), (x1,y1), (x2,y2), (1,1) at `x`.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let curve = |a: f32, b: f32, t: f32| {
+        let u = 1.0 - t;
+        3.0 * u * u * t * a + 3.0 * u * t * t * b + t * t * t
+    };
+    let slope = |a: f32, b: f32, t: f32| {
+        let u = 1.0 - t;
+        3.0 * u * u * a + 6.0 * u * t * (b - a) + 3.0 * t * t * (1.0 - b)
+    };
+    // Newton converges in a few steps on well-behaved curves; bisection
+    // catches the flat-slope cases it cannot.
+    let mut t = x;
+    for _ in 0..8 {
+        let error = curve(x1, x2, t) - x;
+        if error.abs() < 1e-6 {
+            return curve(y1, y2, t);
+        }
+        let derivative = slope(x1, x2, t);
+        if derivative.abs() < 1e-6 {
+            break;
+        }
+        t = (t - error / derivative).clamp(0.0, 1.0);
+    }
+    let (mut low, mut high) = (0.0_f32, 1.0_f32);
+    t = x;
+    for _ in 0..32 {
+        let value = curve(x1, x2, t);
+        if (value - x).abs() < 1e-6 {
+            break;
+        }
+        if value < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) * 0.5;
+    }
+    curve(y1, y2, t)
+}
+
+impl AtomeEasing {
+    /// Eased value of linear progress `t` in `[0, 1]`.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::Ease => cubic_bezier(0.25, 0.1, 0.25, 1.0, t),
+            Self::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, t),
+            Self::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, t),
+            Self::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, t),
+            Self::CubicBezier { x1, y1, x2, y2 } => {
+                cubic_bezier(x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2, t)
+            }
+            Self::Steps { count, jump_start } => {
+                let count = count.max(1) as f32;
+                let step = if jump_start {
+                    (t * count).floor() + 1.0
+                } else {
+                    (t * count).floor()
+                };
+                (step / count).min(1.0)
+            }
+        }
+    }
+
+    fn is_finite(self) -> bool {
+        match self {
+            Self::CubicBezier { x1, y1, x2, y2 } => [x1, y1, x2, y2].iter().all(|v| v.is_finite()),
+            _ => true,
+        }
+    }
+}
+
+/// One keyframe at `offset` in `[0, 1]` of an iteration. Each property
+/// animates between the keyframes that set it and holds its first and last
+/// values outside them; `easing` shapes the segment leading to the next
+/// keyframe that sets the same property.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct AtomeKeyframe {
+    pub offset: f32,
+    #[serde(default)]
+    pub easing: Option<AtomeEasing>,
+    #[serde(default)]
+    pub position: Option<[f32; 2]>,
+    #[serde(default)]
+    pub size: Option<[f32; 2]>,
+    #[serde(default)]
+    pub scale: Option<[f32; 2]>,
+    #[serde(default)]
+    pub rotation: Option<f32>,
+    #[serde(default)]
+    pub opacity: Option<f32>,
+    #[serde(default)]
+    pub color: Option<[f32; 4]>,
+    #[serde(default)]
+    pub filters: Option<AtomeColorFilters>,
+    #[serde(default)]
+    pub procedural: Option<AtomeProceduralSdf>,
+}
+
+/// Keyframe animation of one node, run by the renderer every frame.
+///
+/// `easing` applies to each iteration's overall progress, like a Web
+/// Animations effect easing. `iterations: 0` repeats forever; `yoyo` plays
+/// every other iteration backwards. Starting another animation with the same
+/// `id` replaces this one.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AtomeAnimation {
+    pub id: String,
+    pub target_id: String,
+    pub keyframes: Vec<AtomeKeyframe>,
+    pub duration_ms: f32,
+    #[serde(default)]
+    pub delay_ms: f32,
+    #[serde(default)]
+    pub easing: AtomeEasing,
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+    #[serde(default)]
+    pub yoyo: bool,
+}
+
+/// Where an animation is `elapsed_ms` after it started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AtomeAnimationPhase {
+    Delayed,
+    Running(f32),
+    Finished(f32),
+}
+
+/// Property values of an animation at one instant; `None` where no keyframe
+/// sets the property.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AtomeAnimationSample {
+    pub position: Option<[f32; 2]>,
+    pub size: Option<[f32; 2]>,
+    pub scale: Option<[f32; 2]>,
+    pub rotation: Option<f32>,
+    pub opacity: Option<f32>,
+    pub color: Option<[f32; 4]>,
+    pub filters: Option<AtomeColorFilters>,
+    pub procedural: Option<AtomeProceduralSdf>,
+}
+
+impl AtomeAnimationSample {
+    pub fn has_transform(&self) -> bool {
+        self.position.is_some()
+            || self.size.is_some()
+            || self.scale.is_some()
+            || self.rotation.is_some()
+    }
+
+    pub fn has_style(&self) -> bool {
+        self.opacity.is_some()
+            || self.color.is_some()
+            || self.filters.is_some()
+            || self.procedural.is_some()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeAnimationEventKind {
+    Completed,
+    Cancelled,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AtomeAnimationEvent {
+    pub animation_id: String,
+    pub target_id: String,
+    pub kind: AtomeAnimationEventKind,
+}
+
+fn lerp(from: f32, to: f32, amount: f32) -> f32 {
+    from + (to - from) * amount
+}
+
+fn lerp_array<const N: usize>(from: [f32; N], to: [f32; N], amount: f32) -> [f32; N] {
+    std::array::from_fn(|index| lerp(from[index], to[index], amount))
+}
+
+fn lerp_filters(from: AtomeColorFilters, to: AtomeColorFilters, amount: f32) -> AtomeColorFilters {
+    AtomeColorFilters {
+        brightness: lerp(from.brightness, to.brightness, amount),
+        contrast: lerp(from.contrast, to.contrast, amount),
+        saturate: lerp(from.saturate, to.saturate, amount),
+        grayscale: lerp(from.grayscale, to.grayscale, amount),
+        sepia: lerp(from.sepia, to.sepia, amount),
+        invert: lerp(from.invert, to.invert, amount),
+        hue: lerp(from.hue, to.hue, amount),
+    }
+}
+
+fn sample_track<T: Copy>(
+    keyframes: &[AtomeKeyframe],
+    progress: f32,
+    value: impl Fn(&AtomeKeyframe) -> Option<T>,
+    interpolate: impl Fn(T, T, f32) -> T,
+) -> Option<T> {
+    let mut track = keyframes
+        .iter()
+        .filter_map(|keyframe| Some((keyframe, value(keyframe)?)));
+    let (mut from_keyframe, mut from) = track.next()?;
+    if progress <= from_keyframe.offset {
+        return Some(from);
+    }
+    for (to_keyframe, to) in track {
+        if progress <= to_keyframe.offset {
+            let span = to_keyframe.offset - from_keyframe.offset;
+            let local = if span > 0.0 {
+                (progress - from_keyframe.offset) / span
+            } else {
+                1.0
+            };
+            let eased = from_keyframe.easing.unwrap_or_default().apply(local);
+            return Some(interpolate(from, to, eased));
+        }
+        (from_keyframe, from) = (to_keyframe, to);
+    }
+    Some(from)
+}
+
+impl AtomeAnimation {
+    /// Validated copy with keyframes sorted by offset. Empty ids, no
+    /// keyframes, or non-finite timing yield an error.
+    pub fn normalized(self) -> Result<Self, String> {
+        let invalid = || format!("bevy_animation_invalid:{}", self.id);
+        if self.id.trim().is_empty()
+            || self.target_id.trim().is_empty()
+            || self.keyframes.is_empty()
+            || !self.duration_ms.is_finite()
+            || !self.delay_ms.is_finite()
+            || !self.easing.is_finite()
+            || self.keyframes.iter().any(|keyframe| {
+                !keyframe.offset.is_finite() || !keyframe.easing.unwrap_or_default().is_finite()
+            })
+        {
+            return Err(invalid());
+        }
+        let mut keyframes = self.keyframes;
+        for keyframe in &mut keyframes {
+            keyframe.offset = keyframe.offset.clamp(0.0, 1.0);
+        }
+        keyframes.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Ok(Self {
+            keyframes,
+            duration_ms: self.duration_ms.max(0.0),
+            delay_ms: self.delay_ms.max(0.0),
+            ..self
+        })
+    }
+
+    fn eased(&self, iteration: u32, fraction: f32) -> f32 {
+        let reversed = self.yoyo && iteration % 2 == 1;
+        let progress = if reversed { 1.0 - fraction } else { fraction };
+        self.easing.apply(progress)
+    }
+
+    pub fn phase_at(&self, elapsed_ms: f32) -> AtomeAnimationPhase {
+        let active_ms = elapsed_ms - self.delay_ms;
+        if active_ms < 0.0 {
+            return AtomeAnimationPhase::Delayed;
+        }
+        let last_iteration = self.iterations.max(1) - 1;
+        let finished = || AtomeAnimationPhase::Finished(self.eased(last_iteration, 1.0));
+        if self.duration_ms <= 0.0 {
+            return finished();
+        }
+        let cycles = active_ms / self.duration_ms;
+        let iteration = cycles.floor();
+        if self.iterations != 0 && iteration >= self.iterations as f32 {
+            return finished();
+        }
+        AtomeAnimationPhase::Running(self.eased(iteration as u32, cycles - iteration))
+    }
+
+    pub fn sample(&self, progress: f32) -> AtomeAnimationSample {
+        let keyframes = &self.keyframes;
+        AtomeAnimationSample {
+            position: sample_track(keyframes, progress, |k| k.position, lerp_array),
+            size: sample_track(keyframes, progress, |k| k.size, lerp_array),
+            scale: sample_track(keyframes, progress, |k| k.scale, lerp_array),
+            rotation: sample_track(keyframes, progress, |k| k.rotation, lerp),
+            opacity: sample_track(keyframes, progress, |k| k.opacity, lerp),
+            color: sample_track(keyframes, progress, |k| k.color, lerp_array),
+            filters: sample_track(keyframes, progress, |k| k.filters, lerp_filters),
+            procedural: sample_track(
+                keyframes,
+                progress,
+                |k| k.procedural,
+                AtomeProceduralSdf::lerp,
+            ),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// How a node composites onto what is already drawn beneath it, following
+/// the CSS `mix-blend-mode` formulas on straight-alpha colors.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeBlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    Difference,
+    Additive,
+}
+
+impl AtomeBlendMode {
+    /// Modes the GPU blend unit cannot express, which sample the workspace
+    /// capture as their backdrop instead.
+    pub fn reads_backdrop(self) -> bool {
+        matches!(self, Self::Overlay | Self::Difference)
+    }
+
+    /// Blends one straight-alpha channel of `source` over `backdrop`.
+    pub fn blend_channel(self, backdrop: f32, source: f32) -> f32 {
+        match self {
+            Self::Normal => source,
+            Self::Multiply => backdrop * source,
+            Self::Screen => backdrop + source - backdrop * source,
+            Self::Overlay => {
+                if backdrop <= 0.5 {
+                    2.0 * backdrop * source
+                } else {
+                    1.0 - 2.0 * (1.0 - backdrop) * (1.0 - source)
+                }
+            }
+            Self::Darken => backdrop.min(source),
+            Self::Lighten => backdrop.max(source),
+            Self::Difference => (backdrop - source).abs(),
+            Self::Additive => (backdrop + source).min(1.0),
+        }
+    }
+
+    /// Composites a straight-alpha `source` pixel onto an opaque `backdrop`
+    /// pixel: `(1 - αs)·Cb + αs·B(Cb, Cs)`. The composite shader matches it
+    /// for every mode on opaque pixels; darken and lighten approximate it at
+    /// partially transparent edges.
+    pub fn composite(self, backdrop: [f32; 3], source: [f32; 4]) -> [f32; 3] {
+        let alpha = source[3].clamp(0.0, 1.0);
+        std::array::from_fn(|channel| {
+            let blended = self.blend_channel(backdrop[channel], source[channel]);
+            backdrop[channel] * (1.0 - alpha) + blended * alpha
+        })
+    }
+}
+
+/// Which part of the mask node's pixels gates the masked node: coverage, or
+/// luminance times coverage as in CSS `mask-type: luminance`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeMaskMode {
+    #[default]
+    Alpha,
+    Luminance,
+}
+
+impl AtomeMaskMode {
+    /// Mask coverage of a straight-alpha mask pixel.
+    pub fn coverage(self, mask: [f32; 4]) -> f32 {
+        let alpha = mask[3].clamp(0.0, 1.0);
+        match self {
+            Self::Alpha => alpha,
+            Self::Luminance => {
+                (0.2126 * mask[0] + 0.7152 * mask[1] + 0.0722 * mask[2]).clamp(0.0, 1.0) * alpha
+            }
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+
+/// Stops beyond this count do not fit the fill uniform and are rejected.
+pub const MAX_FILL_STOPS: usize = 8;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AtomeColorStop {
+    pub offset: f32,
+    pub color: [f32; 4],
+}
+
+/// What a linear or radial gradient paints outside its `[0, 1]` range,
+/// matching SVG `spreadMethod`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeGradientSpread {
+    #[default]
+    Pad,
+    Repeat,
+    Reflect,
+}
+
+/// Interior paint of `shape` and `path` nodes, replacing the flat node color.
+///
+/// Points are in the node's unit box (`[0, 0]` top-left, `[1, 1]`
+/// bottom-right), like SVG `objectBoundingBox`, so a gradient follows resizes.
+/// `transform` is an SVG `[a, b, c, d, e, f]` matrix applied to the gradient
+/// in that box. Conic angles are degrees clockwise from up, measured in
+/// logical pixels so a conic stays round on non-square nodes. Stops are
+/// interpolated in premultiplied sRGB, as CSS does.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AtomeFill {
+    Solid {
+        color: [f32; 4],
+    },
+    Linear {
+        start: [f32; 2],
+        end: [f32; 2],
+        stops: Vec<AtomeColorStop>,
+        #[serde(default)]
+        spread: AtomeGradientSpread,
+        #[serde(default)]
+        transform: Option<[f32; 6]>,
+    },
+    Radial {
+        center: [f32; 2],
+        radius: [f32; 2],
+        stops: Vec<AtomeColorStop>,
+        #[serde(default)]
+        spread: AtomeGradientSpread,
+        #[serde(default)]
+        transform: Option<[f32; 6]>,
+    },
+    Conic {
+        center: [f32; 2],
+        #[serde(default)]
+        angle: f32,
+        stops: Vec<AtomeColorStop>,
+        #[serde(default)]
+        transform: Option<[f32; 6]>,
+    },
+}
+
+fn finite(values: &[f32]) -> bool {
+    values.iter().all(|value| value.is_finite())
+}
+
+fn clamp_color(color: [f32; 4]) -> [f32; 4] {
+    color.map(|value| value.clamp(0.0, 1.0))
+}
+
+/// CSS stop fix-up: offsets are clamped to `[0, 1]` and never decrease.
+fn normalized_stops(stops: &[AtomeColorStop]) -> Option<Vec<AtomeColorStop>> {
+    if stops.is_empty()
+        || stops.len() > MAX_FILL_STOPS
+        || stops
+            .iter()
+            .any(|stop| !stop.offset.is_finite() || !finite(&stop.color))
+    {
+        return None;
+    }
+    let mut previous = 0.0_f32;
+    Some(
+        stops
+            .iter()
+            .map(|stop| {
+                previous = stop.offset.clamp(0.0, 1.0).max(previous);
+                AtomeColorStop {
+                    offset: previous,
+                    color: clamp_color(stop.color),
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Inverse of an SVG matrix, or `None` when it collapses the plane.
+pub fn invert_fill_transform(matrix: [f32; 6]) -> Option<[f32; 6]> {
+    let [a, b, c, d, e, f] = matrix;
+    let determinant = a * d - b * c;
+    if !determinant.is_finite() || determinant.abs() < 1e-6 {
+        return None;
+    }
+    Some([
+        d / determinant,
+        -b / determinant,
+        -c / determinant,
+        a / determinant,
+        (c * f - d * e) / determinant,
+        (b * e - a * f) / determinant,
+    ])
+}
+
+fn normalized_transform(transform: Option<[f32; 6]>) -> Option<Option<[f32; 6]>> {
+    match transform {
+        None => Some(None),
+        Some(matrix) if finite(&matrix) && invert_fill_transform(matrix).is_some() => {
+            Some(Some(matrix))
+        }
+        Some(_) => None,
+    }
+}
+
+fn spread_position(t: f32, spread: AtomeGradientSpread) -> f32 {
+    match spread {
+        AtomeGradientSpread::Pad => t.clamp(0.0, 1.0),
+        AtomeGradientSpread::Repeat => t - t.floor(),
+        AtomeGradientSpread::Reflect => 1.0 - ((t - 2.0 * (t * 0.5).floor()) - 1.0).abs(),
+    }
+}
+
+fn premultiplied(color: [f32; 4]) -> [f32; 4] {
+    [
+        color[0] * color[3],
+        color[1] * color[3],
+        color[2] * color[3],
+        color[3],
+    ]
+}
+
+/// Walks the stops in order like `sample_stops` in
+/// `assets/shaders/fill_gradient.wgsl`, so hard stops resolve identically.
+fn sample_stops(stops: &[AtomeColorStop], t: f32) -> [f32; 4] {
+    let mut color = premultiplied(stops[0].color);
+    for pair in stops.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        if t >= to.offset {
+            color = premultiplied(to.color);
+            continue;
+        }
+        if t > from.offset {
+            let amount = ((t - from.offset) / (to.offset - from.offset).max(1e-6)).clamp(0.0, 1.0);
+            let (from, to) = (premultiplied(from.color), premultiplied(to.color));
+            color = std::array::from_fn(|channel| {
+                from[channel] + (to[channel] - from[channel]) * amount
+            });
+        }
+        break;
+    }
+    if color[3] <= 0.0 {
+        return [0.0; 4];
+    }
+    [
+        color[0] / color[3],
+        color[1] / color[3],
+        color[2] / color[3],
+        color[3],
+    ]
+}
+
+impl AtomeFill {
+    /// Validated copy with clamped colors and fixed-up stops. Non-finite
+    /// geometry, a singular transform, or an empty or oversized stop list
+    /// yield `None`.
+    pub fn normalized(&self) -> Option<Self> {
+        match self {
+            Self::Solid { color } => finite(color).then(|| Self::Solid {
+                color: clamp_color(*color),
+            }),
+            Self::Linear {
+                start,
+                end,
+                stops,
+                spread,
+                transform,
+            } => {
+                if !finite(start) || !finite(end) {
+                    return None;
+                }
+                Some(Self::Linear {
+                    start: *start,
+                    end: *end,
+                    stops: normalized_stops(stops)?,
+                    spread: *spread,
+                    transform: normalized_transform(*transform)?,
+                })
+            }
+            Self::Radial {
+                center,
+                radius,
+                stops,
+                spread,
+                transform,
+            } => {
+                if !finite(center) || !finite(radius) {
+                    return None;
+                }
+                Some(Self::Radial {
+                    center: *center,
+                    radius: radius.map(|value| value.max(0.0)),
+                    stops: normalized_stops(stops)?,
+                    spread: *spread,
+                    transform: normalized_transform(*transform)?,
+                })
+            }
+            Self::Conic {
+                center,
+                angle,
+                stops,
+                transform,
+            } => {
+                if !finite(center) || !angle.is_finite() {
+                    return None;
+                }
+                Some(Self::Conic {
+                    center: *center,
+                    angle: *angle,
+                    stops: normalized_stops(stops)?,
+                    transform: normalized_transform(*transform)?,
+                })
+            }
+        }
+    }
+
+    pub fn stops(&self) -> &[AtomeColorStop] {
+        match self {
+            Self::Solid { .. } => &[],
+            Self::Linear { stops, .. } | Self::Radial { stops, .. } | Self::Conic { stops, .. } => {
+                stops
+            }
+        }
+    }
+
+    pub fn transform(&self) -> Option<[f32; 6]> {
+        match self {
+            Self::Solid { .. } => None,
+            Self::Linear { transform, .. }
+            | Self::Radial { transform, .. }
+            | Self::Conic { transform, .. } => *transform,
+        }
+    }
+
+    /// Straight-alpha sRGB color at `point` in the node's unit box for a node
+    /// of `size` logical pixels. Expects a `normalized` fill; this is the CPU
+    /// twin of `fill_color` in `assets/shaders/fill_gradient.wgsl`.
+    pub fn color_at(&self, point: [f32; 2], size: [f32; 2]) -> [f32; 4] {
+        let point = match self.transform().and_then(invert_fill_transform) {
+            Some([a, b, c, d, e, f]) => [
+                a * point[0] + c * point[1] + e,
+                b * point[0] + d * point[1] + f,
+            ],
+            None => point,
+        };
+        match self {
+            Self::Solid { color } => *color,
+            Self::Linear {
+                start,
+                end,
+                stops,
+                spread,
+                ..
+            } => {
+                let axis = [end[0] - start[0], end[1] - start[1]];
+                let length_squared = axis[0] * axis[0] + axis[1] * axis[1];
+                if length_squared <= 1e-12 {
+                    return sample_stops(stops, 1.0);
+                }
+                let t = ((point[0] - start[0]) * axis[0] + (point[1] - start[1]) * axis[1])
+                    / length_squared;
+                sample_stops(stops, spread_position(t, *spread))
+            }
+            Self::Radial {
+                center,
+                radius,
+                stops,
+                spread,
+                ..
+            } => {
+                if radius[0] <= 1e-6 || radius[1] <= 1e-6 {
+                    return sample_stops(stops, 1.0);
+                }
+                let delta = [
+                    (point[0] - center[0]) / radius[0],
+                    (point[1] - center[1]) / radius[1],
+                ];
+                let t = (delta[0] * delta[0] + delta[1] * delta[1]).sqrt();
+                sample_stops(stops, spread_position(t, *spread))
+            }
+            Self::Conic {
+                center,
+                angle,
+                stops,
+                ..
+            } => {
+                let delta = [
+                    (point[0] - center[0]) * size[0].max(1.0),
+                    (point[1] - center[1]) * size[1].max(1.0),
+                ];
+                let turns = delta[0].atan2(-delta[1]).to_degrees() - angle;
+                let t = turns / 360.0;
+                sample_stops(stops, t - t.floor())
+            }
+        }
+    }
+}
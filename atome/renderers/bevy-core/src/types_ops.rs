@@ -1,9 +1,10 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 
 use crate::types::{
-    default_transform_origin, default_transform_scale, AtomeBackdropStyle, AtomeColorFilters,
-    AtomeProceduralSdf, AtomeRenderNode, AtomeSceneEffectsPatch, AtomeShadowStyle, AtomeTexture,
-    AtomeTransition,
+    default_transform_origin, default_transform_scale, AtomeAnimation, AtomeBackdropStyle,
+    AtomeBlendMode, AtomeColorFilters, AtomeFill, AtomeMaskMode, AtomePathPatch,
+    AtomeProceduralSdf, AtomeRenderNode, AtomeSceneEffectsPatch, AtomeShadowStyle, AtomeTextStyle,
+    AtomeTexture, AtomeTransition, AtomeViewportPatch,
 };
 
 #[derive(Clone, Debug, Deserialize)]
@@ -79,6 +80,25 @@ pub struct AtomeStylePatch {
     pub transition: Option<AtomeTransition>,
     #[serde(default)]
     pub procedural: Option<AtomeProceduralSdf>,
+    // An explicit `null` (`Some(None)`) clears the fill back to the flat
+    // node color; an absent field leaves it unchanged.
+    #[serde(default, deserialize_with = "present_or_null")]
+    pub fill: Option<Option<AtomeFill>>,
+    #[serde(default)]
+    pub blend_mode: Option<AtomeBlendMode>,
+    // Same convention as `fill`: `null` removes the mask.
+    #[serde(default, deserialize_with = "present_or_null")]
+    pub mask_id: Option<Option<String>>,
+    #[serde(default)]
+    pub mask_mode: Option<AtomeMaskMode>,
+}
+
+fn present_or_null<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -104,6 +124,8 @@ pub struct AtomeTextPatch {
     pub id: String,
     pub text: Option<String>,
     pub texture: Option<AtomeTexture>,
+    #[serde(default)]
+    pub style: Option<AtomeTextStyle>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -131,4 +153,7 @@ pub enum AtomeRenderOp {
     Surface(AtomeSurfacePatch),
     SurfaceBackground(AtomeSurfaceBackgroundPatch),
     SceneEffects(AtomeSceneEffectsPatch),
+    Path(AtomePathPatch),
+    Animate(AtomeAnimation),
+    Viewport(AtomeViewportPatch),
 }
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+/// One SVG-style path command in node-local logical pixels (origin at the
+/// node's top-left, y down), or in `view_box` units when the path has one.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AtomePathCommand {
+    MoveTo {
+        to: [f32; 2],
+    },
+    LineTo {
+        to: [f32; 2],
+    },
+    QuadTo {
+        ctrl: [f32; 2],
+        to: [f32; 2],
+    },
+    CubicTo {
+        ctrl1: [f32; 2],
+        ctrl2: [f32; 2],
+        to: [f32; 2],
+    },
+    ArcTo {
+        radii: [f32; 2],
+        #[serde(default)]
+        x_rotation: f32,
+        #[serde(default)]
+        large_arc: bool,
+        #[serde(default)]
+        sweep: bool,
+        to: [f32; 2],
+    },
+    Close,
+}
+
+impl AtomePathCommand {
+    pub fn is_finite(&self) -> bool {
+        let finite = |point: &[f32; 2]| point[0].is_finite() && point[1].is_finite();
+        match self {
+            Self::MoveTo { to } | Self::LineTo { to } => finite(to),
+            Self::QuadTo { ctrl, to } => finite(ctrl) && finite(to),
+            Self::CubicTo { ctrl1, ctrl2, to } => finite(ctrl1) && finite(ctrl2) && finite(to),
+            Self::ArcTo {
+                radii,
+                x_rotation,
+                to,
+                ..
+            } => finite(radii) && x_rotation.is_finite() && finite(to),
+            Self::Close => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeFillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeLineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeLineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+pub fn default_miter_limit() -> f32 {
+    4.0
+}
+
+/// Vector geometry for `path` nodes. The outline comes from `commands`, or
+/// from SVG path data in `d` when `commands` is empty. With neither `fill`
+/// nor `stroke` the path is filled with the node color, so a bare outline
+/// behaves like a `shape`. `stroke_width` and `dash` are logical pixels even
+/// when a `view_box` scales the outline into the node rect.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AtomePath {
+    #[serde(default)]
+    pub commands: Vec<AtomePathCommand>,
+    #[serde(default)]
+    pub d: Option<String>,
+    #[serde(default)]
+    pub fill_rule: AtomeFillRule,
+    #[serde(default)]
+    pub fill: Option<[f32; 4]>,
+    #[serde(default)]
+    pub stroke: Option<[f32; 4]>,
+    #[serde(default)]
+    pub stroke_width: f32,
+    #[serde(default)]
+    pub line_join: AtomeLineJoin,
+    #[serde(default = "default_miter_limit")]
+    pub miter_limit: f32,
+    #[serde(default)]
+    pub line_cap: AtomeLineCap,
+    #[serde(default)]
+    pub dash: Vec<f32>,
+    #[serde(default)]
+    pub dash_offset: f32,
+    // [x, y, width, height] in path units, fitted into the node rect with
+    // SVG's default `xMidYMid meet`.
+    #[serde(default)]
+    pub view_box: Option<[f32; 4]>,
+}
+
+impl Default for AtomePath {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            d: None,
+            fill_rule: AtomeFillRule::default(),
+            fill: None,
+            stroke: None,
+            stroke_width: 0.0,
+            line_join: AtomeLineJoin::default(),
+            miter_limit: default_miter_limit(),
+            line_cap: AtomeLineCap::default(),
+            dash: Vec::new(),
+            dash_offset: 0.0,
+            view_box: None,
+        }
+    }
+}
+
+impl AtomePath {
+    pub fn stroke_width(&self) -> f32 {
+        if self.stroke_width.is_finite() {
+            self.stroke_width.max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn has_stroke(&self) -> bool {
+        self.stroke.is_some() && self.stroke_width() > 0.0
+    }
+
+    /// The paint used for the interior, falling back to `node_color` when the
+    /// path declares no paint at all.
+    pub fn fill_color(&self, node_color: [f32; 4]) -> Option<[f32; 4]> {
+        match (self.fill, self.stroke) {
+            (Some(fill), _) => Some(fill),
+            (None, None) => Some(node_color),
+            (None, Some(_)) => None,
+        }
+    }
+
+    /// Dash lengths with SVG semantics: an odd-length list is repeated once,
+    /// and a list with a negative or non-finite entry, or summing to zero,
+    /// disables dashing.
+    pub fn dash_pattern(&self) -> Option<Vec<f32>> {
+        if self.dash.is_empty()
+            || self
+                .dash
+                .iter()
+                .any(|value| !value.is_finite() || *value < 0.0)
+            || self.dash.iter().sum::<f32>() <= 0.0
+        {
+            return None;
+        }
+        let mut pattern = self.dash.clone();
+        if pattern.len() % 2 == 1 {
+            pattern.extend_from_slice(&self.dash);
+        }
+        Some(pattern)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AtomePathPatch {
+    pub id: String,
+    pub path: AtomePath,
+}
@@ -0,0 +1,25 @@
+use serde::Deserialize;
+
+fn default_alpha_threshold() -> f32 {
+    0.5
+}
+
+/// Options of the `pick_at` scene query. With `texture_alpha`, a point over
+/// a textured node only hits where the texel's alpha reaches
+/// `alpha_threshold`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct AtomePickOptions {
+    #[serde(default)]
+    pub texture_alpha: bool,
+    #[serde(default = "default_alpha_threshold")]
+    pub alpha_threshold: f32,
+}
+
+impl Default for AtomePickOptions {
+    fn default() -> Self {
+        Self {
+            texture_alpha: false,
+            alpha_threshold: default_alpha_threshold(),
+        }
+    }
+}
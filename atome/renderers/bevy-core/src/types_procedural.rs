@@ -1,6 +1,6 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
 pub struct AtomeProceduralSdf {
     pub morph: [f32; 4],
     #[serde(default)]
@@ -63,14 +63,30 @@ pub struct AtomeProceduralSdf {
     pub flower_petals: [[f32; 4]; 8],
 }
 
-fn default_reveal() -> f32 { 1.0 }
-fn default_surface_size() -> [f32; 2] { [1.0, 1.0] }
-fn default_assistant_size() -> f32 { 1.0 }
-fn default_assistant_blur() -> f32 { 48.0 }
-fn default_assistant_refraction() -> f32 { 24.0 }
-fn default_flower_edge_softness() -> f32 { 1.0 }
+fn default_reveal() -> f32 {
+    1.0
+}
+fn default_surface_size() -> [f32; 2] {
+    [1.0, 1.0]
+}
+fn default_assistant_size() -> f32 {
+    1.0
+}
+fn default_assistant_blur() -> f32 {
+    48.0
+}
+fn default_assistant_refraction() -> f32 {
+    24.0
+}
+fn default_flower_edge_softness() -> f32 {
+    1.0
+}
 fn finite_or(value: f32, fallback: f32) -> f32 {
-    if value.is_finite() { value } else { fallback }
+    if value.is_finite() {
+        value
+    } else {
+        fallback
+    }
 }
 
 impl AtomeProceduralSdf {
@@ -115,7 +131,9 @@ impl AtomeProceduralSdf {
             assistant_size: finite_or(self.assistant_size, 1.0).max(1.0),
             background_blur_px: finite_or(self.background_blur_px, 48.0).clamp(0.0, 128.0),
             lens_refraction_px: finite_or(self.lens_refraction_px, 24.0).clamp(0.0, 128.0),
-            assistant_background_tint: self.assistant_background_tint.map(|value| finite_or(value, 0.0).clamp(0.0, 1.0)),
+            assistant_background_tint: self
+                .assistant_background_tint
+                .map(|value| finite_or(value, 0.0).clamp(0.0, 1.0)),
             mode: finite_or(self.mode, 0.0).clamp(0.0, 1.0),
             flower_count: finite_or(self.flower_count, 0.0).clamp(0.0, 8.0),
             flower_core_radius: finite_or(self.flower_core_radius, 0.0).max(0.0),
@@ -127,12 +145,68 @@ impl AtomeProceduralSdf {
                 finite_or(self.flower_tint[2], 0.0).clamp(0.0, 1.0),
                 finite_or(self.flower_tint[3], 0.0).clamp(0.0, 1.0),
             ],
-            flower_petals: self.flower_petals.map(|petal| [
-                finite_or(petal[0], 0.0),
-                finite_or(petal[1], 0.0),
-                finite_or(petal[2], 0.0).max(0.0),
-                finite_or(petal[3], 0.0).clamp(0.0, 1.0),
-            ]),
+            flower_petals: self.flower_petals.map(|petal| {
+                [
+                    finite_or(petal[0], 0.0),
+                    finite_or(petal[1], 0.0),
+                    finite_or(petal[2], 0.0).max(0.0),
+                    finite_or(petal[3], 0.0).clamp(0.0, 1.0),
+                ]
+            }),
+        }
+    }
+
+    /// Field-wise blend used by keyframe animations. The shader mode
+    /// selectors are discrete and switch halfway, as CSS does for discrete
+    /// properties.
+    pub fn lerp(self, to: Self, amount: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * amount;
+        let mix2 = |a: [f32; 2], b: [f32; 2]| [mix(a[0], b[0]), mix(a[1], b[1])];
+        let mix4 = |a: [f32; 4], b: [f32; 4]| {
+            [
+                mix(a[0], b[0]),
+                mix(a[1], b[1]),
+                mix(a[2], b[2]),
+                mix(a[3], b[3]),
+            ]
+        };
+        let step = |a: f32, b: f32| if amount < 0.5 { a } else { b };
+        Self {
+            morph: mix4(self.morph, to.morph),
+            phase: mix(self.phase, to.phase),
+            pulse: mix(self.pulse, to.pulse),
+            time: mix(self.time, to.time),
+            intensity: mix(self.intensity, to.intensity),
+            listening_rms: mix(self.listening_rms, to.listening_rms),
+            glow_reveal: mix(self.glow_reveal, to.glow_reveal),
+            core_reveal: mix(self.core_reveal, to.core_reveal),
+            shell_reveal: mix(self.shell_reveal, to.shell_reveal),
+            disappearing: mix(self.disappearing, to.disappearing),
+            contact: mix2(self.contact, to.contact),
+            attraction: mix(self.attraction, to.attraction),
+            stretch: mix(self.stretch, to.stretch),
+            gesture_velocity: mix(self.gesture_velocity, to.gesture_velocity),
+            destructive_direction: mix2(self.destructive_direction, to.destructive_direction),
+            destructive_mode: step(self.destructive_mode, to.destructive_mode),
+            destructive_progress: mix(self.destructive_progress, to.destructive_progress),
+            surface_size: mix2(self.surface_size, to.surface_size),
+            assistant_center: mix2(self.assistant_center, to.assistant_center),
+            assistant_size: mix(self.assistant_size, to.assistant_size),
+            background_blur_px: mix(self.background_blur_px, to.background_blur_px),
+            lens_refraction_px: mix(self.lens_refraction_px, to.lens_refraction_px),
+            assistant_background_tint: mix4(
+                self.assistant_background_tint,
+                to.assistant_background_tint,
+            ),
+            mode: step(self.mode, to.mode),
+            flower_count: step(self.flower_count, to.flower_count),
+            flower_core_radius: mix(self.flower_core_radius, to.flower_core_radius),
+            flower_bridge_width: mix(self.flower_bridge_width, to.flower_bridge_width),
+            flower_edge_softness: mix(self.flower_edge_softness, to.flower_edge_softness),
+            flower_tint: mix4(self.flower_tint, to.flower_tint),
+            flower_petals: std::array::from_fn(|index| {
+                mix4(self.flower_petals[index], to.flower_petals[index])
+            }),
         }
     }
 }
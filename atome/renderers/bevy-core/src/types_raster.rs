@@ -0,0 +1,35 @@
+use serde::Deserialize;
+
+use crate::types::AtomeViewport;
+
+fn default_pixel_ratio() -> f32 {
+    1.0
+}
+
+/// Options of `rasterize_scene`: a `width` x `height` logical surface drawn
+/// at `pixel_ratio` device pixels per logical pixel, seen through
+/// `viewport`. `background` is straight-alpha sRGB and defaults to
+/// transparent; the platform clear color is not part of the scene.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct AtomeRasterOptions {
+    pub width: f32,
+    pub height: f32,
+    #[serde(default = "default_pixel_ratio")]
+    pub pixel_ratio: f32,
+    #[serde(default)]
+    pub background: [f32; 4],
+    #[serde(default)]
+    pub viewport: AtomeViewport,
+}
+
+impl AtomeRasterOptions {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            pixel_ratio: default_pixel_ratio(),
+            background: [0.0; 4],
+            viewport: AtomeViewport::default(),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use serde::Serialize;
+
+/// Fields of one node that differ between the expected scene and the
+/// renderer, named as in `AtomeRenderNode`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AtomeNodeDiff {
+    pub id: String,
+    pub fields: Vec<String>,
+}
+
+/// Where the renderer disagrees with the scene JS expects it to hold.
+/// `missing` nodes are expected but not spawned, `unexpected` nodes are
+/// spawned but not expected; both lists follow scene order.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct AtomeSceneDiff {
+    pub missing: Vec<String>,
+    pub unexpected: Vec<String>,
+    pub changed: Vec<AtomeNodeDiff>,
+    pub effects_changed: bool,
+    pub selection_style_changed: bool,
+}
+
+impl AtomeSceneDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.unexpected.is_empty()
+            && self.changed.is_empty()
+            && !self.effects_changed
+            && !self.selection_style_changed
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+pub fn default_text_font_size() -> f32 {
+    14.0
+}
+
+pub fn default_text_font_weight() -> f32 {
+    400.0
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeTextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    Justified,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AtomeTextWrap {
+    #[default]
+    Word,
+    Character,
+    None,
+}
+
+/// Renderer-side layout for `text` nodes, replacing the JS-rasterized
+/// `AtomeTexture`. Sizes are logical pixels. Lines break at `wrap_width`,
+/// or at the node width when absent or wider; the text box keeps its left
+/// edge on the node's. `font_family` selects a face registered with
+/// `register_text_font`, falling back to the UI font table by weight.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AtomeTextStyle {
+    #[serde(default)]
+    pub font_family: Option<String>,
+    #[serde(default = "default_text_font_weight")]
+    pub font_weight: f32,
+    #[serde(default = "default_text_font_size")]
+    pub font_size: f32,
+    #[serde(default)]
+    pub line_height: Option<f32>,
+    #[serde(default)]
+    pub align: AtomeTextAlign,
+    #[serde(default)]
+    pub wrap: AtomeTextWrap,
+    #[serde(default)]
+    pub wrap_width: Option<f32>,
+}
+
+impl Default for AtomeTextStyle {
+    fn default() -> Self {
+        Self {
+            font_family: None,
+            font_weight: default_text_font_weight(),
+            font_size: default_text_font_size(),
+            line_height: None,
+            align: AtomeTextAlign::default(),
+            wrap: AtomeTextWrap::default(),
+            wrap_width: None,
+        }
+    }
+}
+
+fn positive(value: Option<f32>) -> Option<f32> {
+    value.filter(|value| value.is_finite() && *value > 0.0)
+}
+
+impl AtomeTextStyle {
+    pub fn normalized(self) -> Self {
+        let font_size = if self.font_size.is_finite() {
+            self.font_size.clamp(1.0, 1024.0)
+        } else {
+            default_text_font_size()
+        };
+        let font_weight = if self.font_weight.is_finite() {
+            self.font_weight.clamp(1.0, 1000.0)
+        } else {
+            default_text_font_weight()
+        };
+        Self {
+            font_family: self
+                .font_family
+                .map(|family| family.trim().to_string())
+                .filter(|family| !family.is_empty()),
+            font_weight,
+            font_size,
+            line_height: positive(self.line_height),
+            align: self.align,
+            wrap: self.wrap,
+            wrap_width: positive(self.wrap_width),
+        }
+    }
+
+    /// Width of the box lines are laid out in for a node `node_width` wide.
+    pub fn box_width(&self, node_width: f32) -> f32 {
+        self.wrap_width
+            .map_or(node_width, |wrap_width| wrap_width.min(node_width))
+            .max(1.0)
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::AtomeEasing;
+
+pub const ATOME_VIEWPORT_ZOOM_MIN: f32 = 0.01;
+pub const ATOME_VIEWPORT_ZOOM_MAX: f32 = 100.0;
+
+fn default_viewport_zoom() -> f32 {
+    1.0
+}
+
+fn default_viewport_easing() -> AtomeEasing {
+    AtomeEasing::EaseInOut
+}
+
+/// The camera looking at the scene. `pan` scrolls the scene in logical
+/// units as seen at zoom 1; `zoom` and `rotation` (degrees, clockwise)
+/// pivot around the surface center. The default shows the scene exactly as
+/// node transforms place it.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AtomeViewport {
+    #[serde(default)]
+    pub pan: [f32; 2],
+    #[serde(default = "default_viewport_zoom")]
+    pub zoom: f32,
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+impl Default for AtomeViewport {
+    fn default() -> Self {
+        Self {
+            pan: [0.0, 0.0],
+            zoom: default_viewport_zoom(),
+            rotation: 0.0,
+        }
+    }
+}
+
+fn rotate(point: [f32; 2], degrees: f32) -> [f32; 2] {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    [
+        point[0] * cos - point[1] * sin,
+        point[0] * sin + point[1] * cos,
+    ]
+}
+
+impl AtomeViewport {
+    pub fn normalized(self) -> Self {
+        let finite = |value: f32, fallback: f32| if value.is_finite() { value } else { fallback };
+        Self {
+            pan: self.pan.map(|value| finite(value, 0.0)),
+            zoom: finite(self.zoom, 1.0).clamp(ATOME_VIEWPORT_ZOOM_MIN, ATOME_VIEWPORT_ZOOM_MAX),
+            rotation: finite(self.rotation, 0.0),
+        }
+    }
+
+    /// Where the logical scene point lands on a `surface`-sized surface.
+    pub fn scene_to_surface(&self, point: [f32; 2], surface: [f32; 2]) -> [f32; 2] {
+        let center = [surface[0] / 2.0, surface[1] / 2.0];
+        let offset = rotate(
+            [
+                (point[0] - self.pan[0] - center[0]) * self.zoom,
+                (point[1] - self.pan[1] - center[1]) * self.zoom,
+            ],
+            self.rotation,
+        );
+        [center[0] + offset[0], center[1] + offset[1]]
+    }
+
+    /// The logical scene point shown at `point` on a `surface`-sized surface.
+    pub fn surface_to_scene(&self, point: [f32; 2], surface: [f32; 2]) -> [f32; 2] {
+        let center = [surface[0] / 2.0, surface[1] / 2.0];
+        let offset = rotate([point[0] - center[0], point[1] - center[1]], -self.rotation);
+        [
+            self.pan[0] + center[0] + offset[0] / self.zoom,
+            self.pan[1] + center[1] + offset[1] / self.zoom,
+        ]
+    }
+
+    /// Axis-aligned scene bounds `[x, y, width, height]` of everything the
+    /// surface shows.
+    pub fn visible_scene_rect(&self, surface: [f32; 2]) -> [f32; 4] {
+        let corners = [
+            [0.0, 0.0],
+            [surface[0], 0.0],
+            [surface[0], surface[1]],
+            [0.0, surface[1]],
+        ]
+        .map(|corner| self.surface_to_scene(corner, surface));
+        let (mut min, mut max) = ([f32::INFINITY; 2], [f32::NEG_INFINITY; 2]);
+        for corner in corners {
+            for axis in 0..2 {
+                min[axis] = min[axis].min(corner[axis]);
+                max[axis] = max[axis].max(corner[axis]);
+            }
+        }
+        [min[0], min[1], max[0] - min[0], max[1] - min[1]]
+    }
+
+    /// Fly-to blend: pan and rotation move linearly while zoom moves
+    /// geometrically, so zooming from 1 to 4 passes 2 halfway.
+    pub fn lerp(self, to: Self, amount: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * amount;
+        Self {
+            pan: [mix(self.pan[0], to.pan[0]), mix(self.pan[1], to.pan[1])],
+            zoom: mix(self.zoom.ln(), to.zoom.ln()).exp(),
+            rotation: mix(self.rotation, to.rotation),
+        }
+    }
+}
+
+/// Moves the viewport. Fields left out keep their current value; a positive
+/// `duration_ms` flies there instead of jumping, and any new viewport op
+/// takes over from wherever a running flight is.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AtomeViewportPatch {
+    #[serde(default)]
+    pub pan: Option<[f32; 2]>,
+    #[serde(default)]
+    pub zoom: Option<f32>,
+    #[serde(default)]
+    pub rotation: Option<f32>,
+    #[serde(default)]
+    pub duration_ms: f32,
+    #[serde(default = "default_viewport_easing")]
+    pub easing: AtomeEasing,
+}
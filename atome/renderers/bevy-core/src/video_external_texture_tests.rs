@@ -36,12 +36,19 @@ fn video_node(id: &str) -> AtomeRenderNode {
         texture_size: Some([320, 180]),
         uv_rect: None,
         texture: None,
+        texture_hash: None,
         peaks: None,
         playback_progress: None,
         selected: None,
         filters: None,
         transition: None,
         procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
     }
 }
 
@@ -418,6 +425,10 @@ fn video_style_patch_updates_external_texture_opacity() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -443,6 +454,10 @@ fn video_style_patch_updates_external_texture_opacity() {
             filters: None,
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -560,6 +575,10 @@ fn video_style_patch_updates_color_filters() {
             }),
             transition: None,
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
@@ -637,6 +656,10 @@ fn video_node_and_style_carry_normalized_transition() {
                 softness: 0.0,
             }),
             procedural: None,
+            fill: None,
+            blend_mode: None,
+            mask_id: None,
+            mask_mode: None,
         },
     )
     .unwrap();
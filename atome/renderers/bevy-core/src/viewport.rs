@@ -0,0 +1,490 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{prelude::*, window::RequestRedraw};
+
+use crate::{
+    composite::AtomeCompositeCamera,
+    path::{sync_path_mesh, AtomePathVisual},
+    picking::node_scene_bounds,
+    render_math::atome_viewport_projection,
+    selection_overlay::rebuild_selection_overlay,
+    text_layout::{sync_text_layout, AtomeTextLayout},
+    types::*,
+    workspace_backdrop::{
+        AtomePresentationCamera, AtomeWorkspaceBackdropVisual, AtomeWorkspaceCamera,
+    },
+};
+
+/// Zoom changes rebuild screen-constant overlays once per eighth of an
+/// octave rather than every frame of a pinch.
+const VIEWPORT_DETAIL_STEPS_PER_OCTAVE: f32 = 8.0;
+/// Side of a spatial index cell, in logical scene units.
+const CULL_CELL_SIZE: f32 = 512.0;
+/// Surface pixels kept around the view so strokes and glyph overhang do not
+/// pop in at the edges.
+const CULL_MARGIN: f32 = 64.0;
+/// Nodes spanning more cells than this are tested on every query instead.
+const CULL_MAX_CELLS_PER_NODE: i64 = 256;
+
+/// Marks a node hidden because it is outside the viewport, holding the
+/// visibility it gets back once it scrolls into view.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct AtomeViewportCulled(pub Visibility);
+
+#[derive(Clone, Debug)]
+pub struct AtomeViewportFlight {
+    pub from: AtomeViewport,
+    pub to: AtomeViewport,
+    pub duration_ms: f32,
+    pub easing: AtomeEasing,
+    // Clock reading of the first frame after the op, as for animations.
+    pub started_at: Option<f64>,
+}
+
+#[derive(Clone, Debug, Resource)]
+pub struct AtomeViewportState {
+    pub current: AtomeViewport,
+    pub flight: Option<AtomeViewportFlight>,
+    // Quantized zoom the selection overlays were last built for.
+    pub detail_zoom: f32,
+}
+
+impl Default for AtomeViewportState {
+    fn default() -> Self {
+        Self {
+            current: AtomeViewport::default(),
+            flight: None,
+            detail_zoom: 1.0,
+        }
+    }
+}
+
+/// Uniform grid over node scene bounds. It only exists once a viewport op
+/// arrived; until then nothing is culled.
+#[derive(Clone, Debug, Default, Resource)]
+pub struct AtomeSpatialIndex {
+    bounds: HashMap<Entity, [f32; 4]>,
+    cells: HashMap<(i32, i32), HashSet<Entity>>,
+    oversized: HashSet<Entity>,
+    // Indexed nodes currently inside `view`.
+    visible: HashSet<Entity>,
+    view: [f32; 4],
+}
+
+fn cell_range(rect: [f32; 4]) -> Option<[i32; 4]> {
+    if rect.iter().any(|value| !value.is_finite()) {
+        return None;
+    }
+    let cell = |value: f32| (value / CULL_CELL_SIZE).floor() as i32;
+    Some([
+        cell(rect[0]),
+        cell(rect[1]),
+        cell(rect[0] + rect[2]),
+        cell(rect[1] + rect[3]),
+    ])
+}
+
+fn cell_count(range: [i32; 4]) -> i64 {
+    (range[2] as i64 - range[0] as i64 + 1) * (range[3] as i64 - range[1] as i64 + 1)
+}
+
+fn rects_overlap(a: [f32; 4], b: [f32; 4]) -> bool {
+    a[0] <= b[0] + b[2] && b[0] <= a[0] + a[2] && a[1] <= b[1] + b[3] && b[1] <= a[1] + a[3]
+}
+
+impl AtomeSpatialIndex {
+    pub fn insert(&mut self, entity: Entity, bounds: [f32; 4]) {
+        if self.bounds.get(&entity) == Some(&bounds) {
+            return;
+        }
+        self.remove(entity);
+        self.bounds.insert(entity, bounds);
+        match cell_range(bounds) {
+            Some(range) if cell_count(range) <= CULL_MAX_CELLS_PER_NODE => {
+                for x in range[0]..=range[2] {
+                    for y in range[1]..=range[3] {
+                        self.cells.entry((x, y)).or_default().insert(entity);
+                    }
+                }
+            }
+            _ => {
+                self.oversized.insert(entity);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        self.visible.remove(&entity);
+        let Some(bounds) = self.bounds.remove(&entity) else {
+            return;
+        };
+        if self.oversized.remove(&entity) {
+            return;
+        }
+        let Some(range) = cell_range(bounds) else {
+            return;
+        };
+        for x in range[0]..=range[2] {
+            for y in range[1]..=range[3] {
+                if let Some(cell) = self.cells.get_mut(&(x, y)) {
+                    cell.remove(&entity);
+                    if cell.is_empty() {
+                        self.cells.remove(&(x, y));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Indexed nodes whose bounds overlap `rect`.
+    pub fn query(&self, rect: [f32; 4]) -> HashSet<Entity> {
+        let mut candidates = self.oversized.clone();
+        match cell_range(rect) {
+            // Zoomed far out, walking the occupied cells beats walking the
+            // range.
+            Some(range) if cell_count(range) <= self.cells.len() as i64 => {
+                for x in range[0]..=range[2] {
+                    for y in range[1]..=range[3] {
+                        if let Some(cell) = self.cells.get(&(x, y)) {
+                            candidates.extend(cell);
+                        }
+                    }
+                }
+            }
+            _ => {
+                for cell in self.cells.values() {
+                    candidates.extend(cell);
+                }
+            }
+        }
+        candidates.retain(|entity| {
+            self.bounds
+                .get(entity)
+                .is_some_and(|bounds| rects_overlap(*bounds, rect))
+        });
+        candidates
+    }
+
+    pub fn is_visible(&self, entity: Entity) -> bool {
+        self.visible.contains(&entity)
+    }
+}
+
+pub struct ViewportPlugin;
+
+impl Plugin for ViewportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, advance_atome_viewport);
+    }
+}
+
+pub fn viewport(world: &World) -> AtomeViewport {
+    world
+        .get_resource::<AtomeViewportState>()
+        .map(|state| state.current)
+        .unwrap_or_default()
+}
+
+pub fn viewport_zoom(world: &World) -> f32 {
+    viewport(world).zoom
+}
+
+/// The zoom screen-constant overlays are built for.
+pub fn viewport_detail_zoom(world: &World) -> f32 {
+    world
+        .get_resource::<AtomeViewportState>()
+        .map_or(1.0, |state| state.detail_zoom)
+}
+
+pub(crate) fn detail_zoom_for(zoom: f32) -> f32 {
+    ((zoom.log2() * VIEWPORT_DETAIL_STEPS_PER_OCTAVE).round() / VIEWPORT_DETAIL_STEPS_PER_OCTAVE)
+        .exp2()
+}
+
+pub fn viewport_flying(world: &World) -> bool {
+    world
+        .get_resource::<AtomeViewportState>()
+        .is_some_and(|state| state.flight.is_some())
+}
+
+pub fn apply_viewport(world: &mut World, patch: AtomeViewportPatch) -> Result<(), String> {
+    world.init_resource::<AtomeViewportState>();
+    let current = viewport(world);
+    let target = AtomeViewport {
+        pan: patch.pan.unwrap_or(current.pan),
+        zoom: patch.zoom.unwrap_or(current.zoom),
+        rotation: patch.rotation.unwrap_or(current.rotation),
+    }
+    .normalized();
+    if patch.duration_ms.is_finite() && patch.duration_ms > 0.0 {
+        world.resource_mut::<AtomeViewportState>().flight = Some(AtomeViewportFlight {
+            from: current,
+            to: target,
+            duration_ms: patch.duration_ms,
+            easing: patch.easing,
+            started_at: None,
+        });
+        return Ok(());
+    }
+    world.resource_mut::<AtomeViewportState>().flight = None;
+    set_viewport(world, target)
+}
+
+/// Steps a running fly-to to clock time `now` (seconds).
+pub fn advance_viewport(world: &mut World, now: f64) -> Result<(), String> {
+    let Some(flight) = world
+        .get_resource_mut::<AtomeViewportState>()
+        .and_then(|mut state| {
+            let flight = state.flight.as_mut()?;
+            flight.started_at.get_or_insert(now);
+            Some(flight.clone())
+        })
+    else {
+        return Ok(());
+    };
+    let elapsed_ms = ((now - flight.started_at.unwrap_or(now)) * 1000.0) as f32;
+    let progress = (elapsed_ms / flight.duration_ms).clamp(0.0, 1.0);
+    let next = if progress >= 1.0 {
+        world.resource_mut::<AtomeViewportState>().flight = None;
+        flight.to
+    } else {
+        flight.from.lerp(flight.to, flight.easing.apply(progress))
+    };
+    set_viewport(world, next)
+}
+
+fn set_viewport(world: &mut World, next: AtomeViewport) -> Result<(), String> {
+    let detail_zoom = detail_zoom_for(next.zoom);
+    let detail_changed = {
+        let mut state = world.resource_mut::<AtomeViewportState>();
+        state.current = next;
+        let changed = state.detail_zoom != detail_zoom;
+        state.detail_zoom = detail_zoom;
+        changed
+    };
+    sync_viewport(world)?;
+    if detail_changed {
+        let selected = world
+            .query_filtered::<Entity, With<AtomeSelectionOverlay>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        for entity in selected {
+            rebuild_selection_overlay(world, entity)?;
+        }
+        let visible = world
+            .get_resource::<AtomeSpatialIndex>()
+            .map(|index| index.visible.iter().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+        for entity in visible {
+            sync_zoom_detail(world, entity)?;
+        }
+    }
+    Ok(())
+}
+
+// Text glyphs and path tessellation follow the on-screen scale, so they are
+// refreshed when zoom changes, for nodes on screen only.
+fn sync_zoom_detail(world: &mut World, entity: Entity) -> Result<(), String> {
+    if world.get::<AtomeTextLayout>(entity).is_some() {
+        sync_text_layout(world, entity)?;
+    }
+    if let Some(visible_rect) = world
+        .get::<AtomePathVisual>(entity)
+        .map(|visual| visual.visible_rect)
+    {
+        sync_path_mesh(world, entity, visible_rect)?;
+    }
+    Ok(())
+}
+
+/// Places a camera rendering the scene where `viewport` looks and returns
+/// the projection it needs.
+pub(crate) fn pose_scene_camera(
+    viewport: AtomeViewport,
+    surface: [f32; 2],
+    transform: &mut Transform,
+) -> Projection {
+    transform.translation.x = viewport.pan[0];
+    transform.translation.y = -viewport.pan[1];
+    transform.rotation = Quat::from_rotation_z(viewport.rotation.to_radians());
+    atome_viewport_projection(surface[0], surface[1], viewport.zoom)
+}
+
+/// Points the scene cameras at the current viewport and re-culls. Also run
+/// after a surface resize, which resets camera projections.
+pub fn sync_viewport(world: &mut World) -> Result<(), String> {
+    if !world.contains_resource::<AtomeViewportState>() {
+        return Ok(());
+    }
+    let current = viewport(world);
+    let (surface_width, surface_height) = {
+        let config = world.resource::<AtomeBevyRendererConfig>();
+        (config.width, config.height)
+    };
+    // The backdrop capture and composite group cameras follow too, so what
+    // they render lines up with what is on screen.
+    let mut cameras = world.query_filtered::<(&mut Transform, &mut Projection), Or<(
+        With<AtomePresentationCamera>,
+        With<AtomeWorkspaceCamera>,
+        With<AtomeCompositeCamera>,
+    )>>();
+    for (mut transform, mut projection) in cameras.iter_mut(world) {
+        *projection = pose_scene_camera(current, [surface_width, surface_height], &mut transform);
+    }
+    // The blurred capture fills the surface, so it stays in front of the
+    // presentation camera wherever that looks.
+    let mut backdrops =
+        world.query_filtered::<&mut Transform, With<AtomeWorkspaceBackdropVisual>>();
+    for mut transform in backdrops.iter_mut(world) {
+        transform.translation.x = current.pan[0];
+        transform.translation.y = -current.pan[1];
+        transform.rotation = Quat::from_rotation_z(current.rotation.to_radians());
+        transform.scale = Vec3::new(1.0 / current.zoom, 1.0 / current.zoom, 1.0);
+    }
+    if !world.contains_resource::<AtomeSpatialIndex>() {
+        build_spatial_index(world);
+    }
+    let view = current.visible_scene_rect([surface_width, surface_height]);
+    let margin = CULL_MARGIN / current.zoom;
+    let view = [
+        view[0] - margin,
+        view[1] - margin,
+        view[2] + margin * 2.0,
+        view[3] + margin * 2.0,
+    ];
+    let (entered, left) = {
+        let mut index = world.resource_mut::<AtomeSpatialIndex>();
+        index.view = view;
+        let next = index.query(view);
+        let entered = next.difference(&index.visible).copied().collect::<Vec<_>>();
+        let left = index.visible.difference(&next).copied().collect::<Vec<_>>();
+        index.visible = next;
+        (entered, left)
+    };
+    for entity in left {
+        cull_node(world, entity);
+    }
+    for entity in entered {
+        uncull_node(world, entity)?;
+    }
+    Ok(())
+}
+
+fn build_spatial_index(world: &mut World) {
+    let entities = world
+        .resource::<AtomeEntityTable>()
+        .by_id
+        .values()
+        .copied()
+        .collect::<Vec<_>>();
+    let mut index = AtomeSpatialIndex::default();
+    for entity in entities {
+        if let Some(bounds) = node_scene_bounds(world, entity) {
+            index.insert(entity, bounds);
+            // Everything starts on screen; the first sync culls.
+            index.visible.insert(entity);
+        }
+    }
+    world.insert_resource(index);
+}
+
+fn cull_node(world: &mut World, entity: Entity) {
+    let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+        return;
+    };
+    if entity_mut.contains::<AtomeViewportCulled>() {
+        return;
+    }
+    let Some(mut visibility) = entity_mut.get_mut::<Visibility>() else {
+        return;
+    };
+    let restore = *visibility;
+    *visibility = Visibility::Hidden;
+    entity_mut.insert(AtomeViewportCulled(restore));
+}
+
+fn uncull_node(world: &mut World, entity: Entity) -> Result<(), String> {
+    let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+        return Ok(());
+    };
+    let Some(AtomeViewportCulled(restore)) = entity_mut.take::<AtomeViewportCulled>() else {
+        return Ok(());
+    };
+    if let Some(mut visibility) = entity_mut.get_mut::<Visibility>() {
+        *visibility = restore;
+    }
+    sync_zoom_detail(world, entity)
+}
+
+/// Sets the visibility a node shows while on screen; a culled node keeps it
+/// until it scrolls back into view.
+pub(crate) fn set_node_visibility(world: &mut World, entity: Entity, visibility: Visibility) {
+    if let Some(mut culled) = world.get_mut::<AtomeViewportCulled>(entity) {
+        culled.0 = visibility;
+    } else if let Some(mut current) = world.get_mut::<Visibility>(entity) {
+        *current = visibility;
+    }
+}
+
+/// Whether the node is hidden on its own account, looking past culling.
+pub(crate) fn node_hidden(world: &World, entity: Entity) -> bool {
+    world
+        .get::<AtomeViewportCulled>(entity)
+        .map(|culled| culled.0)
+        .or_else(|| world.get::<Visibility>(entity).copied())
+        == Some(Visibility::Hidden)
+}
+
+/// Re-indexes a node after its geometry changed and culls or reveals it.
+pub(crate) fn sync_node_culling(world: &mut World, entity: Entity) -> Result<(), String> {
+    if !world.contains_resource::<AtomeSpatialIndex>() {
+        return Ok(());
+    }
+    // Clipped-out nodes have no bounds; their clip visibility already hides
+    // them.
+    let Some(bounds) = node_scene_bounds(world, entity) else {
+        forget_node_culling(world, entity);
+        return uncull_node(world, entity);
+    };
+    let on_screen = {
+        let mut index = world.resource_mut::<AtomeSpatialIndex>();
+        index.insert(entity, bounds);
+        let on_screen = rects_overlap(bounds, index.view);
+        if on_screen {
+            index.visible.insert(entity);
+        } else {
+            index.visible.remove(&entity);
+        }
+        on_screen
+    };
+    if on_screen {
+        uncull_node(world, entity)
+    } else {
+        cull_node(world, entity);
+        Ok(())
+    }
+}
+
+pub(crate) fn forget_node_culling(world: &mut World, entity: Entity) {
+    if let Some(mut index) = world.get_resource_mut::<AtomeSpatialIndex>() {
+        index.remove(entity);
+    }
+}
+
+fn advance_atome_viewport(world: &mut World) {
+    if !viewport_flying(world) {
+        return;
+    }
+    let Some(now) = world
+        .get_resource::<Time>()
+        .map(|time| time.elapsed_secs_f64())
+    else {
+        return;
+    };
+    if let Err(error) = advance_viewport(world, now) {
+        world.resource_mut::<AtomeRendererDiagnostics>().last_error = Some(error);
+    }
+    if world.contains_resource::<Messages<RequestRedraw>>() {
+        world.write_message(RequestRedraw);
+    }
+}
@@ -0,0 +1,262 @@
+use bevy::{image::Image, prelude::*};
+
+use crate::{
+    apply_render_op, apply_spawn,
+    picking::pick_at,
+    render_math::atome_camera_projection,
+    types::*,
+    viewport::{advance_viewport, viewport, viewport_flying, AtomeViewportCulled},
+    workspace_backdrop::AtomePresentationCamera,
+};
+
+const SURFACE: [f32; 2] = [640.0, 480.0];
+
+fn shape_node(id: &str, position: [f32; 2], size: [f32; 2]) -> AtomeRenderNode {
+    AtomeRenderNode {
+        id: id.to_string(),
+        kind: "shape".to_string(),
+        parent_id: None,
+        logical_position: position,
+        logical_size: size,
+        clip_rect: None,
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        origin: [0.5, 0.5],
+        layer: 1,
+        opacity: 1.0,
+        corner_radius: 0.0,
+        corner_radii: None,
+        shadow: None,
+        backdrop: None,
+        presentation: false,
+        color: Some([0.2, 0.4, 0.6, 1.0]),
+        text: None,
+        source: None,
+        texture_size: None,
+        uv_rect: None,
+        texture: None,
+        texture_hash: None,
+        peaks: None,
+        playback_progress: None,
+        selected: None,
+        filters: None,
+        transition: None,
+        procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
+    }
+}
+
+fn world() -> (World, Entity) {
+    let mut world = World::new();
+    world.insert_resource(AtomeEntityTable::default());
+    world.insert_resource(AtomeBevyRendererConfig::empty(SURFACE[0], SURFACE[1]));
+    world.insert_resource(AtomeRendererDiagnostics::default());
+    world.insert_resource(Assets::<Image>::default());
+    let camera = world
+        .spawn((
+            Transform::default(),
+            atome_camera_projection(SURFACE[0], SURFACE[1]),
+            AtomePresentationCamera,
+        ))
+        .id();
+    (world, camera)
+}
+
+fn viewport_op(pan: [f32; 2], zoom: f32, rotation: f32) -> AtomeRenderOp {
+    AtomeRenderOp::Viewport(AtomeViewportPatch {
+        pan: Some(pan),
+        zoom: Some(zoom),
+        rotation: Some(rotation),
+        duration_ms: 0.0,
+        easing: AtomeEasing::Linear,
+    })
+}
+
+fn entity(world: &World, id: &str) -> Entity {
+    world.resource::<AtomeEntityTable>().by_id[id]
+}
+
+fn visibility(world: &World, id: &str) -> Visibility {
+    *world.get::<Visibility>(entity(world, id)).unwrap()
+}
+
+fn assert_close(actual: [f32; 2], expected: [f32; 2]) {
+    assert!(
+        (actual[0] - expected[0]).abs() < 1e-3 && (actual[1] - expected[1]).abs() < 1e-3,
+        "{actual:?} != {expected:?}"
+    );
+}
+
+#[test]
+fn viewport_maps_points_both_ways_and_turns_clockwise() {
+    let view = AtomeViewport {
+        pan: [100.0, -40.0],
+        zoom: 2.0,
+        rotation: 30.0,
+    };
+    for point in [[0.0, 0.0], [123.0, 456.0], [-80.0, 900.0]] {
+        assert_close(
+            view.surface_to_scene(view.scene_to_surface(point, SURFACE), SURFACE),
+            point,
+        );
+    }
+    // The surface center shows the panned scene center.
+    assert_close(
+        view.surface_to_scene([320.0, 240.0], SURFACE),
+        [420.0, 200.0],
+    );
+
+    // A point right of center ends up below it after a quarter turn.
+    let turned = AtomeViewport {
+        rotation: 90.0,
+        ..AtomeViewport::default()
+    };
+    assert_close(
+        turned.scene_to_surface([330.0, 240.0], SURFACE),
+        [320.0, 250.0],
+    );
+
+    let zoomed = AtomeViewport {
+        zoom: 2.0,
+        ..AtomeViewport::default()
+    };
+    assert_eq!(
+        zoomed.visible_scene_rect(SURFACE),
+        [160.0, 120.0, 320.0, 240.0]
+    );
+}
+
+#[test]
+fn viewport_op_moves_the_scene_camera_and_picks_through_it() {
+    let (mut world, camera) = world();
+    apply_spawn(&mut world, shape_node("tile", [400.0, 300.0], [20.0, 20.0])).unwrap();
+    assert!(pick_at(&world, 410.0, 310.0, AtomePickOptions::default()).contains(&"tile".into()));
+
+    apply_render_op(&mut world, viewport_op([100.0, 50.0], 2.0, 0.0)).unwrap();
+
+    let transform = world.get::<Transform>(camera).unwrap();
+    assert_eq!(transform.translation.truncate(), Vec2::new(100.0, -50.0));
+    let Projection::Orthographic(projection) = world.get::<Projection>(camera).unwrap() else {
+        panic!("scene camera must stay orthographic");
+    };
+    assert_eq!(projection.scale, 0.5);
+
+    // Scene (410, 310) now shows at center + (410 - 100 - 320, 310 - 50 - 240) * 2.
+    let surface = viewport(&world).scene_to_surface([410.0, 310.0], SURFACE);
+    assert_close(surface, [300.0, 280.0]);
+    assert_eq!(
+        pick_at(&world, 300.0, 280.0, AtomePickOptions::default()),
+        vec!["tile"]
+    );
+    assert!(pick_at(&world, 410.0, 310.0, AtomePickOptions::default()).is_empty());
+}
+
+#[test]
+fn viewport_culls_off_screen_nodes_and_restores_their_visibility() {
+    let (mut world, _) = world();
+    apply_spawn(&mut world, shape_node("near", [0.0, 0.0], [100.0, 100.0])).unwrap();
+    apply_spawn(&mut world, shape_node("far", [5000.0, 0.0], [100.0, 100.0])).unwrap();
+    assert_eq!(visibility(&world, "far"), Visibility::Visible);
+
+    apply_render_op(&mut world, viewport_op([0.0, 0.0], 1.0, 0.0)).unwrap();
+    assert_eq!(visibility(&world, "near"), Visibility::Visible);
+    assert_eq!(visibility(&world, "far"), Visibility::Hidden);
+    assert!(world
+        .get::<AtomeViewportCulled>(entity(&world, "far"))
+        .is_some());
+
+    // Hiding a culled node sticks once it scrolls back into view.
+    apply_render_op(
+        &mut world,
+        AtomeRenderOp::Visibility(AtomeVisibilityPatch {
+            id: "near".to_string(),
+            visible: false,
+        }),
+    )
+    .unwrap();
+    apply_render_op(&mut world, viewport_op([4800.0, 0.0], 1.0, 0.0)).unwrap();
+    assert_eq!(visibility(&world, "far"), Visibility::Visible);
+    assert!(world
+        .get::<AtomeViewportCulled>(entity(&world, "far"))
+        .is_none());
+    assert!(world
+        .get::<AtomeViewportCulled>(entity(&world, "near"))
+        .is_some());
+    apply_render_op(&mut world, viewport_op([0.0, 0.0], 1.0, 0.0)).unwrap();
+    assert_eq!(visibility(&world, "near"), Visibility::Hidden);
+
+    // Zooming out far enough brings everything back on screen.
+    apply_render_op(&mut world, viewport_op([0.0, 0.0], 0.05, 0.0)).unwrap();
+    assert_eq!(visibility(&world, "far"), Visibility::Visible);
+
+    // Nodes spawned after the first viewport op are culled as they arrive.
+    apply_render_op(&mut world, viewport_op([0.0, 0.0], 1.0, 0.0)).unwrap();
+    apply_spawn(&mut world, shape_node("late", [-3000.0, 0.0], [10.0, 10.0])).unwrap();
+    assert_eq!(visibility(&world, "late"), Visibility::Hidden);
+    apply_render_op(&mut world, AtomeRenderOp::Despawn("late".to_string())).unwrap();
+}
+
+#[test]
+fn viewport_fly_to_zooms_geometrically_and_lands_on_target() {
+    let (mut world, _) = world();
+    apply_render_op(
+        &mut world,
+        AtomeRenderOp::Viewport(AtomeViewportPatch {
+            pan: Some([200.0, 0.0]),
+            zoom: Some(4.0),
+            rotation: None,
+            duration_ms: 1000.0,
+            easing: AtomeEasing::Linear,
+        }),
+    )
+    .unwrap();
+    assert!(viewport_flying(&world));
+    assert_eq!(viewport(&world), AtomeViewport::default());
+
+    // The first frame after the op starts the clock.
+    advance_viewport(&mut world, 10.0).unwrap();
+    assert_eq!(viewport(&world), AtomeViewport::default());
+    advance_viewport(&mut world, 10.5).unwrap();
+    let halfway = viewport(&world);
+    assert!((halfway.zoom - 2.0).abs() < 1e-4, "{halfway:?}");
+    assert_close(halfway.pan, [100.0, 0.0]);
+
+    advance_viewport(&mut world, 11.2).unwrap();
+    assert!(!viewport_flying(&world));
+    assert_eq!(viewport(&world).zoom, 4.0);
+    assert_eq!(viewport(&world).pan, [200.0, 0.0]);
+}
+
+#[test]
+fn selection_outline_keeps_its_screen_thickness_when_zoomed() {
+    let (mut world, _) = world();
+    apply_spawn(
+        &mut world,
+        AtomeRenderNode {
+            selected: Some(true),
+            ..shape_node("picked", [300.0, 200.0], [40.0, 40.0])
+        },
+    )
+    .unwrap();
+    let thinnest = |world: &World| {
+        let overlay = world
+            .get::<AtomeSelectionOverlay>(entity(world, "picked"))
+            .unwrap();
+        overlay
+            .entities
+            .iter()
+            .filter_map(|overlay| world.get::<Sprite>(*overlay)?.custom_size)
+            .map(|size| size.x.min(size.y))
+            .fold(f32::INFINITY, f32::min)
+    };
+    let thickness = SelectionVisualStyle::default().border_thickness;
+    assert_eq!(thinnest(&world), thickness);
+
+    apply_render_op(&mut world, viewport_op([0.0, 0.0], 2.0, 0.0)).unwrap();
+    assert_eq!(thinnest(&world), thickness / 2.0);
+}
@@ -6,13 +6,14 @@ use crate::{
         AtomeBevyRendererConfig, AtomeLayer, AtomeLogicalPosition, AtomeLogicalSize,
         AtomeRenderKind, AtomeWaveformPlaybackOverlay, AtomeWaveformPlaybackProgress,
     },
+    viewport::node_hidden,
 };
 
-const PLAYHEAD_WIDTH: f32 = 2.0;
-const PLAYHEAD_COLOR: [f32; 4] = [0.96, 0.98, 1.0, 0.92];
+pub(crate) const PLAYHEAD_WIDTH: f32 = 2.0;
+pub(crate) const PLAYHEAD_COLOR: [f32; 4] = [0.96, 0.98, 1.0, 0.92];
 const PLAYHEAD_DEPTH_OFFSET: f32 = 0.7;
 
-fn playback_depth_for_layer(layer: i32) -> f32 {
+pub(crate) fn playback_depth_for_layer(layer: i32) -> f32 {
     depth_for_layer(layer) + PLAYHEAD_DEPTH_OFFSET
 }
 
@@ -70,11 +71,7 @@ pub fn rebuild_waveform_playback_overlay(world: &mut World, entity: Entity) -> R
     if !is_waveform {
         return Ok(());
     }
-    if world
-        .get::<Visibility>(entity)
-        .map(|value| *value == Visibility::Hidden)
-        .unwrap_or(false)
-    {
+    if node_hidden(world, entity) {
         return Ok(());
     }
     let progress = world
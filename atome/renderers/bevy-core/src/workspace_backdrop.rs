@@ -40,7 +40,7 @@ pub struct AtomeWorkspaceBackdrop {
     pub blur: WorkspaceBlurPipeline,
 }
 
-fn target_image(width: u32, height: u32) -> Image {
+pub(crate) fn target_image(width: u32, height: u32) -> Image {
     Image::new_target_texture(
         width.max(1),
         height.max(1),
@@ -226,8 +226,8 @@ fn flower_backdrop_uses_screen_coordinates_for_gaussian_passes_and_logical_coord
     assert!(!blur_shader.contains("3.2307692308"));
 
     let shader = include_str!("assets/shaders/backdrop_surface.wgsl");
-    assert!(shader.contains("mesh.world_position.x / workspace_size.x + 0.5"));
-    assert!(shader.contains("0.5 - mesh.world_position.y / workspace_size.y"));
+    assert!(shader.contains("view.clip_from_world * mesh.world_position"));
+    assert!(shader.contains("clip.x / clip.w * 0.5 + 0.5, 0.5 - clip.y / clip.w * 0.5"));
     assert!(!shader.contains("mesh.position.xy / max(dimensions"));
 
     let uniform = BackdropSurfaceUniform {
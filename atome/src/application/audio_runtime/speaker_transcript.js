@@ -0,0 +1,119 @@
+import { getTauriInvoke } from './runtime_audio_backend.js';
+
+// Speaker-attributed transcripts (desktop).
+// `audio_diarize` labels speakers S1, S2… by first appearance; display names
+// live on the recording atome as a `speaker_names` particle ({ S1: 'Ana' })
+// so they can be assigned after the fact and survive re-running diarization.
+
+export const SPEAKER_NAMES_PARTICLE = 'speaker_names';
+
+const safeString = (value) => String(value ?? '').trim();
+const optionalCount = (value) => {
+    const numeric = Math.floor(Number(value));
+    return Number.isFinite(numeric) && numeric > 0 ? numeric : null;
+};
+
+const unwrapAtome = (result) => (
+    result?.atome
+    || result?.data?.atome
+    || result?.tauri?.data?.atome
+    || result?.fastify?.data?.atome
+    || result?.data
+    || result
+    || null
+);
+
+export const normalizeSpeakerNames = (input = {}) => {
+    const source = input && typeof input === 'object' ? input : {};
+    const names = {};
+    for (const [id, name] of Object.entries(source)) {
+        const speaker = safeString(id);
+        const label = safeString(name);
+        if (speaker && label) names[speaker] = label;
+    }
+    return names;
+};
+
+export const readSpeakerNames = (record = {}) => {
+    const atome = unwrapAtome(record) || {};
+    const props = atome.particles || atome.properties || atome.props || atome;
+    const raw = props?.[SPEAKER_NAMES_PARTICLE];
+    if (typeof raw === 'string') {
+        try { return normalizeSpeakerNames(JSON.parse(raw)); } catch (_) { return {}; }
+    }
+    return normalizeSpeakerNames(raw);
+};
+
+const requireAtomesApi = (api, method) => {
+    const fn = api?.atomes?.[method];
+    if (typeof fn !== 'function') throw new Error(`AdoleAPI.atomes.${method} is not available`);
+    return fn.bind(api.atomes);
+};
+
+export const loadSpeakerNames = async ({ api, atomeId } = {}) => {
+    const id = safeString(atomeId);
+    if (!id) return {};
+    return readSpeakerNames(await requireAtomesApi(api, 'get')(id));
+};
+
+// Merges `names` into the stored map; an empty name removes that speaker.
+export const saveSpeakerNames = async ({ api, atomeId, names = {} } = {}) => {
+    const id = safeString(atomeId);
+    if (!id) throw new Error('Missing atome id');
+    const merged = { ...(await loadSpeakerNames({ api, atomeId: id })) };
+    for (const [speaker, name] of Object.entries(names || {})) {
+        const key = safeString(speaker);
+        if (!key) continue;
+        const label = safeString(name);
+        if (label) merged[key] = label;
+        else delete merged[key];
+    }
+    await requireAtomesApi(api, 'alter')(id, { particles: { [SPEAKER_NAMES_PARTICLE]: merged } });
+    return merged;
+};
+
+// Re-labels a diarization result without re-running it.
+export const applySpeakerNames = (diarization = {}, names = {}) => {
+    const map = normalizeSpeakerNames(names);
+    const nameOf = (speaker) => (speaker && map[speaker]) || null;
+    return {
+        ...diarization,
+        speakers: (diarization.speakers || []).map((speaker) => ({ ...speaker, name: nameOf(speaker.id) })),
+        ...(Array.isArray(diarization.utterances)
+            ? {
+                utterances: diarization.utterances.map((utterance) => ({
+                    ...utterance,
+                    speaker_name: nameOf(utterance.speaker)
+                }))
+            }
+            : {})
+    };
+};
+
+// Runs `audio_diarize` on a recording, merging `transcript` (the STT
+// `transcribeFile` result) when given and naming speakers from the atome.
+export const diarizeRecording = async ({
+    path,
+    transcript = null,
+    numSpeakers = null,
+    maxSpeakers = null,
+    thresholdDb = null,
+    outputPath = null,
+    atomeId = null,
+    api = null,
+    env = typeof window !== 'undefined' ? window : globalThis
+} = {}) => {
+    const invoke = getTauriInvoke(env);
+    if (typeof invoke !== 'function') throw new Error('audio_diarize_unavailable');
+    const speakerNames = atomeId ? await loadSpeakerNames({ api: api || env?.AdoleAPI, atomeId }) : {};
+    const result = await invoke('audio_diarize', {
+        path: safeString(path),
+        numSpeakers: optionalCount(numSpeakers),
+        maxSpeakers: optionalCount(maxSpeakers),
+        thresholdDb: thresholdDb !== null && Number.isFinite(Number(thresholdDb)) ? Number(thresholdDb) : null,
+        transcript,
+        speakerNames,
+        outputPath: outputPath ? safeString(outputPath) : null
+    });
+    return result?.diarization || result;
+};
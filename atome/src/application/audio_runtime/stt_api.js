@@ -49,8 +49,8 @@
     };
 
     const stt = {
-        async prepare(language = 'fr-FR') {
-            return invoke('plugin:stt|prepare_model', { language });
+        async prepare(language = 'fr-FR', engine = null) {
+            return invoke('plugin:stt|prepare_model', { language, engine });
         },
         async isAvailable() {
             return invoke('plugin:stt|is_available');
@@ -70,6 +70,18 @@
         async stop() {
             return invoke('plugin:stt|stop_listening');
         },
+        async transcribeFile(path, config = {}) {
+            return invoke('plugin:stt|transcribe_file', { path, config });
+        },
+        async listInstalled() {
+            return invoke('plugin:stt|list_installed');
+        },
+        async deleteModel(name) {
+            return invoke('plugin:stt|delete_model', { name });
+        },
+        async importModel(path) {
+            return invoke('plugin:stt|import_model', { path });
+        },
         async onResult(handler) {
             return wrapListener('plugin:stt:result', handler);
         },
@@ -87,6 +99,9 @@
         },
         async onDownloadProgress(handler) {
             return wrapListener('stt://download-progress', handler);
+        },
+        async onTranscribeProgress(handler) {
+            return wrapListener('stt://transcribe-progress', handler);
         }
     };
 
@@ -1,15 +1,17 @@
 // Tauri command handlers for the audio engine
 // Exposes Kira playback + CPAL recording as Tauri commands.
 
-use super::{metering, playback, recorder, transcode};
+use super::{diarize, input_fx, metering, playback, recorder, transcode, vad};
 use serde_json::{json, Value};
-use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
 
 fn clip_metadata_json(metadata: &playback::ClipMetadata) -> Value {
     json!({
         "sample_rate": metadata.sample_rate,
         "frame_count": metadata.frame_count,
-        "duration_seconds": metadata.duration_seconds
+        "duration_seconds": metadata.duration_seconds,
+        "streaming": metadata.streaming
     })
 }
 
@@ -86,7 +88,8 @@ pub async fn audio_load_clip(
         "metadata": clip_metadata_json(&metadata),
         "sample_rate": metadata.sample_rate,
         "frame_count": metadata.frame_count,
-        "duration_seconds": metadata.duration_seconds
+        "duration_seconds": metadata.duration_seconds,
+        "streaming": metadata.streaming
     }))
 }
 
@@ -107,7 +110,8 @@ pub async fn audio_load_clip_from_bytes(id: String, bytes: Vec<u8>) -> Result<Va
         "metadata": clip_metadata_json(&metadata),
         "sample_rate": metadata.sample_rate,
         "frame_count": metadata.frame_count,
-        "duration_seconds": metadata.duration_seconds
+        "duration_seconds": metadata.duration_seconds,
+        "streaming": metadata.streaming
     }))
 }
 
@@ -193,6 +197,67 @@ pub fn audio_set_playback_rate(id: String, rate: f64) -> Result<Value, String> {
     Ok(json!({ "success": true, "id": id }))
 }
 
+fn parse_output_format(output_format: Option<String>) -> Result<recorder::OutputFormat, String> {
+    Ok(output_format
+        .as_deref()
+        .map(recorder::OutputFormat::from_name)
+        .transpose()?
+        .unwrap_or_default())
+}
+
+/// Resolves a recording path relative to the project root if not absolute.
+fn resolve_record_path(paths: &crate::ProjectPaths, file_path: &str) -> String {
+    if std::path::Path::new(file_path).is_absolute() {
+        file_path.to_string()
+    } else {
+        paths
+            .project_root
+            .join(file_path)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+/// Resolves a file an analysis command writes on request and keeps it inside
+/// the project root, like the remote recorder paths. Missing directories are
+/// checked through their nearest existing ancestor.
+fn resolve_project_output_path(project_root: &Path, output_path: &str) -> Result<PathBuf, String> {
+    let raw = output_path.trim();
+    if raw.is_empty() {
+        return Err("Missing output path".to_string());
+    }
+    let candidate = Path::new(raw);
+    if candidate
+        .components()
+        .any(|part| matches!(part, Component::ParentDir))
+    {
+        return Err("Output path must not contain parent directory segments".to_string());
+    }
+    let resolved = if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        project_root.join(candidate)
+    };
+    let canonical_root = project_root
+        .canonicalize()
+        .unwrap_or_else(|_| project_root.to_path_buf());
+    let inside = resolved
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| ancestor.exists())
+        .and_then(|ancestor| ancestor.canonicalize().ok())
+        .is_some_and(|ancestor| ancestor.starts_with(&canonical_root));
+    if !inside {
+        return Err(format!(
+            "Output path is outside the project root: {}",
+            resolved.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Starts a take. On a session opened with `audio_record_arm` the other
+/// arguments must match the armed ones and the file begins with the pre-roll.
 #[tauri::command]
 pub fn audio_record_start(
     paths: tauri::State<crate::ProjectPaths>,
@@ -200,28 +265,127 @@ pub fn audio_record_start(
     file_path: String,
     sample_rate: u32,
     channels: u16,
+    output_format: Option<String>,
 ) -> Result<Value, String> {
-    // Resolve path relative to project root if not absolute
-    let abs_path = if std::path::Path::new(&file_path).is_absolute() {
-        file_path.clone()
-    } else {
-        paths
-            .project_root
-            .join(&file_path)
-            .to_string_lossy()
-            .to_string()
-    };
+    let output_format = parse_output_format(output_format)?;
+    let abs_path = resolve_record_path(&paths, &file_path);
+
+    metering::reset();
+    recorder::start_with_options(
+        &session_id,
+        &abs_path,
+        sample_rate,
+        channels,
+        output_format,
+        recorder::BufferSizeHint::default(),
+    )?;
+    Ok(json!({
+        "success": true,
+        "session_id": session_id,
+        "file_path": file_path,
+        "absolute_file_path": abs_path,
+        "output_format": format!("{:?}", output_format)
+    }))
+}
+
+/// Opens the input stream and keeps the last `pre_roll_sec` seconds (default
+/// 5, at most 30) without writing; `audio_record_start` commits them.
+#[tauri::command]
+pub fn audio_record_arm(
+    paths: tauri::State<crate::ProjectPaths>,
+    session_id: String,
+    file_path: String,
+    sample_rate: u32,
+    channels: u16,
+    output_format: Option<String>,
+    pre_roll_sec: Option<f64>,
+) -> Result<Value, String> {
+    let output_format = parse_output_format(output_format)?;
+    let abs_path = resolve_record_path(&paths, &file_path);
+    let pre_roll_sec = pre_roll_sec.unwrap_or(5.0).min(recorder::MAX_PRE_ROLL_SEC);
+
+    metering::reset();
+    recorder::arm(
+        &session_id,
+        &abs_path,
+        sample_rate,
+        channels,
+        output_format,
+        pre_roll_sec,
+    )?;
+    Ok(json!({
+        "success": true,
+        "session_id": session_id,
+        "file_path": file_path,
+        "absolute_file_path": abs_path,
+        "output_format": format!("{:?}", output_format),
+        "pre_roll_sec": pre_roll_sec
+    }))
+}
+
+/// Arms a session that starts on the first speech and, with
+/// `auto_stop_silence_sec`, stops writing after that much silence. Poll
+/// `audio_record_status` for `stopped`, then call `audio_record_stop`.
+#[tauri::command]
+pub fn audio_record_arm_voice(
+    paths: tauri::State<crate::ProjectPaths>,
+    session_id: String,
+    file_path: String,
+    sample_rate: u32,
+    channels: u16,
+    output_format: Option<String>,
+    auto_stop_silence_sec: Option<f64>,
+) -> Result<Value, String> {
+    let output_format = parse_output_format(output_format)?;
+    let abs_path = resolve_record_path(&paths, &file_path);
 
     metering::reset();
-    recorder::start(&session_id, &abs_path, sample_rate, channels)?;
+    recorder::arm_on_voice(
+        &session_id,
+        &abs_path,
+        sample_rate,
+        channels,
+        output_format,
+        recorder::VoiceTriggerConfig {
+            auto_stop_after_sec: auto_stop_silence_sec,
+            ..Default::default()
+        },
+    )?;
     Ok(json!({
         "success": true,
         "session_id": session_id,
         "file_path": file_path,
-        "absolute_file_path": abs_path
+        "absolute_file_path": abs_path,
+        "output_format": format!("{:?}", output_format),
+        "auto_stop_silence_sec": auto_stop_silence_sec
     }))
 }
 
+#[tauri::command]
+pub fn audio_record_status(session_id: String) -> Result<Value, String> {
+    let status = recorder::status(&session_id)?;
+    Ok(json!({ "success": true, "status": status }))
+}
+
+#[tauri::command]
+pub fn audio_record_pause(session_id: String) -> Result<Value, String> {
+    recorder::pause(&session_id)?;
+    Ok(json!({ "success": true, "session_id": session_id, "paused": true }))
+}
+
+#[tauri::command]
+pub fn audio_record_resume(session_id: String) -> Result<Value, String> {
+    recorder::resume(&session_id)?;
+    Ok(json!({ "success": true, "session_id": session_id, "paused": false }))
+}
+
+/// Marks the current position of the take; `label` defaults to "Marker N".
+#[tauri::command]
+pub fn audio_record_marker(session_id: String, label: Option<String>) -> Result<Value, String> {
+    let marker = recorder::add_marker(&session_id, label)?;
+    Ok(json!({ "success": true, "session_id": session_id, "marker": marker }))
+}
+
 #[tauri::command]
 pub fn audio_record_stop(session_id: String) -> Result<Value, String> {
     let result = recorder::stop(&session_id)?;
@@ -235,10 +399,181 @@ pub fn audio_record_stop(session_id: String) -> Result<Value, String> {
         "overrun_frames": result.overrun_frames,
         "sample_rate": result.sample_rate,
         "channels": result.channels,
-        "output_format": result.output_format
+        "output_format": result.output_format,
+        "pre_roll_frames": result.pre_roll_frames,
+        "paused_frames": result.paused_frames,
+        "markers": result.markers
+    }))
+}
+
+/// Takes repaired by the startup recovery scan (sessions that never reached
+/// `audio_record_stop`). Pass `acknowledge` once the UI has surfaced them.
+#[tauri::command]
+pub fn audio_record_recovered(acknowledge: Option<bool>) -> Result<Value, String> {
+    let recordings = recorder::recovered_recordings(acknowledge.unwrap_or(false));
+    Ok(json!({
+        "success": true,
+        "count": recordings.len(),
+        "recordings": recordings
     }))
 }
 
+fn vad_config(threshold_db: Option<f32>) -> Result<vad::VadConfig, String> {
+    let mut config = vad::VadConfig::default();
+    if let Some(threshold_db) = threshold_db {
+        if !threshold_db.is_finite() || threshold_db <= 0.0 {
+            return Err(format!("Invalid VAD threshold: {threshold_db}"));
+        }
+        config.threshold_db = threshold_db;
+    }
+    Ok(config)
+}
+
+/// Speech regions and timeline segments (split at pauses of at least
+/// `min_pause_sec`, default 0.7 s) of a recording.
+#[tauri::command]
+pub async fn audio_vad_segments(
+    paths: tauri::State<'_, crate::ProjectPaths>,
+    path: String,
+    min_pause_sec: Option<f64>,
+    threshold_db: Option<f32>,
+) -> Result<Value, String> {
+    let project_root = paths.project_root.clone();
+    let config = vad_config(threshold_db)?;
+    let min_pause_sec = min_pause_sec.unwrap_or(vad::DEFAULT_MIN_PAUSE_SEC).max(0.0);
+    let analysis = tauri::async_runtime::spawn_blocking(move || {
+        let resolved_path = resolve_audio_clip_path(&project_root, &path)?;
+        vad::analyze_file(&resolved_path, config, min_pause_sec)
+    })
+    .await
+    .map_err(|error| format!("audio_vad_segments_task_failed: {error}"))??;
+    Ok(json!({ "success": true, "vad": analysis }))
+}
+
+/// Writes a copy without leading/trailing silence; defaults to
+/// `<stem>.trimmed.wav` beside the source. An explicit `output_path` must stay
+/// inside the project.
+#[tauri::command]
+pub async fn audio_vad_trim(
+    paths: tauri::State<'_, crate::ProjectPaths>,
+    path: String,
+    output_path: Option<String>,
+    padding_sec: Option<f64>,
+    threshold_db: Option<f32>,
+) -> Result<Value, String> {
+    let project_root = paths.project_root.clone();
+    let config = vad_config(threshold_db)?;
+    let padding_sec = padding_sec.unwrap_or(vad::DEFAULT_PADDING_SEC);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let source = resolve_audio_clip_path(&project_root, &path)?;
+        let output = match output_path {
+            Some(output) => resolve_project_output_path(&project_root, &output)?,
+            None => {
+                let stem = source
+                    .file_stem()
+                    .and_then(|value| value.to_str())
+                    .unwrap_or("audio");
+                source.with_file_name(format!("{stem}.trimmed.wav"))
+            }
+        };
+        vad::trim_file(&source, &output, config, padding_sec)
+    })
+    .await
+    .map_err(|error| format!("audio_vad_trim_task_failed: {error}"))??;
+    Ok(json!({ "success": true, "trim": result }))
+}
+
+/// Speaker turns (`S1`, `S2`…) of a recording. With `transcript` (the STT
+/// `transcribe_file` result) the words are grouped into speaker-attributed
+/// `utterances`; `speaker_names` maps IDs to display names. `output_path`
+/// also writes the JSON; it must stay inside the project, relative paths
+/// resolving against the project root.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn audio_diarize(
+    paths: tauri::State<'_, crate::ProjectPaths>,
+    path: String,
+    num_speakers: Option<usize>,
+    max_speakers: Option<usize>,
+    threshold_db: Option<f32>,
+    transcript: Option<Value>,
+    speaker_names: Option<HashMap<String, String>>,
+    output_path: Option<String>,
+) -> Result<Value, String> {
+    let project_root = paths.project_root.clone();
+    if num_speakers == Some(0) || max_speakers == Some(0) {
+        return Err("Speaker counts must be at least 1".to_string());
+    }
+    let config = diarize::DiarizeConfig {
+        num_speakers,
+        max_speakers: max_speakers.unwrap_or(diarize::DEFAULT_MAX_SPEAKERS),
+        vad: vad_config(threshold_db)?,
+    };
+    let transcript = transcript
+        .map(serde_json::from_value::<diarize::TranscriptInput>)
+        .transpose()
+        .map_err(|error| format!("Invalid transcript: {error}"))?;
+    let names = speaker_names.unwrap_or_default();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let source = resolve_audio_clip_path(&project_root, &path)?;
+        let result = diarize::diarize_file(&source, config, transcript.as_ref(), &names)?;
+        if let Some(output) = output_path {
+            let output = resolve_project_output_path(&project_root, &output)?;
+            let body = serde_json::to_vec_pretty(&result).map_err(|error| error.to_string())?;
+            std::fs::write(&output, body)
+                .map_err(|error| format!("Failed to write {}: {error}", output.display()))?;
+        }
+        Ok::<Value, String>(result)
+    })
+    .await
+    .map_err(|error| format!("audio_diarize_task_failed: {error}"))??;
+    Ok(json!({ "success": true, "diarization": result }))
+}
+
+#[tauri::command]
+pub fn audio_input_fx_get() -> Result<Value, String> {
+    Ok(json!({ "success": true, "settings": input_fx::settings() }))
+}
+
+/// Updates the recording input chain (gain, high-pass, gate, limiter); omitted
+/// arguments keep their value. Applies to running sessions on the next block.
+#[tauri::command]
+pub fn audio_input_fx_set(
+    gain_db: Option<f32>,
+    high_pass_enabled: Option<bool>,
+    high_pass_hz: Option<f32>,
+    gate_enabled: Option<bool>,
+    gate_threshold_db: Option<f32>,
+    limiter_enabled: Option<bool>,
+    limiter_ceiling_db: Option<f32>,
+) -> Result<Value, String> {
+    let settings = input_fx::update(input_fx::InputFxPatch {
+        gain_db,
+        high_pass_enabled,
+        high_pass_hz,
+        gate_enabled,
+        gate_threshold_db,
+        limiter_enabled,
+        limiter_ceiling_db,
+        ..Default::default()
+    })?;
+    Ok(json!({ "success": true, "settings": settings }))
+}
+
+/// Routes the processed input to the default output while recording.
+#[tauri::command]
+pub fn audio_monitor_set(enabled: bool, gain_db: Option<f32>) -> Result<Value, String> {
+    let settings = input_fx::update(input_fx::InputFxPatch {
+        monitor_enabled: Some(enabled),
+        monitor_gain_db: gain_db,
+        ..Default::default()
+    })?;
+    if enabled {
+        recorder::refresh_monitoring()?;
+    }
+    Ok(json!({ "success": true, "settings": settings }))
+}
+
 #[tauri::command]
 pub fn audio_get_levels() -> Result<Value, String> {
     let levels = metering::get_levels();
@@ -268,6 +603,28 @@ pub fn audio_get_scope() -> Result<Value, String> {
     }))
 }
 
+#[tauri::command]
+pub fn audio_get_spectrum(
+    fft_size: Option<usize>,
+    window: Option<String>,
+    smoothing: Option<f32>,
+) -> Result<Value, String> {
+    let spectrum = metering::get_spectrum(fft_size, window.as_deref(), smoothing)?;
+    Ok(json!({
+        "available": spectrum.available,
+        "sequence": spectrum.sequence,
+        "sample_rate": spectrum.sample_rate,
+        "channels": spectrum.channels,
+        "fft_size": spectrum.fft_size,
+        "window": spectrum.window,
+        "smoothing": spectrum.smoothing,
+        "bin_hz": spectrum.bin_hz,
+        "magnitudes_db": spectrum.magnitudes_db,
+        "peak_hz": spectrum.peak_hz,
+        "peak_db": spectrum.peak_db
+    }))
+}
+
 #[tauri::command]
 pub fn audio_shutdown() -> Result<Value, String> {
     playback::shutdown()?;
@@ -0,0 +1,930 @@
+// Offline speaker diarization
+// One decoding pass feeds the voice-activity detector and a log-mel cepstrum
+// (MFCC) extractor. Speech regions are tiled with overlapping windows; each
+// window's embedding is the mean and spread of its cepstra after
+// normalisation over all speech in the file, so embeddings describe how a
+// voice differs from the recording's average voice. Windows are clustered
+// bottom-up with average linkage on cosine distance; without a requested
+// speaker count, the cut with the best silhouette wins, and a single speaker
+// is kept unless that cut also passes a BIC test on the raw cepstra (the
+// normalised embeddings alone cannot tell one voice from two).
+//
+// Turns are the windows' central spans merged per speaker. They can be
+// joined with the word timings of an STT transcript (`transcribe_file`
+// output) into a speaker-attributed transcript. Speaker IDs are `S1`, `S2`…
+// in order of first appearance; names come from the caller, typically the
+// recording atome's `speaker_names` particle.
+
+use super::fft::{FftPlan, SpectrumWindow};
+use super::vad::{self, SpeechRegion, VadConfig, VadEvent, VoiceActivityDetector};
+use super::SymphoniaStreamDecoder;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+const FEATURE_FRAME_SEC: f64 = 0.025;
+const MEL_BANDS: usize = 32;
+/// Cepstral coefficients kept, c1..=c19 (c0 is loudness, not voice).
+const CEPSTRA: usize = 19;
+const MEL_RANGE_HZ: (f32, f32) = (60.0, 7_600.0);
+const WINDOW_SEC: f64 = 1.5;
+const WINDOW_HOP_SEC: f64 = 0.75;
+/// Regions shorter than this carry too little voice to embed.
+const MIN_REGION_SEC: f64 = 0.3;
+/// Pauses shorter than this stay inside one speech region.
+const REGION_MERGE_PAUSE_SEC: f64 = 0.3;
+/// Bounds clustering cost (quadratic memory, cubic worst case).
+const MAX_WINDOWS: usize = 2_000;
+pub const DEFAULT_MAX_SPEAKERS: usize = 6;
+const MIN_SILHOUETTE: f32 = 0.2;
+/// Weight of the model-size penalty when testing one voice against several;
+/// above the textbook 1.0 because half-overlapping frames are correlated.
+const BIC_PENALTY: f64 = 2.0;
+/// Same-speaker turns closer than this are one turn.
+const TURN_JOIN_GAP_SEC: f64 = 0.5;
+/// Shorter turns between two turns of one speaker are taken as flips.
+const MIN_TURN_SEC: f64 = 1.0;
+/// Words outside every turn go to the nearest one within this distance.
+const WORD_SNAP_SEC: f64 = 1.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DiarizeConfig {
+    /// Known number of speakers; estimated when `None`.
+    pub num_speakers: Option<usize>,
+    pub max_speakers: usize,
+    pub vad: VadConfig,
+}
+
+impl Default for DiarizeConfig {
+    fn default() -> Self {
+        Self {
+            num_speakers: None,
+            max_speakers: DEFAULT_MAX_SPEAKERS,
+            vad: VadConfig::default(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+pub struct SpeakerTurn {
+    pub speaker: String,
+    pub start_sec: f64,
+    pub end_sec: f64,
+}
+
+pub struct Diarization {
+    pub duration_sec: f64,
+    pub speaker_count: usize,
+    pub turns: Vec<SpeakerTurn>,
+    pub silhouette: Option<f32>,
+}
+
+struct MfccExtractor {
+    frame_len: usize,
+    hop: usize,
+    hop_sec: f64,
+    plan: FftPlan,
+    magnitudes: Vec<f32>,
+    filters: Vec<(usize, Vec<f32>)>,
+    dct: Vec<[f32; MEL_BANDS]>,
+    pending: Vec<f32>,
+    features: Vec<[f32; CEPSTRA]>,
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+impl MfccExtractor {
+    fn new(sample_rate: u32) -> Result<Self, String> {
+        let frame_len = ((f64::from(sample_rate) * FEATURE_FRAME_SEC) as usize).next_power_of_two();
+        let plan = FftPlan::new(frame_len, SpectrumWindow::Hamming)?;
+        let bin_hz = sample_rate as f32 / frame_len as f32;
+        let top_hz = MEL_RANGE_HZ.1.min(sample_rate as f32 / 2.0);
+        let (low_mel, high_mel) = (hz_to_mel(MEL_RANGE_HZ.0), hz_to_mel(top_hz));
+        let edges: Vec<f32> = (0..MEL_BANDS + 2)
+            .map(|index| {
+                mel_to_hz(low_mel + (high_mel - low_mel) * index as f32 / (MEL_BANDS + 1) as f32)
+                    / bin_hz
+            })
+            .collect();
+        let filters = edges
+            .windows(3)
+            .map(|edge| {
+                let (left, centre, right) = (edge[0], edge[1], edge[2]);
+                let first = left.ceil() as usize;
+                let last = (right.floor() as usize).min(plan.bin_count() - 1);
+                let weights = (first..=last)
+                    .map(|bin| {
+                        let bin = bin as f32;
+                        if bin <= centre {
+                            (bin - left) / (centre - left).max(f32::EPSILON)
+                        } else {
+                            (right - bin) / (right - centre).max(f32::EPSILON)
+                        }
+                    })
+                    .collect();
+                (first, weights)
+            })
+            .collect();
+        let dct = (1..=CEPSTRA)
+            .map(|coefficient| {
+                let mut row = [0.0; MEL_BANDS];
+                for (band, value) in row.iter_mut().enumerate() {
+                    *value = (std::f32::consts::PI * coefficient as f32 * (band as f32 + 0.5)
+                        / MEL_BANDS as f32)
+                        .cos();
+                }
+                row
+            })
+            .collect();
+        let hop = frame_len / 2;
+        Ok(Self {
+            frame_len,
+            hop,
+            hop_sec: hop as f64 / f64::from(sample_rate),
+            magnitudes: vec![0.0; plan.bin_count()],
+            plan,
+            filters,
+            dct,
+            pending: Vec::with_capacity(frame_len * 2),
+            features: Vec::new(),
+        })
+    }
+
+    fn push(&mut self, mono: &[f32]) {
+        self.pending.extend_from_slice(mono);
+        let mut start = 0;
+        while start + self.frame_len <= self.pending.len() {
+            self.plan.magnitudes(
+                &self.pending[start..start + self.frame_len],
+                &mut self.magnitudes,
+            );
+            let mut log_mel = [0.0f32; MEL_BANDS];
+            for (value, (first, weights)) in log_mel.iter_mut().zip(&self.filters) {
+                let energy: f32 = weights
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, weight)| {
+                        let magnitude = self.magnitudes[first + offset];
+                        weight * magnitude * magnitude
+                    })
+                    .sum();
+                *value = energy.max(1e-10).ln();
+            }
+            let mut cepstra = [0.0f32; CEPSTRA];
+            for (value, row) in cepstra.iter_mut().zip(&self.dct) {
+                *value = row.iter().zip(&log_mel).map(|(a, b)| a * b).sum();
+            }
+            self.features.push(cepstra);
+            start += self.hop;
+        }
+        self.pending.drain(..start);
+    }
+
+    /// Feature frames whose centre lies in `[start_sec, end_sec)`.
+    fn frame_range(&self, start_sec: f64, end_sec: f64) -> std::ops::Range<usize> {
+        let centre_offset = self.frame_len as f64 / 2.0 * self.hop_sec / self.hop as f64;
+        let index = |sec: f64| {
+            (((sec - centre_offset) / self.hop_sec).ceil().max(0.0) as usize)
+                .min(self.features.len())
+        };
+        index(start_sec)..index(end_sec)
+    }
+}
+
+/// Streaming front end: voice activity and cepstra from one pass.
+pub struct SpeakerAnalyzer {
+    sample_rate: u32,
+    detector: VoiceActivityDetector,
+    events: Vec<VadEvent>,
+    mfcc: MfccExtractor,
+    samples: u64,
+}
+
+struct Window {
+    start_sec: f64,
+    end_sec: f64,
+    core_start_sec: f64,
+    core_end_sec: f64,
+}
+
+impl SpeakerAnalyzer {
+    pub fn new(sample_rate: u32, vad_config: VadConfig) -> Result<Self, String> {
+        Ok(Self {
+            sample_rate,
+            detector: VoiceActivityDetector::new(sample_rate, vad_config)?,
+            events: Vec::new(),
+            mfcc: MfccExtractor::new(sample_rate)?,
+            samples: 0,
+        })
+    }
+
+    pub fn push(&mut self, mono: &[f32]) {
+        self.samples += mono.len() as u64;
+        self.detector.push(mono, &mut self.events);
+        self.mfcc.push(mono);
+    }
+
+    pub fn finish(mut self, config: &DiarizeConfig) -> Diarization {
+        self.detector.finish(&mut self.events);
+        let duration_sec = self.samples as f64 / f64::from(self.sample_rate);
+        let regions: Vec<SpeechRegion> = vad::split_segments(
+            &vad::regions_from_events(&self.events),
+            REGION_MERGE_PAUSE_SEC,
+            0.0,
+            duration_sec,
+        )
+        .into_iter()
+        .filter(|region| region.end_sec - region.start_sec >= MIN_REGION_SEC)
+        .collect();
+        let windows = tile_windows(&regions);
+        let embeddings = self.embeddings(&windows);
+        let (mut labels, mut silhouette) = cluster(&embeddings, config);
+        // Silhouette only ranks cuts against each other; whether the best
+        // cut separates voices at all is decided on the raw cepstra.
+        if config.num_speakers.is_none() && self.bic_gain(&windows, &labels) <= 0.0 {
+            labels.iter_mut().for_each(|label| *label = 0);
+            silhouette = None;
+        }
+        let turns = build_turns(&windows, &labels);
+        let speaker_count = turns
+            .iter()
+            .map(|turn| turn.speaker.as_str())
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        Diarization {
+            duration_sec,
+            speaker_count,
+            turns,
+            silhouette,
+        }
+    }
+
+    /// ΔBIC of modelling each cluster's frames with its own diagonal
+    /// Gaussian instead of one for all speech; positive means the split
+    /// explains more than its extra parameters cost.
+    fn bic_gain(&self, windows: &[Window], labels: &[usize]) -> f64 {
+        let clusters = labels.iter().max().map_or(0, |max| max + 1);
+        if clusters < 2 {
+            return 0.0;
+        }
+        let mut stats = vec![(0usize, [0.0f64; CEPSTRA], [0.0f64; CEPSTRA]); clusters + 1];
+        for (window, label) in windows.iter().zip(labels) {
+            for frame in &self.mfcc.features[self
+                .mfcc
+                .frame_range(window.core_start_sec, window.core_end_sec)]
+            {
+                for slot in [*label, clusters] {
+                    let (count, sum, square) = &mut stats[slot];
+                    *count += 1;
+                    for (index, value) in frame.iter().enumerate() {
+                        sum[index] += f64::from(*value);
+                        square[index] += f64::from(*value) * f64::from(*value);
+                    }
+                }
+            }
+        }
+        let log_det = |(count, sum, square): &(usize, [f64; CEPSTRA], [f64; CEPSTRA])| {
+            let count = (*count).max(1) as f64;
+            (0..CEPSTRA)
+                .map(|index| {
+                    let mean = sum[index] / count;
+                    (square[index] / count - mean * mean).max(1e-6).ln()
+                })
+                .sum::<f64>()
+        };
+        let total = stats[clusters].0 as f64;
+        let split: f64 = stats[..clusters]
+            .iter()
+            .map(|cluster| cluster.0 as f64 * log_det(cluster))
+            .sum();
+        let penalty = BIC_PENALTY * (clusters - 1) as f64 * CEPSTRA as f64 * total.max(1.0).ln();
+        0.5 * (total * log_det(&stats[clusters]) - split) - penalty
+    }
+
+    fn embeddings(&self, windows: &[Window]) -> Vec<Vec<f32>> {
+        let features = &self.mfcc.features;
+        // Normalise each coefficient over the speech of the whole file.
+        let mut mean = [0.0f64; CEPSTRA];
+        let mut square = [0.0f64; CEPSTRA];
+        let mut count = 0usize;
+        for window in windows {
+            for frame in &features[self
+                .mfcc
+                .frame_range(window.core_start_sec, window.core_end_sec)]
+            {
+                for (index, value) in frame.iter().enumerate() {
+                    mean[index] += f64::from(*value);
+                    square[index] += f64::from(*value) * f64::from(*value);
+                }
+                count += 1;
+            }
+        }
+        let count = count.max(1) as f64;
+        let scale: Vec<(f32, f32)> = (0..CEPSTRA)
+            .map(|index| {
+                let mean = mean[index] / count;
+                let std = (square[index] / count - mean * mean).max(1e-9).sqrt();
+                (mean as f32, std as f32)
+            })
+            .collect();
+
+        windows
+            .iter()
+            .map(|window| {
+                let frames = &features[self.mfcc.frame_range(window.start_sec, window.end_sec)];
+                let count = frames.len().max(1) as f32;
+                let mut embedding = vec![0.0f32; CEPSTRA * 2];
+                for frame in frames {
+                    for (index, value) in frame.iter().enumerate() {
+                        let normalised = (value - scale[index].0) / scale[index].1;
+                        embedding[index] += normalised / count;
+                        embedding[CEPSTRA + index] += normalised * normalised / count;
+                    }
+                }
+                for index in 0..CEPSTRA {
+                    let mean = embedding[index];
+                    // Spread relative to the file's (unit) spread, so a window
+                    // that varies like the average voice adds nothing.
+                    embedding[CEPSTRA + index] =
+                        ((embedding[CEPSTRA + index] - mean * mean).max(0.0).sqrt() - 1.0) * 0.5;
+                }
+                let norm = embedding
+                    .iter()
+                    .map(|value| value * value)
+                    .sum::<f32>()
+                    .sqrt()
+                    .max(1e-9);
+                embedding.iter_mut().for_each(|value| *value /= norm);
+                embedding
+            })
+            .collect()
+    }
+}
+
+/// Overlapping analysis windows over each region; cores tile the region.
+fn tile_windows(regions: &[SpeechRegion]) -> Vec<Window> {
+    let speech_sec: f64 = regions
+        .iter()
+        .map(|region| region.end_sec - region.start_sec)
+        .sum();
+    let hop = WINDOW_HOP_SEC.max(speech_sec / MAX_WINDOWS as f64);
+    let length = WINDOW_SEC.max(hop * 2.0);
+    let mut windows = Vec::new();
+    for region in regions {
+        let duration = region.end_sec - region.start_sec;
+        let count = (((duration - length) / hop).ceil().max(0.0) as usize) + 1;
+        for index in 0..count {
+            let start_sec = region.start_sec + index as f64 * hop;
+            windows.push(Window {
+                start_sec,
+                end_sec: (start_sec + length).min(region.end_sec),
+                core_start_sec: if index == 0 {
+                    region.start_sec
+                } else {
+                    start_sec + (length - hop) / 2.0
+                },
+                core_end_sec: if index + 1 == count {
+                    region.end_sec
+                } else {
+                    start_sec + (length + hop) / 2.0
+                },
+            });
+        }
+    }
+    windows
+}
+
+/// Condensed upper-triangle distance matrix.
+struct Distances {
+    size: usize,
+    values: Vec<f32>,
+}
+
+impl Distances {
+    fn cosine(embeddings: &[Vec<f32>]) -> Self {
+        let size = embeddings.len();
+        let mut values = Vec::with_capacity(size * size.saturating_sub(1) / 2);
+        for (index, left) in embeddings.iter().enumerate() {
+            for right in &embeddings[index + 1..] {
+                let similarity: f32 = left.iter().zip(right).map(|(a, b)| a * b).sum();
+                values.push(1.0 - similarity);
+            }
+        }
+        Self { size, values }
+    }
+
+    fn index(&self, left: usize, right: usize) -> usize {
+        let (low, high) = if left < right {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        low * self.size - low * (low + 1) / 2 + high - low - 1
+    }
+
+    fn get(&self, left: usize, right: usize) -> f32 {
+        self.values[self.index(left, right)]
+    }
+
+    fn set(&mut self, left: usize, right: usize, value: f32) {
+        let index = self.index(left, right);
+        self.values[index] = value;
+    }
+}
+
+/// Average-linkage merges `(kept, absorbed, distance)` found with the
+/// nearest-neighbour chain; sorted by distance they form the dendrogram.
+fn average_linkage(mut distances: Distances) -> Vec<(usize, usize, f32)> {
+    let size = distances.size;
+    let mut active = vec![true; size];
+    let mut members = vec![1usize; size];
+    let mut chain: Vec<usize> = Vec::new();
+    let mut merges = Vec::with_capacity(size.saturating_sub(1));
+    while merges.len() + 1 < size {
+        if chain.is_empty() {
+            chain.extend(active.iter().position(|active| *active));
+        }
+        let current = chain[chain.len() - 1];
+        let previous = chain.len().checked_sub(2).map(|index| chain[index]);
+        let mut nearest = previous;
+        let mut nearest_distance =
+            previous.map_or(f32::INFINITY, |previous| distances.get(current, previous));
+        for candidate in (0..size).filter(|candidate| active[*candidate] && *candidate != current) {
+            let distance = distances.get(current, candidate);
+            if distance < nearest_distance {
+                nearest = Some(candidate);
+                nearest_distance = distance;
+            }
+        }
+        let Some(nearest) = nearest else {
+            break;
+        };
+        if Some(nearest) != previous {
+            chain.push(nearest);
+            continue;
+        }
+        chain.truncate(chain.len() - 2);
+        let (kept, absorbed) = (current.min(nearest), current.max(nearest));
+        let (kept_size, absorbed_size) = (members[kept] as f32, members[absorbed] as f32);
+        for other in
+            (0..size).filter(|other| active[*other] && *other != kept && *other != absorbed)
+        {
+            let merged = (kept_size * distances.get(kept, other)
+                + absorbed_size * distances.get(absorbed, other))
+                / (kept_size + absorbed_size);
+            distances.set(kept, other, merged);
+        }
+        active[absorbed] = false;
+        members[kept] += members[absorbed];
+        merges.push((kept, absorbed, nearest_distance));
+    }
+    merges.sort_by(|left, right| left.2.total_cmp(&right.2));
+    merges
+}
+
+fn find(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    let mut node = index;
+    while parents[node] != root {
+        let next = parents[node];
+        parents[node] = root;
+        node = next;
+    }
+    root
+}
+
+/// Cluster of each item after the first `merge_count` merges, numbered by
+/// first appearance.
+fn cut(size: usize, merges: &[(usize, usize, f32)], merge_count: usize) -> Vec<usize> {
+    let mut parents: Vec<usize> = (0..size).collect();
+    for (kept, absorbed, _) in &merges[..merge_count.min(merges.len())] {
+        let (kept, absorbed) = (find(&mut parents, *kept), find(&mut parents, *absorbed));
+        parents[absorbed] = kept;
+    }
+    let mut numbering: HashMap<usize, usize> = HashMap::new();
+    (0..size)
+        .map(|index| {
+            let root = find(&mut parents, index);
+            let next = numbering.len();
+            *numbering.entry(root).or_insert(next)
+        })
+        .collect()
+}
+
+/// Mean silhouette of a labelling; `None` for fewer than two clusters.
+fn silhouette(distances: &Distances, labels: &[usize]) -> Option<f32> {
+    let clusters = labels.iter().max().map_or(0, |max| max + 1);
+    if clusters < 2 {
+        return None;
+    }
+    let total: f32 = (0..labels.len())
+        .map(|item| {
+            let mut sums = vec![0.0f32; clusters];
+            let mut counts = vec![0usize; clusters];
+            for other in (0..labels.len()).filter(|other| *other != item) {
+                sums[labels[other]] += distances.get(item, other);
+                counts[labels[other]] += 1;
+            }
+            let own = labels[item];
+            if counts[own] == 0 {
+                return 0.0;
+            }
+            let cohesion = sums[own] / counts[own] as f32;
+            let separation = (0..clusters)
+                .filter(|cluster| *cluster != own && counts[*cluster] > 0)
+                .map(|cluster| sums[cluster] / counts[cluster] as f32)
+                .fold(f32::INFINITY, f32::min);
+            (separation - cohesion) / cohesion.max(separation).max(f32::EPSILON)
+        })
+        .sum();
+    Some(total / labels.len() as f32)
+}
+
+fn cluster(embeddings: &[Vec<f32>], config: &DiarizeConfig) -> (Vec<usize>, Option<f32>) {
+    let size = embeddings.len();
+    if size < 2 {
+        return (vec![0; size], None);
+    }
+    let distances = Distances::cosine(embeddings);
+    let merges = average_linkage(Distances {
+        size,
+        values: distances.values.clone(),
+    });
+    let labels_for = |speakers: usize| cut(size, &merges, size - speakers.clamp(1, size));
+    if let Some(speakers) = config.num_speakers {
+        let labels = labels_for(speakers);
+        let score = silhouette(&distances, &labels);
+        return (labels, score);
+    }
+    (2..=config.max_speakers.max(1).min(size))
+        .map(|speakers| {
+            let labels = labels_for(speakers);
+            let score = silhouette(&distances, &labels);
+            (labels, score)
+        })
+        .filter(|(_, score)| score.is_some_and(|score| score >= MIN_SILHOUETTE))
+        .max_by(|left, right| left.1.unwrap_or(0.0).total_cmp(&right.1.unwrap_or(0.0)))
+        .unwrap_or_else(|| (vec![0; size], None))
+}
+
+fn build_turns(windows: &[Window], labels: &[usize]) -> Vec<SpeakerTurn> {
+    let mut spans: Vec<(usize, f64, f64)> = Vec::new();
+    for (window, label) in windows.iter().zip(labels) {
+        match spans.last_mut() {
+            Some(last)
+                if last.0 == *label && window.core_start_sec - last.2 < TURN_JOIN_GAP_SEC =>
+            {
+                last.2 = window.core_end_sec;
+            }
+            _ => spans.push((*label, window.core_start_sec, window.core_end_sec)),
+        }
+    }
+    // A short turn wedged between two turns of one speaker is a flip.
+    let mut index = 1;
+    while index + 1 < spans.len() {
+        let (before, current, after) = (spans[index - 1], spans[index], spans[index + 1]);
+        if before.0 == after.0
+            && current.2 - current.1 < MIN_TURN_SEC
+            && current.1 - before.2 < TURN_JOIN_GAP_SEC
+            && after.1 - current.2 < TURN_JOIN_GAP_SEC
+        {
+            spans[index - 1].2 = after.2;
+            spans.drain(index..=index + 1);
+        } else {
+            index += 1;
+        }
+    }
+    // Renumber by first appearance after smoothing.
+    let mut numbering: HashMap<usize, usize> = HashMap::new();
+    spans
+        .into_iter()
+        .map(|(label, start_sec, end_sec)| {
+            let next = numbering.len();
+            let number = *numbering.entry(label).or_insert(next) + 1;
+            SpeakerTurn {
+                speaker: format!("S{number}"),
+                start_sec,
+                end_sec,
+            }
+        })
+        .collect()
+}
+
+/// Word and segment timings as produced by the STT plugin's
+/// `transcribe_file` (camelCase) or stored in snake_case.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptInput {
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub engine: Option<String>,
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegmentInput>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSegmentInput {
+    #[serde(default)]
+    pub text: String,
+    #[serde(alias = "start_sec")]
+    pub start_sec: f64,
+    #[serde(alias = "end_sec")]
+    pub end_sec: f64,
+    #[serde(default)]
+    pub words: Vec<TranscriptWordInput>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptWordInput {
+    pub word: String,
+    #[serde(alias = "start_sec")]
+    pub start_sec: f64,
+    #[serde(alias = "end_sec")]
+    pub end_sec: f64,
+    #[serde(default)]
+    pub confidence: Option<f32>,
+}
+
+fn speaker_at(turns: &[SpeakerTurn], start_sec: f64, end_sec: f64) -> Option<&str> {
+    let overlap = |turn: &SpeakerTurn| turn.end_sec.min(end_sec) - turn.start_sec.max(start_sec);
+    if let Some(turn) = turns
+        .iter()
+        .filter(|turn| overlap(turn) > 0.0)
+        .max_by(|left, right| overlap(left).total_cmp(&overlap(right)))
+    {
+        return Some(&turn.speaker);
+    }
+    let middle = (start_sec + end_sec) / 2.0;
+    let distance = |turn: &SpeakerTurn| (turn.start_sec - middle).max(middle - turn.end_sec);
+    turns
+        .iter()
+        .filter(|turn| distance(turn) <= WORD_SNAP_SEC)
+        .min_by(|left, right| distance(left).total_cmp(&distance(right)))
+        .map(|turn| turn.speaker.as_str())
+}
+
+/// Groups the transcript's words into utterances of one speaker each.
+pub fn attribute_transcript(
+    turns: &[SpeakerTurn],
+    transcript: &TranscriptInput,
+    names: &HashMap<String, String>,
+) -> Vec<Value> {
+    let mut utterances: Vec<(Option<String>, Vec<TranscriptWordInput>)> = Vec::new();
+    for segment in &transcript.segments {
+        let words = if segment.words.is_empty() {
+            vec![TranscriptWordInput {
+                word: segment.text.trim().to_string(),
+                start_sec: segment.start_sec,
+                end_sec: segment.end_sec,
+                confidence: None,
+            }]
+        } else {
+            segment.words.clone()
+        };
+        for word in words
+            .into_iter()
+            .filter(|word| !word.word.trim().is_empty())
+        {
+            let speaker = speaker_at(turns, word.start_sec, word.end_sec).map(str::to_string);
+            match utterances.last_mut() {
+                Some((last_speaker, last_words)) if *last_speaker == speaker => {
+                    last_words.push(word)
+                }
+                _ => utterances.push((speaker, vec![word])),
+            }
+        }
+    }
+    utterances
+        .into_iter()
+        .map(|(speaker, words)| {
+            json!({
+                "speaker": speaker,
+                "speaker_name": speaker.as_ref().and_then(|speaker| names.get(speaker)),
+                "start_sec": words[0].start_sec,
+                "end_sec": words[words.len() - 1].end_sec,
+                "text": words
+                    .iter()
+                    .map(|word| word.word.trim())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                "words": words
+                    .iter()
+                    .map(|word| json!({
+                        "word": word.word.trim(),
+                        "start_sec": word.start_sec,
+                        "end_sec": word.end_sec,
+                        "confidence": word.confidence
+                    }))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect()
+}
+
+fn speakers_json(turns: &[SpeakerTurn], names: &HashMap<String, String>) -> Vec<Value> {
+    let mut speakers: Vec<(String, f64, usize)> = Vec::new();
+    for turn in turns {
+        match speakers
+            .iter_mut()
+            .find(|speaker| speaker.0 == turn.speaker)
+        {
+            Some(speaker) => {
+                speaker.1 += turn.end_sec - turn.start_sec;
+                speaker.2 += 1;
+            }
+            None => speakers.push((turn.speaker.clone(), turn.end_sec - turn.start_sec, 1)),
+        }
+    }
+    speakers
+        .into_iter()
+        .map(|(id, speech_sec, turn_count)| {
+            json!({
+                "id": id,
+                "name": names.get(&id),
+                "speech_sec": speech_sec,
+                "turn_count": turn_count
+            })
+        })
+        .collect()
+}
+
+/// Diarizes any symphonia-decodable file; with a transcript, also returns the
+/// speaker-attributed `utterances`.
+pub fn diarize_file(
+    path: &Path,
+    config: DiarizeConfig,
+    transcript: Option<&TranscriptInput>,
+    names: &HashMap<String, String>,
+) -> Result<Value, String> {
+    let mut decoder = SymphoniaStreamDecoder::open_path(path)?;
+    let sample_rate = decoder.sample_rate();
+    let mut analyzer = SpeakerAnalyzer::new(sample_rate, config.vad)?;
+    while let Some(frames) = decoder.next_frames()? {
+        analyzer.push(&vad::mono(&frames));
+    }
+    let diarization = analyzer.finish(&config);
+    let mut result = json!({
+        "version": 1,
+        "source_path": path.to_string_lossy(),
+        "duration_sec": diarization.duration_sec,
+        "sample_rate": sample_rate,
+        "speaker_count": diarization.speaker_count,
+        "silhouette": diarization.silhouette,
+        "speakers": speakers_json(&diarization.turns, names),
+        "turns": diarization
+            .turns
+            .iter()
+            .enumerate()
+            .map(|(index, turn)| json!({
+                "index": index,
+                "speaker": turn.speaker,
+                "start_sec": turn.start_sec,
+                "end_sec": turn.end_sec,
+                "duration_sec": turn.end_sec - turn.start_sec
+            }))
+            .collect::<Vec<_>>()
+    });
+    if let Some(transcript) = transcript {
+        result["language"] = json!(transcript.language);
+        result["engine"] = json!(transcript.engine);
+        result["utterances"] = json!(attribute_transcript(&diarization.turns, transcript, names));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        attribute_transcript, DiarizeConfig, SpeakerAnalyzer, SpeakerTurn, TranscriptInput,
+    };
+    use crate::audio_engine::vad::VadConfig;
+    use std::collections::HashMap;
+
+    const RATE: u32 = 16_000;
+
+    /// A crude voice: harmonics of a gliding pitch around `pitch_hz`, shaped
+    /// by two drifting formants and a syllable-rate envelope. `seed` varies
+    /// the intonation so no two turns are copies.
+    fn voice(seconds: f64, pitch_hz: f64, formants: (f64, f64), seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        let offset = f64::from(seed) * 1.7;
+        let mut phase = 0.0f64;
+        (0..(seconds * f64::from(RATE)) as usize)
+            .map(|index| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let hiss = ((state >> 8) as f32 / (1u32 << 24) as f32 - 0.5) * 0.002;
+                let time = index as f64 / f64::from(RATE);
+                let pitch = pitch_hz * (1.0 + 0.08 * (1.3 * time + offset).sin());
+                phase += std::f64::consts::TAU * pitch / f64::from(RATE);
+                let drift = 1.0 + 0.12 * (13.0 * time + offset * 0.7).sin();
+                let envelope = 0.6 + 0.4 * (std::f64::consts::TAU * 3.5 * time + offset).sin();
+                let sample: f64 = (1..=30)
+                    .map(|harmonic| {
+                        let frequency = pitch * f64::from(harmonic);
+                        let gain = [formants.0 * drift, formants.1 * drift]
+                            .iter()
+                            .map(|formant| 1.0 / (1.0 + ((frequency - formant) / 150.0).powi(2)))
+                            .sum::<f64>();
+                        gain * (phase * f64::from(harmonic)).sin()
+                    })
+                    .sum();
+                (sample * 0.03 * envelope) as f32 + hiss
+            })
+            .collect()
+    }
+
+    fn silence(seconds: f64) -> Vec<f32> {
+        vec![0.0; (seconds * f64::from(RATE)) as usize]
+    }
+
+    fn diarize(parts: &[Vec<f32>], config: DiarizeConfig) -> super::Diarization {
+        let mut analyzer = SpeakerAnalyzer::new(RATE, VadConfig::default()).unwrap();
+        for part in parts {
+            for chunk in part.chunks(1_000) {
+                analyzer.push(chunk);
+            }
+        }
+        analyzer.finish(&config)
+    }
+
+    #[test]
+    fn separates_two_alternating_voices() {
+        let low = |seed| voice(3.0, 110.0, (500.0, 1_000.0), seed);
+        let high = |seed| voice(3.0, 230.0, (900.0, 2_300.0), seed);
+        let parts = vec![
+            silence(0.5),
+            low(1),
+            silence(0.6),
+            high(2),
+            silence(0.6),
+            low(3),
+            silence(0.6),
+            high(4),
+            silence(0.5),
+        ];
+        let diarization = diarize(&parts, DiarizeConfig::default());
+        assert_eq!(diarization.speaker_count, 2, "{:?}", diarization.turns);
+        let speakers: Vec<&str> = diarization
+            .turns
+            .iter()
+            .map(|turn| turn.speaker.as_str())
+            .collect();
+        assert_eq!(speakers, vec!["S1", "S2", "S1", "S2"]);
+        assert!((diarization.turns[1].start_sec - 4.1).abs() < 0.1);
+    }
+
+    #[test]
+    fn keeps_a_single_voice_together() {
+        let parts: Vec<Vec<f32>> = (0..4)
+            .flat_map(|seed| [voice(2.5, 140.0, (700.0, 1_600.0), seed), silence(0.6)])
+            .collect();
+        let diarization = diarize(&parts, DiarizeConfig::default());
+        assert_eq!(diarization.speaker_count, 1, "{:?}", diarization.turns);
+    }
+
+    #[test]
+    fn words_follow_the_overlapping_turn() {
+        let turns = vec![
+            SpeakerTurn {
+                speaker: "S1".to_string(),
+                start_sec: 0.0,
+                end_sec: 2.0,
+            },
+            SpeakerTurn {
+                speaker: "S2".to_string(),
+                start_sec: 2.2,
+                end_sec: 4.0,
+            },
+        ];
+        let transcript: TranscriptInput = serde_json::from_str(
+            r#"{"language": "fr", "segments": [{"text": "bonjour à vous merci",
+                "startSec": 0.1, "endSec": 3.5, "words": [
+                {"word": "bonjour", "startSec": 0.1, "endSec": 0.6},
+                {"word": "à", "startSec": 0.7, "endSec": 0.8},
+                {"word": "vous", "startSec": 1.9, "endSec": 2.3},
+                {"word": "merci", "startSec": 2.9, "endSec": 3.5}]}]}"#,
+        )
+        .unwrap();
+        let names = HashMap::from([("S2".to_string(), "Camille".to_string())]);
+        let utterances = attribute_transcript(&turns, &transcript, &names);
+        assert_eq!(utterances.len(), 2);
+        assert_eq!(utterances[0]["text"], "bonjour à vous");
+        assert_eq!(utterances[0]["speaker_name"], serde_json::Value::Null);
+        assert_eq!(utterances[1]["speaker"], "S2");
+        assert_eq!(utterances[1]["speaker_name"], "Camille");
+        assert_eq!(utterances[1]["start_sec"], 2.9);
+    }
+}
@@ -0,0 +1,176 @@
+// Radix-2 real-input FFT helpers shared by the spectral analyzer and the
+// live spectrum tap. Plans are precomputed so the per-block path only does
+// arithmetic and never allocates.
+
+use std::f32::consts::PI;
+
+pub const MIN_FFT_SIZE: usize = 64;
+pub const MAX_FFT_SIZE: usize = 32_768;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SpectrumWindow {
+    Rectangular,
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl SpectrumWindow {
+    pub fn coefficients(self, size: usize) -> Vec<f32> {
+        let denominator = size.saturating_sub(1).max(1) as f32;
+        (0..size)
+            .map(|index| {
+                let phase = 2.0 * PI * index as f32 / denominator;
+                match self {
+                    Self::Rectangular => 1.0,
+                    Self::Hann => 0.5 - 0.5 * phase.cos(),
+                    Self::Hamming => 0.54 - 0.46 * phase.cos(),
+                    Self::Blackman => 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "rectangular" | "none" => Ok(Self::Rectangular),
+            "hann" | "hanning" => Ok(Self::Hann),
+            "hamming" => Ok(Self::Hamming),
+            "blackman" => Ok(Self::Blackman),
+            other => Err(format!("Unsupported spectrum window: {other}")),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Rectangular => "rectangular",
+            Self::Hann => "hann",
+            Self::Hamming => "hamming",
+            Self::Blackman => "blackman",
+        }
+    }
+}
+
+pub struct FftPlan {
+    size: usize,
+    window: Vec<f32>,
+    window_gain: f32,
+    twiddles: Vec<(f32, f32)>,
+    bit_reverse: Vec<usize>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+}
+
+impl FftPlan {
+    pub fn new(size: usize, window: SpectrumWindow) -> Result<Self, String> {
+        if !size.is_power_of_two() || !(MIN_FFT_SIZE..=MAX_FFT_SIZE).contains(&size) {
+            return Err(format!(
+                "FFT size must be a power of two between {MIN_FFT_SIZE} and {MAX_FFT_SIZE}, got {size}"
+            ));
+        }
+        let bits = size.trailing_zeros();
+        let bit_reverse = (0..size)
+            .map(|index| index.reverse_bits() >> (usize::BITS - bits))
+            .collect();
+        let twiddles = (0..size / 2)
+            .map(|index| {
+                let angle = -2.0 * PI * index as f32 / size as f32;
+                (angle.cos(), angle.sin())
+            })
+            .collect();
+        let window = window.coefficients(size);
+        let window_gain = window.iter().sum::<f32>().max(f32::EPSILON);
+        Ok(Self {
+            size,
+            window,
+            window_gain,
+            twiddles,
+            bit_reverse,
+            re: vec![0.0; size],
+            im: vec![0.0; size],
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn bin_count(&self) -> usize {
+        self.size / 2 + 1
+    }
+
+    fn transform(&mut self) {
+        for index in 0..self.size {
+            let target = self.bit_reverse[index];
+            if target > index {
+                self.re.swap(index, target);
+                self.im.swap(index, target);
+            }
+        }
+        let mut length = 2;
+        while length <= self.size {
+            let half = length / 2;
+            let stride = self.size / length;
+            for start in (0..self.size).step_by(length) {
+                for offset in 0..half {
+                    let (w_re, w_im) = self.twiddles[offset * stride];
+                    let even = start + offset;
+                    let odd = even + half;
+                    let odd_re = self.re[odd] * w_re - self.im[odd] * w_im;
+                    let odd_im = self.re[odd] * w_im + self.im[odd] * w_re;
+                    self.re[odd] = self.re[even] - odd_re;
+                    self.im[odd] = self.im[even] - odd_im;
+                    self.re[even] += odd_re;
+                    self.im[even] += odd_im;
+                }
+            }
+            length *= 2;
+        }
+    }
+
+    /// Windowed amplitude spectrum of `input` (zero-padded or truncated to the
+    /// plan size). A full-scale sine on a bin centre reads ~1.0.
+    pub fn magnitudes(&mut self, input: &[f32], output: &mut [f32]) {
+        for index in 0..self.size {
+            self.re[index] = input.get(index).copied().unwrap_or(0.0) * self.window[index];
+            self.im[index] = 0.0;
+        }
+        self.transform();
+        let scale = 2.0 / self.window_gain;
+        for (bin, value) in output.iter_mut().enumerate().take(self.bin_count()) {
+            *value = (self.re[bin] * self.re[bin] + self.im[bin] * self.im[bin]).sqrt() * scale;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FftPlan, SpectrumWindow};
+
+    #[test]
+    fn rejects_non_power_of_two_sizes() {
+        assert!(FftPlan::new(1000, SpectrumWindow::Hann).is_err());
+        assert!(FftPlan::new(32, SpectrumWindow::Hann).is_err());
+    }
+
+    #[test]
+    fn bin_centred_sine_peaks_at_its_bin_with_unit_amplitude() {
+        let size = 1024;
+        let mut plan = FftPlan::new(size, SpectrumWindow::Rectangular).unwrap();
+        let input: Vec<f32> = (0..size)
+            .map(|index| (2.0 * std::f32::consts::PI * 32.0 * index as f32 / size as f32).sin())
+            .collect();
+        let mut output = vec![0.0; plan.bin_count()];
+        plan.magnitudes(&input, &mut output);
+        let peak_bin = output
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(bin, _)| bin)
+            .unwrap();
+        assert_eq!(peak_bin, 32);
+        assert!((output[32] - 1.0).abs() < 1e-3, "amplitude={}", output[32]);
+        assert!(output[10] < 1e-3);
+    }
+}
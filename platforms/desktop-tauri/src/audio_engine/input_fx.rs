@@ -0,0 +1,354 @@
+// Input effects chain
+// Runs in the recorder input callback before anything else sees the samples:
+// gain -> high-pass -> noise gate -> limiter. The recorded file, the meters
+// and the monitor output all carry the processed signal.
+//
+// Settings are global (they survive between takes and can be changed while
+// recording). The callback never blocks on them: a version counter tells the
+// processor something changed and it picks the new values up with `try_lock`.
+
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const GATE_ATTACK_SEC: f32 = 0.001;
+const GATE_HOLD_SEC: f32 = 0.05;
+const GATE_RELEASE_SEC: f32 = 0.08;
+const LIMITER_RELEASE_SEC: f32 = 0.1;
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct InputFxSettings {
+    pub gain_db: f32,
+    pub high_pass_enabled: bool,
+    pub high_pass_hz: f32,
+    pub gate_enabled: bool,
+    pub gate_threshold_db: f32,
+    pub limiter_enabled: bool,
+    pub limiter_ceiling_db: f32,
+    pub monitor_enabled: bool,
+    pub monitor_gain_db: f32,
+}
+
+impl Default for InputFxSettings {
+    fn default() -> Self {
+        Self {
+            gain_db: 0.0,
+            high_pass_enabled: false,
+            high_pass_hz: 80.0,
+            gate_enabled: false,
+            gate_threshold_db: -50.0,
+            limiter_enabled: false,
+            limiter_ceiling_db: -1.0,
+            monitor_enabled: false,
+            monitor_gain_db: 0.0,
+        }
+    }
+}
+
+/// Partial update; `None` keeps the current value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InputFxPatch {
+    pub gain_db: Option<f32>,
+    pub high_pass_enabled: Option<bool>,
+    pub high_pass_hz: Option<f32>,
+    pub gate_enabled: Option<bool>,
+    pub gate_threshold_db: Option<f32>,
+    pub limiter_enabled: Option<bool>,
+    pub limiter_ceiling_db: Option<f32>,
+    pub monitor_enabled: Option<bool>,
+    pub monitor_gain_db: Option<f32>,
+}
+
+static SETTINGS: Lazy<Mutex<InputFxSettings>> =
+    Lazy::new(|| Mutex::new(InputFxSettings::default()));
+static VERSION: AtomicU64 = AtomicU64::new(1);
+
+fn clamped(name: &str, value: Option<f32>, min: f32, max: f32) -> Result<Option<f32>, String> {
+    match value {
+        Some(value) if !value.is_finite() => Err(format!("Invalid {name}: {value}")),
+        Some(value) => Ok(Some(value.clamp(min, max))),
+        None => Ok(None),
+    }
+}
+
+pub fn settings() -> InputFxSettings {
+    SETTINGS
+        .lock()
+        .map(|settings| *settings)
+        .unwrap_or_default()
+}
+
+/// Applies `patch` and returns the resulting settings. Values are clamped to
+/// gain ±24 dB, high-pass 20–500 Hz, gate threshold −90–0 dBFS, limiter
+/// ceiling −24–0 dBFS and monitor gain −60–+12 dB.
+pub fn update(patch: InputFxPatch) -> Result<InputFxSettings, String> {
+    let gain_db = clamped("gain_db", patch.gain_db, -24.0, 24.0)?;
+    let high_pass_hz = clamped("high_pass_hz", patch.high_pass_hz, 20.0, 500.0)?;
+    let gate_threshold_db = clamped("gate_threshold_db", patch.gate_threshold_db, -90.0, 0.0)?;
+    let limiter_ceiling_db = clamped("limiter_ceiling_db", patch.limiter_ceiling_db, -24.0, 0.0)?;
+    let monitor_gain_db = clamped("monitor_gain_db", patch.monitor_gain_db, -60.0, 12.0)?;
+
+    let mut settings = SETTINGS.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let next = InputFxSettings {
+        gain_db: gain_db.unwrap_or(settings.gain_db),
+        high_pass_enabled: patch
+            .high_pass_enabled
+            .unwrap_or(settings.high_pass_enabled),
+        high_pass_hz: high_pass_hz.unwrap_or(settings.high_pass_hz),
+        gate_enabled: patch.gate_enabled.unwrap_or(settings.gate_enabled),
+        gate_threshold_db: gate_threshold_db.unwrap_or(settings.gate_threshold_db),
+        limiter_enabled: patch.limiter_enabled.unwrap_or(settings.limiter_enabled),
+        limiter_ceiling_db: limiter_ceiling_db.unwrap_or(settings.limiter_ceiling_db),
+        monitor_enabled: patch.monitor_enabled.unwrap_or(settings.monitor_enabled),
+        monitor_gain_db: monitor_gain_db.unwrap_or(settings.monitor_gain_db),
+    };
+    if next != *settings {
+        *settings = next;
+        VERSION.fetch_add(1, Ordering::Release);
+    }
+    Ok(next)
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn one_pole(seconds: f32, sample_rate: f32) -> f32 {
+    1.0 - (-1.0 / (seconds * sample_rate).max(1.0)).exp()
+}
+
+/// Second-order Butterworth high-pass (RBJ cookbook), transposed direct form II.
+#[derive(Clone, Copy, Debug)]
+struct HighPass {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl HighPass {
+    fn new(cutoff_hz: f32, sample_rate: f32) -> Self {
+        let cutoff = cutoff_hz.clamp(10.0, sample_rate * 0.45);
+        let w0 = std::f32::consts::TAU * cutoff / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / std::f32::consts::SQRT_2;
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+
+    fn process(&self, input: f32, state: &mut [f32; 2]) -> f32 {
+        let output = self.b0 * input + state[0];
+        state[0] = self.b1 * input - self.a1 * output + state[1];
+        state[1] = self.b2 * input - self.a2 * output;
+        output
+    }
+}
+
+/// Per-session processing state, owned by the input callback.
+pub(super) struct InputProcessor {
+    sample_rate: f32,
+    channels: usize,
+    version: u64,
+    settings: InputFxSettings,
+    gain: f32,
+    high_pass: HighPass,
+    high_pass_state: Vec<[f32; 2]>,
+    gate_threshold: f32,
+    gate_gain: f32,
+    gate_hold: u32,
+    limiter_ceiling: f32,
+    limiter_gain: f32,
+    monitor_gain: f32,
+}
+
+impl InputProcessor {
+    pub(super) fn new(sample_rate: u32, channels: u16) -> Self {
+        let sample_rate = sample_rate.max(1) as f32;
+        let channels = usize::from(channels.max(1));
+        let settings = InputFxSettings::default();
+        let mut processor = Self {
+            sample_rate,
+            channels,
+            version: 0,
+            settings,
+            gain: 1.0,
+            high_pass: HighPass::new(settings.high_pass_hz, sample_rate),
+            high_pass_state: vec![[0.0; 2]; channels],
+            gate_threshold: 0.0,
+            gate_gain: 1.0,
+            gate_hold: 0,
+            limiter_ceiling: 1.0,
+            limiter_gain: 1.0,
+            monitor_gain: 1.0,
+        };
+        processor.refresh();
+        processor
+    }
+
+    fn refresh(&mut self) {
+        let version = VERSION.load(Ordering::Acquire);
+        if version == self.version {
+            return;
+        }
+        // Never wait on the control thread from the audio callback; a busy
+        // lock just means the new values land on the next block.
+        let Ok(settings) = SETTINGS.try_lock() else {
+            return;
+        };
+        self.apply(*settings);
+        self.version = version;
+    }
+
+    fn apply(&mut self, settings: InputFxSettings) {
+        if settings.high_pass_hz != self.settings.high_pass_hz {
+            self.high_pass = HighPass::new(settings.high_pass_hz, self.sample_rate);
+        }
+        if settings.high_pass_enabled && !self.settings.high_pass_enabled {
+            self.high_pass_state.fill([0.0; 2]);
+        }
+        self.settings = settings;
+        self.gain = db_to_linear(settings.gain_db);
+        self.gate_threshold = db_to_linear(settings.gate_threshold_db);
+        self.limiter_ceiling = db_to_linear(settings.limiter_ceiling_db);
+        self.monitor_gain = db_to_linear(settings.monitor_gain_db);
+    }
+
+    /// Monitor output gain, or `None` while monitoring is off.
+    pub(super) fn monitor_gain(&self) -> Option<f32> {
+        self.settings.monitor_enabled.then_some(self.monitor_gain)
+    }
+
+    /// Processes interleaved samples in place.
+    pub(super) fn process(&mut self, samples: &mut [f32]) {
+        self.refresh();
+        let settings = self.settings;
+        let gate_attack = one_pole(GATE_ATTACK_SEC, self.sample_rate);
+        let gate_release = one_pole(GATE_RELEASE_SEC, self.sample_rate);
+        let limiter_release = one_pole(LIMITER_RELEASE_SEC, self.sample_rate);
+        let gate_hold_frames = (GATE_HOLD_SEC * self.sample_rate) as u32;
+
+        for frame in samples.chunks_exact_mut(self.channels) {
+            let mut peak = 0.0f32;
+            for (sample, state) in frame.iter_mut().zip(&mut self.high_pass_state) {
+                let mut value = *sample * self.gain;
+                if settings.high_pass_enabled {
+                    value = self.high_pass.process(value, state);
+                }
+                *sample = value;
+                peak = peak.max(value.abs());
+            }
+
+            let mut frame_gain = 1.0;
+            if settings.gate_enabled {
+                let open = if peak >= self.gate_threshold {
+                    self.gate_hold = gate_hold_frames;
+                    true
+                } else if self.gate_hold > 0 {
+                    self.gate_hold -= 1;
+                    true
+                } else {
+                    false
+                };
+                let (target, coefficient) = if open {
+                    (1.0, gate_attack)
+                } else {
+                    (0.0, gate_release)
+                };
+                self.gate_gain += (target - self.gate_gain) * coefficient;
+                frame_gain *= self.gate_gain;
+            }
+            if settings.limiter_enabled {
+                // No lookahead: gain drops instantly to the ceiling and
+                // recovers smoothly, so the output never exceeds it.
+                self.limiter_gain += (1.0 - self.limiter_gain) * limiter_release;
+                let level = peak * frame_gain;
+                if level * self.limiter_gain > self.limiter_ceiling {
+                    self.limiter_gain = self.limiter_ceiling / level;
+                }
+                frame_gain *= self.limiter_gain;
+            }
+            if frame_gain != 1.0 {
+                for sample in frame.iter_mut() {
+                    *sample *= frame_gain;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HighPass, InputFxSettings, InputProcessor};
+
+    fn processor(settings: InputFxSettings) -> InputProcessor {
+        let mut processor = InputProcessor::new(48_000, 1);
+        processor.apply(settings);
+        processor
+    }
+
+    #[test]
+    fn high_pass_removes_dc_and_keeps_speech_band() {
+        let filter = HighPass::new(80.0, 48_000.0);
+        let mut state = [0.0; 2];
+        let mut last = 0.0;
+        for _ in 0..48_000 {
+            last = filter.process(0.5, &mut state);
+        }
+        assert!(last.abs() < 1e-4, "dc leaked: {last}");
+
+        let mut state = [0.0; 2];
+        let mut peak = 0.0f32;
+        for index in 0..48_000 {
+            let input = (std::f32::consts::TAU * 1_000.0 * index as f32 / 48_000.0).sin();
+            let output = filter.process(input, &mut state);
+            if index > 4_800 {
+                peak = peak.max(output.abs());
+            }
+        }
+        assert!((peak - 1.0).abs() < 0.01, "1 kHz peak {peak}");
+    }
+
+    #[test]
+    fn gain_gate_and_limiter_shape_the_block() {
+        let mut fx = processor(InputFxSettings {
+            gain_db: 12.0,
+            gate_enabled: true,
+            gate_threshold_db: -40.0,
+            limiter_enabled: true,
+            limiter_ceiling_db: -6.0,
+            ..InputFxSettings::default()
+        });
+
+        // Hiss at -60 dBFS (-48 after gain) stays under the gate.
+        let mut hiss: Vec<f32> = (0..48_000)
+            .map(|index| if index % 2 == 0 { 0.001 } else { -0.001 })
+            .collect();
+        fx.process(&mut hiss);
+        assert!(hiss[43_200..].iter().all(|sample| sample.abs() < 1e-6));
+
+        // A loud tone opens the gate and is held at the ceiling.
+        let ceiling = 10f32.powf(-6.0 / 20.0);
+        let mut tone: Vec<f32> = (0..9_600)
+            .map(|index| 0.9 * (std::f32::consts::TAU * 440.0 * index as f32 / 48_000.0).sin())
+            .collect();
+        fx.process(&mut tone);
+        let peak = tone
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        assert!(peak <= ceiling + 1e-6, "peak {peak} over ceiling {ceiling}");
+        assert!(peak > ceiling * 0.9, "tone was not passed: {peak}");
+
+        // Unity settings leave samples untouched.
+        let mut clean = processor(InputFxSettings::default());
+        let mut block = vec![0.25, -0.5, 0.75];
+        clean.process(&mut block);
+        assert_eq!(block, vec![0.25, -0.5, 0.75]);
+    }
+}
@@ -0,0 +1,531 @@
+// EBU R128 / ITU-R BS.1770-4 loudness measurement
+// K-weighted energy is accumulated in 100 ms sub-blocks; momentary (400 ms),
+// short-term (3 s), integrated and loudness-range values are derived from
+// those sub-blocks after the whole file has streamed through.
+//
+// True peak uses 4x polyphase oversampling (12 taps per phase), as in
+// BS.1770-4 Annex 2. Sources are measured as mono or stereo; wider layouts
+// arrive already folded to stereo by the symphonia decoder.
+
+use super::playback_stream::SymphoniaStreamDecoder;
+use kira::Frame;
+use serde_json::{json, Value};
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// Default ceiling applied when normalizing, in dBTP.
+pub const DEFAULT_TRUE_PEAK_CEILING_DBTP: f64 = -1.0;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const INTEGRATED_RELATIVE_GATE_LU: f64 = -10.0;
+const RANGE_RELATIVE_GATE_LU: f64 = -20.0;
+const SUB_BLOCKS_PER_SECOND: f64 = 10.0;
+const MOMENTARY_SUB_BLOCKS: usize = 4;
+const SHORT_TERM_SUB_BLOCKS: usize = 30;
+/// Short-term curve resolution reported to the UI (one point per second).
+const CURVE_STRIDE_SUB_BLOCKS: usize = 10;
+const OVERSAMPLE: usize = 4;
+const TAPS_PER_PHASE: usize = 12;
+
+fn energy_to_lufs(energy: f64) -> Option<f64> {
+    (energy > 0.0).then(|| -0.691 + 10.0 * energy.log10())
+}
+
+fn lufs_to_energy(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+fn linear_to_db(value: f64) -> Option<f64> {
+    (value > 0.0).then(|| 20.0 * value.log10())
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b0 * input + self.z1;
+        self.z1 = self.b1 * input - self.a1 * output + self.z2;
+        self.z2 = self.b2 * input - self.a2 * output;
+        output
+    }
+}
+
+/// BS.1770 pre-filter (high shelf) and RLB high-pass, re-derived for any
+/// sample rate rather than using the 48 kHz table.
+fn k_weighting_filters(sample_rate: u32) -> [Biquad; 2] {
+    let fs = sample_rate.max(1) as f64;
+
+    let f0 = 1681.974_450_955_533;
+    let gain_db = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+    let k = (PI * f0 / fs).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        ..Biquad::default()
+    };
+
+    let f0 = 38.135_470_876_024_44;
+    let q = 0.500_327_037_323_877_3;
+    let k = (PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b0: 1.0,
+        b1: -2.0,
+        b2: 1.0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        ..Biquad::default()
+    };
+
+    [shelf, high_pass]
+}
+
+/// Hann-windowed sinc, split into `OVERSAMPLE` phases each normalized to
+/// unity DC gain.
+fn true_peak_phases() -> [[f64; TAPS_PER_PHASE]; OVERSAMPLE] {
+    let taps = OVERSAMPLE * TAPS_PER_PHASE;
+    let center = (taps - 1) as f64 / 2.0;
+    let mut phases = [[0.0; TAPS_PER_PHASE]; OVERSAMPLE];
+    for (phase, coefficients) in phases.iter_mut().enumerate() {
+        for (tap, coefficient) in coefficients.iter_mut().enumerate() {
+            let n = (phase + tap * OVERSAMPLE) as f64;
+            let x = (n - center) / OVERSAMPLE as f64;
+            let sinc = if x.abs() < 1e-12 {
+                1.0
+            } else {
+                (PI * x).sin() / (PI * x)
+            };
+            let window = 0.5 - 0.5 * (2.0 * PI * (n + 0.5) / taps as f64).cos();
+            *coefficient = sinc * window;
+        }
+        let sum: f64 = coefficients.iter().sum();
+        if sum.abs() > 1e-12 {
+            coefficients.iter_mut().for_each(|c| *c /= sum);
+        }
+    }
+    phases
+}
+
+struct ChannelState {
+    filters: [Biquad; 2],
+    history: [f64; TAPS_PER_PHASE],
+    history_pos: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LoudnessReport {
+    pub integrated_lufs: Option<f64>,
+    pub loudness_range_lu: Option<f64>,
+    pub true_peak_dbtp: Option<f64>,
+    pub sample_peak_dbfs: Option<f64>,
+    pub max_momentary_lufs: Option<f64>,
+    pub max_short_term_lufs: Option<f64>,
+    /// (time in seconds at the end of the 3 s window, short-term loudness).
+    pub short_term: Vec<(f64, Option<f64>)>,
+}
+
+impl LoudnessReport {
+    pub fn to_json(&self) -> Value {
+        let short_term: Vec<Value> = self
+            .short_term
+            .iter()
+            .map(|(time_sec, lufs)| json!({ "time_sec": time_sec, "lufs": lufs }))
+            .collect();
+        json!({
+            "integrated_lufs": self.integrated_lufs,
+            "loudness_range_lu": self.loudness_range_lu,
+            "true_peak_dbtp": self.true_peak_dbtp,
+            "sample_peak_dbfs": self.sample_peak_dbfs,
+            "max_momentary_lufs": self.max_momentary_lufs,
+            "max_short_term_lufs": self.max_short_term_lufs,
+            "short_term": short_term
+        })
+    }
+}
+
+pub struct LoudnessMeter {
+    channels: Vec<ChannelState>,
+    phases: [[f64; TAPS_PER_PHASE]; OVERSAMPLE],
+    sub_block_frames: usize,
+    sub_block_fill: usize,
+    sub_block_energy: f64,
+    sub_blocks: Vec<f64>,
+    sample_peak: f64,
+    true_peak: f64,
+}
+
+impl LoudnessMeter {
+    /// `channels` is the source channel count; mono sources only meter the
+    /// left side of the (duplicated) stereo frames.
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let channel_count = channels.clamp(1, 2);
+        Self {
+            channels: (0..channel_count)
+                .map(|_| ChannelState {
+                    filters: k_weighting_filters(sample_rate),
+                    history: [0.0; TAPS_PER_PHASE],
+                    history_pos: 0,
+                })
+                .collect(),
+            phases: true_peak_phases(),
+            sub_block_frames: ((sample_rate.max(1) as f64 / SUB_BLOCKS_PER_SECOND).round()
+                as usize)
+                .max(1),
+            sub_block_fill: 0,
+            sub_block_energy: 0.0,
+            sub_blocks: Vec::new(),
+            sample_peak: 0.0,
+            true_peak: 0.0,
+        }
+    }
+
+    pub fn push_frames(&mut self, frames: &[Frame]) {
+        for frame in frames {
+            let samples = [frame.left as f64, frame.right as f64];
+            for (channel, sample) in self.channels.iter_mut().zip(samples) {
+                let weighted = channel.filters[1].process(channel.filters[0].process(sample));
+                self.sub_block_energy += weighted * weighted;
+
+                self.sample_peak = self.sample_peak.max(sample.abs());
+                channel.history[channel.history_pos] = sample;
+                for phase in &self.phases {
+                    let mut interpolated = 0.0;
+                    for (tap, coefficient) in phase.iter().enumerate() {
+                        let index = (channel.history_pos + TAPS_PER_PHASE - tap) % TAPS_PER_PHASE;
+                        interpolated += coefficient * channel.history[index];
+                    }
+                    self.true_peak = self.true_peak.max(interpolated.abs());
+                }
+                channel.history_pos = (channel.history_pos + 1) % TAPS_PER_PHASE;
+            }
+            self.sub_block_fill += 1;
+            if self.sub_block_fill == self.sub_block_frames {
+                self.sub_blocks
+                    .push(self.sub_block_energy / self.sub_block_frames as f64);
+                self.sub_block_fill = 0;
+                self.sub_block_energy = 0.0;
+            }
+        }
+    }
+
+    fn windowed_energies(&self, sub_blocks_per_window: usize) -> Vec<f64> {
+        if self.sub_blocks.len() < sub_blocks_per_window {
+            return Vec::new();
+        }
+        self.sub_blocks
+            .windows(sub_blocks_per_window)
+            .map(|window| window.iter().sum::<f64>() / sub_blocks_per_window as f64)
+            .collect()
+    }
+
+    fn gated_mean(energies: &[f64], relative_gate_lu: f64) -> Option<(f64, Vec<f64>)> {
+        let absolute_gate = lufs_to_energy(ABSOLUTE_GATE_LUFS);
+        let above_absolute: Vec<f64> = energies
+            .iter()
+            .copied()
+            .filter(|energy| *energy > absolute_gate)
+            .collect();
+        if above_absolute.is_empty() {
+            return None;
+        }
+        let mean = above_absolute.iter().sum::<f64>() / above_absolute.len() as f64;
+        let relative_gate = lufs_to_energy(energy_to_lufs(mean)? + relative_gate_lu);
+        let gated: Vec<f64> = above_absolute
+            .into_iter()
+            .filter(|energy| *energy > relative_gate)
+            .collect();
+        if gated.is_empty() {
+            return None;
+        }
+        let gated_mean = gated.iter().sum::<f64>() / gated.len() as f64;
+        Some((gated_mean, gated))
+    }
+
+    fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+        let position = fraction * (sorted.len() - 1) as f64;
+        let lower = position.floor() as usize;
+        let upper = position.ceil() as usize;
+        let weight = position - lower as f64;
+        sorted[lower] * (1.0 - weight) + sorted[upper] * weight
+    }
+
+    pub fn finish(&self) -> LoudnessReport {
+        let momentary = self.windowed_energies(MOMENTARY_SUB_BLOCKS);
+        let short_term = self.windowed_energies(SHORT_TERM_SUB_BLOCKS);
+
+        let integrated_lufs = Self::gated_mean(&momentary, INTEGRATED_RELATIVE_GATE_LU)
+            .and_then(|(energy, _)| energy_to_lufs(energy));
+
+        let loudness_range_lu =
+            Self::gated_mean(&short_term, RANGE_RELATIVE_GATE_LU).and_then(|(_, gated)| {
+                let mut loudness: Vec<f64> = gated.into_iter().filter_map(energy_to_lufs).collect();
+                if loudness.len() < 2 {
+                    return None;
+                }
+                loudness.sort_by(|a, b| a.total_cmp(b));
+                Some(Self::percentile(&loudness, 0.95) - Self::percentile(&loudness, 0.10))
+            });
+
+        let max_of = |energies: &[f64]| {
+            energies
+                .iter()
+                .copied()
+                .fold(None, |max: Option<f64>, energy| {
+                    Some(max.map_or(energy, |current| current.max(energy)))
+                })
+                .and_then(energy_to_lufs)
+        };
+
+        let curve = short_term
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| index % CURVE_STRIDE_SUB_BLOCKS == 0)
+            .map(|(index, energy)| {
+                let end_sec = (index + SHORT_TERM_SUB_BLOCKS) as f64 / SUB_BLOCKS_PER_SECOND;
+                (end_sec, energy_to_lufs(*energy))
+            })
+            .collect();
+
+        LoudnessReport {
+            integrated_lufs,
+            loudness_range_lu,
+            true_peak_dbtp: linear_to_db(self.true_peak.max(self.sample_peak)),
+            sample_peak_dbfs: linear_to_db(self.sample_peak),
+            max_momentary_lufs: max_of(&momentary),
+            max_short_term_lufs: max_of(&short_term),
+            short_term: curve,
+        }
+    }
+}
+
+/// Measures any symphonia-decodable file in a single streaming pass.
+pub fn measure_file(path: &Path) -> Result<LoudnessReport, String> {
+    let mut decoder = SymphoniaStreamDecoder::open_path(path)?;
+    let mut meter = LoudnessMeter::new(decoder.sample_rate(), decoder.channels());
+    while let Some(frames) = decoder.next_frames()? {
+        meter.push_frames(&frames);
+    }
+    Ok(meter.finish())
+}
+
+/// Gain in dB that reaches `target_lufs` without pushing the true peak above
+/// `ceiling_dbtp`; the second value reports whether the ceiling limited it.
+pub fn normalization_gain_db(
+    report: &LoudnessReport,
+    target_lufs: f64,
+    ceiling_dbtp: f64,
+) -> Result<(f64, bool), String> {
+    let integrated = report
+        .integrated_lufs
+        .ok_or("Cannot normalize silent audio".to_string())?;
+    let wanted = target_lufs - integrated;
+    match report.true_peak_dbtp {
+        Some(true_peak) if true_peak + wanted > ceiling_dbtp => {
+            Ok((ceiling_dbtp - true_peak, true))
+        }
+        _ => Ok((wanted, false)),
+    }
+}
+
+/// Writes a 32-bit float WAV copy of `source` normalized to `target_lufs`.
+/// The source is left untouched; the result is re-measured and returned.
+pub fn normalize_file(
+    source: &Path,
+    output: &Path,
+    target_lufs: f64,
+    ceiling_dbtp: f64,
+) -> Result<Value, String> {
+    if !target_lufs.is_finite() || !ceiling_dbtp.is_finite() {
+        return Err("Normalization target must be finite".to_string());
+    }
+    if source == output {
+        return Err("Normalization output must differ from the source".to_string());
+    }
+    let before = measure_file(source)?;
+    let (gain_db, limited) = normalization_gain_db(&before, target_lufs, ceiling_dbtp)?;
+    let gain = 10f64.powf(gain_db / 20.0) as f32;
+
+    let mut decoder = SymphoniaStreamDecoder::open_path(source)?;
+    let channels = decoder.channels().clamp(1, 2) as u16;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| {
+            format!(
+                "Unable to create normalization output directory {}: {error}",
+                parent.display()
+            )
+        })?;
+    }
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate: decoder.sample_rate(),
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(output, spec)
+        .map_err(|error| format!("Unable to create normalized WAV: {error}"))?;
+    while let Some(frames) = decoder.next_frames()? {
+        for frame in frames {
+            writer
+                .write_sample(frame.left * gain)
+                .map_err(|error| format!("Unable to write normalized sample: {error}"))?;
+            if channels == 2 {
+                writer
+                    .write_sample(frame.right * gain)
+                    .map_err(|error| format!("Unable to write normalized sample: {error}"))?;
+            }
+        }
+    }
+    writer
+        .finalize()
+        .map_err(|error| format!("Unable to finalize normalized WAV: {error}"))?;
+
+    let after = measure_file(output)?;
+    Ok(json!({
+        "output_path": output.to_string_lossy(),
+        "target_lufs": target_lufs,
+        "true_peak_ceiling_dbtp": ceiling_dbtp,
+        "gain_db": gain_db,
+        "limited_by_true_peak": limited,
+        "before": before.to_json(),
+        "after": after.to_json()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalization_gain_db, normalize_file, LoudnessMeter, LoudnessReport};
+    use kira::Frame;
+
+    fn sine_frames(sample_rate: u32, freq: f64, amplitude: f64, seconds: f64) -> Vec<Frame> {
+        let total = (sample_rate as f64 * seconds) as usize;
+        (0..total)
+            .map(|index| {
+                let phase = index as f64 * freq * std::f64::consts::TAU / sample_rate as f64;
+                Frame::from_mono((phase.sin() * amplitude) as f32)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stereo_sine_at_minus_23_dbfs_reads_minus_23_lufs() {
+        let mut meter = LoudnessMeter::new(48_000, 2);
+        meter.push_frames(&sine_frames(48_000, 1_000.0, 10f64.powf(-23.0 / 20.0), 5.0));
+        let integrated = meter.finish().integrated_lufs.unwrap();
+        assert!((integrated + 23.0).abs() < 0.1, "integrated={integrated}");
+    }
+
+    #[test]
+    fn mono_full_scale_sine_reads_minus_3_lufs() {
+        let mut meter = LoudnessMeter::new(44_100, 1);
+        meter.push_frames(&sine_frames(44_100, 1_000.0, 1.0, 3.0));
+        let integrated = meter.finish().integrated_lufs.unwrap();
+        assert!((integrated + 3.01).abs() < 0.1, "integrated={integrated}");
+    }
+
+    #[test]
+    fn alternating_levels_give_ten_lu_range() {
+        let mut meter = LoudnessMeter::new(48_000, 2);
+        meter.push_frames(&sine_frames(
+            48_000,
+            1_000.0,
+            10f64.powf(-20.0 / 20.0),
+            20.0,
+        ));
+        meter.push_frames(&sine_frames(
+            48_000,
+            1_000.0,
+            10f64.powf(-30.0 / 20.0),
+            20.0,
+        ));
+        let report = meter.finish();
+        let range = report.loudness_range_lu.unwrap();
+        assert!((range - 10.0).abs() < 1.0, "range={range}");
+        assert_eq!(report.short_term.first().unwrap().0, 3.0);
+    }
+
+    #[test]
+    fn true_peak_recovers_inter_sample_overs() {
+        let mut meter = LoudnessMeter::new(48_000, 1);
+        let frames: Vec<Frame> = (0..4_800)
+            .map(|index| {
+                let phase =
+                    index as f64 * std::f64::consts::FRAC_PI_2 + std::f64::consts::FRAC_PI_4;
+                Frame::from_mono((phase.sin() * 0.5) as f32)
+            })
+            .collect();
+        meter.push_frames(&frames);
+        let report = meter.finish();
+        let sample_peak = report.sample_peak_dbfs.unwrap();
+        let true_peak = report.true_peak_dbtp.unwrap();
+        assert!(
+            (sample_peak + 9.03).abs() < 0.1,
+            "sample_peak={sample_peak}"
+        );
+        assert!((true_peak + 6.02).abs() < 0.5, "true_peak={true_peak}");
+    }
+
+    #[test]
+    fn silence_has_no_integrated_loudness() {
+        let mut meter = LoudnessMeter::new(48_000, 2);
+        meter.push_frames(&vec![Frame::ZERO; 48_000]);
+        assert_eq!(meter.finish().integrated_lufs, None);
+    }
+
+    #[test]
+    fn normalization_gain_respects_true_peak_ceiling() {
+        let report = LoudnessReport {
+            integrated_lufs: Some(-24.0),
+            true_peak_dbtp: Some(-3.0),
+            ..LoudnessReport::default()
+        };
+        assert_eq!(
+            normalization_gain_db(&report, -16.0, -1.0).unwrap(),
+            (2.0, true)
+        );
+        assert_eq!(
+            normalization_gain_db(&report, -23.0, -1.0).unwrap(),
+            (1.0, false)
+        );
+    }
+
+    #[test]
+    fn normalize_file_writes_a_new_file_near_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("speech.wav");
+        let output = dir.path().join("speech.normalized.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&source, spec).unwrap();
+        for frame in sine_frames(48_000, 1_000.0, 0.05, 4.0) {
+            let sample = (frame.left * i16::MAX as f32) as i16;
+            writer.write_sample(sample).unwrap();
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let result = normalize_file(&source, &output, -16.0, -1.0).unwrap();
+        let after = result["after"]["integrated_lufs"].as_f64().unwrap();
+        assert!((after + 16.0).abs() < 0.2, "after={after}");
+        assert!(source.exists());
+        assert_eq!(result["limited_by_true_peak"], false);
+    }
+}
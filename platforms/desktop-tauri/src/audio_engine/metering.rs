@@ -5,10 +5,15 @@
 // - Exponential smoothing for RMS (avoids jumpy meters)
 // - Peak hold with configurable decay (professional VU-style behavior)
 // - Clip detection counter
+//
+// Shared with the web engine (platforms/web/audio-wasm), which includes this
+// file next to `fft.rs`, `metering_scope.rs` and `metering_spectrum.rs`.
 
 use std::sync::atomic::{AtomicU64, Ordering};
 
+use super::fft::SpectrumWindow;
 use super::metering_scope;
+use super::metering_spectrum::{self, SpectrumWriter};
 
 // Store levels as u64 bits (f64 reinterpreted) for lock-free atomic access
 static RMS_BITS: AtomicU64 = AtomicU64::new(0);
@@ -58,8 +63,16 @@ where
     let mut clips: u64 = 0;
     let mut scope_minimums = [0.0f32; 64];
     let mut scope_maximums = [0.0f32; 64];
+    let spectrum_channels = metering_spectrum::channels() as usize;
+    let mut spectrum = SpectrumWriter::begin();
+    let mut spectrum_frame_sum = 0.0f32;
     for index in 0..sample_count {
         let s = sample_at(index);
+        spectrum_frame_sum += s;
+        if (index + 1) % spectrum_channels == 0 {
+            spectrum.push(spectrum_frame_sum / spectrum_channels as f32);
+            spectrum_frame_sum = 0.0;
+        }
         let abs = s.abs();
         let scope_index = (index.saturating_mul(64) / sample_count).min(63);
         scope_minimums[scope_index] = scope_minimums[scope_index].min(s);
@@ -89,6 +102,7 @@ where
         CLIP_COUNT.fetch_add(clips, Ordering::Relaxed);
     }
     metering_scope::publish(&scope_minimums, &scope_maximums);
+    spectrum.commit();
 }
 
 #[derive(serde::Serialize, Clone, Debug)]
@@ -134,25 +148,39 @@ pub fn reset() {
     SMOOTHED_RMS_BITS.store(0, Ordering::Relaxed);
     CLIP_COUNT.store(0, Ordering::Relaxed);
     metering_scope::reset();
+    metering_spectrum::reset();
 }
 
 pub fn configure_scope(sample_rate: u32, channels: u16) {
     metering_scope::configure(sample_rate, channels);
+    metering_spectrum::configure_stream(sample_rate, channels);
 }
 
 pub fn get_scope() -> super::metering_scope::ScopeFrame {
     metering_scope::snapshot()
 }
 
+/// Applies any provided spectrum settings, then reads the current spectrum.
+pub fn get_spectrum(
+    fft_size: Option<usize>,
+    window: Option<&str>,
+    smoothing: Option<f32>,
+) -> Result<metering_spectrum::SpectrumFrame, String> {
+    let window = window.map(SpectrumWindow::from_name).transpose()?;
+    metering_spectrum::configure(fft_size, window, smoothing)?;
+    Ok(metering_spectrum::snapshot())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{get_levels, push_i16_samples, push_samples, push_u16_samples, reset};
+    use super::{
+        configure_scope, get_levels, get_spectrum, push_i16_samples, push_samples,
+        push_u16_samples, reset,
+    };
 
     #[test]
     fn metering_accepts_i16_samples_without_float_buffer() {
-        let _lock = crate::audio_engine::metering_scope::METERING_TEST_LOCK
-            .lock()
-            .unwrap();
+        let _lock = super::metering_scope::METERING_TEST_LOCK.lock().unwrap();
         reset();
         push_i16_samples(&[0, 16_384, -16_384, 32_767]);
         let levels = get_levels();
@@ -162,9 +190,7 @@ mod tests {
 
     #[test]
     fn metering_accepts_u16_samples_without_float_buffer() {
-        let _lock = crate::audio_engine::metering_scope::METERING_TEST_LOCK
-            .lock()
-            .unwrap();
+        let _lock = super::metering_scope::METERING_TEST_LOCK.lock().unwrap();
         reset();
         push_u16_samples(&[32_768, 49_152, 16_384, 65_535]);
         let levels = get_levels();
@@ -174,13 +200,36 @@ mod tests {
 
     #[test]
     fn metering_keeps_f32_path() {
-        let _lock = crate::audio_engine::metering_scope::METERING_TEST_LOCK
-            .lock()
-            .unwrap();
+        let _lock = super::metering_scope::METERING_TEST_LOCK.lock().unwrap();
         reset();
         push_samples(&[0.0, 0.25, -0.5, 1.0]);
         let levels = get_levels();
         assert!(levels.rms > 0.0);
         assert!(levels.peak >= 1.0);
     }
+
+    #[test]
+    fn spectrum_tap_finds_a_tone_pushed_through_the_meter() {
+        let _lock = super::metering_scope::METERING_TEST_LOCK.lock().unwrap();
+        reset();
+        assert!(!get_spectrum(None, None, None).unwrap().available);
+        configure_scope(48_000, 2);
+        let interleaved: Vec<f32> = (0..4096)
+            .flat_map(|frame| {
+                let sample = (std::f32::consts::TAU * 1_000.0 * frame as f32 / 48_000.0).sin();
+                [sample * 0.5, sample * 0.5]
+            })
+            .collect();
+        push_samples(&interleaved);
+
+        let spectrum = get_spectrum(Some(2048), Some("blackman"), Some(0.0)).unwrap();
+        assert!(spectrum.available);
+        assert_eq!(spectrum.window, "blackman");
+        assert_eq!(spectrum.magnitudes_db.len(), 1025);
+        let peak_hz = spectrum.peak_hz.unwrap();
+        assert!((peak_hz - 1_000.0).abs() < 12.0, "peak_hz={peak_hz}");
+        assert!(get_spectrum(Some(1000), None, None).is_err());
+        assert!(get_spectrum(None, Some("kaiser"), None).is_err());
+        get_spectrum(Some(2048), Some("hann"), Some(0.8)).unwrap();
+    }
 }
@@ -0,0 +1,242 @@
+// Real-time spectrum tap for the metering path
+// The input callback only appends mono samples to an atomic ring; the FFT,
+// smoothing and dB conversion run on the reader side when a UI polls.
+//
+// Shared with the web engine (platforms/web/audio-wasm), which includes this
+// file next to `fft.rs`, so it must only depend on `super::fft` and std.
+
+use super::fft::{FftPlan, SpectrumWindow, MAX_FFT_SIZE};
+use std::sync::atomic::{AtomicU16, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const RING_CAPACITY: usize = MAX_FFT_SIZE * 2;
+const RING_MASK: usize = RING_CAPACITY - 1;
+const DB_FLOOR: f32 = -120.0;
+pub const DEFAULT_FFT_SIZE: usize = 2048;
+/// Same meaning as Web Audio's `smoothingTimeConstant`.
+pub const DEFAULT_SMOOTHING: f32 = 0.8;
+
+static RING: [AtomicU32; RING_CAPACITY] = [const { AtomicU32::new(0) }; RING_CAPACITY];
+static WRITE_POSITION: AtomicUsize = AtomicUsize::new(0);
+static SAMPLE_RATE: AtomicU32 = AtomicU32::new(0);
+static CHANNELS: AtomicU16 = AtomicU16::new(0);
+static FFT_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_FFT_SIZE);
+static WINDOW: AtomicU8 = AtomicU8::new(1);
+static SMOOTHING_BITS: AtomicU32 = AtomicU32::new(DEFAULT_SMOOTHING.to_bits());
+
+/// Reader-side state; never touched by the audio callback.
+struct SpectrumAnalyzer {
+    plan: FftPlan,
+    window: SpectrumWindow,
+    samples: Vec<f32>,
+    magnitudes: Vec<f32>,
+    smoothed: Vec<f32>,
+}
+
+static ANALYZER: Mutex<Option<SpectrumAnalyzer>> = Mutex::new(None);
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SpectrumFrame {
+    pub available: bool,
+    pub sequence: u64,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub fft_size: usize,
+    pub window: &'static str,
+    pub smoothing: f32,
+    pub bin_hz: f32,
+    pub magnitudes_db: Vec<f32>,
+    pub peak_hz: Option<f32>,
+    pub peak_db: f32,
+}
+
+fn window_from_index(index: u8) -> SpectrumWindow {
+    match index {
+        0 => SpectrumWindow::Rectangular,
+        2 => SpectrumWindow::Hamming,
+        3 => SpectrumWindow::Blackman,
+        _ => SpectrumWindow::Hann,
+    }
+}
+
+fn window_index(window: SpectrumWindow) -> u8 {
+    match window {
+        SpectrumWindow::Rectangular => 0,
+        SpectrumWindow::Hann => 1,
+        SpectrumWindow::Hamming => 2,
+        SpectrumWindow::Blackman => 3,
+    }
+}
+
+/// Appends mono samples for one callback block; publishes them with a single
+/// release store in `commit` so readers never see a half-written block.
+pub struct SpectrumWriter {
+    start: usize,
+    written: usize,
+}
+
+impl SpectrumWriter {
+    pub fn begin() -> Self {
+        Self {
+            start: WRITE_POSITION.load(Ordering::Relaxed),
+            written: 0,
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, sample: f32) {
+        RING[(self.start + self.written) & RING_MASK].store(sample.to_bits(), Ordering::Relaxed);
+        self.written += 1;
+    }
+
+    pub fn commit(self) {
+        if self.written > 0 {
+            WRITE_POSITION.fetch_add(self.written, Ordering::Release);
+        }
+    }
+}
+
+pub fn channels() -> u16 {
+    CHANNELS.load(Ordering::Relaxed).max(1)
+}
+
+pub fn configure_stream(sample_rate: u32, channels: u16) {
+    SAMPLE_RATE.store(sample_rate, Ordering::Release);
+    CHANNELS.store(channels, Ordering::Release);
+}
+
+/// Updates any of size, window and smoothing; omitted values are kept.
+pub fn configure(
+    fft_size: Option<usize>,
+    window: Option<SpectrumWindow>,
+    smoothing: Option<f32>,
+) -> Result<(), String> {
+    if let Some(size) = fft_size {
+        FftPlan::new(size, SpectrumWindow::Rectangular)?;
+    }
+    if let Some(value) = smoothing {
+        if !(0.0..1.0).contains(&value) {
+            return Err(format!("Spectrum smoothing must be in [0, 1), got {value}"));
+        }
+        SMOOTHING_BITS.store(value.to_bits(), Ordering::Relaxed);
+    }
+    if let Some(size) = fft_size {
+        FFT_SIZE.store(size, Ordering::Relaxed);
+    }
+    if let Some(window) = window {
+        WINDOW.store(window_index(window), Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+pub fn reset() {
+    WRITE_POSITION.store(0, Ordering::Release);
+    SAMPLE_RATE.store(0, Ordering::Release);
+    CHANNELS.store(0, Ordering::Release);
+    if let Ok(mut analyzer) = ANALYZER.lock() {
+        *analyzer = None;
+    }
+}
+
+/// Copies the newest `size` samples; retries when the writer lapped the copy.
+fn copy_latest(target: &mut [f32]) -> Option<usize> {
+    for _ in 0..4 {
+        let end = WRITE_POSITION.load(Ordering::Acquire);
+        if end == 0 {
+            return None;
+        }
+        let count = target.len().min(end);
+        let padding = target.len() - count;
+        target[..padding].fill(0.0);
+        for (offset, slot) in target[padding..].iter_mut().enumerate() {
+            let position = end - count + offset;
+            *slot = f32::from_bits(RING[position & RING_MASK].load(Ordering::Relaxed));
+        }
+        let after = WRITE_POSITION.load(Ordering::Acquire);
+        if after.wrapping_sub(end) <= RING_CAPACITY - target.len() {
+            return Some(end);
+        }
+    }
+    None
+}
+
+pub fn snapshot() -> SpectrumFrame {
+    let sample_rate = SAMPLE_RATE.load(Ordering::Acquire);
+    let fft_size = FFT_SIZE.load(Ordering::Relaxed);
+    let window = window_from_index(WINDOW.load(Ordering::Relaxed));
+    let smoothing = f32::from_bits(SMOOTHING_BITS.load(Ordering::Relaxed));
+    let bin_hz = sample_rate as f32 / fft_size as f32;
+    let mut frame = SpectrumFrame {
+        available: false,
+        sequence: WRITE_POSITION.load(Ordering::Acquire) as u64,
+        sample_rate,
+        channels: CHANNELS.load(Ordering::Acquire),
+        fft_size,
+        window: window.as_str(),
+        smoothing,
+        bin_hz,
+        magnitudes_db: Vec::new(),
+        peak_hz: None,
+        peak_db: DB_FLOOR,
+    };
+
+    let Ok(mut guard) = ANALYZER.lock() else {
+        return frame;
+    };
+    let stale = guard
+        .as_ref()
+        .is_none_or(|analyzer| analyzer.plan.size() != fft_size || analyzer.window != window);
+    if stale {
+        let Ok(plan) = FftPlan::new(fft_size, window) else {
+            return frame;
+        };
+        let bins = plan.bin_count();
+        *guard = Some(SpectrumAnalyzer {
+            plan,
+            window,
+            samples: vec![0.0; fft_size],
+            magnitudes: vec![0.0; bins],
+            smoothed: vec![0.0; bins],
+        });
+    }
+    let Some(analyzer) = guard.as_mut() else {
+        return frame;
+    };
+    let Some(end) = copy_latest(&mut analyzer.samples) else {
+        return frame;
+    };
+
+    analyzer
+        .plan
+        .magnitudes(&analyzer.samples, &mut analyzer.magnitudes);
+    for (smoothed, magnitude) in analyzer.smoothed.iter_mut().zip(&analyzer.magnitudes) {
+        *smoothed = smoothing * *smoothed + (1.0 - smoothing) * magnitude;
+    }
+    frame.magnitudes_db = analyzer
+        .smoothed
+        .iter()
+        .map(|magnitude| (20.0 * magnitude.max(1e-9).log10()).max(DB_FLOOR))
+        .collect();
+
+    // Skip DC so a tuner does not lock onto offset; refine with a parabola.
+    let peak_bin = (1..frame.magnitudes_db.len())
+        .max_by(|a, b| frame.magnitudes_db[*a].total_cmp(&frame.magnitudes_db[*b]));
+    if let Some(bin) = peak_bin.filter(|bin| frame.magnitudes_db[*bin] > DB_FLOOR) {
+        let db = &frame.magnitudes_db;
+        let shift = if bin + 1 < db.len() {
+            let denominator = db[bin - 1] - 2.0 * db[bin] + db[bin + 1];
+            if denominator.abs() > f32::EPSILON {
+                (0.5 * (db[bin - 1] - db[bin + 1]) / denominator).clamp(-0.5, 0.5)
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        };
+        frame.peak_hz = Some((bin as f32 + shift) * bin_hz);
+        frame.peak_db = db[bin];
+    }
+    frame.available = true;
+    frame.sequence = end as u64;
+    frame
+}
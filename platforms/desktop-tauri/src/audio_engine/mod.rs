@@ -2,12 +2,30 @@
 // Provides playback (Kira), recording (CPAL), and metering.
 
 pub mod bridge;
+pub mod diarize;
+mod fft;
+pub mod input_fx;
+pub mod loudness;
 pub mod metering;
 mod metering_scope;
+mod metering_spectrum;
+pub mod peaks;
 pub mod playback;
+mod playback_opus;
+mod playback_stream;
 pub mod recorder;
+mod recorder_flac;
+mod recorder_journal;
+mod recorder_markers;
+mod recorder_monitor;
+mod recorder_opus;
+mod recorder_voice;
 mod recorder_wav;
+pub mod spectral;
 pub mod transcode;
+pub mod vad;
+
+pub use playback_stream::SymphoniaStreamDecoder;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,403 @@
+// Multi-resolution waveform peak pyramids
+// Decodes any symphonia-readable media once and stores min/max envelopes at
+// doubling resolutions, so timeline zooming only reads the level it needs.
+//
+// File layout (little endian), designed for HTTP range reads:
+//   0   magic "APKS"
+//   4   u32 version
+//   8   u32 sample_rate
+//   12  u32 source channel count
+//   16  u64 frame_count
+//   24  u32 level_count
+//   28  level table, 24 bytes per level:
+//       u32 frames_per_peak, u32 reserved, u64 peak_count, u64 byte_offset
+//   ..  peak data, per level: peak_count × (i16 min, i16 max)
+
+use super::playback_stream::SymphoniaStreamDecoder;
+use super::transcode;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const PEAK_FILE_MAGIC: [u8; 4] = *b"APKS";
+pub const PEAK_FILE_VERSION: u32 = 1;
+/// Finest level resolution in source frames per min/max pair.
+pub const BASE_FRAMES_PER_PEAK: u32 = 256;
+/// Coarser levels stop once a level fits in this many pairs.
+const MAX_TOP_LEVEL_PEAKS: usize = 512;
+const HEADER_BYTES: usize = 28;
+const LEVEL_ENTRY_BYTES: usize = 24;
+const PEAK_PAIR_BYTES: usize = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeakLevel {
+    pub frames_per_peak: u32,
+    /// (min, max) pairs across all channels, scaled to i16 full scale.
+    pub peaks: Vec<[i16; 2]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeakPyramid {
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub frame_count: u64,
+    pub levels: Vec<PeakLevel>,
+}
+
+fn quantize(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+fn downsample_level(level: &PeakLevel) -> PeakLevel {
+    PeakLevel {
+        frames_per_peak: level.frames_per_peak.saturating_mul(2),
+        peaks: level
+            .peaks
+            .chunks(2)
+            .map(|pair| {
+                let min = pair.iter().map(|peak| peak[0]).min().unwrap_or(0);
+                let max = pair.iter().map(|peak| peak[1]).max().unwrap_or(0);
+                [min, max]
+            })
+            .collect(),
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|slice| u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]]))
+        .ok_or_else(|| format!("Peak file truncated at byte {offset}"))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, String> {
+    bytes
+        .get(offset..offset + 8)
+        .map(|slice| {
+            let mut raw = [0u8; 8];
+            raw.copy_from_slice(slice);
+            u64::from_le_bytes(raw)
+        })
+        .ok_or_else(|| format!("Peak file truncated at byte {offset}"))
+}
+
+impl PeakPyramid {
+    /// Builds coarser levels from `base`, halving each time until a level fits
+    /// in `MAX_TOP_LEVEL_PEAKS` pairs.
+    pub fn from_base_level(
+        sample_rate: u32,
+        channels: u32,
+        frame_count: u64,
+        base: PeakLevel,
+    ) -> Self {
+        let mut levels = vec![base];
+        while let Some(last) = levels.last() {
+            if last.peaks.len() <= MAX_TOP_LEVEL_PEAKS {
+                break;
+            }
+            let next = downsample_level(last);
+            levels.push(next);
+        }
+        Self {
+            sample_rate,
+            channels,
+            frame_count,
+            levels,
+        }
+    }
+
+    /// Streams the file through symphonia; memory use is bounded by the base
+    /// level, never by the decoded PCM.
+    pub fn generate(path: &Path) -> Result<Self, String> {
+        let mut decoder = SymphoniaStreamDecoder::open_path(path)?;
+        let frames_per_peak = BASE_FRAMES_PER_PEAK as usize;
+        let mut peaks = Vec::with_capacity(decoder.frame_count() / frames_per_peak + 1);
+        let mut frame_count = 0u64;
+        let mut bucket_frames = 0usize;
+        let mut bucket_min = f32::MAX;
+        let mut bucket_max = f32::MIN;
+
+        while let Some(frames) = decoder.next_frames()? {
+            frame_count += frames.len() as u64;
+            for frame in frames {
+                bucket_min = bucket_min.min(frame.left).min(frame.right);
+                bucket_max = bucket_max.max(frame.left).max(frame.right);
+                bucket_frames += 1;
+                if bucket_frames == frames_per_peak {
+                    peaks.push([quantize(bucket_min), quantize(bucket_max)]);
+                    bucket_frames = 0;
+                    bucket_min = f32::MAX;
+                    bucket_max = f32::MIN;
+                }
+            }
+        }
+        if bucket_frames > 0 {
+            peaks.push([quantize(bucket_min), quantize(bucket_max)]);
+        }
+        if frame_count == 0 {
+            return Err(format!("No audio frames decoded from {}", path.display()));
+        }
+
+        Ok(Self::from_base_level(
+            decoder.sample_rate(),
+            decoder.channels() as u32,
+            frame_count,
+            PeakLevel {
+                frames_per_peak: BASE_FRAMES_PER_PEAK,
+                peaks,
+            },
+        ))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let table_bytes = self.levels.len() * LEVEL_ENTRY_BYTES;
+        let data_bytes: usize = self
+            .levels
+            .iter()
+            .map(|level| level.peaks.len() * PEAK_PAIR_BYTES)
+            .sum();
+        let mut bytes = Vec::with_capacity(HEADER_BYTES + table_bytes + data_bytes);
+        bytes.extend_from_slice(&PEAK_FILE_MAGIC);
+        bytes.extend_from_slice(&PEAK_FILE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&self.channels.to_le_bytes());
+        bytes.extend_from_slice(&self.frame_count.to_le_bytes());
+        bytes.extend_from_slice(&(self.levels.len() as u32).to_le_bytes());
+
+        let mut byte_offset = (HEADER_BYTES + table_bytes) as u64;
+        for level in &self.levels {
+            bytes.extend_from_slice(&level.frames_per_peak.to_le_bytes());
+            bytes.extend_from_slice(&0u32.to_le_bytes());
+            bytes.extend_from_slice(&(level.peaks.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&byte_offset.to_le_bytes());
+            byte_offset += (level.peaks.len() * PEAK_PAIR_BYTES) as u64;
+        }
+        for level in &self.levels {
+            for [min, max] in &level.peaks {
+                bytes.extend_from_slice(&min.to_le_bytes());
+                bytes.extend_from_slice(&max.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.get(..4) != Some(&PEAK_FILE_MAGIC[..]) {
+            return Err("Not a peak file".to_string());
+        }
+        let version = read_u32(bytes, 4)?;
+        if version != PEAK_FILE_VERSION {
+            return Err(format!("Unsupported peak file version {version}"));
+        }
+        let sample_rate = read_u32(bytes, 8)?;
+        let channels = read_u32(bytes, 12)?;
+        let frame_count = read_u64(bytes, 16)?;
+        let level_count = read_u32(bytes, 24)? as usize;
+
+        let mut levels = Vec::with_capacity(level_count.min(64));
+        for index in 0..level_count {
+            let entry = HEADER_BYTES + index * LEVEL_ENTRY_BYTES;
+            let frames_per_peak = read_u32(bytes, entry)?;
+            let peak_count = read_u64(bytes, entry + 8)? as usize;
+            let byte_offset = read_u64(bytes, entry + 16)? as usize;
+            let data = peak_count
+                .checked_mul(PEAK_PAIR_BYTES)
+                .and_then(|len| byte_offset.checked_add(len))
+                .and_then(|end| bytes.get(byte_offset..end))
+                .ok_or_else(|| format!("Peak level {index} exceeds file bounds"))?;
+            let peaks = data
+                .chunks_exact(PEAK_PAIR_BYTES)
+                .map(|pair| {
+                    [
+                        i16::from_le_bytes([pair[0], pair[1]]),
+                        i16::from_le_bytes([pair[2], pair[3]]),
+                    ]
+                })
+                .collect();
+            levels.push(PeakLevel {
+                frames_per_peak,
+                peaks,
+            });
+        }
+
+        Ok(Self {
+            sample_rate,
+            channels,
+            frame_count,
+            levels,
+        })
+    }
+
+    /// Header and level table as JSON, so clients can issue range reads for a
+    /// single level without parsing the binary header themselves.
+    pub fn summary_json(&self) -> Value {
+        let table_bytes = self.levels.len() * LEVEL_ENTRY_BYTES;
+        let mut byte_offset = HEADER_BYTES + table_bytes;
+        let levels: Vec<Value> = self
+            .levels
+            .iter()
+            .map(|level| {
+                let byte_length = level.peaks.len() * PEAK_PAIR_BYTES;
+                let entry = json!({
+                    "frames_per_peak": level.frames_per_peak,
+                    "peak_count": level.peaks.len(),
+                    "byte_offset": byte_offset,
+                    "byte_length": byte_length
+                });
+                byte_offset += byte_length;
+                entry
+            })
+            .collect();
+        json!({
+            "version": PEAK_FILE_VERSION,
+            "sample_rate": self.sample_rate,
+            "channels": self.channels,
+            "frame_count": self.frame_count,
+            "duration_sec": self.frame_count as f64 / self.sample_rate.max(1) as f64,
+            "levels": levels
+        })
+    }
+}
+
+/// `<media dir>/.audio_cache/<file name>.peaks`; the full file name keeps
+/// `take.wav` and `take.mp3` from sharing a cache entry.
+pub fn peak_cache_path(source_path: &Path) -> Result<PathBuf, String> {
+    let parent = source_path.parent().ok_or_else(|| {
+        format!(
+            "Missing parent directory for peak source {}",
+            source_path.display()
+        )
+    })?;
+    let file_name = source_path
+        .file_name()
+        .and_then(|value| value.to_str())
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| format!("Missing file name for {}", source_path.display()))?;
+    Ok(parent
+        .join(".audio_cache")
+        .join(format!("{file_name}.peaks")))
+}
+
+/// Returns the cached peak file for `source_path`, regenerating it when the
+/// media is newer than the cache. Video containers go through the same
+/// extracted-audio cache as native playback.
+pub fn ensure_peak_file(source_path: &Path) -> Result<PathBuf, String> {
+    let cache_path = peak_cache_path(source_path)?;
+    if !transcode::should_refresh_native_audio_cache(source_path, &cache_path) {
+        return Ok(cache_path);
+    }
+    let decode_path = transcode::prepare_native_audio_decode_path(source_path)?;
+    let pyramid = PeakPyramid::generate(&decode_path)?;
+    let parent = cache_path
+        .parent()
+        .ok_or_else(|| format!("Invalid peak cache path {}", cache_path.display()))?;
+    fs::create_dir_all(parent)
+        .map_err(|error| format!("Unable to create peak cache {}: {error}", parent.display()))?;
+    // A uniquely named sibling keeps concurrent writers from clobbering each
+    // other's partial output before the atomic rename.
+    let mut temp_file = tempfile::NamedTempFile::new_in(parent)
+        .map_err(|error| format!("Unable to create peak cache {}: {error}", parent.display()))?;
+    temp_file.write_all(&pyramid.encode()).map_err(|error| {
+        format!(
+            "Unable to write peak cache {}: {error}",
+            temp_file.path().display()
+        )
+    })?;
+    temp_file.persist(&cache_path).map_err(|error| {
+        format!(
+            "Unable to finalize peak cache {}: {error}",
+            cache_path.display()
+        )
+    })?;
+    Ok(cache_path)
+}
+
+pub fn read_peak_summary(peak_path: &Path) -> Result<Value, String> {
+    let bytes = fs::read(peak_path)
+        .map_err(|error| format!("Unable to read peak file {}: {error}", peak_path.display()))?;
+    Ok(PeakPyramid::decode(&bytes)?.summary_json())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ensure_peak_file, PeakLevel, PeakPyramid, BASE_FRAMES_PER_PEAK};
+    use std::fs;
+
+    fn write_ramp_wav(path: &std::path::Path, frames: usize) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for index in 0..frames {
+            let value = if index % 2 == 0 { 16_000 } else { -8_000 };
+            writer.write_sample(value as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn pyramid_halves_until_top_level_is_small() {
+        let base = PeakLevel {
+            frames_per_peak: BASE_FRAMES_PER_PEAK,
+            peaks: (0..2_000)
+                .map(|index| [-(index as i16), index as i16])
+                .collect(),
+        };
+        let pyramid = PeakPyramid::from_base_level(48_000, 2, 512_000, base);
+        let counts: Vec<usize> = pyramid.levels.iter().map(|l| l.peaks.len()).collect();
+        assert_eq!(counts, vec![2_000, 1_000, 500]);
+        assert_eq!(pyramid.levels[2].frames_per_peak, BASE_FRAMES_PER_PEAK * 4);
+        assert_eq!(pyramid.levels[1].peaks[0], [-1, 1]);
+    }
+
+    #[test]
+    fn encoded_pyramid_round_trips() {
+        let base = PeakLevel {
+            frames_per_peak: BASE_FRAMES_PER_PEAK,
+            peaks: vec![[-3, 7]; 1_100],
+        };
+        let pyramid = PeakPyramid::from_base_level(44_100, 1, 281_600, base);
+        let decoded = PeakPyramid::decode(&pyramid.encode()).unwrap();
+        assert_eq!(decoded, pyramid);
+        let summary = pyramid.summary_json();
+        assert_eq!(summary["levels"][0]["byte_offset"], 28 + 24 * 3);
+    }
+
+    #[test]
+    fn rejects_truncated_peak_files() {
+        let base = PeakLevel {
+            frames_per_peak: BASE_FRAMES_PER_PEAK,
+            peaks: vec![[0, 1]; 10],
+        };
+        let bytes = PeakPyramid::from_base_level(8_000, 1, 2_560, base).encode();
+        assert!(PeakPyramid::decode(&bytes[..bytes.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn generates_and_reuses_cached_peaks_next_to_media() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("take.wav");
+        write_ramp_wav(&source, 1_000);
+
+        let peak_path = ensure_peak_file(&source).unwrap();
+        assert_eq!(peak_path, dir.path().join(".audio_cache/take.wav.peaks"));
+        let pyramid = PeakPyramid::decode(&fs::read(&peak_path).unwrap()).unwrap();
+        assert_eq!(pyramid.frame_count, 1_000);
+        assert_eq!(pyramid.channels, 1);
+        assert_eq!(pyramid.levels[0].peaks.len(), 4);
+        assert!(pyramid.levels[0].peaks[0][1] > 15_000);
+        assert!(pyramid.levels[0].peaks[0][0] < -7_000);
+
+        let cached_at = fs::metadata(&peak_path).unwrap().modified().unwrap();
+        ensure_peak_file(&source).unwrap();
+        assert_eq!(
+            fs::metadata(&peak_path).unwrap().modified().unwrap(),
+            cached_at
+        );
+    }
+}
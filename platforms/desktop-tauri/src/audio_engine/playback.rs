@@ -5,14 +5,18 @@
 // - Per-clip RwLock replaces global Mutex (concurrent reads, write-only on mutation)
 // - Arc<StaticSoundData> avoids full data clone on every play()
 // - Configurable tween durations (stop, volume, rate) instead of hardcoded values
-// - Zero-copy load_clip_from_bytes via Cursor over owned Vec (no extra .to_vec())
+// - load_clip_from_bytes shares one Arc<[u8]> between probing, decoding and streaming
+// - Long clips stream through a symphonia decoder instead of full StaticSoundData
 
+use super::playback_opus;
+use super::playback_stream::{self, StreamSource, StreamingClip};
 use kira::backend::cpal::CpalBackend;
 use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
+use kira::sound::streaming::{StreamingSoundData, StreamingSoundHandle};
 use kira::{AudioManager, AudioManagerSettings, Decibels, Frame, Panning, PlaybackRate, Tween};
 use std::collections::HashMap;
 use std::fs;
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use symphonia::core::audio::{AudioBufferRef, SampleBuffer};
@@ -27,6 +31,14 @@ use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 
+macro_rules! eprintln {
+    ($($arg:tt)*) => {
+        if crate::runtime_logging::xcode_logs_enabled() {
+            std::eprintln!($($arg)*);
+        }
+    };
+}
+
 /// Configurable tween durations for audio parameter changes.
 /// Allows the caller to tune fade times for professional-grade audio.
 pub struct TweenConfig {
@@ -45,9 +57,27 @@ impl Default for TweenConfig {
     }
 }
 
-struct ClipEntry {
+enum ClipEntry {
     /// Shared reference — play() can clone the Arc (cheap) instead of the full data.
-    data: Arc<StaticSoundData>,
+    Static(Arc<StaticSoundData>),
+    /// Re-openable source; each voice gets its own streaming decoder.
+    Streaming(StreamingClip),
+}
+
+impl ClipEntry {
+    fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Static(data) => data.sample_rate,
+            Self::Streaming(clip) => clip.sample_rate,
+        }
+    }
+
+    fn duration_seconds(&self) -> Result<f64, String> {
+        match self {
+            Self::Static(data) => sound_duration_seconds(data),
+            Self::Streaming(clip) => Ok(clip.duration_seconds()),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -55,6 +85,7 @@ pub struct ClipMetadata {
     pub sample_rate: u32,
     pub frame_count: usize,
     pub duration_seconds: f64,
+    pub streaming: bool,
 }
 
 impl ClipMetadata {
@@ -70,12 +101,63 @@ impl ClipMetadata {
             sample_rate: data.sample_rate,
             frame_count,
             duration_seconds: frame_count as f64 / data.sample_rate as f64,
+            streaming: false,
+        })
+    }
+
+    fn from_stream(clip: &StreamingClip) -> Result<Self, String> {
+        if clip.sample_rate == 0 {
+            return Err("Clip sample rate is zero".to_string());
+        }
+        if clip.frame_count == 0 {
+            return Err("Clip has no decodable frames".to_string());
+        }
+        Ok(Self {
+            sample_rate: clip.sample_rate,
+            frame_count: clip.frame_count,
+            duration_seconds: clip.duration_seconds(),
+            streaming: true,
         })
     }
 }
 
+enum VoiceHandle {
+    Static(StaticSoundHandle),
+    Streaming(StreamingSoundHandle<String>),
+}
+
+impl VoiceHandle {
+    fn stop(&mut self, tween: Tween) {
+        match self {
+            Self::Static(handle) => handle.stop(tween),
+            Self::Streaming(handle) => handle.stop(tween),
+        }
+    }
+
+    fn set_volume(&mut self, volume: Decibels, tween: Tween) {
+        match self {
+            Self::Static(handle) => handle.set_volume(volume, tween),
+            Self::Streaming(handle) => handle.set_volume(volume, tween),
+        }
+    }
+
+    fn set_panning(&mut self, panning: Panning, tween: Tween) {
+        match self {
+            Self::Static(handle) => handle.set_panning(panning, tween),
+            Self::Streaming(handle) => handle.set_panning(panning, tween),
+        }
+    }
+
+    fn set_playback_rate(&mut self, rate: PlaybackRate, tween: Tween) {
+        match self {
+            Self::Static(handle) => handle.set_playback_rate(rate, tween),
+            Self::Streaming(handle) => handle.set_playback_rate(rate, tween),
+        }
+    }
+}
+
 struct VoiceEntry {
-    handle: StaticSoundHandle,
+    handle: VoiceHandle,
 }
 
 pub struct PlaybackEngine {
@@ -106,6 +188,7 @@ enum NativeContainer {
     Mp3,
     Wav,
     Ogg,
+    OggOpus,
     Flac,
     Unknown,
 }
@@ -114,6 +197,8 @@ enum NativeContainer {
 enum NativeDecodeRoute {
     Kira,
     SymphoniaIsoBmff,
+    /// Kira's bundled symphonia has no Opus codec; decode through ours.
+    SymphoniaOggOpus,
 }
 
 fn is_isobmff_extension(extension_hint: Option<&str>) -> bool {
@@ -123,7 +208,7 @@ fn is_isobmff_extension(extension_hint: Option<&str>) -> bool {
 fn is_kira_audio_extension(extension_hint: Option<&str>) -> bool {
     matches!(
         extension_hint,
-        Some("wav" | "wave" | "mp3" | "ogg" | "oga" | "flac" | "aac")
+        Some("wav" | "wave" | "mp3" | "ogg" | "oga" | "opus" | "flac" | "aac")
     )
 }
 
@@ -231,6 +316,9 @@ fn sniff_native_container(bytes: &[u8]) -> NativeContainer {
     if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
         return NativeContainer::Wav;
     }
+    if bytes.len() >= 36 && &bytes[..4] == b"OggS" && &bytes[28..36] == b"OpusHead" {
+        return NativeContainer::OggOpus;
+    }
     if bytes.len() >= 4 && &bytes[..4] == b"OggS" {
         return NativeContainer::Ogg;
     }
@@ -264,6 +352,9 @@ fn resolve_native_decode_route(
     if container == NativeContainer::IsoBmff || is_isobmff_extension(extension_hint) {
         return Ok(NativeDecodeRoute::SymphoniaIsoBmff);
     }
+    if container == NativeContainer::OggOpus {
+        return Ok(NativeDecodeRoute::SymphoniaOggOpus);
+    }
     if matches!(
         container,
         NativeContainer::Mp3 | NativeContainer::Wav | NativeContainer::Ogg | NativeContainer::Flac
@@ -324,6 +415,30 @@ mod decode_route_tests {
         assert!(error.contains("extension=mp3"));
     }
 
+    fn ogg_first_page(codec_header: &[u8]) -> Vec<u8> {
+        let mut bytes = b"OggS\x00\x02".to_vec();
+        bytes.extend_from_slice(&[0; 20]);
+        bytes.extend_from_slice(&[1, codec_header.len() as u8]);
+        bytes.extend_from_slice(codec_header);
+        bytes
+    }
+
+    #[test]
+    fn routes_ogg_opus_to_symphonia_and_vorbis_to_kira() {
+        let opus = ogg_first_page(b"OpusHead\x01\x01\x38\x01\x80\xbb\x00\x00\x00\x00\x00");
+        assert_eq!(sniff_native_container(&opus), NativeContainer::OggOpus);
+        assert_eq!(
+            resolve_native_decode_route(Some("opus"), &opus, "clip").unwrap(),
+            NativeDecodeRoute::SymphoniaOggOpus
+        );
+        let vorbis = ogg_first_page(b"\x01vorbis\x00\x00\x00\x00\x02");
+        assert_eq!(sniff_native_container(&vorbis), NativeContainer::Ogg);
+        assert_eq!(
+            resolve_native_decode_route(Some("ogg"), &vorbis, "clip").unwrap(),
+            NativeDecodeRoute::Kira
+        );
+    }
+
     #[test]
     fn routes_wav_bytes_to_kira() {
         let bytes = b"RIFF\x24\x00\x00\x00WAVEfmt ";
@@ -335,7 +450,7 @@ mod decode_route_tests {
     }
 }
 
-fn is_likely_audio_codec(codec: CodecType) -> bool {
+pub(super) fn is_likely_audio_codec(codec: CodecType) -> bool {
     matches!(
         codec,
         CODEC_TYPE_AAC
@@ -350,7 +465,7 @@ fn is_likely_audio_codec(codec: CodecType) -> bool {
     )
 }
 
-fn append_audio_buffer_frames(
+pub(super) fn append_audio_buffer_frames(
     audio_buffer: AudioBufferRef<'_>,
     frames: &mut Vec<Frame>,
 ) -> Result<u32, String> {
@@ -379,8 +494,8 @@ fn append_audio_buffer_frames(
     Ok(spec.rate)
 }
 
-fn decode_clip_with_symphonia(
-    bytes: Vec<u8>,
+pub(super) fn decode_clip_with_symphonia<B: AsRef<[u8]> + Send + Sync + 'static>(
+    bytes: B,
     extension_hint: Option<&str>,
 ) -> Result<StaticSoundData, String> {
     let mut hint = Hint::new();
@@ -409,7 +524,7 @@ fn decode_clip_with_symphonia(
         })
         .ok_or("No decodable audio track found".to_string())?;
     let track_id = track.id;
-    let mut decoder = symphonia::default::get_codecs()
+    let mut decoder = playback_opus::codec_registry()
         .make(&track.codec_params, &DecoderOptions::default())
         .map_err(|error| format!("Failed to create audio decoder: {error}"))?;
 
@@ -464,12 +579,12 @@ fn decode_clip_with_symphonia(
     })
 }
 
-fn decode_static_sound_data(
-    bytes: Vec<u8>,
+pub(super) fn decode_static_sound_data<B: AsRef<[u8]> + Send + Sync + 'static>(
+    bytes: B,
     extension_hint: Option<&str>,
     source_label: &str,
 ) -> Result<StaticSoundData, String> {
-    match resolve_native_decode_route(extension_hint, &bytes, source_label)? {
+    match resolve_native_decode_route(extension_hint, bytes.as_ref(), source_label)? {
         NativeDecodeRoute::SymphoniaIsoBmff => {
             let symphonia_hint = if is_isobmff_extension(extension_hint) {
                 extension_hint
@@ -479,6 +594,8 @@ fn decode_static_sound_data(
             decode_clip_with_symphonia(bytes, symphonia_hint)
                 .map_err(|error| format!("Failed to decode audio from {source_label}: {error}"))
         }
+        NativeDecodeRoute::SymphoniaOggOpus => decode_clip_with_symphonia(bytes, Some("ogg"))
+            .map_err(|error| format!("Failed to decode audio from {source_label}: {error}")),
         NativeDecodeRoute::Kira => {
             let cursor = Cursor::new(bytes);
             StaticSoundData::from_cursor(cursor)
@@ -507,35 +624,113 @@ pub fn init_with_config(tween_config: TweenConfig) -> Result<(), String> {
     Ok(())
 }
 
+/// Symphonia hint for a streamed source, reusing the static decode routing so
+/// both paths accept and reject the same containers.
+fn streaming_decode_hint(
+    extension_hint: Option<&str>,
+    head: &[u8],
+    source_label: &str,
+) -> Result<Option<String>, String> {
+    match resolve_native_decode_route(extension_hint, head, source_label)? {
+        NativeDecodeRoute::SymphoniaIsoBmff if !is_isobmff_extension(extension_hint) => {
+            Ok(Some("mp4".to_string()))
+        }
+        NativeDecodeRoute::SymphoniaOggOpus => Ok(Some("ogg".to_string())),
+        _ => Ok(extension_hint.map(str::to_string)),
+    }
+}
+
+/// Returns a streaming clip when the source is long or large enough and its
+/// length is known from the container header; `None` keeps the static path.
+fn probe_streaming_candidate(
+    source: StreamSource,
+    size_bytes: u64,
+    extension_hint: Option<&str>,
+    head: &[u8],
+    source_label: &str,
+) -> Result<Option<StreamingClip>, String> {
+    let hint = streaming_decode_hint(extension_hint, head, source_label)?;
+    // A failed probe still falls back to the static decode, which reports its
+    // own error if the source is unreadable.
+    let clip = match playback_stream::probe_streaming_clip(source, hint.as_deref()) {
+        Ok(Some(clip)) => clip,
+        Ok(None) => return Ok(None),
+        Err(error) => {
+            eprintln!(
+                "[audio_engine::playback] Streaming probe failed for {source_label}: {error}"
+            );
+            return Ok(None);
+        }
+    };
+    if playback_stream::should_stream(size_bytes, clip.duration_seconds()) {
+        Ok(Some(clip))
+    } else {
+        Ok(None)
+    }
+}
+
+fn read_head(path: &str) -> Result<Vec<u8>, String> {
+    let file =
+        fs::File::open(path).map_err(|e| format!("Failed to read audio file {path}: {e}"))?;
+    let mut head = Vec::with_capacity(4096);
+    file.take(4096)
+        .read_to_end(&mut head)
+        .map_err(|e| format!("Failed to read audio file {path}: {e}"))?;
+    Ok(head)
+}
+
+fn insert_clip(id: &str, entry: ClipEntry) -> Result<(), String> {
+    let mut guard = ENGINE.write().map_err(lock_err)?;
+    let engine = guard.as_mut().ok_or("Audio engine not initialized")?;
+    engine.clips.insert(id.to_string(), entry);
+    Ok(())
+}
+
 pub fn load_clip(id: &str, path: &str) -> Result<ClipMetadata, String> {
+    let extension_hint = clip_extension_hint(path);
+    let size_bytes = fs::metadata(path)
+        .map_err(|e| format!("Failed to read audio file {path}: {e}"))?
+        .len();
+    let head = read_head(path)?;
+    if let Some(clip) = probe_streaming_candidate(
+        StreamSource::File(path.into()),
+        size_bytes,
+        extension_hint.as_deref(),
+        &head,
+        path,
+    )? {
+        let metadata = ClipMetadata::from_stream(&clip)?;
+        insert_clip(id, ClipEntry::Streaming(clip))?;
+        return Ok(metadata);
+    }
+
     // Decode from owned bytes even for file-backed assets so MP4-family media
     // can be extracted deterministically into PCM before Kira playback.
     let bytes = fs::read(path).map_err(|e| format!("Failed to read audio file {path}: {e}"))?;
-    let extension_hint = clip_extension_hint(path);
     let data = decode_static_sound_data(bytes, extension_hint.as_deref(), path)?;
     let metadata = ClipMetadata::from_data(&data)?;
-    let mut guard = ENGINE.write().map_err(lock_err)?;
-    let engine = guard.as_mut().ok_or("Audio engine not initialized")?;
-    engine.clips.insert(
-        id.to_string(),
-        ClipEntry {
-            data: Arc::new(data),
-        },
-    );
+    insert_clip(id, ClipEntry::Static(Arc::new(data)))?;
     Ok(metadata)
 }
 
 pub fn load_clip_from_bytes(id: &str, bytes: Vec<u8>) -> Result<ClipMetadata, String> {
+    let bytes: Arc<[u8]> = bytes.into();
+    let head = &bytes[..bytes.len().min(4096)];
+    if let Some(clip) = probe_streaming_candidate(
+        StreamSource::Bytes(bytes.clone()),
+        bytes.len() as u64,
+        None,
+        head,
+        "audio bytes",
+    )? {
+        let metadata = ClipMetadata::from_stream(&clip)?;
+        insert_clip(id, ClipEntry::Streaming(clip))?;
+        return Ok(metadata);
+    }
+
     let data = decode_static_sound_data(bytes, None, "audio bytes")?;
     let metadata = ClipMetadata::from_data(&data)?;
-    let mut guard = ENGINE.write().map_err(lock_err)?;
-    let engine = guard.as_mut().ok_or("Audio engine not initialized")?;
-    engine.clips.insert(
-        id.to_string(),
-        ClipEntry {
-            data: Arc::new(data),
-        },
-    );
+    insert_clip(id, ClipEntry::Static(Arc::new(data)))?;
     Ok(metadata)
 }
 
@@ -609,44 +804,26 @@ fn normalize_playback_duration(
     }
 }
 
-pub fn play_instance(
-    asset_id: &str,
-    voice_id: &str,
+/// Normalized region shared by static and streaming voices so both clip types
+/// seek, slice and loop identically.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PlaybackRegion {
+    start: f64,
+    duration: Option<f64>,
+    /// Loop bounds, already relative to the slice when `duration` is set.
+    loop_region: Option<(f64, f64)>,
+}
+
+fn resolve_playback_region(
+    sample_rate: u32,
+    source_duration: f64,
     start_seconds: f64,
     duration_seconds: Option<f64>,
-    gain: f64,
-    pan: f64,
-    rate: f64,
     loop_start_seconds: Option<f64>,
     loop_end_seconds: Option<f64>,
-) -> Result<(), String> {
-    let mut guard = ENGINE.write().map_err(lock_err)?;
-    let engine = guard.as_mut().ok_or("Audio engine not initialized")?;
-    let sound_data_template = engine
-        .clips
-        .get(asset_id)
-        .map(|clip| (*clip.data).clone())
-        .ok_or(format!("Clip '{asset_id}' not found"))?;
-
-    if let Some(mut existing_voice) = engine.voices.remove(voice_id) {
-        existing_voice.handle.stop(Tween::default());
-    }
-
-    let mut sound_data: StaticSoundData = sound_data_template;
-    let source_duration = sound_duration_seconds(&sound_data)?;
-    if source_duration <= 0.0 {
-        return Err(format!("Clip '{asset_id}' has no playable duration"));
-    }
-    if !gain.is_finite() {
-        return Err("gain must be finite".to_string());
-    }
-    if !rate.is_finite() {
-        return Err("rate must be finite".to_string());
-    }
-    let min_region_duration =
-        playback_min_region_duration(sound_data.sample_rate, source_duration)?;
+) -> Result<PlaybackRegion, String> {
+    let min_region_duration = playback_min_region_duration(sample_rate, source_duration)?;
     let start = normalize_playback_start(start_seconds, source_duration, min_region_duration)?;
-    let requested_rate = rate.max(0.0001);
     let max_duration = (source_duration - start).max(0.0);
     let duration =
         normalize_playback_duration(duration_seconds, min_region_duration, max_duration)?;
@@ -657,13 +834,7 @@ pub fn play_instance(
         .filter(|value| value.is_finite() && *value > 0.0)
         .map(|value| value.min(source_duration));
 
-    if let Some(duration) = duration {
-        let slice_end = start + duration;
-        sound_data = sound_data.slice(start..slice_end);
-    } else if start > 0.0 {
-        sound_data = sound_data.start_position(start);
-    }
-
+    let mut loop_region = None;
     if let (Some(loop_start), Some(loop_end)) = (loop_start, loop_end) {
         if loop_end - loop_start >= min_region_duration {
             if duration.is_some() {
@@ -672,23 +843,127 @@ pub fn play_instance(
                     .max(relative_loop_start + min_region_duration)
                     .min(duration.unwrap_or(max_duration));
                 if relative_loop_end - relative_loop_start >= min_region_duration {
-                    sound_data = sound_data.loop_region(relative_loop_start..relative_loop_end);
+                    loop_region = Some((relative_loop_start, relative_loop_end));
                 }
             } else {
-                sound_data = sound_data.loop_region(loop_start..loop_end);
+                loop_region = Some((loop_start, loop_end));
             }
         }
     }
 
-    sound_data = sound_data
-        .volume(gain_to_decibels(gain))
-        .panning(Panning(pan.clamp(-1.0, 1.0) as f32))
-        .playback_rate(PlaybackRate(requested_rate));
+    Ok(PlaybackRegion {
+        start,
+        duration,
+        loop_region,
+    })
+}
+
+/// Static and streaming sound data expose the same builder methods but no
+/// common trait, so the region/mix settings are applied through one macro.
+macro_rules! apply_playback_settings {
+    ($sound_data:expr, $region:expr, $gain:expr, $pan:expr, $rate:expr) => {{
+        let region: PlaybackRegion = $region;
+        let mut sound_data = $sound_data;
+        if let Some(duration) = region.duration {
+            sound_data = sound_data.slice(region.start..region.start + duration);
+        } else if region.start > 0.0 {
+            sound_data = sound_data.start_position(region.start);
+        }
+        if let Some((loop_start, loop_end)) = region.loop_region {
+            sound_data = sound_data.loop_region(loop_start..loop_end);
+        }
+        sound_data
+            .volume(gain_to_decibels($gain))
+            .panning(Panning($pan.clamp(-1.0, 1.0) as f32))
+            .playback_rate(PlaybackRate($rate))
+    }};
+}
+
+pub fn play_instance(
+    asset_id: &str,
+    voice_id: &str,
+    start_seconds: f64,
+    duration_seconds: Option<f64>,
+    gain: f64,
+    pan: f64,
+    rate: f64,
+    loop_start_seconds: Option<f64>,
+    loop_end_seconds: Option<f64>,
+) -> Result<(), String> {
+    // Opening a streaming decoder probes the container, which can block on a
+    // slow disk; do it before taking the engine write lock.
+    let mut stream_decoder = {
+        let guard = ENGINE.read().map_err(lock_err)?;
+        let engine = guard.as_ref().ok_or("Audio engine not initialized")?;
+        match engine.clips.get(asset_id) {
+            Some(ClipEntry::Streaming(stream)) => Some(stream.clone()),
+            _ => None,
+        }
+    }
+    .map(|stream| stream.open_decoder())
+    .transpose()?;
+
+    let mut guard = ENGINE.write().map_err(lock_err)?;
+    let engine = guard.as_mut().ok_or("Audio engine not initialized")?;
+    let clip = engine
+        .clips
+        .get(asset_id)
+        .ok_or(format!("Clip '{asset_id}' not found"))?;
 
-    let handle = engine
-        .manager
-        .play(sound_data)
-        .map_err(|e| format!("Failed to play clip '{asset_id}': {e}"))?;
+    let source_duration = clip.duration_seconds()?;
+    if source_duration <= 0.0 {
+        return Err(format!("Clip '{asset_id}' has no playable duration"));
+    }
+    if !gain.is_finite() {
+        return Err("gain must be finite".to_string());
+    }
+    if !rate.is_finite() {
+        return Err("rate must be finite".to_string());
+    }
+    let region = resolve_playback_region(
+        clip.sample_rate(),
+        source_duration,
+        start_seconds,
+        duration_seconds,
+        loop_start_seconds,
+        loop_end_seconds,
+    )?;
+    let requested_rate = rate.max(0.0001);
+
+    let handle = match clip {
+        ClipEntry::Static(data) => {
+            let sound_data: StaticSoundData =
+                apply_playback_settings!((**data).clone(), region, gain, pan, requested_rate);
+            if let Some(mut existing_voice) = engine.voices.remove(voice_id) {
+                existing_voice.handle.stop(Tween::default());
+            }
+            engine
+                .manager
+                .play(sound_data)
+                .map(VoiceHandle::Static)
+                .map_err(|e| format!("Failed to play clip '{asset_id}': {e}"))?
+        }
+        ClipEntry::Streaming(_) => {
+            let decoder = stream_decoder.take().ok_or(format!(
+                "Clip '{asset_id}' was replaced while opening its stream"
+            ))?;
+            let sound_data: StreamingSoundData<String> = apply_playback_settings!(
+                StreamingSoundData::from_decoder(decoder),
+                region,
+                gain,
+                pan,
+                requested_rate
+            );
+            if let Some(mut existing_voice) = engine.voices.remove(voice_id) {
+                existing_voice.handle.stop(Tween::default());
+            }
+            engine
+                .manager
+                .play(sound_data)
+                .map(VoiceHandle::Streaming)
+                .map_err(|e| format!("Failed to play clip '{asset_id}': {e}"))?
+        }
+    };
     engine
         .voices
         .insert(voice_id.to_string(), VoiceEntry { handle });
@@ -785,6 +1060,7 @@ pub fn shutdown() -> Result<(), String> {
 mod playback_region_tests {
     use super::{
         normalize_playback_duration, normalize_playback_start, playback_min_region_duration,
+        resolve_playback_region,
     };
 
     #[test]
@@ -817,4 +1093,21 @@ mod playback_region_tests {
         let duration = normalize_playback_duration(Some(0.0001), 0.0005, 1.0).unwrap();
         assert_eq!(duration, Some(0.0005));
     }
+
+    #[test]
+    fn region_loop_is_relative_to_sliced_start() {
+        let region =
+            resolve_playback_region(48_000, 10.0, 2.0, Some(4.0), Some(3.0), Some(5.0)).unwrap();
+        assert_eq!(region.start, 2.0);
+        assert_eq!(region.duration, Some(4.0));
+        assert_eq!(region.loop_region, Some((1.0, 3.0)));
+    }
+
+    #[test]
+    fn region_loop_stays_absolute_without_slice() {
+        let region =
+            resolve_playback_region(48_000, 10.0, 2.0, None, Some(3.0), Some(5.0)).unwrap();
+        assert_eq!(region.duration, None);
+        assert_eq!(region.loop_region, Some((3.0, 5.0)));
+    }
 }
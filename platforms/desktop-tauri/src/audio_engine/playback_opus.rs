@@ -0,0 +1,122 @@
+// Opus decoding for symphonia
+// symphonia 0.5 demuxes Ogg/Opus but ships no Opus codec, so libopus is
+// wrapped as a symphonia `Decoder` and registered next to the default codecs.
+// Every symphonia consumer in the engine (static clips, streaming voices,
+// peaks, loudness, spectral analysis) goes through `codec_registry`, so
+// recorder `.opus` takes play back and analyse like any other clip.
+
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use symphonia::core::audio::{
+    AsAudioBufferRef, AudioBuffer, AudioBufferRef, Channels, Signal, SignalSpec,
+};
+use symphonia::core::codecs::{
+    CodecDescriptor, CodecParameters, CodecRegistry, Decoder, DecoderOptions, FinalizeResult,
+    CODEC_TYPE_OPUS,
+};
+use symphonia::core::errors::{decode_error, unsupported_error, Result};
+use symphonia::core::formats::Packet;
+use symphonia::core::support_codec;
+
+const OPUS_RATE: u32 = 48_000;
+/// Longest Opus packet (120 ms) at 48 kHz.
+const MAX_PACKET_FRAMES: usize = 5760;
+
+static CODECS: Lazy<CodecRegistry> = Lazy::new(|| {
+    let mut registry = CodecRegistry::new();
+    symphonia::default::register_enabled_codecs(&mut registry);
+    registry.register_all::<OpusPacketDecoder>();
+    registry
+});
+
+/// Default symphonia codecs plus Opus.
+pub(super) fn codec_registry() -> &'static CodecRegistry {
+    &CODECS
+}
+
+pub(super) struct OpusPacketDecoder {
+    params: CodecParameters,
+    // libopus state is Send but not Sync; symphonia requires both.
+    decoder: Mutex<opus::Decoder>,
+    channels: usize,
+    pre_skip: u64,
+    interleaved: Vec<f32>,
+    buffer: AudioBuffer<f32>,
+}
+
+impl Decoder for OpusPacketDecoder {
+    fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> Result<Self> {
+        let channel_count = params
+            .channels
+            .map(|channels| channels.count())
+            .unwrap_or(0);
+        let (opus_channels, layout) = match channel_count {
+            1 => (opus::Channels::Mono, Channels::FRONT_LEFT),
+            2 => (
+                opus::Channels::Stereo,
+                Channels::FRONT_LEFT | Channels::FRONT_RIGHT,
+            ),
+            _ => return unsupported_error("opus: only mono and stereo streams are supported"),
+        };
+        let decoder = opus::Decoder::new(OPUS_RATE, opus_channels)
+            .map_err(|_| symphonia::core::errors::Error::Unsupported("opus: decoder init"))?;
+        Ok(Self {
+            params: params.clone(),
+            decoder: Mutex::new(decoder),
+            channels: channel_count,
+            pre_skip: u64::from(params.delay.unwrap_or(0)),
+            interleaved: vec![0.0; MAX_PACKET_FRAMES * channel_count],
+            buffer: AudioBuffer::new(MAX_PACKET_FRAMES as u64, SignalSpec::new(OPUS_RATE, layout)),
+        })
+    }
+
+    fn supported_codecs() -> &'static [CodecDescriptor] {
+        &[support_codec!(CODEC_TYPE_OPUS, "opus", "Opus")]
+    }
+
+    fn reset(&mut self) {
+        if let Ok(mut decoder) = self.decoder.lock() {
+            let _ = decoder.reset_state();
+        }
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        &self.params
+    }
+
+    fn decode(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>> {
+        let frames = {
+            let Ok(mut decoder) = self.decoder.lock() else {
+                return decode_error("opus: decoder lock poisoned");
+            };
+            decoder
+                .decode_float(packet.buf(), &mut self.interleaved, false)
+                .or_else(|_| decode_error("opus: invalid packet"))?
+        };
+
+        self.buffer.clear();
+        self.buffer.render_reserved(Some(frames));
+        for channel in 0..self.channels {
+            let plane = self.buffer.chan_mut(channel);
+            for (frame, sample) in plane.iter_mut().enumerate() {
+                *sample = self.interleaved[frame * self.channels + channel];
+            }
+        }
+
+        // Drop the encoder lookahead (OpusHead pre-skip) at the start of the
+        // stream; packet timestamps count it, so this also holds after a seek.
+        let skip = self.pre_skip.saturating_sub(packet.ts()) as usize;
+        let trim_start = skip.max(packet.trim_start as usize).min(frames);
+        let trim_end = (packet.trim_end as usize).min(frames - trim_start);
+        self.buffer.trim(trim_start, trim_end);
+        Ok(self.buffer.as_audio_buffer_ref())
+    }
+
+    fn finalize(&mut self) -> FinalizeResult {
+        FinalizeResult::default()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.buffer.as_audio_buffer_ref()
+    }
+}
@@ -0,0 +1,322 @@
+// Streaming clip support for long media
+// Long podcasts and video soundtracks are not decoded into memory: Kira's
+// streaming sound pulls frames from a symphonia decoder on its decode thread.
+//
+// The engine picks streaming automatically from the probed duration or the
+// encoded size, so short clips keep the zero-latency StaticSoundData path.
+
+use super::playback::{append_audio_buffer_frames, is_likely_audio_codec};
+use super::playback_opus;
+use kira::sound::streaming::Decoder as KiraDecoder;
+use kira::Frame;
+use std::fs::File;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use symphonia::core::codecs::{Decoder as SymphoniaDecoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Clips longer than this are streamed instead of decoded up front.
+pub(super) const STREAMING_MIN_DURATION_SECONDS: f64 = 120.0;
+/// Encoded inputs larger than this are streamed regardless of duration.
+pub(super) const STREAMING_MIN_SIZE_BYTES: u64 = 32 * 1024 * 1024;
+/// Silence block returned once the container runs out before the advertised length.
+const END_OF_STREAM_PADDING_FRAMES: usize = 4096;
+
+pub(super) fn should_stream(size_bytes: u64, duration_seconds: f64) -> bool {
+    size_bytes >= STREAMING_MIN_SIZE_BYTES
+        || (duration_seconds.is_finite() && duration_seconds >= STREAMING_MIN_DURATION_SECONDS)
+}
+
+/// Re-openable origin of a streaming clip; every voice gets its own decoder.
+#[derive(Clone, Debug)]
+pub(super) enum StreamSource {
+    File(PathBuf),
+    Bytes(Arc<[u8]>),
+}
+
+impl StreamSource {
+    fn open(&self) -> Result<Box<dyn MediaSource>, String> {
+        match self {
+            Self::File(path) => File::open(path)
+                .map(|file| Box::new(file) as Box<dyn MediaSource>)
+                .map_err(|e| format!("Failed to open audio stream {}: {e}", path.display())),
+            Self::Bytes(bytes) => Ok(Box::new(Cursor::new(bytes.clone()))),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct StreamingClip {
+    pub source: StreamSource,
+    pub extension_hint: Option<String>,
+    pub sample_rate: u32,
+    pub frame_count: usize,
+}
+
+impl StreamingClip {
+    pub fn duration_seconds(&self) -> f64 {
+        self.frame_count as f64 / self.sample_rate.max(1) as f64
+    }
+
+    pub fn open_decoder(&self) -> Result<SymphoniaStreamDecoder, String> {
+        SymphoniaStreamDecoder::open(&self.source, self.extension_hint.as_deref())
+    }
+}
+
+/// Probes the container header only; returns `None` when the length is unknown
+/// up front (e.g. VBR MP3 without a Xing header), in which case the caller
+/// falls back to a full static decode.
+pub(super) fn probe_streaming_clip(
+    source: StreamSource,
+    extension_hint: Option<&str>,
+) -> Result<Option<StreamingClip>, String> {
+    let decoder = SymphoniaStreamDecoder::open(&source, extension_hint)?;
+    if decoder.frame_count == 0 {
+        return Ok(None);
+    }
+    Ok(Some(StreamingClip {
+        source,
+        extension_hint: extension_hint.map(str::to_string),
+        sample_rate: decoder.sample_rate,
+        frame_count: decoder.frame_count,
+    }))
+}
+
+/// Packet-by-packet symphonia decoder. Kira drives it for streaming voices and
+/// the offline analyzers (peaks, loudness) iterate it through `next_frames`.
+pub struct SymphoniaStreamDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn SymphoniaDecoder>,
+    track_id: u32,
+    sample_rate: u32,
+    channels: usize,
+    frame_count: usize,
+    position: usize,
+}
+
+impl SymphoniaStreamDecoder {
+    pub fn open_path(path: &Path) -> Result<Self, String> {
+        let extension_hint = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.trim().to_ascii_lowercase());
+        Self::open(
+            &StreamSource::File(path.to_path_buf()),
+            extension_hint.as_deref(),
+        )
+    }
+
+    fn open(source: &StreamSource, extension_hint: Option<&str>) -> Result<Self, String> {
+        let mut hint = Hint::new();
+        if let Some(extension) = extension_hint {
+            hint.with_extension(extension);
+        }
+        let media_source_stream = MediaSourceStream::new(source.open()?, Default::default());
+        let probed = symphonia::default::get_probe()
+            .format(
+                &hint,
+                media_source_stream,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .map_err(|error| format!("Failed to probe audio container: {error}"))?;
+        let format = probed.format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|track| {
+                track.codec_params.codec != CODEC_TYPE_NULL
+                    && is_likely_audio_codec(track.codec_params.codec)
+            })
+            .ok_or("No decodable audio track found".to_string())?;
+        let track_id = track.id;
+        let sample_rate = track
+            .codec_params
+            .sample_rate
+            .ok_or("Could not detect the sample rate of the audio".to_string())?;
+        let channels = track
+            .codec_params
+            .channels
+            .map(|channels| channels.count())
+            .unwrap_or(2)
+            .max(1);
+        let frame_count = track.codec_params.n_frames.unwrap_or(0) as usize;
+        let decoder = playback_opus::codec_registry()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|error| format!("Failed to create audio decoder: {error}"))?;
+
+        Ok(Self {
+            format,
+            decoder,
+            track_id,
+            sample_rate,
+            channels,
+            frame_count,
+            position: 0,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Source channel count; frames are always delivered as stereo pairs, with
+    /// mono sources duplicated to both sides.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Advertised length in frames, `0` when the container does not say.
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// Next decoded block, `None` once the container is exhausted.
+    pub fn next_frames(&mut self) -> Result<Option<Vec<Frame>>, String> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(_)) => return Ok(None),
+                Err(SymphoniaError::ResetRequired) => {
+                    return Err("Audio decoder reset required during stream".to_string());
+                }
+                Err(error) => return Err(format!("Failed to read audio packet: {error}")),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            match self.decoder.decode(&packet) {
+                Ok(audio_buffer) => {
+                    let mut frames = Vec::with_capacity(audio_buffer.frames());
+                    append_audio_buffer_frames(audio_buffer, &mut frames)?;
+                    if frames.is_empty() {
+                        continue;
+                    }
+                    self.position += frames.len();
+                    return Ok(Some(frames));
+                }
+                Err(SymphoniaError::IoError(_)) | Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(SymphoniaError::ResetRequired) => {
+                    return Err("Audio decoder reset required during packet decode".to_string());
+                }
+                Err(error) => return Err(format!("Failed to decode audio packet: {error}")),
+            }
+        }
+    }
+
+    fn end_of_stream_padding(&mut self) -> Vec<Frame> {
+        let remaining = self
+            .frame_count
+            .saturating_sub(self.position)
+            .clamp(1, END_OF_STREAM_PADDING_FRAMES);
+        self.position += remaining;
+        vec![Frame::ZERO; remaining]
+    }
+}
+
+impl KiraDecoder for SymphoniaStreamDecoder {
+    type Error = String;
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn num_frames(&self) -> usize {
+        self.frame_count
+    }
+
+    fn decode(&mut self) -> Result<Vec<Frame>, Self::Error> {
+        match self.next_frames()? {
+            Some(frames) => Ok(frames),
+            None => Ok(self.end_of_stream_padding()),
+        }
+    }
+
+    fn seek(&mut self, index: usize) -> Result<usize, Self::Error> {
+        let seeked = self
+            .format
+            .seek(
+                SeekMode::Accurate,
+                SeekTo::TimeStamp {
+                    ts: index as u64,
+                    track_id: self.track_id,
+                },
+            )
+            .map_err(|error| format!("Failed to seek audio stream: {error}"))?;
+        self.decoder.reset();
+        self.position = seeked.actual_ts as usize;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod stream_selection_tests {
+    use super::{
+        probe_streaming_clip, should_stream, StreamSource, STREAMING_MIN_DURATION_SECONDS,
+        STREAMING_MIN_SIZE_BYTES,
+    };
+    use kira::sound::streaming::Decoder;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    fn sine_wav_bytes(sample_rate: u32, seconds: f32) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+            let total = (sample_rate as f32 * seconds) as usize;
+            for index in 0..total {
+                let phase = index as f32 * 440.0 * std::f32::consts::TAU / sample_rate as f32;
+                writer
+                    .write_sample((phase.sin() * i16::MAX as f32 * 0.5) as i16)
+                    .unwrap();
+            }
+            writer.finalize().unwrap();
+        }
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn short_small_clips_stay_static() {
+        assert!(!should_stream(1024 * 1024, 30.0));
+        assert!(!should_stream(0, f64::NAN));
+    }
+
+    #[test]
+    fn long_or_large_clips_stream() {
+        assert!(should_stream(1024, STREAMING_MIN_DURATION_SECONDS));
+        assert!(should_stream(STREAMING_MIN_SIZE_BYTES, 1.0));
+    }
+
+    #[test]
+    fn stream_decoder_reports_length_and_seeks_by_frame() {
+        let bytes: Arc<[u8]> = sine_wav_bytes(8_000, 1.0).into();
+        let clip = probe_streaming_clip(StreamSource::Bytes(bytes), Some("wav"))
+            .unwrap()
+            .expect("wav headers advertise their frame count");
+        assert_eq!(clip.sample_rate, 8_000);
+        assert_eq!(clip.frame_count, 8_000);
+        assert_eq!(clip.duration_seconds(), 1.0);
+
+        let mut decoder = clip.open_decoder().unwrap();
+        assert!(!decoder.decode().unwrap().is_empty());
+        let position = decoder.seek(4_000).unwrap();
+        assert!(position <= 4_000);
+        let mut decoded = position;
+        while decoded < clip.frame_count {
+            decoded += decoder.decode().unwrap().len();
+        }
+        assert!(decoded >= clip.frame_count);
+    }
+}
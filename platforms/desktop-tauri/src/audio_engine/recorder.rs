@@ -1,26 +1,40 @@
 // CPAL-based audio recorder
 // Pure Rust replacement for the Obj-C AVAudioEngine recorder.
-// Records input audio to WAV files via CPAL + hound.
+// Records input audio to WAV (hound), FLAC or Ogg/Opus files via CPAL.
 // The CPAL stream lives on a dedicated thread to avoid Send issues.
 //
 // Optimizations over previous version:
 // - Condvar-based stop signaling replaces 50ms polling loop (near-zero CPU when idle)
 // - Configurable buffer size (low-latency or high-throughput)
-// - Support for 16-bit int, 24-bit int, and 32-bit float WAV output
+// - Support for 16-bit int, 24-bit int, and 32-bit float WAV, FLAC and Ogg/Opus output
 // - Sample rate validation with explicit warnings
 // - Batch sample writes to reduce per-sample overhead
+// - Arm/pre-roll, pause/resume within one file, and markers (WAV cue chunks)
+// - Voice-armed sessions that start on speech and stop after silence (VAD)
+// - Input effects chain (gain, high-pass, gate, limiter) ahead of the writer,
+//   the meters and the optional live monitor output
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{SampleFormat, StreamConfig};
-use ringbuf::{traits::*, HeapRb};
+use ringbuf::{traits::*, HeapProd, HeapRb};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{mpsc, Arc, Condvar, Mutex};
 
+use super::input_fx::{self, InputProcessor};
 use super::metering;
-use super::recorder_wav::{create_wav_writer, spawn_wav_writer_thread};
+pub use super::recorder_journal::RecoveredRecording;
+use super::recorder_journal::{self, RecordingJournal};
+use super::recorder_markers;
+pub use super::recorder_markers::RecordMarker;
+use super::recorder_monitor::{self, MonitorSender};
+pub use super::recorder_voice::VoiceTriggerConfig;
+use super::recorder_voice::{VoiceTrigger, VOICE_PRE_ROLL_SEC};
+use super::recorder_wav::{
+    spawn_writer_thread, RecordState, RecordingProgress, RecordingSink, WriterRouter,
+};
 pub use super::recorder_wav::{BufferSizeHint, OutputFormat};
 
 macro_rules! eprintln {
@@ -31,35 +45,65 @@ macro_rules! eprintln {
     };
 }
 
+/// How long `refresh_monitoring` waits for a session to open its monitor
+/// output; opening a device normally takes a few milliseconds.
+const MONITOR_REPLY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+type MonitorReply = mpsc::Sender<Result<(), String>>;
+
+#[derive(Default)]
+struct SignalState {
+    stopped: bool,
+    woken: bool,
+    /// Callers waiting to hear whether the monitor output opened.
+    monitor_replies: Vec<MonitorReply>,
+}
+
 struct StopSignal {
-    flag: Mutex<bool>,
+    flag: Mutex<SignalState>,
     cvar: Condvar,
 }
 
 impl StopSignal {
     fn new() -> Self {
         Self {
-            flag: Mutex::new(false),
+            flag: Mutex::new(SignalState::default()),
             cvar: Condvar::new(),
         }
     }
 
     fn stop(&self) {
         let mut flag = self.flag.lock().unwrap();
-        *flag = true;
+        flag.stopped = true;
         self.cvar.notify_all();
     }
 
-    fn wait(&self) {
+    /// Wakes the session thread to open its monitor output; the outcome is
+    /// sent to `reply`.
+    fn request_monitor(&self, reply: MonitorReply) {
+        let mut flag = self.flag.lock().unwrap();
+        flag.woken = true;
+        flag.monitor_replies.push(reply);
+        self.cvar.notify_all();
+    }
+
+    fn take_monitor_replies(&self) -> Vec<MonitorReply> {
+        std::mem::take(&mut self.flag.lock().unwrap().monitor_replies)
+    }
+
+    /// Blocks until stopped (returns `true`) or woken (returns `false`).
+    fn wait(&self) -> bool {
         let mut flag = self.flag.lock().unwrap();
-        while !*flag {
+        while !flag.stopped && !flag.woken {
             flag = self.cvar.wait(flag).unwrap();
         }
+        flag.woken = false;
+        flag.stopped
     }
 
     #[allow(dead_code)]
     fn is_stopped(&self) -> bool {
-        *self.flag.lock().unwrap()
+        self.flag.lock().unwrap().stopped
     }
 }
 
@@ -73,8 +117,78 @@ struct RecordingSession {
     sample_rate: u32,
     channels: u16,
     output_format: OutputFormat,
-    frame_count: Arc<AtomicU64>,
+    progress: Arc<RecordingProgress>,
     overrun_frames: Arc<AtomicU64>,
+    markers: Vec<RecordMarker>,
+}
+
+/// Everything the input callback owns: sample conversion scratch, the effects
+/// chain, the writer ring buffer and the monitor path.
+struct CaptureInput {
+    channels: usize,
+    /// Allocated once for `block_samples`; larger callback blocks are handled
+    /// in slices so the callback never grows it.
+    scratch: Vec<f32>,
+    block_samples: usize,
+    processor: InputProcessor,
+    producer: HeapProd<f32>,
+    monitor: MonitorSender,
+    overrun_frames: Arc<AtomicU64>,
+    progress: Arc<RecordingProgress>,
+}
+
+impl CaptureInput {
+    fn new(
+        sample_rate: u32,
+        channels: u16,
+        producer: HeapProd<f32>,
+        monitor: MonitorSender,
+        overrun_frames: Arc<AtomicU64>,
+        progress: Arc<RecordingProgress>,
+    ) -> Self {
+        let channel_count = usize::from(channels.max(1));
+        // One second of input, far above any callback block size.
+        let block_samples = usize::try_from(sample_rate)
+            .unwrap_or(48_000)
+            .max(1)
+            .saturating_mul(channel_count);
+        Self {
+            channels: channel_count,
+            scratch: Vec::with_capacity(block_samples),
+            block_samples,
+            processor: InputProcessor::new(sample_rate, channels),
+            producer,
+            monitor,
+            overrun_frames,
+            progress,
+        }
+    }
+
+    fn push<T: Copy>(&mut self, data: &[T], convert: impl Fn(T) -> f32) {
+        for block in data.chunks(self.block_samples) {
+            self.scratch.clear();
+            self.scratch
+                .extend(block.iter().map(|&sample| convert(sample)));
+            self.push_scratch();
+        }
+    }
+
+    fn push_scratch(&mut self) {
+        self.processor.process(&mut self.scratch);
+        metering::push_samples(&self.scratch);
+        if let Some(gain) = self.processor.monitor_gain() {
+            self.monitor.send(&self.scratch, gain);
+        }
+        let frames = (self.scratch.len() / self.channels) as u64;
+        if self.producer.vacant_len() >= self.scratch.len() {
+            self.producer.push_slice(&self.scratch);
+            self.progress
+                .captured_frames
+                .fetch_add(frames, Ordering::Relaxed);
+        } else {
+            self.overrun_frames.fetch_add(frames, Ordering::Relaxed);
+        }
+    }
 }
 
 static SESSIONS: once_cell::sync::Lazy<Mutex<HashMap<String, RecordingSession>>> =
@@ -91,8 +205,14 @@ pub struct RecordResult {
     pub sample_rate: u32,
     pub channels: u16,
     pub output_format: String,
+    pub pre_roll_frames: u64,
+    pub paused_frames: u64,
+    pub markers: Vec<RecordMarker>,
 }
 
+/// Longest pre-roll an armed session keeps.
+pub const MAX_PRE_ROLL_SEC: f64 = 30.0;
+
 pub fn start(
     session_id: &str,
     abs_wav_path: &str,
@@ -109,6 +229,11 @@ pub fn start(
     )
 }
 
+/// Starts writing. An armed session (see `arm`) begins with its pre-roll;
+/// otherwise the input stream is opened now. Starting an armed session with a
+/// different path, sample rate, channel count or format is an error (zero
+/// rate or channels mean "whatever was armed"); `buffer_hint` is ignored
+/// because the armed stream is already open.
 pub fn start_with_options(
     session_id: &str,
     abs_wav_path: &str,
@@ -117,11 +242,129 @@ pub fn start_with_options(
     output_format: OutputFormat,
     buffer_hint: BufferSizeHint,
 ) -> Result<(), String> {
+    let mut sessions = SESSIONS.lock().map_err(|e| format!("Lock error: {e}"))?;
+    if let Some(session) = sessions.get(session_id) {
+        if session.progress.state() != RecordState::Armed {
+            return Err(format!("Session '{session_id}' already active"));
+        }
+        let mismatch = if session.file_path != abs_wav_path {
+            Some(format!("path {abs_wav_path} (armed {})", session.file_path))
+        } else if sample_rate > 0 && sample_rate != session.sample_rate {
+            Some(format!(
+                "sample rate {sample_rate} (armed {})",
+                session.sample_rate
+            ))
+        } else if channels > 0 && channels != session.channels {
+            Some(format!("channels {channels} (armed {})", session.channels))
+        } else if output_format != session.output_format {
+            Some(format!(
+                "format {output_format:?} (armed {:?})",
+                session.output_format
+            ))
+        } else {
+            None
+        };
+        if let Some(mismatch) = mismatch {
+            return Err(format!(
+                "audio_recording_config_mismatch: session '{session_id}' was armed with a different {mismatch}"
+            ));
+        }
+        session.progress.set_state(RecordState::Recording);
+        return Ok(());
+    }
+    open_session(
+        &mut sessions,
+        session_id,
+        abs_wav_path,
+        sample_rate,
+        channels,
+        output_format,
+        buffer_hint,
+        RecordState::Recording,
+        0.0,
+        None,
+    )
+}
+
+/// Opens the input stream without writing: the last `pre_roll_sec` seconds
+/// (capped at `MAX_PRE_ROLL_SEC`) are kept and become the head of the file
+/// once `start` is called for the same session.
+pub fn arm(
+    session_id: &str,
+    abs_wav_path: &str,
+    sample_rate: u32,
+    channels: u16,
+    output_format: OutputFormat,
+    pre_roll_sec: f64,
+) -> Result<(), String> {
+    if !pre_roll_sec.is_finite() || pre_roll_sec < 0.0 {
+        return Err(format!("Invalid pre-roll duration: {pre_roll_sec}"));
+    }
     let mut sessions = SESSIONS.lock().map_err(|e| format!("Lock error: {e}"))?;
     if sessions.contains_key(session_id) {
         return Err(format!("Session '{session_id}' already active"));
     }
+    open_session(
+        &mut sessions,
+        session_id,
+        abs_wav_path,
+        sample_rate,
+        channels,
+        output_format,
+        BufferSizeHint::default(),
+        RecordState::Armed,
+        pre_roll_sec.min(MAX_PRE_ROLL_SEC),
+        None,
+    )
+}
 
+/// Arms a session that starts writing on the first speech onset (with
+/// `VOICE_PRE_ROLL_SEC` of pre-roll) and, if `auto_stop_after_sec` is set,
+/// stops writing after that much silence. `start` still forces it to record.
+pub fn arm_on_voice(
+    session_id: &str,
+    abs_wav_path: &str,
+    sample_rate: u32,
+    channels: u16,
+    output_format: OutputFormat,
+    voice: VoiceTriggerConfig,
+) -> Result<(), String> {
+    if let Some(seconds) = voice.auto_stop_after_sec {
+        if !seconds.is_finite() || seconds <= 0.0 {
+            return Err(format!("Invalid auto-stop silence: {seconds}"));
+        }
+    }
+    let mut sessions = SESSIONS.lock().map_err(|e| format!("Lock error: {e}"))?;
+    if sessions.contains_key(session_id) {
+        return Err(format!("Session '{session_id}' already active"));
+    }
+    open_session(
+        &mut sessions,
+        session_id,
+        abs_wav_path,
+        sample_rate,
+        channels,
+        output_format,
+        BufferSizeHint::default(),
+        RecordState::Armed,
+        VOICE_PRE_ROLL_SEC,
+        Some(voice),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn open_session(
+    sessions: &mut HashMap<String, RecordingSession>,
+    session_id: &str,
+    abs_wav_path: &str,
+    sample_rate: u32,
+    channels: u16,
+    output_format: OutputFormat,
+    buffer_hint: BufferSizeHint,
+    initial_state: RecordState,
+    pre_roll_sec: f64,
+    voice: Option<VoiceTriggerConfig>,
+) -> Result<(), String> {
     let default_input_config = cpal::default_host()
         .default_input_device()
         .ok_or("No default input (microphone) device found")?
@@ -148,11 +391,12 @@ pub fn start_with_options(
     let stop_signal_thread = Arc::clone(&stop_signal);
     let stop_atomic = Arc::new(AtomicBool::new(false));
     let stop_atomic_cb = Arc::clone(&stop_atomic);
-    let frame_count = Arc::new(AtomicU64::new(0));
-    let frame_count_thread = Arc::clone(&frame_count);
+    let progress = Arc::new(RecordingProgress::new(initial_state));
+    let progress_thread = Arc::clone(&progress);
     let overrun_frames = Arc::new(AtomicU64::new(0));
     let overrun_frames_thread = Arc::clone(&overrun_frames);
     let path_owned = abs_wav_path.to_string();
+    let session_id_owned = session_id.to_string();
     let sr = actual_sample_rate;
     let ch = actual_channels;
     let fmt = output_format;
@@ -190,7 +434,14 @@ pub fn start_with_options(
                 buffer_size: buf_hint.to_cpal(),
             };
 
-            let writer = create_wav_writer(&path_owned, fmt, actual_sr, actual_ch)?;
+            let sink = RecordingSink::create(&path_owned, fmt, actual_sr, actual_ch)?;
+            let journal = RecordingJournal::new(
+                &session_id_owned,
+                &path_owned,
+                actual_sr,
+                actual_ch,
+                format!("{:?}", fmt),
+            );
             let rb_capacity = usize::try_from(actual_sr)
                 .unwrap_or(48_000)
                 .saturating_mul(usize::from(actual_ch.max(1)))
@@ -199,12 +450,33 @@ pub fn start_with_options(
             let rb = HeapRb::<f32>::new(rb_capacity);
             let (producer, consumer) = rb.split();
             let writer_stop = Arc::new(AtomicBool::new(false));
-            let writer_handle =
-                spawn_wav_writer_thread(consumer, writer, fmt, Arc::clone(&writer_stop));
+            let pre_roll_frames = (pre_roll_sec * f64::from(actual_sr)).round() as usize;
+            let router = WriterRouter::new(actual_ch, pre_roll_frames, initial_state);
+            let voice = voice
+                .map(|config| VoiceTrigger::new(actual_sr, actual_ch, config))
+                .transpose()?;
+            let writer_handle = spawn_writer_thread(
+                consumer,
+                sink,
+                router,
+                Arc::clone(&progress_thread),
+                voice,
+                Arc::clone(&writer_stop),
+                journal,
+            );
 
+            // The monitor output is opened on this thread the first time
+            // monitoring is enabled; until then its ring just drops input.
+            let (monitor, monitor_receiver) = recorder_monitor::channel(actual_sr, actual_ch);
+            let mut capture = CaptureInput::new(
+                actual_sr,
+                actual_ch,
+                producer,
+                monitor,
+                Arc::clone(&overrun_frames_thread),
+                Arc::clone(&progress_thread),
+            );
             let stop_for_callback = Arc::clone(&stop_atomic_cb);
-            let frame_count_cb = Arc::clone(&frame_count_thread);
-            let overrun_frames_cb = Arc::clone(&overrun_frames_thread);
 
             let sample_format = default_config.sample_format();
 
@@ -213,105 +485,42 @@ pub fn start_with_options(
             };
 
             let stream = match sample_format {
-                SampleFormat::F32 => {
-                    let mut producer = producer;
-                    device
-                        .build_input_stream(
-                            &stream_config,
-                            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                                if stop_for_callback.load(Ordering::Relaxed) {
-                                    return;
-                                }
-                                metering::push_samples(data);
-                                let frames = (data.len() / usize::from(actual_ch.max(1))) as u64;
-                                if producer.vacant_len() >= data.len() {
-                                    let written = producer.push_slice(data);
-                                    frame_count_cb.fetch_add(
-                                        (written / usize::from(actual_ch.max(1))) as u64,
-                                        Ordering::Relaxed,
-                                    );
-                                } else {
-                                    overrun_frames_cb.fetch_add(frames, Ordering::Relaxed);
-                                }
-                            },
-                            err_fn,
-                            None,
-                        )
-                        .map_err(|e| format!("Failed to build input stream: {e}"))?
-                }
-                SampleFormat::I16 => {
-                    let mut producer = producer;
-                    let stop_for_callback2 = Arc::clone(&stop_atomic_cb);
-                    let frame_count_cb2 = Arc::clone(&frame_count_thread);
-                    let overrun_frames_cb2 = Arc::clone(&overrun_frames_thread);
-                    device
-                        .build_input_stream(
-                            &stream_config,
-                            move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                                if stop_for_callback2.load(Ordering::Relaxed) {
-                                    return;
-                                }
-                                metering::push_i16_samples(data);
-                                let frames = (data.len() / usize::from(actual_ch.max(1))) as u64;
-                                if producer.vacant_len() >= data.len() {
-                                    let mut written = 0usize;
-                                    for &sample in data {
-                                        if producer.try_push(sample as f32 / 32768.0).is_ok() {
-                                            written += 1;
-                                        } else {
-                                            break;
-                                        }
-                                    }
-                                    frame_count_cb2.fetch_add(
-                                        (written / usize::from(actual_ch.max(1))) as u64,
-                                        Ordering::Relaxed,
-                                    );
-                                } else {
-                                    overrun_frames_cb2.fetch_add(frames, Ordering::Relaxed);
-                                }
-                            },
-                            err_fn,
-                            None,
-                        )
-                        .map_err(|e| format!("Failed to build input stream (i16): {e}"))?
-                }
-                SampleFormat::U16 => {
-                    let mut producer = producer;
-                    let stop_for_callback3 = Arc::clone(&stop_atomic_cb);
-                    let frame_count_cb3 = Arc::clone(&frame_count_thread);
-                    let overrun_frames_cb3 = Arc::clone(&overrun_frames_thread);
-                    device
-                        .build_input_stream(
-                            &stream_config,
-                            move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                                if stop_for_callback3.load(Ordering::Relaxed) {
-                                    return;
-                                }
-                                metering::push_u16_samples(data);
-                                let frames = (data.len() / usize::from(actual_ch.max(1))) as u64;
-                                if producer.vacant_len() >= data.len() {
-                                    let mut written = 0usize;
-                                    for &sample in data {
-                                        let centered = sample as f32 - 32768.0;
-                                        if producer.try_push(centered / 32768.0).is_ok() {
-                                            written += 1;
-                                        } else {
-                                            break;
-                                        }
-                                    }
-                                    frame_count_cb3.fetch_add(
-                                        (written / usize::from(actual_ch.max(1))) as u64,
-                                        Ordering::Relaxed,
-                                    );
-                                } else {
-                                    overrun_frames_cb3.fetch_add(frames, Ordering::Relaxed);
-                                }
-                            },
-                            err_fn,
-                            None,
-                        )
-                        .map_err(|e| format!("Failed to build input stream (u16): {e}"))?
-                }
+                SampleFormat::F32 => device
+                    .build_input_stream(
+                        &stream_config,
+                        move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                            if !stop_for_callback.load(Ordering::Relaxed) {
+                                capture.push(data, |sample| sample);
+                            }
+                        },
+                        err_fn,
+                        None,
+                    )
+                    .map_err(|e| format!("Failed to build input stream: {e}"))?,
+                SampleFormat::I16 => device
+                    .build_input_stream(
+                        &stream_config,
+                        move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                            if !stop_for_callback.load(Ordering::Relaxed) {
+                                capture.push(data, |sample| sample as f32 / 32768.0);
+                            }
+                        },
+                        err_fn,
+                        None,
+                    )
+                    .map_err(|e| format!("Failed to build input stream (i16): {e}"))?,
+                SampleFormat::U16 => device
+                    .build_input_stream(
+                        &stream_config,
+                        move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                            if !stop_for_callback.load(Ordering::Relaxed) {
+                                capture.push(data, |sample| (sample as f32 - 32768.0) / 32768.0);
+                            }
+                        },
+                        err_fn,
+                        None,
+                    )
+                    .map_err(|e| format!("Failed to build input stream (u16): {e}"))?,
                 _ => {
                     return Err(format!("Unsupported sample format: {:?}", sample_format));
                 }
@@ -321,10 +530,10 @@ pub fn start_with_options(
                 .play()
                 .map_err(|e| format!("Failed to start recording stream: {e}"))?;
 
-            Ok((stream, writer_stop, writer_handle))
+            Ok((stream, monitor_receiver, writer_stop, writer_handle))
         })();
 
-        let (stream, writer_stop, writer_handle) = match init_result {
+        let (stream, monitor_receiver, writer_stop, writer_handle) = match init_result {
             Ok(value) => {
                 let _ = ready_tx.send(Ok(()));
                 value
@@ -335,17 +544,42 @@ pub fn start_with_options(
             }
         };
 
-        // Block this thread until stop signal — uses condvar (zero CPU when waiting)
-        stop_signal_thread.wait();
+        // Block this thread until stop signal — uses condvar (zero CPU when waiting).
+        // Wake-ups in between come from monitoring being switched on.
+        let mut monitor_receiver = Some(monitor_receiver);
+        let mut monitor_stream = None;
+        let mut monitor_status: Result<(), String> = Ok(());
+        loop {
+            if input_fx::settings().monitor_enabled {
+                if let Some(receiver) = monitor_receiver.take() {
+                    // Monitoring is optional: a missing or busy output device
+                    // must not prevent recording, only fail the monitor call.
+                    match recorder_monitor::open(receiver) {
+                        Ok(stream) => monitor_stream = Some(stream),
+                        Err(error) => {
+                            eprintln!("[audio_engine::recorder] Monitoring unavailable: {error}");
+                            monitor_status = Err(format!("audio_monitor_unavailable: {error}"));
+                        }
+                    }
+                }
+            }
+            for reply in stop_signal_thread.take_monitor_replies() {
+                let _ = reply.send(monitor_status.clone());
+            }
+            if stop_signal_thread.wait() {
+                break;
+            }
+        }
 
-        // Drop stream first (stops audio callbacks)
+        // Drop streams first (stops audio callbacks)
         drop(stream);
+        drop(monitor_stream);
 
         writer_stop.store(true, Ordering::Release);
         match writer_handle.join() {
             Ok(Ok(())) => {}
             Ok(Err(error)) => return Err(error),
-            Err(_) => return Err("WAV writer thread panicked".to_string()),
+            Err(_) => return Err("Recording writer thread panicked".to_string()),
         }
 
         Ok(0.0) // duration computed from Instant in stop()
@@ -384,14 +618,116 @@ pub fn start_with_options(
             sample_rate: actual_sample_rate,
             channels: actual_channels,
             output_format,
-            frame_count,
+            progress,
             overrun_frames,
+            markers: Vec::new(),
         },
     );
 
     Ok(())
 }
 
+fn switch_state(session_id: &str, from: RecordState, to: RecordState) -> Result<(), String> {
+    let sessions = SESSIONS.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let session = sessions
+        .get(session_id)
+        .ok_or(format!("Session '{session_id}' not found"))?;
+    let state = session.progress.state();
+    if state == to {
+        return Ok(());
+    }
+    if state != from {
+        return Err(format!(
+            "Session '{session_id}' is {state:?}, expected {from:?}"
+        ));
+    }
+    session.progress.set_state(to);
+    Ok(())
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RecordStatus {
+    pub session_id: String,
+    /// `armed`, `recording`, `paused` or `stopped` (voice auto-stop).
+    pub state: &'static str,
+    pub frame_count: u64,
+    pub duration_sec: f64,
+    pub voice_active: bool,
+    pub marker_count: usize,
+}
+
+pub fn status(session_id: &str) -> Result<RecordStatus, String> {
+    let sessions = SESSIONS.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let session = sessions
+        .get(session_id)
+        .ok_or(format!("Session '{session_id}' not found"))?;
+    let frame_count = session.progress.frames.load(Ordering::Relaxed);
+    Ok(RecordStatus {
+        session_id: session_id.to_string(),
+        state: session.progress.state().as_str(),
+        frame_count,
+        duration_sec: frame_count as f64 / f64::from(session.sample_rate.max(1)),
+        voice_active: session.progress.voice_active.load(Ordering::Relaxed),
+        marker_count: session.markers.len(),
+    })
+}
+
+/// Lets running sessions open their monitor output after monitoring was
+/// switched on, and fails if any of them could not open it.
+pub fn refresh_monitoring() -> Result<(), String> {
+    let (reply, replies) = mpsc::channel();
+    let pending = {
+        let sessions = SESSIONS.lock().map_err(|e| format!("Lock error: {e}"))?;
+        for session in sessions.values() {
+            session.stop_signal.request_monitor(reply.clone());
+        }
+        sessions.len()
+    };
+    drop(reply);
+    for _ in 0..pending {
+        match replies.recv_timeout(MONITOR_REPLY_TIMEOUT) {
+            Ok(result) => result?,
+            // The session stopped (or is stuck stopping) before it answered.
+            Err(_) => break,
+        }
+    }
+    Ok(())
+}
+
+/// Stops writing without closing the file; input is dropped until `resume`.
+pub fn pause(session_id: &str) -> Result<(), String> {
+    switch_state(session_id, RecordState::Recording, RecordState::Paused)
+}
+
+pub fn resume(session_id: &str) -> Result<(), String> {
+    switch_state(session_id, RecordState::Paused, RecordState::Recording)
+}
+
+/// Drops a marker at the current write position of the take.
+pub fn add_marker(session_id: &str, label: Option<String>) -> Result<RecordMarker, String> {
+    let mut sessions = SESSIONS.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let session = sessions
+        .get_mut(session_id)
+        .ok_or(format!("Session '{session_id}' not found"))?;
+    if session.progress.state() == RecordState::Armed {
+        return Err(format!(
+            "audio_recording_not_started: session '{session_id}' is armed but not recording"
+        ));
+    }
+    let frame = session.progress.input_frame();
+    let id = session.markers.len() as u32 + 1;
+    let marker = RecordMarker {
+        id,
+        label: label
+            .filter(|label| !label.trim().is_empty())
+            .unwrap_or_else(|| format!("Marker {id}")),
+        frame,
+        time_sec: frame as f64 / f64::from(session.sample_rate.max(1)),
+    };
+    session.markers.push(marker.clone());
+    Ok(marker)
+}
+
 pub fn stop(session_id: &str) -> Result<RecordResult, String> {
     let mut sessions = SESSIONS.lock().map_err(|e| format!("Lock error: {e}"))?;
     let mut session = sessions
@@ -411,7 +747,13 @@ pub fn stop(session_id: &str) -> Result<RecordResult, String> {
             Err(_) => return Err("Recording thread panicked".to_string()),
         }
     }
-    let frame_count = session.frame_count.load(Ordering::Relaxed);
+    if session.progress.state() == RecordState::Armed {
+        let _ = fs::remove_file(&session.file_path);
+        return Err(format!(
+            "audio_recording_not_started: session '{session_id}' was armed but never started"
+        ));
+    }
+    let frame_count = session.progress.frames.load(Ordering::Relaxed);
     let overrun_frames = session.overrun_frames.load(Ordering::Relaxed);
     let duration_sec = if session.sample_rate > 0 {
         frame_count as f64 / session.sample_rate as f64
@@ -423,6 +765,9 @@ pub fn stop(session_id: &str) -> Result<RecordResult, String> {
             "audio_recording_empty: no input frames were captured for session '{session_id}'"
         ));
     }
+    if session.output_format.is_wav() {
+        recorder_markers::append_wav_markers(Path::new(&session.file_path), &session.markers)?;
+    }
     let size_bytes = fs::metadata(&session.file_path)
         .map(|metadata| metadata.len())
         .map_err(|error| format!(
@@ -444,5 +789,27 @@ pub fn stop(session_id: &str) -> Result<RecordResult, String> {
         sample_rate: session.sample_rate,
         channels: session.channels,
         output_format: format!("{:?}", session.output_format),
+        pre_roll_frames: session.progress.pre_roll_frames.load(Ordering::Relaxed),
+        paused_frames: session.progress.paused_frames.load(Ordering::Relaxed),
+        markers: session.markers,
+    })
+}
+
+/// Repairs takes whose sessions died before `stop` (crash, kill, power loss)
+/// anywhere under `root`, skipping sessions still recording in this process.
+pub fn recover_orphaned_recordings(root: &Path) -> Vec<RecoveredRecording> {
+    // The session registry is re-read and held per journal: `start` takes the
+    // same lock, so a take begun during the scan is never repaired under it.
+    recorder_journal::recover_orphaned_recordings(root, |journal| {
+        let sessions = SESSIONS.lock().ok()?;
+        let active = sessions.values().any(|session| {
+            recorder_journal::journal_path(Path::new(&session.file_path)) == journal
+        });
+        (!active).then_some(sessions)
     })
 }
+
+/// Takes repaired by recovery scans since the last acknowledgement.
+pub fn recovered_recordings(acknowledge: bool) -> Vec<RecoveredRecording> {
+    recorder_journal::recovered_recordings(acknowledge)
+}
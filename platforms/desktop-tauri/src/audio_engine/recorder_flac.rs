@@ -0,0 +1,642 @@
+// Streaming FLAC encoder for the recorder writer thread
+// Fixed-blocksize frames with FIXED predictors (orders 0–4), partitioned Rice
+// residuals and stereo decorrelation. That keeps the encoder small enough to
+// run per block on the writer thread while landing close to reference FLAC
+// at its fast presets. STREAMINFO is rewritten on every checkpoint so a file
+// cut off mid-take is still decodable up to the last complete frame.
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+
+pub(super) const FLAC_BLOCK_SIZE: usize = 4096;
+const STREAMINFO_OFFSET: u64 = 8;
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 8;
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::with_capacity(FLAC_BLOCK_SIZE * 8),
+            accumulator: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn write(&mut self, bits: u32, value: u64) {
+        debug_assert!(bits <= 32);
+        if bits == 0 {
+            return;
+        }
+        self.accumulator = (self.accumulator << bits) | (value & ((1u64 << bits) - 1));
+        self.bit_count += bits;
+        while self.bit_count >= 8 {
+            self.bit_count -= 8;
+            self.bytes.push((self.accumulator >> self.bit_count) as u8);
+        }
+    }
+
+    fn write_signed(&mut self, bits: u32, value: i64) {
+        self.write(bits, value as u64);
+    }
+
+    fn write_unary_zeros(&mut self, mut zeros: u64) {
+        while zeros >= 32 {
+            self.write(32, 0);
+            zeros -= 32;
+        }
+        self.write(zeros as u32 + 1, 1);
+    }
+
+    fn align(&mut self) {
+        if self.bit_count > 0 {
+            self.write(8 - self.bit_count, 0);
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, byte| {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn sample_rate_code(sample_rate: u32) -> u64 {
+    match sample_rate {
+        88_200 => 1,
+        176_400 => 2,
+        192_000 => 3,
+        8_000 => 4,
+        16_000 => 5,
+        22_050 => 6,
+        24_000 => 7,
+        32_000 => 8,
+        44_100 => 9,
+        48_000 => 10,
+        96_000 => 11,
+        // 0 = "take it from STREAMINFO"
+        _ => 0,
+    }
+}
+
+fn sample_size_code(bits_per_sample: u32) -> u64 {
+    match bits_per_sample {
+        8 => 1,
+        12 => 2,
+        16 => 4,
+        20 => 5,
+        24 => 6,
+        _ => 0,
+    }
+}
+
+/// FLAC's UTF-8-like variable-length frame number.
+fn write_frame_number(bits: &mut BitWriter, number: u64) {
+    if number < 0x80 {
+        bits.write(8, number);
+        return;
+    }
+    let mut continuation = 1;
+    while number >= 1u64 << (5 * continuation + 6) {
+        continuation += 1;
+    }
+    let lead_bits = 6 - continuation;
+    let marker = (0xFFu64 << (8 - continuation - 1)) & 0xFF;
+    bits.write(
+        8,
+        marker | (number >> (6 * continuation)) & ((1 << lead_bits) - 1),
+    );
+    for index in (0..continuation).rev() {
+        bits.write(8, 0x80 | ((number >> (6 * index)) & 0x3F));
+    }
+}
+
+fn fixed_residual(samples: &[i64], order: usize, output: &mut Vec<i64>) {
+    output.clear();
+    output.extend((order..samples.len()).map(|i| match order {
+        0 => samples[i],
+        1 => samples[i] - samples[i - 1],
+        2 => samples[i] - 2 * samples[i - 1] + samples[i - 2],
+        3 => samples[i] - 3 * samples[i - 1] + 3 * samples[i - 2] - samples[i - 3],
+        _ => {
+            samples[i] - 4 * samples[i - 1] + 6 * samples[i - 2] - 4 * samples[i - 3]
+                + samples[i - 4]
+        }
+    }));
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Bits for one partition at Rice parameter `k` (excluding the parameter).
+fn rice_bits(residuals: &[i64], k: u32) -> u64 {
+    residuals
+        .iter()
+        .map(|value| (zigzag(*value) >> k) + 1 + k as u64)
+        .sum()
+}
+
+fn best_rice_parameter(residuals: &[i64], max_parameter: u32) -> (u32, u64) {
+    if residuals.is_empty() {
+        return (0, 0);
+    }
+    let mean = residuals.iter().map(|value| zigzag(*value)).sum::<u64>() / residuals.len() as u64;
+    let estimate = (64 - mean.leading_zeros()).min(max_parameter);
+    (estimate.saturating_sub(1)..=(estimate + 1).min(max_parameter))
+        .map(|k| (k, rice_bits(residuals, k)))
+        .min_by_key(|(_, bits)| *bits)
+        .unwrap_or((0, rice_bits(residuals, 0)))
+}
+
+struct ResidualPlan {
+    method: u64,
+    partition_order: u32,
+    parameters: Vec<u32>,
+    bits: u64,
+}
+
+fn plan_residual(residuals: &[i64], block_size: usize, order: usize) -> ResidualPlan {
+    let mut best: Option<ResidualPlan> = None;
+    for partition_order in 0..=MAX_PARTITION_ORDER {
+        let partitions = 1usize << partition_order;
+        if !block_size.is_multiple_of(partitions) || block_size / partitions <= order {
+            break;
+        }
+        let partition_len = block_size / partitions;
+        let mut parameters = Vec::with_capacity(partitions);
+        let mut bits = 0u64;
+        let mut start = 0;
+        for partition in 0..partitions {
+            let len = if partition == 0 {
+                partition_len - order
+            } else {
+                partition_len
+            };
+            let (k, partition_bits) = best_rice_parameter(&residuals[start..start + len], 30);
+            parameters.push(k);
+            bits += partition_bits;
+            start += len;
+        }
+        let method = u64::from(parameters.iter().any(|k| *k > 14));
+        bits += partitions as u64 * if method == 0 { 4 } else { 5 };
+        if best.as_ref().is_none_or(|plan| bits < plan.bits) {
+            best = Some(ResidualPlan {
+                method,
+                partition_order,
+                parameters,
+                bits,
+            });
+        }
+    }
+    best.unwrap_or(ResidualPlan {
+        method: 1,
+        partition_order: 0,
+        parameters: vec![best_rice_parameter(residuals, 30).0],
+        bits: u64::MAX,
+    })
+}
+
+enum Subframe {
+    Constant(i64),
+    Verbatim,
+    Fixed {
+        order: usize,
+        residuals: Vec<i64>,
+        plan: ResidualPlan,
+    },
+}
+
+fn plan_subframe(samples: &[i64], bits_per_sample: u32) -> (Subframe, u64) {
+    if samples.iter().all(|sample| *sample == samples[0]) {
+        return (Subframe::Constant(samples[0]), 8 + bits_per_sample as u64);
+    }
+    let verbatim_bits = 8 + samples.len() as u64 * bits_per_sample as u64;
+    let mut best = (Subframe::Verbatim, verbatim_bits);
+    let mut residuals = Vec::with_capacity(samples.len());
+    for order in 0..=MAX_FIXED_ORDER.min(samples.len().saturating_sub(1)) {
+        fixed_residual(samples, order, &mut residuals);
+        let plan = plan_residual(&residuals, samples.len(), order);
+        let bits = 8 + order as u64 * bits_per_sample as u64 + 6 + plan.bits;
+        if bits < best.1 {
+            best = (
+                Subframe::Fixed {
+                    order,
+                    residuals: residuals.clone(),
+                    plan,
+                },
+                bits,
+            );
+        }
+    }
+    best
+}
+
+fn write_subframe(bits: &mut BitWriter, samples: &[i64], subframe: &Subframe, sample_bits: u32) {
+    match subframe {
+        Subframe::Constant(value) => {
+            bits.write(8, 0b0000_0000);
+            bits.write_signed(sample_bits, *value);
+        }
+        Subframe::Verbatim => {
+            bits.write(8, 0b0000_0010);
+            for sample in samples {
+                bits.write_signed(sample_bits, *sample);
+            }
+        }
+        Subframe::Fixed {
+            order,
+            residuals,
+            plan,
+        } => {
+            bits.write(8, (0b0000_1000 | *order as u64) << 1);
+            for sample in &samples[..*order] {
+                bits.write_signed(sample_bits, *sample);
+            }
+            bits.write(2, plan.method);
+            bits.write(4, plan.partition_order as u64);
+            let parameter_bits = if plan.method == 0 { 4 } else { 5 };
+            let partition_len = samples.len() >> plan.partition_order;
+            let mut start = 0;
+            for (partition, k) in plan.parameters.iter().enumerate() {
+                let len = if partition == 0 {
+                    partition_len - order
+                } else {
+                    partition_len
+                };
+                bits.write(parameter_bits, *k as u64);
+                for residual in &residuals[start..start + len] {
+                    let value = zigzag(*residual);
+                    bits.write_unary_zeros(value >> k);
+                    bits.write(*k, value);
+                }
+                start += len;
+            }
+        }
+    }
+}
+
+/// Encodes one frame from per-channel integer samples.
+fn encode_frame(
+    channels: &[Vec<i64>],
+    frame_number: u64,
+    sample_rate: u32,
+    bits_per_sample: u32,
+) -> Vec<u8> {
+    let block_size = channels[0].len();
+    let mut bits = BitWriter::new();
+    bits.write(16, 0xFFF8);
+    let block_code = match block_size {
+        192 => 1,
+        576 | 1152 | 2304 | 4608 => 2 + (block_size / 576).trailing_zeros() as u64,
+        256 | 512 | 1024 | 2048 | 4096 | 8192 | 16384 | 32768 => {
+            8 + (block_size / 256).trailing_zeros() as u64
+        }
+        size if size <= 256 => 6,
+        _ => 7,
+    };
+    bits.write(4, block_code);
+    bits.write(4, sample_rate_code(sample_rate));
+
+    // Pick the cheapest of independent, left/side, right/side and mid/side.
+    let mut assignment = channels.len() as u64 - 1;
+    let mut planned: Vec<(Vec<i64>, Subframe, u32)> = Vec::new();
+    if channels.len() == 2 {
+        let (left, right) = (&channels[0], &channels[1]);
+        let side: Vec<i64> = left.iter().zip(right).map(|(l, r)| l - r).collect();
+        let mid: Vec<i64> = left.iter().zip(right).map(|(l, r)| (l + r) >> 1).collect();
+        let (left_plan, left_bits) = plan_subframe(left, bits_per_sample);
+        let (right_plan, right_bits) = plan_subframe(right, bits_per_sample);
+        let (side_plan, side_bits) = plan_subframe(&side, bits_per_sample + 1);
+        let (mid_plan, mid_bits) = plan_subframe(&mid, bits_per_sample);
+        let options = [
+            left_bits + right_bits,
+            left_bits + side_bits,
+            side_bits + right_bits,
+            mid_bits + side_bits,
+        ];
+        let choice = (0..4).min_by_key(|index| options[*index]).unwrap_or(0);
+        let s = bits_per_sample;
+        planned = match choice {
+            1 => {
+                assignment = 8;
+                vec![(left.clone(), left_plan, s), (side, side_plan, s + 1)]
+            }
+            2 => {
+                assignment = 9;
+                vec![(side, side_plan, s + 1), (right.clone(), right_plan, s)]
+            }
+            3 => {
+                assignment = 10;
+                vec![(mid, mid_plan, s), (side, side_plan, s + 1)]
+            }
+            _ => vec![(left.clone(), left_plan, s), (right.clone(), right_plan, s)],
+        };
+    } else {
+        for channel in channels {
+            let (plan, _) = plan_subframe(channel, bits_per_sample);
+            planned.push((channel.clone(), plan, bits_per_sample));
+        }
+    }
+
+    bits.write(4, assignment);
+    bits.write(3, sample_size_code(bits_per_sample));
+    bits.write(1, 0);
+    write_frame_number(&mut bits, frame_number);
+    match block_code {
+        6 => bits.write(8, block_size as u64 - 1),
+        7 => bits.write(16, block_size as u64 - 1),
+        _ => {}
+    }
+    debug_assert_eq!(bits.bit_count, 0);
+    let header_crc = crc8(&bits.bytes);
+    bits.write(8, header_crc as u64);
+
+    for (samples, subframe, sample_bits) in &planned {
+        write_subframe(&mut bits, samples, subframe, *sample_bits);
+    }
+    let mut bytes = bits.into_bytes();
+    let footer = crc16(&bytes);
+    bytes.extend_from_slice(&footer.to_be_bytes());
+    bytes
+}
+
+pub(super) struct FlacStreamWriter {
+    file: BufWriter<File>,
+    sample_rate: u32,
+    channels: usize,
+    bits_per_sample: u32,
+    pending: Vec<Vec<i64>>,
+    next_channel: usize,
+    frame_number: u64,
+    total_frames: u64,
+    min_frame_bytes: u32,
+    max_frame_bytes: u32,
+}
+
+impl FlacStreamWriter {
+    pub fn create(
+        path: &str,
+        sample_rate: u32,
+        channels: u16,
+        bits_per_sample: u32,
+    ) -> Result<Self, String> {
+        if !(1..=8).contains(&channels) {
+            return Err(format!("FLAC supports 1–8 channels, got {channels}"));
+        }
+        let file = File::create(path)
+            .map_err(|error| format!("Failed to create FLAC file {path}: {error}"))?;
+        let mut writer = Self {
+            file: BufWriter::new(file),
+            sample_rate,
+            channels: channels as usize,
+            bits_per_sample,
+            pending: vec![Vec::with_capacity(FLAC_BLOCK_SIZE); channels as usize],
+            next_channel: 0,
+            frame_number: 0,
+            total_frames: 0,
+            min_frame_bytes: 0,
+            max_frame_bytes: 0,
+        };
+        writer
+            .file
+            .write_all(b"fLaC")
+            .and_then(|_| writer.file.write_all(&[0x80, 0, 0, 34]))
+            .and_then(|_| writer.file.write_all(&writer.stream_info()))
+            .map_err(|error| format!("Failed to write FLAC header: {error}"))?;
+        Ok(writer)
+    }
+
+    fn stream_info(&self) -> [u8; 34] {
+        // Every frame but the last is FLAC_BLOCK_SIZE, which the spec allows
+        // to be advertised as both the minimum and maximum block size.
+        let mut bits = BitWriter::new();
+        bits.write(16, FLAC_BLOCK_SIZE as u64);
+        bits.write(16, FLAC_BLOCK_SIZE as u64);
+        bits.write(24, self.min_frame_bytes as u64);
+        bits.write(24, self.max_frame_bytes as u64);
+        bits.write(20, self.sample_rate as u64);
+        bits.write(3, self.channels as u64 - 1);
+        bits.write(5, self.bits_per_sample as u64 - 1);
+        bits.write(4, self.total_frames >> 32);
+        bits.write(32, self.total_frames & 0xFFFF_FFFF);
+        // MD5 left zeroed: "not computed" per the spec.
+        bits.write(32, 0);
+        bits.write(32, 0);
+        bits.write(32, 0);
+        bits.write(32, 0);
+        let mut info = [0u8; 34];
+        info.copy_from_slice(&bits.into_bytes());
+        info
+    }
+
+    pub fn frames_written(&self) -> u64 {
+        self.total_frames
+    }
+
+    /// Interleaved samples in [-1, 1]; partial frames are carried over.
+    pub fn write_interleaved(&mut self, samples: &[f32]) -> Result<(), String> {
+        let scale = ((1i64 << (self.bits_per_sample - 1)) - 1) as f32;
+        let (minimum, maximum) = (-(scale as i64) - 1, scale as i64);
+        for sample in samples {
+            let value = ((sample * scale).round() as i64).clamp(minimum, maximum);
+            self.pending[self.next_channel].push(value);
+            self.next_channel = (self.next_channel + 1) % self.channels;
+            if self.next_channel == 0 && self.pending[0].len() == FLAC_BLOCK_SIZE {
+                self.flush_block()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_block(&mut self) -> Result<(), String> {
+        let block_size = self.pending[self.channels - 1].len();
+        if block_size == 0 {
+            return Ok(());
+        }
+        let block: Vec<Vec<i64>> = self
+            .pending
+            .iter_mut()
+            .map(|channel| channel.drain(..block_size).collect())
+            .collect();
+        let frame = encode_frame(
+            &block,
+            self.frame_number,
+            self.sample_rate,
+            self.bits_per_sample,
+        );
+        self.file
+            .write_all(&frame)
+            .map_err(|error| format!("Failed to write FLAC frame: {error}"))?;
+        let frame_bytes = frame.len() as u32;
+        self.min_frame_bytes = if self.frame_number == 0 {
+            frame_bytes
+        } else {
+            self.min_frame_bytes.min(frame_bytes)
+        };
+        self.max_frame_bytes = self.max_frame_bytes.max(frame_bytes);
+        self.frame_number += 1;
+        self.total_frames += block_size as u64;
+        Ok(())
+    }
+
+    fn rewrite_stream_info(&mut self) -> Result<(), String> {
+        let info = self.stream_info();
+        let file = self.file.get_mut();
+        let position = file
+            .stream_position()
+            .map_err(|error| format!("Failed to checkpoint FLAC: {error}"))?;
+        file.seek(SeekFrom::Start(STREAMINFO_OFFSET))
+            .and_then(|_| file.write_all(&info))
+            .and_then(|_| file.seek(SeekFrom::Start(position)))
+            .map(|_| ())
+            .map_err(|error| format!("Failed to checkpoint FLAC: {error}"))
+    }
+
+    /// Flushes encoded frames and patches STREAMINFO without closing.
+    pub fn checkpoint(&mut self) -> Result<(), String> {
+        self.file
+            .flush()
+            .map_err(|error| format!("Failed to flush FLAC: {error}"))?;
+        self.rewrite_stream_info()?;
+        self.file
+            .get_ref()
+            .sync_data()
+            .map_err(|error| format!("Failed to sync FLAC: {error}"))
+    }
+
+    pub fn finish(mut self) -> Result<(), String> {
+        // Drop a trailing partial frame (ring-buffer pops are not frame aligned).
+        let complete = self.pending[self.channels - 1].len();
+        for channel in &mut self.pending {
+            channel.truncate(complete);
+        }
+        self.flush_block()?;
+        self.checkpoint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc16, crc8, write_frame_number, BitWriter, FlacStreamWriter, FLAC_BLOCK_SIZE};
+    use crate::audio_engine::playback::decode_static_sound_data;
+
+    #[test]
+    fn crcs_match_reference_check_values() {
+        assert_eq!(crc8(b"123456789"), 0xF4);
+        assert_eq!(crc16(b"123456789"), 0xFEE8);
+    }
+
+    #[test]
+    fn frame_numbers_use_utf8_style_coding() {
+        let encode = |number| {
+            let mut bits = BitWriter::new();
+            write_frame_number(&mut bits, number);
+            bits.into_bytes()
+        };
+        assert_eq!(encode(0x7F), vec![0x7F]);
+        assert_eq!(encode(0x80), vec![0xC2, 0x80]);
+        assert_eq!(encode(0x800), vec![0xE0, 0xA0, 0x80]);
+    }
+
+    #[test]
+    fn stream_header_reports_frames_and_compresses_tones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.flac");
+        let mut writer = FlacStreamWriter::create(path.to_str().unwrap(), 48_000, 2, 16).unwrap();
+        let frames = FLAC_BLOCK_SIZE * 3 + 100;
+        let samples: Vec<f32> = (0..frames)
+            .flat_map(|index| {
+                let phase = index as f32 * 440.0 * std::f32::consts::TAU / 48_000.0;
+                [phase.sin() * 0.5, phase.sin() * 0.25]
+            })
+            .collect();
+        writer.write_interleaved(&samples[..1001]).unwrap();
+        writer.write_interleaved(&samples[1001..]).unwrap();
+        assert_eq!(writer.frames_written(), (FLAC_BLOCK_SIZE * 3) as u64);
+        writer.finish().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], b"fLaC");
+        let total = u64::from_be_bytes([
+            0,
+            0,
+            0,
+            bytes[21] & 0x0F,
+            bytes[22],
+            bytes[23],
+            bytes[24],
+            bytes[25],
+        ]);
+        assert_eq!(total, frames as u64);
+        assert!(bytes.len() < frames * 4 / 2, "len={}", bytes.len());
+    }
+
+    #[test]
+    fn recorded_tone_decodes_bit_exact_through_the_clip_loader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.flac");
+        let mut writer = FlacStreamWriter::create(path.to_str().unwrap(), 48_000, 2, 16).unwrap();
+        let frames = FLAC_BLOCK_SIZE * 2 + 300;
+        let samples: Vec<f32> = (0..frames)
+            .flat_map(|index| {
+                let phase = index as f32 * 440.0 * std::f32::consts::TAU / 48_000.0;
+                [phase.sin() * 0.8, phase.cos() * -0.3]
+            })
+            .collect();
+        writer.write_interleaved(&samples).unwrap();
+        writer.finish().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let sound = decode_static_sound_data(bytes, Some("flac"), "take.flac").unwrap();
+        assert_eq!(sound.sample_rate, 48_000);
+        assert_eq!(sound.frames.len(), frames);
+        // 16-bit PCM is exact in f32, so the decoded samples must match the
+        // writer's quantization one for one.
+        let quantize = |sample: f32| (sample * 32_767.0).round().clamp(-32_768.0, 32_767.0);
+        for (index, frame) in sound.frames.iter().enumerate() {
+            assert_eq!(
+                frame.left * 32_768.0,
+                quantize(samples[index * 2]),
+                "frame {index}"
+            );
+            assert_eq!(
+                frame.right * 32_768.0,
+                quantize(samples[index * 2 + 1]),
+                "frame {index}"
+            );
+        }
+    }
+}
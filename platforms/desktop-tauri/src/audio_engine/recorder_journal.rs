@@ -0,0 +1,476 @@
+// Crash safety for recordings
+// While a take is running the writer thread periodically patches the RIFF
+// sizes and rewrites a sidecar journal (`<file>.recording.json`). A clean
+// stop removes the journal, so any journal found at startup belongs to a
+// session that died; the scan repairs its WAV header from the file length
+// (FLAC and Ogg/Opus takes are already playable up to the last checkpoint).
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub(super) const JOURNAL_SUFFIX: &str = ".recording.json";
+/// How often the writer thread patches the header and journal.
+pub(super) const HEADER_FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_SCAN_DEPTH: usize = 8;
+
+static RECOVERED: Mutex<Vec<RecoveredRecording>> = Mutex::new(Vec::new());
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub(super) fn journal_path(wav_path: &Path) -> PathBuf {
+    let mut name = wav_path.as_os_str().to_owned();
+    name.push(JOURNAL_SUFFIX);
+    PathBuf::from(name)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(super) struct RecordingJournal {
+    pub session_id: String,
+    pub file_path: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub output_format: String,
+    pub started_at_ms: u64,
+    pub updated_at_ms: u64,
+    pub frames_written: u64,
+}
+
+impl RecordingJournal {
+    pub fn new(
+        session_id: &str,
+        file_path: &str,
+        sample_rate: u32,
+        channels: u16,
+        output_format: String,
+    ) -> Self {
+        let now = unix_millis();
+        Self {
+            session_id: session_id.to_string(),
+            file_path: file_path.to_string(),
+            sample_rate,
+            channels,
+            output_format,
+            started_at_ms: now,
+            updated_at_ms: now,
+            frames_written: 0,
+        }
+    }
+
+    /// Atomically replaces the sidecar so a crash never leaves half a journal.
+    pub fn write(&mut self, frames_written: u64) -> Result<(), String> {
+        self.frames_written = frames_written;
+        self.updated_at_ms = unix_millis();
+        let path = journal_path(Path::new(&self.file_path));
+        let temp_path = path.with_extension("json.tmp");
+        let encoded = serde_json::to_vec_pretty(self)
+            .map_err(|error| format!("Failed to encode recording journal: {error}"))?;
+        fs::write(&temp_path, encoded).map_err(|error| {
+            format!(
+                "Failed to write recording journal {}: {error}",
+                temp_path.display()
+            )
+        })?;
+        fs::rename(&temp_path, &path).map_err(|error| {
+            format!(
+                "Failed to commit recording journal {}: {error}",
+                path.display()
+            )
+        })
+    }
+
+    pub fn remove(&self) {
+        let _ = fs::remove_file(journal_path(Path::new(&self.file_path)));
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct WavRepair {
+    pub data_bytes: u64,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub block_align: u16,
+    pub patched: bool,
+}
+
+fn read_u32_at(file: &mut fs::File, offset: u64) -> Result<u32, String> {
+    let mut bytes = [0u8; 4];
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.read_exact(&mut bytes))
+        .map_err(|error| format!("Failed to read WAV header: {error}"))?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn write_u32_at(file: &mut fs::File, offset: u64, value: u32) -> Result<(), String> {
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.write_all(&value.to_le_bytes()))
+        .map_err(|error| format!("Failed to patch WAV header: {error}"))
+}
+
+/// Rewrites the RIFF and `data` sizes from the actual file length and drops a
+/// trailing partial frame. Chunks before `data` are walked, not assumed, so
+/// both plain PCM and WAVE_FORMAT_EXTENSIBLE headers from hound are handled.
+pub(super) fn repair_wav_header(path: &Path) -> Result<WavRepair, String> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(|error| format!("Failed to open {} for repair: {error}", path.display()))?;
+    let file_len = file
+        .metadata()
+        .map_err(|error| format!("Failed to stat {}: {error}", path.display()))?
+        .len();
+    let mut riff = [0u8; 12];
+    file.read_exact(&mut riff)
+        .map_err(|error| format!("Failed to read WAV header: {error}"))?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        return Err(format!("{} is not a RIFF/WAVE file", path.display()));
+    }
+
+    let mut format: Option<(u16, u32, u16)> = None;
+    let mut offset = 12u64;
+    let data_offset = loop {
+        if offset + 8 > file_len {
+            return Err(format!("{} has no data chunk", path.display()));
+        }
+        let mut header = [0u8; 8];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut header))
+            .map_err(|error| format!("Failed to read WAV chunk: {error}"))?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+        match &header[0..4] {
+            b"data" => break offset + 8,
+            b"fmt " => {
+                let mut body = [0u8; 16];
+                file.read_exact(&mut body)
+                    .map_err(|error| format!("Failed to read WAV fmt chunk: {error}"))?;
+                format = Some((
+                    u16::from_le_bytes([body[2], body[3]]),
+                    u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+                    u16::from_le_bytes([body[12], body[13]]),
+                ));
+            }
+            _ => {}
+        }
+        offset += 8 + size + (size & 1);
+    };
+    let (channels, sample_rate, block_align) =
+        format.ok_or_else(|| format!("{} has no fmt chunk before data", path.display()))?;
+    if block_align == 0 {
+        return Err(format!(
+            "{} declares a zero block alignment",
+            path.display()
+        ));
+    }
+
+    let available = file_len - data_offset;
+    let max_data = (u32::MAX as u64 - (data_offset - 8)) / block_align as u64 * block_align as u64;
+    let data_bytes = (available - available % block_align as u64).min(max_data);
+    let riff_size = (data_offset - 8 + data_bytes) as u32;
+    let mut patched = false;
+    if data_offset + data_bytes < file_len {
+        file.set_len(data_offset + data_bytes)
+            .map_err(|error| format!("Failed to truncate {}: {error}", path.display()))?;
+        patched = true;
+    }
+    if read_u32_at(&mut file, 4)? != riff_size {
+        write_u32_at(&mut file, 4, riff_size)?;
+        patched = true;
+    }
+    if read_u32_at(&mut file, data_offset - 4)? != data_bytes as u32 {
+        write_u32_at(&mut file, data_offset - 4, data_bytes as u32)?;
+        patched = true;
+    }
+    if patched {
+        file.sync_all()
+            .map_err(|error| format!("Failed to sync {}: {error}", path.display()))?;
+    }
+    Ok(WavRepair {
+        data_bytes,
+        sample_rate,
+        channels,
+        block_align,
+        patched,
+    })
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RecoveredRecording {
+    pub session_id: String,
+    pub file_path: String,
+    pub journal_path: String,
+    pub recovered: bool,
+    pub header_patched: bool,
+    pub frame_count: u64,
+    pub duration_sec: f64,
+    pub size_bytes: u64,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub output_format: String,
+    pub started_at_ms: u64,
+    pub last_journal_update_ms: u64,
+    pub error: Option<String>,
+}
+
+fn recover_journal(journal_file: &Path) -> Result<RecoveredRecording, String> {
+    let bytes = fs::read(journal_file).map_err(|error| {
+        format!(
+            "Failed to read recording journal {}: {error}",
+            journal_file.display()
+        )
+    })?;
+    let journal: RecordingJournal = serde_json::from_slice(&bytes).map_err(|error| {
+        format!(
+            "Invalid recording journal {}: {error}",
+            journal_file.display()
+        )
+    })?;
+    let mut entry = RecoveredRecording {
+        session_id: journal.session_id.clone(),
+        file_path: journal.file_path.clone(),
+        journal_path: journal_file.to_string_lossy().to_string(),
+        recovered: false,
+        header_patched: false,
+        frame_count: 0,
+        duration_sec: 0.0,
+        size_bytes: 0,
+        sample_rate: journal.sample_rate,
+        channels: journal.channels,
+        output_format: journal.output_format.clone(),
+        started_at_ms: journal.started_at_ms,
+        last_journal_update_ms: journal.updated_at_ms,
+        error: None,
+    };
+    // The journal lives next to its file; prefer that location if the tree moved.
+    let sibling = journal_file
+        .to_string_lossy()
+        .strip_suffix(JOURNAL_SUFFIX)
+        .map(PathBuf::from);
+    let wav_path = sibling
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(&journal.file_path));
+    entry.file_path = wav_path.to_string_lossy().to_string();
+
+    // FLAC STREAMINFO and Ogg pages are brought up to date at every
+    // checkpoint and decoders stop at a truncated trailing frame or page, so
+    // only WAV headers need patching; the journal supplies the frame count.
+    if matches!(journal.output_format.as_str(), "Flac" | "Opus") {
+        match fs::metadata(&wav_path) {
+            Ok(metadata) => {
+                entry.size_bytes = metadata.len();
+                entry.recovered = metadata.len() > 0 && journal.frames_written > 0;
+                entry.frame_count = journal.frames_written;
+                entry.duration_sec = entry.frame_count as f64 / journal.sample_rate.max(1) as f64;
+                remove_journal(journal_file)?;
+            }
+            Err(error) => {
+                entry.error = Some(format!(
+                    "Failed to inspect recording {}: {error}",
+                    wav_path.display()
+                ))
+            }
+        }
+        return Ok(entry);
+    }
+
+    match repair_wav_header(&wav_path) {
+        Ok(repair) => {
+            let block_align = repair.block_align.max(1) as u64;
+            entry.recovered = repair.data_bytes > 0;
+            entry.header_patched = repair.patched;
+            entry.frame_count = repair.data_bytes / block_align;
+            entry.sample_rate = repair.sample_rate;
+            entry.channels = repair.channels;
+            entry.duration_sec = entry.frame_count as f64 / repair.sample_rate.max(1) as f64;
+            entry.size_bytes = fs::metadata(&wav_path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0);
+            remove_journal(journal_file)?;
+        }
+        Err(error) => entry.error = Some(error),
+    }
+    Ok(entry)
+}
+
+fn remove_journal(journal_file: &Path) -> Result<(), String> {
+    fs::remove_file(journal_file).map_err(|error| {
+        format!(
+            "Failed to remove recording journal {}: {error}",
+            journal_file.display()
+        )
+    })
+}
+
+fn collect_journals(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if depth < MAX_SCAN_DEPTH && !name.starts_with('.') && name != "node_modules" {
+                collect_journals(&path, depth + 1, found);
+            }
+        } else if name.ends_with(JOURNAL_SUFFIX) {
+            found.push(path);
+        }
+    }
+}
+
+/// Repairs every orphaned take under `root`. `claim` is asked once per
+/// journal and returns `None` for sessions still recording in this process;
+/// otherwise the guard it returns is held while that journal is repaired, so
+/// a session cannot start on the same file mid-repair.
+pub(super) fn recover_orphaned_recordings<G>(
+    root: &Path,
+    claim: impl Fn(&Path) -> Option<G>,
+) -> Vec<RecoveredRecording> {
+    let mut journals = Vec::new();
+    collect_journals(root, 0, &mut journals);
+    journals.sort();
+    let recovered: Vec<RecoveredRecording> = journals
+        .into_iter()
+        .filter_map(|journal| {
+            let _guard = claim(&journal)?;
+            Some(
+                recover_journal(&journal).unwrap_or_else(|error| RecoveredRecording {
+                    session_id: String::new(),
+                    file_path: String::new(),
+                    journal_path: journal.to_string_lossy().to_string(),
+                    recovered: false,
+                    header_patched: false,
+                    frame_count: 0,
+                    duration_sec: 0.0,
+                    size_bytes: 0,
+                    sample_rate: 0,
+                    channels: 0,
+                    output_format: String::new(),
+                    started_at_ms: 0,
+                    last_journal_update_ms: 0,
+                    error: Some(error),
+                }),
+            )
+        })
+        .collect();
+    if let Ok(mut report) = RECOVERED.lock() {
+        report.extend(recovered.iter().cloned());
+    }
+    recovered
+}
+
+/// Takes reported by startup scans; `acknowledge` clears the list once the UI
+/// has shown them.
+pub(super) fn recovered_recordings(acknowledge: bool) -> Vec<RecoveredRecording> {
+    let Ok(mut report) = RECOVERED.lock() else {
+        return Vec::new();
+    };
+    if acknowledge {
+        std::mem::take(&mut *report)
+    } else {
+        report.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{journal_path, recover_orphaned_recordings, repair_wav_header, RecordingJournal};
+    use std::fs::{self, OpenOptions};
+    use std::io::{Seek, SeekFrom, Write};
+
+    fn write_wav(path: &std::path::Path, spec: hound::WavSpec, frames: usize) {
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for index in 0..frames * spec.channels as usize {
+            writer.write_sample((index % 1000) as i32).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    /// Zeroes both size fields like a take that never reached `finalize`.
+    fn simulate_crash(path: &std::path::Path, data_size_offset: u64, trailing: &[u8]) {
+        let mut file = OpenOptions::new().write(true).open(path).unwrap();
+        file.seek(SeekFrom::Start(4)).unwrap();
+        file.write_all(&0u32.to_le_bytes()).unwrap();
+        file.seek(SeekFrom::Start(data_size_offset)).unwrap();
+        file.write_all(&0u32.to_le_bytes()).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+        file.write_all(trailing).unwrap();
+    }
+
+    fn data_size_offset(path: &std::path::Path) -> u64 {
+        let bytes = fs::read(path).unwrap();
+        bytes
+            .windows(4)
+            .position(|window| window == b"data")
+            .unwrap() as u64
+            + 4
+    }
+
+    #[test]
+    fn repairs_zeroed_header_and_drops_partial_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48_000,
+            bits_per_sample: 24,
+            sample_format: hound::SampleFormat::Int,
+        };
+        write_wav(&path, spec, 1_000);
+        simulate_crash(&path, data_size_offset(&path), &[1, 2, 3]);
+
+        let repair = repair_wav_header(&path).unwrap();
+        assert!(repair.patched);
+        assert_eq!(repair.block_align, 6);
+        assert_eq!(repair.data_bytes, 6_000);
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.duration(), 1_000);
+        assert!(!repair_wav_header(&path).unwrap().patched);
+    }
+
+    #[test]
+    fn startup_scan_recovers_orphans_and_skips_active_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let recordings = dir.path().join("users").join("u1").join("recordings");
+        fs::create_dir_all(&recordings).unwrap();
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let orphan = recordings.join("orphan.wav");
+        let active = recordings.join("active.wav");
+        for path in [&orphan, &active] {
+            write_wav(path, spec, 16_000);
+            simulate_crash(path, data_size_offset(path), &[]);
+            RecordingJournal::new("s", &path.to_string_lossy(), 16_000, 1, "Int16".into())
+                .write(8_000)
+                .unwrap();
+        }
+
+        let report = recover_orphaned_recordings(dir.path(), |journal| {
+            (journal != journal_path(&active).as_path()).then_some(())
+        });
+        assert_eq!(report.len(), 1);
+        assert!(report[0].recovered);
+        assert!(report[0].header_patched);
+        assert_eq!(report[0].frame_count, 16_000);
+        assert_eq!(report[0].duration_sec, 1.0);
+        assert!(!journal_path(&orphan).exists());
+        assert!(journal_path(&active).exists());
+        assert_eq!(hound::WavReader::open(&orphan).unwrap().duration(), 16_000);
+    }
+}
@@ -0,0 +1,151 @@
+// Recording markers
+// Markers dropped during a take are kept on the session and, for WAV output,
+// appended after the `data` chunk as a `cue ` chunk plus a `LIST`/`adtl` chunk
+// carrying one `labl` per cue, the layout DAWs and sample editors read.
+// FLAC and Ogg/Opus headers are written up front, so for those formats the
+// markers are only reported in `RecordResult`.
+
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RecordMarker {
+    pub id: u32,
+    pub label: String,
+    /// Frame offset in the written file (pre-roll included, pauses excluded).
+    pub frame: u64,
+    pub time_sec: f64,
+}
+
+fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(8 + body.len() + 1);
+    bytes.extend_from_slice(id);
+    bytes.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn cue_chunks(markers: &[RecordMarker]) -> Vec<u8> {
+    let mut cue = Vec::with_capacity(4 + markers.len() * 24);
+    cue.extend_from_slice(&(markers.len() as u32).to_le_bytes());
+    let mut adtl = b"adtl".to_vec();
+    for marker in markers {
+        let position = u32::try_from(marker.frame).unwrap_or(u32::MAX);
+        cue.extend_from_slice(&marker.id.to_le_bytes());
+        cue.extend_from_slice(&position.to_le_bytes());
+        cue.extend_from_slice(b"data");
+        cue.extend_from_slice(&0u32.to_le_bytes());
+        cue.extend_from_slice(&0u32.to_le_bytes());
+        cue.extend_from_slice(&position.to_le_bytes());
+
+        let mut label = marker.id.to_le_bytes().to_vec();
+        label.extend_from_slice(marker.label.as_bytes());
+        label.push(0);
+        adtl.extend_from_slice(&chunk(b"labl", &label));
+    }
+    let mut bytes = chunk(b"cue ", &cue);
+    bytes.extend_from_slice(&chunk(b"LIST", &adtl));
+    bytes
+}
+
+/// Appends cue/label chunks to a finalized WAV and patches the RIFF size.
+pub(super) fn append_wav_markers(path: &Path, markers: &[RecordMarker]) -> Result<(), String> {
+    if markers.is_empty() {
+        return Ok(());
+    }
+    let error =
+        |error: std::io::Error| format!("Failed to write markers to {}: {error}", path.display());
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(error)?;
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header).map_err(error)?;
+    if &header[..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(format!("{} is not a RIFF/WAVE file", path.display()));
+    }
+    // hound does not pad an odd-sized data chunk; chunks must start on an
+    // even offset.
+    let mut length = file.seek(SeekFrom::End(0)).map_err(error)?;
+    if length % 2 == 1 {
+        file.write_all(&[0]).map_err(error)?;
+        length += 1;
+    }
+    let chunks = cue_chunks(markers);
+    file.write_all(&chunks).map_err(error)?;
+    let riff_size = u32::try_from(length + chunks.len() as u64 - 8)
+        .map_err(|_| format!("{} is too large for RIFF markers", path.display()))?;
+    file.seek(SeekFrom::Start(4)).map_err(error)?;
+    file.write_all(&riff_size.to_le_bytes()).map_err(error)?;
+    file.flush().map_err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_wav_markers, RecordMarker};
+
+    fn find_chunk<'a>(bytes: &'a [u8], id: &[u8; 4]) -> Option<&'a [u8]> {
+        let mut offset = 12;
+        while offset + 8 <= bytes.len() {
+            let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap());
+            let body = &bytes[offset + 8..offset + 8 + size as usize];
+            if &bytes[offset..offset + 4] == id {
+                return Some(body);
+            }
+            offset += 8 + size as usize + (size as usize & 1);
+        }
+        None
+    }
+
+    #[test]
+    fn markers_land_in_cue_and_label_chunks_of_a_readable_wav() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 48_000,
+            bits_per_sample: 24,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for index in 0..4_801 {
+            writer.write_sample(index).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let markers = vec![
+            RecordMarker {
+                id: 1,
+                label: "question".to_string(),
+                frame: 480,
+                time_sec: 0.01,
+            },
+            RecordMarker {
+                id: 2,
+                label: "answer".to_string(),
+                frame: 4_000,
+                time_sec: 4_000.0 / 48_000.0,
+            },
+        ];
+        append_wav_markers(&path, &markers).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let riff_size = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_size, bytes.len() - 8);
+        let cue = find_chunk(&bytes, b"cue ").unwrap();
+        assert_eq!(u32::from_le_bytes(cue[..4].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(cue[28..32].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(cue[48..52].try_into().unwrap()), 4_000);
+        let list = find_chunk(&bytes, b"LIST").unwrap();
+        assert_eq!(&list[..4], b"adtl");
+        assert_eq!(&list[16..24], b"question");
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.len(), 4_801);
+    }
+}
@@ -0,0 +1,251 @@
+// Live input monitoring
+// A second CPAL stream on the default output device plays the processed input
+// back while recording. The input callback pushes into a small ring buffer and
+// the output callback drains it, dropping whatever is older than
+// `MAX_LATENCY_SEC` so a slow start or a device hiccup cannot build up delay.
+// The output stream is only opened once monitoring is first enabled, at the
+// output device's own rate and sample format: the output callback resamples
+// linearly from the input rate, duplicates mono input to every output channel
+// and converts to i16/u16 when the device does not take f32.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{SampleFormat, StreamConfig};
+use ringbuf::{traits::*, HeapCons, HeapProd, HeapRb};
+
+macro_rules! eprintln {
+    ($($arg:tt)*) => {
+        if crate::runtime_logging::xcode_logs_enabled() {
+            std::eprintln!($($arg)*);
+        }
+    };
+}
+
+const MAX_LATENCY_SEC: f32 = 0.03;
+const RING_SEC: f32 = 0.25;
+
+/// Input side of the monitor path, owned by the input callback.
+pub(super) struct MonitorSender {
+    producer: HeapProd<f32>,
+}
+
+impl MonitorSender {
+    /// Pushes processed samples scaled by `gain`; drops them when the output
+    /// side is not keeping up or has not been opened yet.
+    pub(super) fn send(&mut self, samples: &[f32], gain: f32) {
+        if self.producer.vacant_len() < samples.len() {
+            return;
+        }
+        for &sample in samples {
+            let _ = self.producer.try_push(sample * gain);
+        }
+    }
+}
+
+/// Output side of the monitor path, held by the recorder thread until the
+/// output stream is opened.
+pub(super) struct MonitorReceiver {
+    consumer: HeapCons<f32>,
+    sample_rate: u32,
+    channels: usize,
+}
+
+/// Creates the ring between the input callback and the (not yet opened)
+/// monitor output, sized for the input format.
+pub(super) fn channel(sample_rate: u32, channels: u16) -> (MonitorSender, MonitorReceiver) {
+    let channels = usize::from(channels.max(1));
+    let ring_samples = ((sample_rate as f32 * RING_SEC) as usize * channels).max(4096);
+    let (producer, consumer) = HeapRb::<f32>::new(ring_samples).split();
+    (
+        MonitorSender { producer },
+        MonitorReceiver {
+            consumer,
+            sample_rate: sample_rate.max(1),
+            channels,
+        },
+    )
+}
+
+/// Output callback state: drains the ring and resamples to the output rate.
+/// Everything is allocated up front so the callback never allocates.
+struct MonitorReader {
+    consumer: HeapCons<f32>,
+    channels: usize,
+    max_latency_samples: usize,
+    /// Input frames per output frame.
+    step: f64,
+    /// Position between `previous` and `next`, in input frames.
+    position: f64,
+    previous: Vec<f32>,
+    next: Vec<f32>,
+}
+
+impl MonitorReader {
+    fn new(receiver: MonitorReceiver, output_rate: u32) -> Self {
+        let MonitorReceiver {
+            consumer,
+            sample_rate,
+            channels,
+        } = receiver;
+        Self {
+            consumer,
+            channels,
+            max_latency_samples: (sample_rate as f32 * MAX_LATENCY_SEC).round() as usize * channels,
+            step: f64::from(sample_rate) / f64::from(output_rate.max(1)),
+            // Two frames ahead, so the first output frame is the first input
+            // frame rather than interpolated silence.
+            position: 2.0,
+            previous: vec![0.0; channels],
+            next: vec![0.0; channels],
+        }
+    }
+
+    fn advance(&mut self) {
+        std::mem::swap(&mut self.previous, &mut self.next);
+        if self.consumer.pop_slice(&mut self.next) < self.channels {
+            self.next.fill(0.0);
+        }
+    }
+
+    fn fill<T>(&mut self, output: &mut [T], output_channels: usize, convert: impl Fn(f32) -> T) {
+        let queued = self.consumer.occupied_len();
+        if queued > self.max_latency_samples {
+            let excess = queued - self.max_latency_samples;
+            self.consumer.skip(excess - excess % self.channels);
+        }
+        for out_frame in output.chunks_exact_mut(output_channels) {
+            while self.position >= 1.0 {
+                self.advance();
+                self.position -= 1.0;
+            }
+            let weight = self.position as f32;
+            for (channel, sample) in out_frame.iter_mut().enumerate() {
+                let channel = channel % self.channels;
+                let (from, to) = (self.previous[channel], self.next[channel]);
+                *sample = convert(from + (to - from) * weight);
+            }
+            self.position += self.step;
+        }
+    }
+}
+
+/// Opens the monitor output at the default output device's rate, channel
+/// count and sample format. The returned stream must stay on the thread that
+/// created it (it is `!Send`), like the input stream.
+pub(super) fn open(receiver: MonitorReceiver) -> Result<cpal::Stream, String> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("No default output device found for monitoring")?;
+    let default_config = device
+        .default_output_config()
+        .map_err(|e| format!("No default output config for monitoring: {e}"))?;
+    let output_channels = default_config.channels().max(1);
+    let output_rate = default_config.sample_rate().0;
+    let mut reader = MonitorReader::new(receiver, output_rate);
+
+    let config = StreamConfig {
+        channels: output_channels,
+        sample_rate: cpal::SampleRate(output_rate),
+        buffer_size: cpal::BufferSize::Default,
+    };
+    let channels = usize::from(output_channels);
+    let err_fn = |err: cpal::StreamError| {
+        eprintln!("[audio_engine::monitor] Stream error: {err}");
+    };
+    let sample_format = default_config.sample_format();
+    let stream = match sample_format {
+        SampleFormat::F32 => device
+            .build_output_stream(
+                &config,
+                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                    reader.fill(data, channels, |sample| sample);
+                },
+                err_fn,
+                None,
+            )
+            .map_err(|e| format!("Failed to build monitor output stream: {e}"))?,
+        SampleFormat::I16 => device
+            .build_output_stream(
+                &config,
+                move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
+                    reader.fill(data, channels, f32_to_i16);
+                },
+                err_fn,
+                None,
+            )
+            .map_err(|e| format!("Failed to build monitor output stream (i16): {e}"))?,
+        SampleFormat::U16 => device
+            .build_output_stream(
+                &config,
+                move |data: &mut [u16], _: &cpal::OutputCallbackInfo| {
+                    reader.fill(data, channels, |sample| {
+                        (i32::from(f32_to_i16(sample)) + 32768) as u16
+                    });
+                },
+                err_fn,
+                None,
+            )
+            .map_err(|e| format!("Failed to build monitor output stream (u16): {e}"))?,
+        _ => {
+            return Err(format!(
+                "Unsupported monitor output sample format: {sample_format:?}"
+            ));
+        }
+    };
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start monitor output stream: {e}"))?;
+    Ok(stream)
+}
+
+/// Mirrors the input side's `/ 32768.0` conversion, clamped to full scale.
+fn f32_to_i16(sample: f32) -> i16 {
+    (sample * 32768.0).round().clamp(-32768.0, 32767.0) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{channel, f32_to_i16, MonitorReader};
+
+    #[test]
+    fn output_duplicates_mono_and_drops_stale_audio() {
+        let (mut sender, receiver) = channel(100, 1);
+        let input: Vec<f32> = (1..=10).map(|value| value as f32).collect();
+        sender.send(&input, 0.5);
+
+        // 30 ms at 100 Hz keeps the newest three samples.
+        let mut reader = MonitorReader::new(receiver, 100);
+        let mut output = [0.0f32; 8];
+        reader.fill(&mut output, 2, |sample| sample);
+        // Missing input is silence.
+        assert_eq!(output, [4.0, 4.0, 4.5, 4.5, 5.0, 5.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn output_resamples_to_the_device_rate() {
+        let (mut sender, receiver) = channel(1_000, 2);
+        let input: Vec<f32> = (0..20)
+            .flat_map(|frame| [frame as f32, -(frame as f32)])
+            .collect();
+        sender.send(&input, 1.0);
+
+        let mut reader = MonitorReader::new(receiver, 2_000);
+        let mut output = [0.0f32; 12];
+        reader.fill(&mut output, 2, |sample| sample);
+        // Twice the rate: every other output frame is interpolated.
+        assert_eq!(
+            output,
+            [0.0, 0.0, 0.5, -0.5, 1.0, -1.0, 1.5, -1.5, 2.0, -2.0, 2.5, -2.5]
+        );
+    }
+
+    #[test]
+    fn output_converts_to_integer_device_formats() {
+        let (mut sender, receiver) = channel(100, 1);
+        sender.send(&[0.5, -1.0, 2.0], 1.0);
+
+        let mut reader = MonitorReader::new(receiver, 100);
+        let mut output = [0i16; 4];
+        reader.fill(&mut output, 1, f32_to_i16);
+        assert_eq!(output, [16384, -32768, 32767, 0]);
+    }
+}
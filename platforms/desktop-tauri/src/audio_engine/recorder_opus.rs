@@ -0,0 +1,398 @@
+// Ogg/Opus writer for the recorder writer thread
+// libopus does the encoding; the Ogg framing (RFC 3533, RFC 7845) is written
+// here so pages can be flushed on the same checkpoint cadence as WAV headers.
+// Opus only takes 8/12/16/24/48 kHz, so other rates are linearly resampled
+// to 48 kHz first, and takes with more than two channels keep the first two.
+
+use opus::{Application, Bitrate, Channels, Encoder};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+pub(super) const OPUS_GRANULE_RATE: u32 = 48_000;
+const OPUS_ENCODER_RATES: [u32; 5] = [8_000, 12_000, 16_000, 24_000, 48_000];
+const OPUS_FRAMES_PER_SECOND: u32 = 50;
+const OPUS_BITRATE_PER_CHANNEL: i32 = 32_000;
+const MAX_PACKET_BYTES: usize = 4000;
+const MAX_PAGE_SEGMENTS: usize = 255;
+/// About one second of audio per page; bounds what a crash can lose.
+const PAGE_PACKETS: usize = 50;
+const HEADER_TYPE_BOS: u8 = 0x02;
+const HEADER_TYPE_EOS: u8 = 0x04;
+
+pub(super) fn ogg_crc(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |mut crc, byte| {
+        crc ^= (*byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+struct OggPageWriter {
+    file: BufWriter<File>,
+    serial: u32,
+    sequence: u32,
+    segments: Vec<u8>,
+    body: Vec<u8>,
+    granule: u64,
+}
+
+impl OggPageWriter {
+    fn new(file: File, serial: u32) -> Self {
+        Self {
+            file: BufWriter::new(file),
+            serial,
+            sequence: 0,
+            segments: Vec::with_capacity(MAX_PAGE_SEGMENTS),
+            body: Vec::new(),
+            granule: 0,
+        }
+    }
+
+    fn packet_count(&self) -> usize {
+        self.segments.iter().filter(|lace| **lace < 255).count()
+    }
+
+    fn push_packet(&mut self, packet: &[u8], granule: u64) -> Result<(), String> {
+        let laces = packet.len() / 255 + 1;
+        if self.segments.len() + laces > MAX_PAGE_SEGMENTS {
+            self.flush_page(0)?;
+        }
+        self.segments.extend(std::iter::repeat_n(255, laces - 1));
+        self.segments.push((packet.len() % 255) as u8);
+        self.body.extend_from_slice(packet);
+        self.granule = granule;
+        Ok(())
+    }
+
+    fn flush_page(&mut self, header_type: u8) -> Result<(), String> {
+        if self.segments.is_empty() && header_type & HEADER_TYPE_EOS == 0 {
+            return Ok(());
+        }
+        let mut page = Vec::with_capacity(27 + self.segments.len() + self.body.len());
+        page.extend_from_slice(b"OggS");
+        page.push(0);
+        page.push(header_type);
+        page.extend_from_slice(&self.granule.to_le_bytes());
+        page.extend_from_slice(&self.serial.to_le_bytes());
+        page.extend_from_slice(&self.sequence.to_le_bytes());
+        page.extend_from_slice(&[0; 4]);
+        page.push(self.segments.len() as u8);
+        page.extend_from_slice(&self.segments);
+        page.extend_from_slice(&self.body);
+        let crc = ogg_crc(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+        self.file
+            .write_all(&page)
+            .map_err(|error| format!("Failed to write Ogg page: {error}"))?;
+        self.sequence += 1;
+        self.segments.clear();
+        self.body.clear();
+        Ok(())
+    }
+}
+
+/// Streaming linear interpolation between interleaved frames.
+struct LinearResampler {
+    channels: usize,
+    step: f64,
+    position: f64,
+    history: Vec<f32>,
+}
+
+impl LinearResampler {
+    fn new(channels: usize, from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            channels,
+            step: from_rate as f64 / to_rate as f64,
+            position: 0.0,
+            history: Vec::new(),
+        }
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        self.history.extend_from_slice(input);
+        let frames = self.history.len() / self.channels;
+        while self.position + 1.0 < frames as f64 {
+            let index = self.position as usize;
+            let fraction = (self.position - index as f64) as f32;
+            for channel in 0..self.channels {
+                let a = self.history[index * self.channels + channel];
+                let b = self.history[(index + 1) * self.channels + channel];
+                output.push(a + (b - a) * fraction);
+            }
+            self.position += self.step;
+        }
+        let consumed = (self.position as usize).min(frames.saturating_sub(1));
+        self.history.drain(..consumed * self.channels);
+        self.position -= consumed as f64;
+    }
+}
+
+pub(super) struct OpusStreamWriter {
+    pages: OggPageWriter,
+    encoder: Encoder,
+    input_rate: u32,
+    input_channels: usize,
+    channels: usize,
+    encoder_rate: u32,
+    frame_size: usize,
+    resampler: Option<LinearResampler>,
+    carry: Vec<f32>,
+    pending: Vec<f32>,
+    packet: Vec<u8>,
+    pre_skip: u64,
+    frames_written: u64,
+    encoded_frames: u64,
+}
+
+impl OpusStreamWriter {
+    pub fn create(path: &str, sample_rate: u32, channels: u16) -> Result<Self, String> {
+        if sample_rate == 0 || channels == 0 {
+            return Err("Opus recording needs a sample rate and channel count".to_string());
+        }
+        let input_channels = usize::from(channels);
+        let encoder_channels = input_channels.min(2);
+        let encoder_rate = if OPUS_ENCODER_RATES.contains(&sample_rate) {
+            sample_rate
+        } else {
+            OPUS_GRANULE_RATE
+        };
+        let mut encoder = Encoder::new(
+            encoder_rate,
+            if encoder_channels == 1 {
+                Channels::Mono
+            } else {
+                Channels::Stereo
+            },
+            Application::Audio,
+        )
+        .map_err(|error| format!("Failed to create Opus encoder: {error}"))?;
+        encoder
+            .set_bitrate(Bitrate::Bits(
+                OPUS_BITRATE_PER_CHANNEL * encoder_channels as i32,
+            ))
+            .map_err(|error| format!("Failed to set Opus bitrate: {error}"))?;
+        let lookahead = encoder
+            .get_lookahead()
+            .map_err(|error| format!("Failed to query Opus lookahead: {error}"))?;
+        let pre_skip =
+            lookahead.max(0) as u64 * u64::from(OPUS_GRANULE_RATE) / u64::from(encoder_rate);
+
+        let file = File::create(path)
+            .map_err(|error| format!("Failed to create Opus file {path}: {error}"))?;
+        let serial = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.subsec_nanos() ^ elapsed.as_secs() as u32)
+            .unwrap_or(0x4f70_7573);
+        let mut pages = OggPageWriter::new(file, serial);
+
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1);
+        head.push(encoder_channels as u8);
+        head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+        head.extend_from_slice(&sample_rate.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes());
+        head.push(0);
+        pages.push_packet(&head, 0)?;
+        pages.flush_page(HEADER_TYPE_BOS)?;
+
+        let vendor = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+        let mut tags = Vec::with_capacity(16 + vendor.len());
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor.as_bytes());
+        tags.extend_from_slice(&0u32.to_le_bytes());
+        pages.push_packet(&tags, 0)?;
+        pages.flush_page(0)?;
+
+        Ok(Self {
+            pages,
+            encoder,
+            input_rate: sample_rate,
+            input_channels,
+            channels: encoder_channels,
+            encoder_rate,
+            frame_size: (encoder_rate / OPUS_FRAMES_PER_SECOND) as usize,
+            resampler: (encoder_rate != sample_rate)
+                .then(|| LinearResampler::new(encoder_channels, sample_rate, encoder_rate)),
+            carry: Vec::new(),
+            pending: Vec::new(),
+            packet: vec![0; MAX_PACKET_BYTES],
+            pre_skip,
+            frames_written: 0,
+            encoded_frames: 0,
+        })
+    }
+
+    pub fn frames_written(&self) -> u64 {
+        self.frames_written
+    }
+
+    fn granule(&self) -> u64 {
+        self.encoded_frames * u64::from(OPUS_GRANULE_RATE) / u64::from(self.encoder_rate)
+    }
+
+    /// Interleaved samples in [-1, 1]; partial frames are carried over.
+    pub fn write_interleaved(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.carry.extend_from_slice(samples);
+        let frames = self.carry.len() / self.input_channels;
+        let mut reduced = Vec::with_capacity(frames * self.channels);
+        for frame in self.carry.chunks_exact(self.input_channels) {
+            reduced.extend_from_slice(&frame[..self.channels]);
+        }
+        self.carry.drain(..frames * self.input_channels);
+        self.frames_written += frames as u64;
+        match self.resampler.as_mut() {
+            Some(resampler) => resampler.process(&reduced, &mut self.pending),
+            None => self.pending.extend_from_slice(&reduced),
+        }
+        self.encode_pending()
+    }
+
+    fn encode_frame(&mut self, offset: usize, frames: usize) -> Result<(), String> {
+        let samples = &self.pending[offset..offset + frames * self.channels];
+        let length = self
+            .encoder
+            .encode_float(samples, &mut self.packet)
+            .map_err(|error| format!("Failed to encode Opus frame: {error}"))?;
+        self.encoded_frames += frames as u64;
+        let granule = self.granule();
+        self.pages.push_packet(&self.packet[..length], granule)?;
+        if self.pages.packet_count() >= PAGE_PACKETS {
+            self.pages.flush_page(0)?;
+        }
+        Ok(())
+    }
+
+    fn encode_pending(&mut self) -> Result<(), String> {
+        let chunk = self.frame_size * self.channels;
+        let mut offset = 0;
+        while self.pending.len() - offset >= chunk {
+            self.encode_frame(offset, self.frame_size)?;
+            offset += chunk;
+        }
+        self.pending.drain(..offset);
+        Ok(())
+    }
+
+    /// Closes the current page and flushes it to disk.
+    pub fn checkpoint(&mut self) -> Result<(), String> {
+        self.pages.flush_page(0)?;
+        self.pages
+            .file
+            .flush()
+            .map_err(|error| format!("Failed to flush Opus: {error}"))?;
+        self.pages
+            .file
+            .get_ref()
+            .sync_data()
+            .map_err(|error| format!("Failed to sync Opus: {error}"))
+    }
+
+    pub fn finish(mut self) -> Result<(), String> {
+        // Push the encoder lookahead out with silence, stepping down through
+        // the shorter Opus frame sizes so at most 2.5 ms of padding is left.
+        // The final granule stays at the packet total: the padding is silent,
+        // and demuxers that ignore end trimming then agree on the length.
+        let encoder_rate = u64::from(self.encoder_rate);
+        let recorded =
+            self.frames_written * u64::from(OPUS_GRANULE_RATE) / u64::from(self.input_rate);
+        let target =
+            ((self.pre_skip + recorded) * encoder_rate).div_ceil(u64::from(OPUS_GRANULE_RATE));
+        let smallest = self.frame_size / 8;
+        let remaining = target.saturating_sub(self.encoded_frames) as usize;
+        let padded = remaining.div_ceil(smallest).max(1) * smallest;
+        self.pending.resize(padded * self.channels, 0.0);
+        let mut offset = 0;
+        for frames in [
+            self.frame_size,
+            self.frame_size / 2,
+            self.frame_size / 4,
+            smallest,
+        ] {
+            while self.pending.len() - offset >= frames * self.channels {
+                self.encode_frame(offset, frames)?;
+                offset += frames * self.channels;
+            }
+        }
+        self.pages.flush_page(HEADER_TYPE_EOS)?;
+        self.pages
+            .file
+            .flush()
+            .map_err(|error| format!("Failed to flush Opus: {error}"))?;
+        self.pages
+            .file
+            .get_ref()
+            .sync_data()
+            .map_err(|error| format!("Failed to sync Opus: {error}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ogg_crc, LinearResampler, OpusStreamWriter};
+    use crate::audio_engine::playback::decode_static_sound_data;
+
+    #[test]
+    fn ogg_crc_matches_reference_check_value() {
+        assert_eq!(ogg_crc(b"123456789"), 0x89A1_897F);
+    }
+
+    #[test]
+    fn resampler_keeps_duration_across_blocks() {
+        let mut resampler = LinearResampler::new(2, 44_100, 48_000);
+        let input: Vec<f32> = (0..44_100 * 2).map(|index| (index / 2) as f32).collect();
+        let mut output = Vec::new();
+        for block in input.chunks(1000) {
+            resampler.process(block, &mut output);
+        }
+        let frames = output.len() / 2;
+        assert!((47_990..=48_000).contains(&frames), "frames={frames}");
+        assert_eq!(output[2], output[3]);
+        assert!((output[2 * 480] - 441.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn recorded_tone_decodes_through_the_clip_loader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.opus");
+        let mut writer = OpusStreamWriter::create(path.to_str().unwrap(), 48_000, 2).unwrap();
+        let frames = 48_000 + 777;
+        let samples: Vec<f32> = (0..frames)
+            .flat_map(|index| {
+                let sample = (index as f32 * 440.0 * std::f32::consts::TAU / 48_000.0).sin() * 0.5;
+                [sample, sample]
+            })
+            .collect();
+        for block in samples.chunks(1_000) {
+            writer.write_interleaved(block).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let sound = decode_static_sound_data(bytes, Some("opus"), "take.opus").unwrap();
+        assert_eq!(sound.sample_rate, 48_000);
+        // Pre-skip is dropped on decode; the silent tail padding is under 2.5 ms.
+        let decoded = sound.frames.len();
+        assert!(
+            (frames..=frames + 120).contains(&decoded),
+            "frames={decoded}"
+        );
+        // Lossy, so compare the steady-state level rather than samples.
+        let middle = &sound.frames[frames / 4..frames * 3 / 4];
+        let rms = (middle
+            .iter()
+            .map(|frame| frame.left * frame.left)
+            .sum::<f32>()
+            / middle.len() as f32)
+            .sqrt();
+        assert!((rms - 0.5 / 2f32.sqrt()).abs() < 0.03, "rms={rms}");
+    }
+}
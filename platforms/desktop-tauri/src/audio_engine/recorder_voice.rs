@@ -0,0 +1,149 @@
+// Voice-triggered recording
+// Runs the VAD on the writer thread for sessions armed with
+// `recorder::arm_on_voice`: the first speech onset switches the session from
+// Armed to Recording (the pre-roll already holds the onset itself), and, when
+// configured, a long enough silence after speech switches it to Stopped so
+// the UI can finalize the take with `stop`.
+
+use std::sync::atomic::Ordering;
+
+use super::recorder_wav::{RecordState, RecordingProgress};
+use super::vad::{VadConfig, VadEvent, VoiceActivityDetector};
+
+/// Pre-roll for voice-armed sessions: enough to cover the detector's onset
+/// confirmation plus a breath before the first word.
+pub const VOICE_PRE_ROLL_SEC: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VoiceTriggerConfig {
+    pub vad: VadConfig,
+    /// Stop writing after this much silence following speech; `None` keeps
+    /// recording until `stop`.
+    pub auto_stop_after_sec: Option<f64>,
+}
+
+pub(super) struct VoiceTrigger {
+    detector: VoiceActivityDetector,
+    channels: usize,
+    auto_stop_after_sec: Option<f64>,
+    pending: Vec<f32>,
+    mono: Vec<f32>,
+    events: Vec<VadEvent>,
+    silent_since: Option<f64>,
+}
+
+impl VoiceTrigger {
+    pub(super) fn new(
+        sample_rate: u32,
+        channels: u16,
+        config: VoiceTriggerConfig,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            detector: VoiceActivityDetector::new(sample_rate, config.vad)?,
+            channels: usize::from(channels.max(1)),
+            auto_stop_after_sec: config.auto_stop_after_sec,
+            pending: Vec::new(),
+            mono: Vec::new(),
+            events: Vec::new(),
+            silent_since: None,
+        })
+    }
+
+    /// Feeds interleaved samples as popped from the ring buffer (not
+    /// necessarily frame-aligned) and updates the session state.
+    pub(super) fn observe(&mut self, samples: &[f32], progress: &RecordingProgress) {
+        self.pending.extend_from_slice(samples);
+        let whole = self.pending.len() - self.pending.len() % self.channels;
+        self.mono.clear();
+        self.mono.extend(
+            self.pending[..whole]
+                .chunks_exact(self.channels)
+                .map(|frame| frame.iter().sum::<f32>() / self.channels as f32),
+        );
+        self.pending.drain(..whole);
+        self.detector.push(&self.mono, &mut self.events);
+
+        for event in self.events.drain(..) {
+            match event {
+                VadEvent::SpeechStart { .. } => {
+                    progress.voice_active.store(true, Ordering::Relaxed);
+                    self.silent_since = None;
+                    if progress.state() == RecordState::Armed {
+                        progress.set_state(RecordState::Recording);
+                    }
+                }
+                VadEvent::SpeechEnd { sec } => {
+                    progress.voice_active.store(false, Ordering::Relaxed);
+                    self.silent_since = Some(sec);
+                }
+            }
+        }
+        if let (Some(limit), Some(since)) = (self.auto_stop_after_sec, self.silent_since) {
+            if progress.state() == RecordState::Recording
+                && self.detector.position_sec() - since >= limit
+            {
+                progress.set_state(RecordState::Stopped);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VoiceTrigger, VoiceTriggerConfig};
+    use crate::audio_engine::recorder_wav::{RecordState, RecordingProgress};
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn speech_starts_the_take_and_silence_stops_it() {
+        let rate = 16_000;
+        let stereo = |seconds: f64, voiced: bool| -> Vec<f32> {
+            (0..(seconds * f64::from(rate)) as usize)
+                .flat_map(|index| {
+                    let time = index as f64 / f64::from(rate);
+                    let sample = if voiced {
+                        (1..=12)
+                            .map(|harmonic| {
+                                let phase = std::f64::consts::TAU * 160.0 * harmonic as f64 * time;
+                                (phase.sin() / harmonic as f64) as f32 * 0.05
+                            })
+                            .sum()
+                    } else {
+                        0.0
+                    };
+                    [sample, sample]
+                })
+                .collect()
+        };
+        let progress = RecordingProgress::new(RecordState::Armed);
+        let mut trigger = VoiceTrigger::new(
+            rate,
+            2,
+            VoiceTriggerConfig {
+                auto_stop_after_sec: Some(1.0),
+                ..VoiceTriggerConfig::default()
+            },
+        )
+        .unwrap();
+
+        // Odd-sized pops exercise the frame carry between calls.
+        for chunk in stereo(1.0, false).chunks(1_001) {
+            trigger.observe(chunk, &progress);
+        }
+        assert_eq!(progress.state(), RecordState::Armed);
+        for chunk in stereo(1.0, true).chunks(1_001) {
+            trigger.observe(chunk, &progress);
+        }
+        assert_eq!(progress.state(), RecordState::Recording);
+        assert!(progress.voice_active.load(Ordering::Relaxed));
+        for chunk in stereo(0.8, false).chunks(1_001) {
+            trigger.observe(chunk, &progress);
+        }
+        assert_eq!(progress.state(), RecordState::Recording);
+        for chunk in stereo(0.8, false).chunks(1_001) {
+            trigger.observe(chunk, &progress);
+        }
+        assert_eq!(progress.state(), RecordState::Stopped);
+        assert!(!progress.voice_active.load(Ordering::Relaxed));
+    }
+}
@@ -1,11 +1,24 @@
+use super::recorder_flac::FlacStreamWriter;
+use super::recorder_journal::{RecordingJournal, HEADER_FLUSH_INTERVAL};
+use super::recorder_opus::OpusStreamWriter;
+use super::recorder_voice::VoiceTrigger;
 use hound::{WavSpec, WavWriter};
 use ringbuf::traits::*;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-/// Output format for WAV recording.
-#[derive(Clone, Copy, Debug, serde::Deserialize)]
+macro_rules! eprintln {
+    ($($arg:tt)*) => {
+        if crate::runtime_logging::xcode_logs_enabled() {
+            std::eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Output format for recordings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
 pub enum OutputFormat {
     /// 16-bit signed integer PCM (CD quality, smaller files)
     Int16,
@@ -13,6 +26,10 @@ pub enum OutputFormat {
     Int24,
     /// 32-bit IEEE float (maximum precision, larger files)
     Float32,
+    /// 24-bit lossless FLAC (roughly half the size of Int24 WAV)
+    Flac,
+    /// Ogg/Opus at 32 kbps per channel (voice memos, ~1/20 of WAV)
+    Opus,
 }
 
 impl Default for OutputFormat {
@@ -22,29 +39,48 @@ impl Default for OutputFormat {
 }
 
 impl OutputFormat {
-    pub(super) fn wav_spec(&self, sample_rate: u32, channels: u16) -> WavSpec {
+    /// Parses the names accepted by the bridge (`int16`, `flac`, `opus`, ...).
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "int16" | "pcm16" | "wav16" => Ok(Self::Int16),
+            "int24" | "pcm24" | "wav24" | "wav" => Ok(Self::Int24),
+            "float32" | "f32" => Ok(Self::Float32),
+            "flac" => Ok(Self::Flac),
+            "opus" | "ogg" => Ok(Self::Opus),
+            other => Err(format!(
+                "Unsupported recording format '{other}' (expected int16, int24, float32, flac or opus)"
+            )),
+        }
+    }
+
+    pub fn is_wav(&self) -> bool {
+        matches!(self, Self::Int16 | Self::Int24 | Self::Float32)
+    }
+
+    /// File extension the format is written with.
+    pub fn extension(&self) -> &'static str {
         match self {
-            Self::Int16 => WavSpec {
-                channels,
-                sample_rate,
-                bits_per_sample: 16,
-                sample_format: hound::SampleFormat::Int,
-            },
-            Self::Int24 => WavSpec {
-                channels,
-                sample_rate,
-                bits_per_sample: 24,
-                sample_format: hound::SampleFormat::Int,
-            },
-            Self::Float32 => WavSpec {
-                channels,
-                sample_rate,
-                bits_per_sample: 32,
-                sample_format: hound::SampleFormat::Float,
-            },
+            Self::Int16 | Self::Int24 | Self::Float32 => "wav",
+            Self::Flac => "flac",
+            Self::Opus => "opus",
         }
     }
 
+    pub(super) fn wav_spec(&self, sample_rate: u32, channels: u16) -> Option<WavSpec> {
+        let (bits_per_sample, sample_format) = match self {
+            Self::Int16 => (16, hound::SampleFormat::Int),
+            Self::Int24 => (24, hound::SampleFormat::Int),
+            Self::Float32 => (32, hound::SampleFormat::Float),
+            Self::Flac | Self::Opus => return None,
+        };
+        Some(WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        })
+    }
+
     fn write_f32_sample<W: std::io::Write + std::io::Seek>(
         &self,
         writer: &mut WavWriter<W>,
@@ -60,6 +96,7 @@ impl OutputFormat {
                 writer.write_sample(s)
             }
             Self::Float32 => writer.write_sample(sample),
+            Self::Flac | Self::Opus => Err(hound::Error::Unsupported),
         }
     }
 }
@@ -95,31 +132,321 @@ impl Default for BufferSizeHint {
     }
 }
 
-fn write_f32_buffer<W: std::io::Write + std::io::Seek>(
-    writer: &mut WavWriter<W>,
-    format: OutputFormat,
-    samples: &[f32],
-) -> Result<(), String> {
-    for &sample in samples {
-        format
-            .write_f32_sample(writer, sample)
-            .map_err(|error| format!("Failed to write WAV sample: {error}"))?;
+/// Encoder fed by the writer thread; every variant checkpoints on the same
+/// cadence so the crash journal stays meaningful across formats.
+pub(super) enum RecordingSink {
+    Wav {
+        writer: WavWriter<std::io::BufWriter<std::fs::File>>,
+        /// Second handle on the same file so checkpoints can `sync_data`.
+        file: std::fs::File,
+        format: OutputFormat,
+    },
+    Flac(FlacStreamWriter),
+    Opus(Box<OpusStreamWriter>),
+}
+
+impl RecordingSink {
+    pub(super) fn create(
+        path: &str,
+        output_format: OutputFormat,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Result<Self, String> {
+        match output_format {
+            OutputFormat::Flac => {
+                FlacStreamWriter::create(path, sample_rate, channels, 24).map(Self::Flac)
+            }
+            OutputFormat::Opus => OpusStreamWriter::create(path, sample_rate, channels)
+                .map(|writer| Self::Opus(Box::new(writer))),
+            format => {
+                let wav_spec = format
+                    .wav_spec(sample_rate, channels)
+                    .ok_or("Missing WAV spec for recording format")?;
+                let create_error = |error| format!("Failed to create WAV file {path}: {error}");
+                let file = std::fs::File::create(path).map_err(create_error)?;
+                let sync_handle = file.try_clone().map_err(create_error)?;
+                WavWriter::new(std::io::BufWriter::new(file), wav_spec)
+                    .map(|writer| Self::Wav {
+                        writer,
+                        file: sync_handle,
+                        format,
+                    })
+                    .map_err(|error| format!("Failed to create WAV file {path}: {error}"))
+            }
+        }
+    }
+
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        match self {
+            Self::Wav { writer, format, .. } => {
+                for &sample in samples {
+                    format
+                        .write_f32_sample(writer, sample)
+                        .map_err(|error| format!("Failed to write WAV sample: {error}"))?;
+                }
+                Ok(())
+            }
+            Self::Flac(writer) => writer.write_interleaved(samples),
+            Self::Opus(writer) => writer.write_interleaved(samples),
+        }
+    }
+
+    /// hound rejects a header patched mid-frame; the encoders buffer
+    /// partial frames themselves.
+    fn can_checkpoint(&self) -> bool {
+        match self {
+            Self::Wav { writer, .. } => {
+                writer.len() % u32::from(writer.spec().channels.max(1)) == 0
+            }
+            Self::Flac(_) | Self::Opus(_) => true,
+        }
+    }
+
+    fn checkpoint(&mut self) -> Result<(), String> {
+        match self {
+            // `flush` patches the header; `sync_data` makes it survive power loss.
+            Self::Wav { writer, file, .. } => writer
+                .flush()
+                .map_err(|error| format!("Failed to checkpoint WAV header: {error}"))
+                .and_then(|_| {
+                    file.sync_data()
+                        .map_err(|error| format!("Failed to sync WAV file: {error}"))
+                }),
+            Self::Flac(writer) => writer.checkpoint(),
+            Self::Opus(writer) => writer.checkpoint(),
+        }
+    }
+
+    fn frames_written(&self) -> u64 {
+        match self {
+            Self::Wav { writer, .. } => u64::from(writer.duration()),
+            Self::Flac(writer) => writer.frames_written(),
+            Self::Opus(writer) => writer.frames_written(),
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            Self::Wav { writer, .. } => writer
+                .finalize()
+                .map_err(|error| format!("Failed to finalize WAV: {error}")),
+            Self::Flac(writer) => writer.finish(),
+            Self::Opus(writer) => writer.finish(),
+        }
+    }
+}
+
+/// Session state as seen by the writer thread.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum RecordState {
+    /// Stream open, nothing written yet; the last seconds are kept as pre-roll.
+    Armed = 0,
+    Recording = 1,
+    /// Input is dropped until resumed; the file continues seamlessly.
+    Paused = 2,
+    /// Voice auto-stop fired; input is dropped until `stop` finalizes the file.
+    Stopped = 3,
+}
+
+impl RecordState {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Self::Armed,
+            2 => Self::Paused,
+            3 => Self::Stopped,
+            _ => Self::Recording,
+        }
+    }
+
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Armed => "armed",
+            Self::Recording => "recording",
+            Self::Paused => "paused",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+/// Shared between the session (control side) and the writer thread.
+pub(super) struct RecordingProgress {
+    state: AtomicU8,
+    /// Frames handed to the encoder: pre-roll included, paused input excluded.
+    pub frames: AtomicU64,
+    pub pre_roll_frames: AtomicU64,
+    pub paused_frames: AtomicU64,
+    /// Frames the input callback queued for the writer, in any state.
+    pub captured_frames: AtomicU64,
+    /// Frames the writer has popped from that queue and routed.
+    pub routed_frames: AtomicU64,
+    /// Voice-armed sessions only: the detector currently hears speech.
+    pub voice_active: AtomicBool,
+}
+
+impl RecordingProgress {
+    pub(super) fn new(state: RecordState) -> Self {
+        Self {
+            state: AtomicU8::new(state as u8),
+            frames: AtomicU64::new(0),
+            pre_roll_frames: AtomicU64::new(0),
+            paused_frames: AtomicU64::new(0),
+            captured_frames: AtomicU64::new(0),
+            routed_frames: AtomicU64::new(0),
+            voice_active: AtomicBool::new(false),
+        }
+    }
+
+    /// File position of the input callback: `frames` trails it by whatever
+    /// still sits in the ring buffer, which lands in the file while recording.
+    pub(super) fn input_frame(&self) -> u64 {
+        let written = self.frames.load(Ordering::Relaxed);
+        if self.state() != RecordState::Recording {
+            return written;
+        }
+        let queued = self
+            .captured_frames
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.routed_frames.load(Ordering::Relaxed));
+        written + queued
+    }
+
+    pub(super) fn state(&self) -> RecordState {
+        RecordState::from_u8(self.state.load(Ordering::Acquire))
+    }
+
+    pub(super) fn set_state(&self, state: RecordState) {
+        self.state.store(state as u8, Ordering::Release);
     }
-    Ok(())
 }
 
-pub(super) fn spawn_wav_writer_thread(
+/// Routes popped samples by session state. State changes only take effect on
+/// frame boundaries so a pause never splits a frame across the gap.
+pub(super) struct WriterRouter {
+    channels: usize,
+    phase: usize,
+    route: RecordState,
+    pre_roll: VecDeque<f32>,
+    pre_roll_capacity: usize,
+    samples_written: u64,
+    samples_paused: u64,
+    samples_routed: u64,
+}
+
+impl WriterRouter {
+    pub(super) fn new(channels: u16, pre_roll_frames: usize, route: RecordState) -> Self {
+        let channels = usize::from(channels.max(1));
+        Self {
+            channels,
+            phase: 0,
+            route,
+            pre_roll: VecDeque::with_capacity(pre_roll_frames * channels),
+            pre_roll_capacity: pre_roll_frames * channels,
+            samples_written: 0,
+            samples_paused: 0,
+            samples_routed: 0,
+        }
+    }
+
+    fn write(
+        &mut self,
+        samples: &[f32],
+        sink: &mut RecordingSink,
+        progress: &RecordingProgress,
+    ) -> Result<(), String> {
+        let head = ((self.channels - self.phase) % self.channels).min(samples.len());
+        self.route_samples(&samples[..head], sink, progress)?;
+        self.phase = (self.phase + head) % self.channels;
+        if self.phase == 0 {
+            let next = progress.state();
+            if self.route == RecordState::Armed && next != RecordState::Armed {
+                let pre_roll: Vec<f32> = self.pre_roll.drain(..).collect();
+                progress
+                    .pre_roll_frames
+                    .store((pre_roll.len() / self.channels) as u64, Ordering::Relaxed);
+                self.route = RecordState::Recording;
+                self.route_samples(&pre_roll, sink, progress)?;
+            }
+            self.route = next;
+        }
+        let tail = &samples[head..];
+        self.route_samples(tail, sink, progress)?;
+        self.phase = (self.phase + tail.len()) % self.channels;
+        self.samples_routed += samples.len() as u64;
+        progress.routed_frames.store(
+            self.samples_routed / self.channels as u64,
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn route_samples(
+        &mut self,
+        samples: &[f32],
+        sink: &mut RecordingSink,
+        progress: &RecordingProgress,
+    ) -> Result<(), String> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        match self.route {
+            RecordState::Armed => {
+                self.pre_roll.extend(samples);
+                let excess = self.pre_roll.len().saturating_sub(self.pre_roll_capacity);
+                let excess = excess.div_ceil(self.channels) * self.channels;
+                self.pre_roll.drain(..excess.min(self.pre_roll.len()));
+            }
+            RecordState::Recording => {
+                sink.write(samples)?;
+                self.samples_written += samples.len() as u64;
+                progress.frames.store(
+                    self.samples_written / self.channels as u64,
+                    Ordering::Relaxed,
+                );
+            }
+            RecordState::Paused => {
+                self.samples_paused += samples.len() as u64;
+                progress.paused_frames.store(
+                    self.samples_paused / self.channels as u64,
+                    Ordering::Relaxed,
+                );
+            }
+            RecordState::Stopped => {}
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn spawn_writer_thread(
     mut consumer: ringbuf::HeapCons<f32>,
-    mut writer: WavWriter<std::io::BufWriter<std::fs::File>>,
-    output_format: OutputFormat,
+    mut sink: RecordingSink,
+    mut router: WriterRouter,
+    progress: Arc<RecordingProgress>,
+    mut voice: Option<VoiceTrigger>,
     writer_stop: Arc<AtomicBool>,
+    mut journal: RecordingJournal,
 ) -> std::thread::JoinHandle<Result<(), String>> {
     std::thread::spawn(move || {
         let mut buffer = vec![0.0_f32; 4096];
+        let mut last_checkpoint = Instant::now();
+        if let Err(error) = journal.write(0) {
+            eprintln!("[audio_engine::recorder] {error}");
+        }
         loop {
             let count = consumer.pop_slice(&mut buffer);
             if count > 0 {
-                write_f32_buffer(&mut writer, output_format, &buffer[..count])?;
+                if let Some(voice) = voice.as_mut() {
+                    voice.observe(&buffer[..count], &progress);
+                }
+                router.write(&buffer[..count], &mut sink, &progress)?;
+            }
+            if last_checkpoint.elapsed() >= HEADER_FLUSH_INTERVAL && sink.can_checkpoint() {
+                sink.checkpoint()?;
+                if let Err(error) = journal.write(sink.frames_written()) {
+                    eprintln!("[audio_engine::recorder] {error}");
+                }
+                last_checkpoint = Instant::now();
+            }
+            if count > 0 {
                 continue;
             }
             if writer_stop.load(Ordering::Acquire) && consumer.is_empty() {
@@ -127,20 +454,79 @@ pub(super) fn spawn_wav_writer_thread(
             }
             std::thread::sleep(Duration::from_millis(2));
         }
-        writer
-            .finalize()
-            .map_err(|error| format!("Failed to finalize WAV: {error}"))?;
+        sink.finish()?;
+        journal.remove();
         Ok(())
     })
 }
 
-pub(super) fn create_wav_writer(
-    path: &str,
-    output_format: OutputFormat,
-    sample_rate: u32,
-    channels: u16,
-) -> Result<WavWriter<std::io::BufWriter<std::fs::File>>, String> {
-    let wav_spec = output_format.wav_spec(sample_rate, channels);
-    WavWriter::create(path, wav_spec)
-        .map_err(|error| format!("Failed to create WAV file {path}: {error}"))
+#[cfg(test)]
+mod tests {
+    use super::{OutputFormat, RecordState, RecordingProgress, RecordingSink, WriterRouter};
+
+    #[test]
+    fn router_keeps_pre_roll_and_skips_paused_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("take.wav");
+        let path = path.to_str().unwrap();
+        let mut sink = RecordingSink::create(path, OutputFormat::Float32, 1_000, 2).unwrap();
+        let progress = RecordingProgress::new(RecordState::Armed);
+        let mut router = WriterRouter::new(2, 4, RecordState::Armed);
+        let frames = |range: std::ops::Range<u32>| -> Vec<f32> {
+            range
+                .flat_map(|frame| [frame as f32, -(frame as f32)])
+                .collect()
+        };
+
+        // 10 armed frames, only the last 4 survive as pre-roll. The pause
+        // lands mid-frame and must wait for the frame boundary.
+        router
+            .write(&frames(0..10)[..7], &mut sink, &progress)
+            .unwrap();
+        router
+            .write(&frames(0..10)[7..], &mut sink, &progress)
+            .unwrap();
+        progress.set_state(RecordState::Recording);
+        router
+            .write(&frames(10..13)[..3], &mut sink, &progress)
+            .unwrap();
+        progress.set_state(RecordState::Paused);
+        router
+            .write(&frames(10..13)[3..], &mut sink, &progress)
+            .unwrap();
+        router.write(&frames(13..16), &mut sink, &progress).unwrap();
+        progress.set_state(RecordState::Recording);
+        router.write(&frames(16..18), &mut sink, &progress).unwrap();
+        sink.finish().unwrap();
+
+        let written: Vec<f32> = hound::WavReader::open(path)
+            .unwrap()
+            .into_samples::<f32>()
+            .step_by(2)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(written, vec![6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 16.0, 17.0]);
+        let load =
+            |value: &std::sync::atomic::AtomicU64| value.load(std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(load(&progress.pre_roll_frames), 4);
+        assert_eq!(load(&progress.paused_frames), 4);
+        assert_eq!(load(&progress.frames), 8);
+        assert_eq!(load(&progress.routed_frames), 18);
+    }
+
+    #[test]
+    fn input_frame_counts_frames_still_queued_for_the_writer() {
+        let progress = RecordingProgress::new(RecordState::Recording);
+        let store = |value: &std::sync::atomic::AtomicU64, frames: u64| {
+            value.store(frames, std::sync::atomic::Ordering::Relaxed)
+        };
+        store(&progress.frames, 100);
+        store(&progress.captured_frames, 180);
+        store(&progress.routed_frames, 150);
+        assert_eq!(progress.input_frame(), 130);
+
+        // Queued input is dropped from the file while paused.
+        progress.set_state(RecordState::Paused);
+        assert_eq!(progress.input_frame(), 100);
+    }
 }
@@ -0,0 +1,598 @@
+// Offline spectral analysis for music features
+// Onsets (spectral flux), tempo (onset autocorrelation), key (chroma against
+// Krumhansl-Schmuckler profiles), pitch (YIN) and a banded log spectrogram.
+//
+// Input is the PCM produced by `playback::decode_clip_with_symphonia`, mixed
+// to mono and decimated to ~22 kHz; results are cached as JSON next to the
+// media the same way `transcode` caches extracted audio.
+
+use super::fft::{FftPlan, SpectrumWindow};
+use super::playback;
+use super::transcode;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const SPECTRAL_CACHE_VERSION: u32 = 1;
+const ANALYSIS_TARGET_RATE: u32 = 22_050;
+const ONSET_FFT_SIZE: usize = 1024;
+const ONSET_HOP: usize = 512;
+const CHROMA_FFT_SIZE: usize = 4096;
+const CHROMA_HOP: usize = 2048;
+const CHROMA_MIN_HZ: f32 = 65.0;
+const CHROMA_MAX_HZ: f32 = 5_000.0;
+const YIN_WINDOW: usize = 1024;
+const YIN_THRESHOLD: f32 = 0.15;
+const YIN_MIN_HZ: f32 = 60.0;
+const YIN_MAX_HZ: f32 = 1_500.0;
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 200.0;
+const SPECTROGRAM_BANDS: usize = 96;
+const SPECTROGRAM_MAX_COLUMNS: usize = 1024;
+const SPECTROGRAM_DB_FLOOR: f32 = -120.0;
+const PITCH_TRACK_MAX_POINTS: usize = 4000;
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+const MAJOR_PROFILE: [f32; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f32; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+fn decimate(samples: &[f32], factor: usize) -> Vec<f32> {
+    if factor <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(factor)
+        .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
+        .collect()
+}
+
+fn hz_to_midi(hz: f32) -> f32 {
+    69.0 + 12.0 * (hz / 440.0).log2()
+}
+
+fn midi_note_name(midi: f32) -> String {
+    let rounded = midi.round() as i32;
+    let name = NOTE_NAMES[rounded.rem_euclid(12) as usize];
+    format!("{name}{}", rounded.div_euclid(12) - 1)
+}
+
+fn frame_at(samples: &[f32], start: usize, size: usize) -> &[f32] {
+    let end = (start + size).min(samples.len());
+    &samples[start.min(end)..end]
+}
+
+/// Half-wave rectified log-magnitude flux per hop, plus the banded log
+/// spectrogram pooled to at most `SPECTROGRAM_MAX_COLUMNS` columns.
+struct OnsetPass {
+    envelope: Vec<f32>,
+    band_edges_hz: Vec<f32>,
+    columns: Vec<Vec<f32>>,
+    frames_per_column: usize,
+}
+
+fn band_edges(sample_rate: u32) -> Vec<f32> {
+    let low = 30.0f32;
+    let high = sample_rate as f32 / 2.0;
+    (0..=SPECTROGRAM_BANDS)
+        .map(|index| low * (high / low).powf(index as f32 / SPECTROGRAM_BANDS as f32))
+        .collect()
+}
+
+fn onset_pass(samples: &[f32], sample_rate: u32) -> Result<OnsetPass, String> {
+    let mut plan = FftPlan::new(ONSET_FFT_SIZE, SpectrumWindow::Hann)?;
+    let bin_hz = sample_rate as f32 / ONSET_FFT_SIZE as f32;
+    let edges = band_edges(sample_rate);
+    let band_of_bin: Vec<Option<usize>> = (0..plan.bin_count())
+        .map(|bin| {
+            let hz = bin as f32 * bin_hz;
+            edges
+                .windows(2)
+                .position(|edge| hz >= edge[0] && hz < edge[1])
+        })
+        .collect();
+
+    let frame_count = samples.len().div_ceil(ONSET_HOP).max(1);
+    let frames_per_column = frame_count.div_ceil(SPECTROGRAM_MAX_COLUMNS).max(1);
+    let mut magnitudes = vec![0.0; plan.bin_count()];
+    let mut previous = vec![0.0; plan.bin_count()];
+    let mut envelope = Vec::with_capacity(frame_count);
+    let mut columns = Vec::new();
+    let mut column = vec![SPECTROGRAM_DB_FLOOR; SPECTROGRAM_BANDS];
+    let mut bands = vec![0.0f32; SPECTROGRAM_BANDS];
+
+    for frame_index in 0..frame_count {
+        plan.magnitudes(
+            frame_at(samples, frame_index * ONSET_HOP, ONSET_FFT_SIZE),
+            &mut magnitudes,
+        );
+        let mut flux = 0.0;
+        bands.iter_mut().for_each(|band| *band = 0.0);
+        for (bin, magnitude) in magnitudes.iter().enumerate() {
+            let compressed = (1.0 + 100.0 * magnitude).ln();
+            flux += (compressed - previous[bin]).max(0.0);
+            previous[bin] = compressed;
+            if let Some(band) = band_of_bin[bin] {
+                bands[band] = bands[band].max(*magnitude);
+            }
+        }
+        envelope.push(flux);
+        for (slot, band) in column.iter_mut().zip(&bands) {
+            let db = (20.0 * band.max(1e-9).log10()).max(SPECTROGRAM_DB_FLOOR);
+            *slot = slot.max(db);
+        }
+        if (frame_index + 1) % frames_per_column == 0 || frame_index + 1 == frame_count {
+            columns.push(column.iter().map(|db| (db * 10.0).round() / 10.0).collect());
+            column.iter_mut().for_each(|db| *db = SPECTROGRAM_DB_FLOOR);
+        }
+    }
+
+    Ok(OnsetPass {
+        envelope,
+        band_edges_hz: edges,
+        columns,
+        frames_per_column,
+    })
+}
+
+/// Local maxima above a moving-average threshold, at least 50 ms apart.
+fn pick_onsets(envelope: &[f32], frame_rate: f32) -> Vec<f32> {
+    let max = envelope.iter().copied().fold(0.0f32, f32::max);
+    if max <= 0.0 {
+        return Vec::new();
+    }
+    let normalized: Vec<f32> = envelope.iter().map(|value| value / max).collect();
+    let radius = 8usize;
+    let min_gap = ((0.05 * frame_rate).ceil() as usize).max(1);
+    let mut onsets = Vec::new();
+    let mut last_onset: Option<usize> = None;
+    for index in 1..normalized.len() {
+        let start = index.saturating_sub(radius);
+        let end = (index + radius + 1).min(normalized.len());
+        let window = &normalized[start..end];
+        let mean = window.iter().sum::<f32>() / window.len() as f32;
+        let is_peak = window.iter().all(|value| *value <= normalized[index]);
+        let spaced = last_onset.is_none_or(|last| index - last >= min_gap);
+        if is_peak && normalized[index] > mean + 0.1 && spaced {
+            onsets.push(index as f32 / frame_rate);
+            last_onset = Some(index);
+        }
+    }
+    onsets
+}
+
+/// Autocorrelation of the onset envelope over the 60–200 BPM lag range,
+/// weighted towards 120 BPM to resolve octave ambiguity.
+fn estimate_tempo(envelope: &[f32], frame_rate: f32) -> Option<(f32, f32)> {
+    let mean = envelope.iter().sum::<f32>() / envelope.len().max(1) as f32;
+    let centered: Vec<f32> = envelope.iter().map(|value| value - mean).collect();
+    let min_lag = (60.0 * frame_rate / MAX_BPM).floor().max(1.0) as usize;
+    let max_lag = (60.0 * frame_rate / MIN_BPM).ceil() as usize;
+    if centered.len() <= max_lag + 1 {
+        return None;
+    }
+    let autocorrelation = |lag: usize| -> f32 {
+        centered
+            .iter()
+            .zip(&centered[lag..])
+            .map(|(a, b)| a * b)
+            .sum::<f32>()
+    };
+    let energy = autocorrelation(0);
+    if energy <= 0.0 {
+        return None;
+    }
+    let values: Vec<f32> = (min_lag - 1..=max_lag + 1).map(autocorrelation).collect();
+    let weight = |lag: f32| {
+        let bpm = 60.0 * frame_rate / lag;
+        let octaves = (bpm / 120.0).log2();
+        (-0.5 * (octaves / 1.0).powi(2)).exp()
+    };
+    let (best_offset, _) = (1..values.len() - 1)
+        .map(|offset| {
+            let lag = (min_lag - 1 + offset) as f32;
+            (offset, values[offset] * weight(lag))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    if values[best_offset] <= 0.0 {
+        return None;
+    }
+    let (left, centre, right) = (
+        values[best_offset - 1],
+        values[best_offset],
+        values[best_offset + 1],
+    );
+    let denominator = left - 2.0 * centre + right;
+    let shift = if denominator.abs() > f32::EPSILON {
+        (0.5 * (left - right) / denominator).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    let lag = (min_lag - 1 + best_offset) as f32 + shift;
+    Some((60.0 * frame_rate / lag, (centre / energy).clamp(0.0, 1.0)))
+}
+
+fn chroma_profile(samples: &[f32], sample_rate: u32) -> Result<[f32; 12], String> {
+    let mut plan = FftPlan::new(CHROMA_FFT_SIZE, SpectrumWindow::Hann)?;
+    let bin_hz = sample_rate as f32 / CHROMA_FFT_SIZE as f32;
+    let pitch_class_of_bin: Vec<Option<usize>> = (0..plan.bin_count())
+        .map(|bin| {
+            let hz = bin as f32 * bin_hz;
+            (CHROMA_MIN_HZ..=CHROMA_MAX_HZ)
+                .contains(&hz)
+                .then(|| (hz_to_midi(hz).round() as i32).rem_euclid(12) as usize)
+        })
+        .collect();
+    let mut magnitudes = vec![0.0; plan.bin_count()];
+    let mut chroma = [0.0f32; 12];
+    let mut start = 0;
+    while start < samples.len() {
+        plan.magnitudes(frame_at(samples, start, CHROMA_FFT_SIZE), &mut magnitudes);
+        for (bin, magnitude) in magnitudes.iter().enumerate() {
+            if let Some(pitch_class) = pitch_class_of_bin[bin] {
+                chroma[pitch_class] += magnitude * magnitude;
+            }
+        }
+        start += CHROMA_HOP;
+    }
+    Ok(chroma)
+}
+
+fn correlation(a: &[f32; 12], b: &[f32; 12]) -> f32 {
+    let mean_a = a.iter().sum::<f32>() / 12.0;
+    let mean_b = b.iter().sum::<f32>() / 12.0;
+    let (mut numerator, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        numerator += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    let denominator = (var_a * var_b).sqrt();
+    if denominator <= f32::EPSILON {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+/// Best (tonic pitch class, is_major, correlation) over the 24 keys.
+fn estimate_key(chroma: &[f32; 12]) -> Option<(usize, bool, f32)> {
+    if chroma.iter().all(|value| *value <= 0.0) {
+        return None;
+    }
+    let rotate = |profile: &[f32; 12], tonic: usize| {
+        let mut rotated = [0.0; 12];
+        for (pitch_class, slot) in rotated.iter_mut().enumerate() {
+            *slot = profile[(pitch_class + 12 - tonic) % 12];
+        }
+        rotated
+    };
+    (0..12)
+        .flat_map(|tonic| {
+            [
+                (
+                    tonic,
+                    true,
+                    correlation(chroma, &rotate(&MAJOR_PROFILE, tonic)),
+                ),
+                (
+                    tonic,
+                    false,
+                    correlation(chroma, &rotate(&MINOR_PROFILE, tonic)),
+                ),
+            ]
+        })
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+}
+
+/// YIN fundamental estimate for one frame; `None` when unvoiced or silent.
+fn yin_pitch(frame: &[f32], sample_rate: f32) -> Option<(f32, f32)> {
+    let tau_min = (sample_rate / YIN_MAX_HZ).floor().max(2.0) as usize;
+    let tau_max = ((sample_rate / YIN_MIN_HZ).ceil() as usize).min(frame.len() / 2);
+    if tau_max <= tau_min + 1 {
+        return None;
+    }
+    let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+    if rms < 1e-3 {
+        return None;
+    }
+    let width = frame.len() - tau_max;
+    let mut cmnd = vec![1.0f32; tau_max + 1];
+    let mut running_sum = 0.0f32;
+    for tau in 1..=tau_max {
+        let difference: f32 = (0..width)
+            .map(|index| {
+                let delta = frame[index] - frame[index + tau];
+                delta * delta
+            })
+            .sum();
+        running_sum += difference;
+        cmnd[tau] = if running_sum > 0.0 {
+            difference * tau as f32 / running_sum
+        } else {
+            1.0
+        };
+    }
+    let mut tau = tau_min;
+    while tau < tau_max {
+        if cmnd[tau] < YIN_THRESHOLD {
+            while tau + 1 < tau_max && cmnd[tau + 1] < cmnd[tau] {
+                tau += 1;
+            }
+            let (left, centre, right) = (cmnd[tau - 1], cmnd[tau], cmnd[tau + 1]);
+            let denominator = left - 2.0 * centre + right;
+            let shift = if denominator.abs() > f32::EPSILON {
+                (0.5 * (left - right) / denominator).clamp(-0.5, 0.5)
+            } else {
+                0.0
+            };
+            return Some((sample_rate / (tau as f32 + shift), 1.0 - centre));
+        }
+        tau += 1;
+    }
+    None
+}
+
+fn pitch_track(samples: &[f32], sample_rate: u32) -> Value {
+    let yin_samples = decimate(samples, 2);
+    let yin_rate = sample_rate as f32 / 2.0;
+    let mut voiced = Vec::new();
+    let mut frame_total = 0usize;
+    let mut start = 0;
+    while start + YIN_WINDOW <= yin_samples.len() {
+        frame_total += 1;
+        if let Some((hz, confidence)) = yin_pitch(&yin_samples[start..start + YIN_WINDOW], yin_rate)
+        {
+            voiced.push((start as f32 / yin_rate, hz, confidence));
+        }
+        start += YIN_WINDOW;
+    }
+
+    let median_hz = if voiced.is_empty() {
+        None
+    } else {
+        let mut frequencies: Vec<f32> = voiced.iter().map(|(_, hz, _)| *hz).collect();
+        frequencies.sort_by(|a, b| a.total_cmp(b));
+        Some(frequencies[frequencies.len() / 2])
+    };
+    let stride = voiced.len().div_ceil(PITCH_TRACK_MAX_POINTS).max(1);
+    let track: Vec<Value> = voiced
+        .iter()
+        .step_by(stride)
+        .map(|(time_sec, hz, confidence)| {
+            let midi = hz_to_midi(*hz);
+            json!({
+                "time_sec": time_sec,
+                "hz": hz,
+                "midi": midi,
+                "note": midi_note_name(midi),
+                "confidence": confidence
+            })
+        })
+        .collect();
+    json!({
+        "algorithm": "yin",
+        "median_hz": median_hz,
+        "median_note": median_hz.map(|hz| midi_note_name(hz_to_midi(hz))),
+        "voiced_ratio": voiced.len() as f32 / frame_total.max(1) as f32,
+        "track": track
+    })
+}
+
+/// Runs every analysis pass over mono PCM at `sample_rate`.
+pub fn analyze_samples(mono: &[f32], sample_rate: u32) -> Result<Value, String> {
+    if sample_rate == 0 || mono.is_empty() {
+        return Err("Spectral analysis needs non-empty audio".to_string());
+    }
+    let factor = (sample_rate / ANALYSIS_TARGET_RATE).max(1) as usize;
+    let samples = decimate(mono, factor);
+    let rate = sample_rate / factor as u32;
+    let frame_rate = rate as f32 / ONSET_HOP as f32;
+
+    let onset = onset_pass(&samples, rate)?;
+    let onsets = pick_onsets(&onset.envelope, frame_rate);
+    let tempo = estimate_tempo(&onset.envelope, frame_rate);
+    let key = estimate_key(&chroma_profile(&samples, rate)?);
+
+    Ok(json!({
+        "version": SPECTRAL_CACHE_VERSION,
+        "sample_rate": sample_rate,
+        "analysis_sample_rate": rate,
+        "duration_sec": mono.len() as f64 / sample_rate as f64,
+        "onset_count": onsets.len(),
+        "onsets": onsets,
+        "tempo": tempo.map(|(bpm, confidence)| json!({
+            "bpm": bpm,
+            "confidence": confidence
+        })),
+        "key": key.map(|(tonic, major, confidence)| {
+            let mode = if major { "major" } else { "minor" };
+            json!({
+                "tonic": NOTE_NAMES[tonic],
+                "mode": mode,
+                "label": format!("{} {mode}", NOTE_NAMES[tonic]),
+                "confidence": confidence
+            })
+        }),
+        "pitch": pitch_track(&samples, rate),
+        "spectrogram": {
+            "fft_size": ONSET_FFT_SIZE,
+            "hop_size": ONSET_HOP,
+            "frames_per_column": onset.frames_per_column,
+            "column_duration_sec": (onset.frames_per_column * ONSET_HOP) as f64 / rate as f64,
+            "db_floor": SPECTROGRAM_DB_FLOOR,
+            "band_edges_hz": onset.band_edges_hz,
+            "columns": onset.columns
+        }
+    }))
+}
+
+/// `<media dir>/.audio_cache/<file name>.spectral.json`
+pub fn spectral_cache_path(source_path: &Path) -> Result<PathBuf, String> {
+    let parent = source_path.parent().ok_or_else(|| {
+        format!(
+            "Missing parent directory for spectral source {}",
+            source_path.display()
+        )
+    })?;
+    let file_name = source_path
+        .file_name()
+        .and_then(|value| value.to_str())
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| format!("Missing file name for {}", source_path.display()))?;
+    Ok(parent
+        .join(".audio_cache")
+        .join(format!("{file_name}.spectral.json")))
+}
+
+fn read_cached_analysis(cache_path: &Path) -> Option<Value> {
+    let bytes = fs::read(cache_path).ok()?;
+    let value: Value = serde_json::from_slice(&bytes).ok()?;
+    (value.get("version").and_then(Value::as_u64) == Some(SPECTRAL_CACHE_VERSION as u64))
+        .then_some(value)
+}
+
+pub fn analyze_file(source_path: &Path) -> Result<Value, String> {
+    let decode_path = transcode::prepare_native_audio_decode_path(source_path)?;
+    let bytes = fs::read(&decode_path).map_err(|error| {
+        format!(
+            "Unable to read audio for spectral analysis {}: {error}",
+            decode_path.display()
+        )
+    })?;
+    let extension_hint = decode_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.trim().to_ascii_lowercase());
+    let sound = playback::decode_clip_with_symphonia(bytes, extension_hint.as_deref())?;
+    let mono: Vec<f32> = sound
+        .frames
+        .iter()
+        .map(|frame| (frame.left + frame.right) * 0.5)
+        .collect();
+    analyze_samples(&mono, sound.sample_rate)
+}
+
+/// Cached analysis, recomputed when the media is newer than the cache entry
+/// or the cache predates `SPECTRAL_CACHE_VERSION`.
+pub fn analyze_file_cached(source_path: &Path) -> Result<Value, String> {
+    let cache_path = spectral_cache_path(source_path)?;
+    if !transcode::should_refresh_native_audio_cache(source_path, &cache_path) {
+        if let Some(cached) = read_cached_analysis(&cache_path) {
+            return Ok(cached);
+        }
+    }
+    let analysis = analyze_file(source_path)?;
+    let parent = cache_path
+        .parent()
+        .ok_or_else(|| format!("Invalid spectral cache path {}", cache_path.display()))?;
+    fs::create_dir_all(parent).map_err(|error| {
+        format!(
+            "Unable to create spectral cache {}: {error}",
+            parent.display()
+        )
+    })?;
+    let encoded = serde_json::to_vec(&analysis)
+        .map_err(|error| format!("Unable to encode spectral analysis: {error}"))?;
+    // Same unique-sibling + atomic rename scheme as the peak cache.
+    let mut temp_file = tempfile::NamedTempFile::new_in(parent).map_err(|error| {
+        format!(
+            "Unable to create spectral cache {}: {error}",
+            parent.display()
+        )
+    })?;
+    temp_file.write_all(&encoded).map_err(|error| {
+        format!(
+            "Unable to write spectral cache {}: {error}",
+            temp_file.path().display()
+        )
+    })?;
+    temp_file.persist(&cache_path).map_err(|error| {
+        format!(
+            "Unable to finalize spectral cache {}: {error}",
+            cache_path.display()
+        )
+    })?;
+    Ok(analysis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze_samples, estimate_key, midi_note_name, yin_pitch};
+    use std::f32::consts::TAU;
+
+    fn tone(sample_rate: u32, freqs: &[f32], seconds: f32) -> Vec<f32> {
+        let total = (sample_rate as f32 * seconds) as usize;
+        (0..total)
+            .map(|index| {
+                let time = index as f32 / sample_rate as f32;
+                freqs.iter().map(|hz| (TAU * hz * time).sin()).sum::<f32>() * 0.2
+            })
+            .collect()
+    }
+
+    #[test]
+    fn yin_tracks_a440() {
+        let samples = tone(11_025, &[440.0], 0.2);
+        let (hz, confidence) = yin_pitch(&samples[..1024], 11_025.0).unwrap();
+        assert!((hz - 440.0).abs() < 2.0, "hz={hz}");
+        assert!(confidence > 0.8);
+        assert_eq!(midi_note_name(69.0), "A4");
+    }
+
+    #[test]
+    fn yin_ignores_silence() {
+        assert!(yin_pitch(&[0.0; 1024], 11_025.0).is_none());
+    }
+
+    #[test]
+    fn c_major_triad_is_detected_as_c_major() {
+        let mut chroma = [0.0f32; 12];
+        chroma[0] = 1.0;
+        chroma[4] = 1.0;
+        chroma[7] = 1.0;
+        assert_eq!(
+            estimate_key(&chroma).map(|(t, m, _)| (t, m)),
+            Some((0, true))
+        );
+    }
+
+    #[test]
+    fn click_track_reports_tempo_and_onsets() {
+        let sample_rate = 22_050;
+        let mut samples = vec![0.0f32; sample_rate as usize * 12];
+        let period = sample_rate as usize / 2;
+        for beat in (0..samples.len()).step_by(period) {
+            for (offset, sample) in samples[beat..].iter_mut().take(200).enumerate() {
+                *sample = (TAU * 1_000.0 * offset as f32 / sample_rate as f32).sin()
+                    * (1.0 - offset as f32 / 200.0);
+            }
+        }
+        let analysis = analyze_samples(&samples, sample_rate).unwrap();
+        let bpm = analysis["tempo"]["bpm"].as_f64().unwrap();
+        assert!((bpm - 120.0).abs() < 2.0, "bpm={bpm}");
+        let onsets = analysis["onset_count"].as_u64().unwrap();
+        assert!((22..=25).contains(&onsets), "onsets={onsets}");
+        assert_eq!(
+            analysis["spectrogram"]["band_edges_hz"]
+                .as_array()
+                .unwrap()
+                .len(),
+            97
+        );
+    }
+
+    #[test]
+    fn sustained_triad_reports_key_and_pitch() {
+        let analysis =
+            analyze_samples(&tone(44_100, &[261.63, 329.63, 392.0], 3.0), 44_100).unwrap();
+        assert_eq!(analysis["key"]["label"], "C major");
+        assert_eq!(analysis["analysis_sample_rate"], 22_050);
+    }
+}
@@ -0,0 +1,488 @@
+// Voice-activity detection
+// ~20 ms frames (the next power of two in samples, so each frame is exactly
+// one FFT) are classified as speech when their energy clears an adaptive
+// noise floor by `threshold_db` and their spectrum looks voiced: most power in
+// the 80–4000 Hz speech band and a low spectral flatness there (rumble and
+// broadband hiss each fail one of the two). Onsets need `min_speech_sec`
+// of consecutive speech frames; offsets wait out `hangover_sec` of silence.
+//
+// The detector is streaming and shared by three users: the recorder's
+// voice-armed sessions (auto-start/auto-stop), `trim_file` (drop leading and
+// trailing silence) and `analyze_file` (segments split at long pauses, as JSON
+// for the timeline).
+
+use super::fft::{FftPlan, SpectrumWindow};
+use super::SymphoniaStreamDecoder;
+use serde_json::{json, Value};
+use std::path::Path;
+
+const TARGET_FRAME_SEC: f64 = 0.02;
+/// Pauses shorter than this stay inside one segment.
+pub const DEFAULT_MIN_PAUSE_SEC: f64 = 0.7;
+/// Silence kept around trimmed or split speech so word edges are not clipped.
+pub const DEFAULT_PADDING_SEC: f64 = 0.15;
+const SPEECH_BAND_HZ: (f32, f32) = (80.0, 4_000.0);
+const MIN_SPEECH_BAND_RATIO: f32 = 0.6;
+const MAX_SPECTRAL_FLATNESS: f32 = 0.4;
+/// Frames quieter than this are never speech, whatever the floor.
+const ABSOLUTE_MIN_DB: f32 = -65.0;
+const INITIAL_FLOOR_DB: f32 = -60.0;
+/// Time constant for the floor creeping up under steady noise.
+const FLOOR_RISE_SEC: f32 = 3.0;
+
+#[derive(serde::Serialize, Clone, Copy, Debug)]
+pub struct VadConfig {
+    /// How far above the noise floor a frame must be to count as speech.
+    pub threshold_db: f32,
+    pub min_speech_sec: f64,
+    pub hangover_sec: f64,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            threshold_db: 10.0,
+            min_speech_sec: 0.1,
+            hangover_sec: 0.3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VadEvent {
+    SpeechStart { sec: f64 },
+    SpeechEnd { sec: f64 },
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct SpeechRegion {
+    pub start_sec: f64,
+    pub end_sec: f64,
+}
+
+pub struct VoiceActivityDetector {
+    config: VadConfig,
+    frame_len: usize,
+    frame_sec: f64,
+    frame: Vec<f32>,
+    plan: FftPlan,
+    magnitudes: Vec<f32>,
+    band_bins: (usize, usize),
+    noise_floor_db: f32,
+    floor_rise: f32,
+    min_speech_frames: u64,
+    hangover_frames: u64,
+    speech_run: u64,
+    silence_run: u64,
+    active: bool,
+    frames_seen: u64,
+    speech_frames: u64,
+}
+
+impl VoiceActivityDetector {
+    pub fn new(sample_rate: u32, config: VadConfig) -> Result<Self, String> {
+        if sample_rate < 8_000 {
+            return Err(format!(
+                "Voice detection needs at least 8 kHz audio, got {sample_rate} Hz"
+            ));
+        }
+        let frame_len = ((f64::from(sample_rate) * TARGET_FRAME_SEC) as usize).next_power_of_two();
+        let frame_sec = frame_len as f64 / f64::from(sample_rate);
+        let plan = FftPlan::new(frame_len, SpectrumWindow::Hann)?;
+        let bin_hz = sample_rate as f32 / frame_len as f32;
+        let band_bins = (
+            (SPEECH_BAND_HZ.0 / bin_hz).ceil() as usize,
+            ((SPEECH_BAND_HZ.1 / bin_hz).floor() as usize).min(plan.bin_count() - 1),
+        );
+        let frames_for = |seconds: f64| (seconds.max(0.0) / frame_sec).round() as u64;
+        Ok(Self {
+            config,
+            frame_len,
+            frame_sec,
+            frame: Vec::with_capacity(frame_len),
+            magnitudes: vec![0.0; plan.bin_count()],
+            plan,
+            band_bins,
+            noise_floor_db: INITIAL_FLOOR_DB,
+            floor_rise: 1.0 - (-(frame_sec as f32) / FLOOR_RISE_SEC).exp(),
+            min_speech_frames: frames_for(config.min_speech_sec).max(1),
+            hangover_frames: frames_for(config.hangover_sec),
+            speech_run: 0,
+            silence_run: 0,
+            active: false,
+            frames_seen: 0,
+            speech_frames: 0,
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn noise_floor_db(&self) -> f32 {
+        self.noise_floor_db
+    }
+
+    /// Seconds of input analysed so far (whole frames only).
+    pub fn position_sec(&self) -> f64 {
+        self.frames_seen as f64 * self.frame_sec
+    }
+
+    pub fn speech_sec(&self) -> f64 {
+        self.speech_frames as f64 * self.frame_sec
+    }
+
+    /// Feeds mono samples; speech boundaries are appended to `events`.
+    pub fn push(&mut self, mono: &[f32], events: &mut Vec<VadEvent>) {
+        let mut rest = mono;
+        while !rest.is_empty() {
+            let take = (self.frame_len - self.frame.len()).min(rest.len());
+            self.frame.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.frame.len() == self.frame_len {
+                let speech = self.classify_frame();
+                self.frame.clear();
+                self.advance(speech, events);
+            }
+        }
+    }
+
+    /// Closes a region still open at the end of the input.
+    pub fn finish(&mut self, events: &mut Vec<VadEvent>) {
+        if self.active {
+            self.active = false;
+            events.push(VadEvent::SpeechEnd {
+                sec: self.position_sec(),
+            });
+        }
+    }
+
+    fn classify_frame(&mut self) -> bool {
+        let energy =
+            self.frame.iter().map(|sample| sample * sample).sum::<f32>() / self.frame_len as f32;
+        let energy_db = 10.0 * energy.max(1e-12).log10();
+
+        self.plan.magnitudes(&self.frame, &mut self.magnitudes);
+        let (low, high) = self.band_bins;
+        let mut total = 0.0f32;
+        let mut band = 0.0f32;
+        let mut log_sum = 0.0f32;
+        for (bin, magnitude) in self.magnitudes.iter().enumerate().skip(1) {
+            let power = magnitude * magnitude;
+            total += power;
+            if (low..=high).contains(&bin) {
+                band += power;
+                log_sum += power.max(1e-20).ln();
+            }
+        }
+        let band_bins = (high + 1).saturating_sub(low).max(1) as f32;
+        let band_ratio = band / total.max(1e-20);
+        let flatness = (log_sum / band_bins).exp() / (band / band_bins).max(1e-20);
+
+        let loud = energy_db > ABSOLUTE_MIN_DB
+            && energy_db > self.noise_floor_db + self.config.threshold_db;
+        let speech =
+            loud && band_ratio >= MIN_SPEECH_BAND_RATIO && flatness <= MAX_SPECTRAL_FLATNESS;
+        if !speech {
+            // Drop straight to quieter frames, creep up under louder noise.
+            if energy_db < self.noise_floor_db {
+                self.noise_floor_db = energy_db.max(-100.0);
+            } else {
+                self.noise_floor_db += (energy_db - self.noise_floor_db) * self.floor_rise;
+            }
+        }
+        speech
+    }
+
+    fn advance(&mut self, speech: bool, events: &mut Vec<VadEvent>) {
+        let frame = self.frames_seen;
+        self.frames_seen += 1;
+        if speech {
+            self.speech_frames += 1;
+            self.speech_run += 1;
+            self.silence_run = 0;
+            if !self.active && self.speech_run >= self.min_speech_frames {
+                self.active = true;
+                events.push(VadEvent::SpeechStart {
+                    sec: (frame + 1 - self.speech_run) as f64 * self.frame_sec,
+                });
+            }
+        } else {
+            self.speech_run = 0;
+            self.silence_run += 1;
+            if self.active && self.silence_run > self.hangover_frames {
+                self.active = false;
+                events.push(VadEvent::SpeechEnd {
+                    sec: (frame + 1 - self.silence_run) as f64 * self.frame_sec,
+                });
+            }
+        }
+    }
+}
+
+/// Pairs start/end events into regions.
+pub fn regions_from_events(events: &[VadEvent]) -> Vec<SpeechRegion> {
+    let mut regions = Vec::new();
+    let mut open = None;
+    for event in events {
+        match *event {
+            VadEvent::SpeechStart { sec } => open = Some(sec),
+            VadEvent::SpeechEnd { sec } => {
+                if let Some(start_sec) = open.take() {
+                    regions.push(SpeechRegion {
+                        start_sec,
+                        end_sec: sec,
+                    });
+                }
+            }
+        }
+    }
+    regions
+}
+
+/// Merges regions separated by less than `min_pause_sec`, then pads each
+/// segment by `padding_sec` within `[0, duration_sec]`.
+pub fn split_segments(
+    regions: &[SpeechRegion],
+    min_pause_sec: f64,
+    padding_sec: f64,
+    duration_sec: f64,
+) -> Vec<SpeechRegion> {
+    let mut merged: Vec<SpeechRegion> = Vec::new();
+    for region in regions {
+        match merged.last_mut() {
+            Some(last) if region.start_sec - last.end_sec < min_pause_sec => {
+                last.end_sec = region.end_sec;
+            }
+            _ => merged.push(*region),
+        }
+    }
+    for segment in &mut merged {
+        segment.start_sec = (segment.start_sec - padding_sec).max(0.0);
+        segment.end_sec = (segment.end_sec + padding_sec).min(duration_sec);
+    }
+    merged
+}
+
+pub(super) fn mono(frames: &[kira::Frame]) -> Vec<f32> {
+    frames
+        .iter()
+        .map(|frame| (frame.left + frame.right) * 0.5)
+        .collect()
+}
+
+struct FileScan {
+    sample_rate: u32,
+    frame_count: u64,
+    regions: Vec<SpeechRegion>,
+    noise_floor_db: f32,
+    speech_sec: f64,
+}
+
+fn scan_file(path: &Path, config: VadConfig) -> Result<FileScan, String> {
+    let mut decoder = SymphoniaStreamDecoder::open_path(path)?;
+    let sample_rate = decoder.sample_rate();
+    let mut detector = VoiceActivityDetector::new(sample_rate, config)?;
+    let mut events = Vec::new();
+    let mut frame_count = 0u64;
+    while let Some(frames) = decoder.next_frames()? {
+        frame_count += frames.len() as u64;
+        detector.push(&mono(&frames), &mut events);
+    }
+    detector.finish(&mut events);
+    Ok(FileScan {
+        sample_rate,
+        frame_count,
+        regions: regions_from_events(&events),
+        noise_floor_db: detector.noise_floor_db(),
+        speech_sec: detector.speech_sec(),
+    })
+}
+
+fn segment_json(index: usize, segment: &SpeechRegion) -> Value {
+    json!({
+        "index": index,
+        "start_sec": segment.start_sec,
+        "end_sec": segment.end_sec,
+        "duration_sec": segment.end_sec - segment.start_sec
+    })
+}
+
+/// Speech regions and pause-split segments of any symphonia-decodable file.
+pub fn analyze_file(path: &Path, config: VadConfig, min_pause_sec: f64) -> Result<Value, String> {
+    let scan = scan_file(path, config)?;
+    let duration_sec = scan.frame_count as f64 / f64::from(scan.sample_rate);
+    let segments = split_segments(
+        &scan.regions,
+        min_pause_sec,
+        DEFAULT_PADDING_SEC,
+        duration_sec,
+    );
+    Ok(json!({
+        "duration_sec": duration_sec,
+        "sample_rate": scan.sample_rate,
+        "config": config,
+        "min_pause_sec": min_pause_sec,
+        "padding_sec": DEFAULT_PADDING_SEC,
+        "noise_floor_db": scan.noise_floor_db,
+        "speech_ratio": scan.speech_sec / duration_sec.max(f64::EPSILON),
+        "first_speech_sec": scan.regions.first().map(|region| region.start_sec),
+        "last_speech_sec": scan.regions.last().map(|region| region.end_sec),
+        "speech_regions": scan.regions,
+        "segment_count": segments.len(),
+        "segments": segments
+            .iter()
+            .enumerate()
+            .map(|(index, segment)| segment_json(index, segment))
+            .collect::<Vec<_>>()
+    }))
+}
+
+/// Writes a 32-bit float WAV copy of `source` without the silence before the
+/// first and after the last speech, keeping `padding_sec` on each side.
+pub fn trim_file(
+    source: &Path,
+    output: &Path,
+    config: VadConfig,
+    padding_sec: f64,
+) -> Result<Value, String> {
+    if source == output {
+        return Err("Trim output must differ from the source".to_string());
+    }
+    if !padding_sec.is_finite() || padding_sec < 0.0 {
+        return Err(format!("Invalid trim padding: {padding_sec}"));
+    }
+    let scan = scan_file(source, config)?;
+    let (Some(first), Some(last)) = (scan.regions.first(), scan.regions.last()) else {
+        return Err("audio_vad_no_speech: nothing to keep in this recording".to_string());
+    };
+    let rate = f64::from(scan.sample_rate);
+    let start_frame = ((first.start_sec - padding_sec).max(0.0) * rate).floor() as u64;
+    let end_frame = (((last.end_sec + padding_sec) * rate).ceil() as u64).min(scan.frame_count);
+
+    let mut decoder = SymphoniaStreamDecoder::open_path(source)?;
+    let channels = decoder.channels().clamp(1, 2) as u16;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| {
+            format!(
+                "Unable to create trim output directory {}: {error}",
+                parent.display()
+            )
+        })?;
+    }
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate: scan.sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(output, spec)
+        .map_err(|error| format!("Unable to create trimmed WAV: {error}"))?;
+    let write_error = |error: hound::Error| format!("Unable to write trimmed sample: {error}");
+    let mut position = 0u64;
+    while let Some(frames) = decoder.next_frames()? {
+        for frame in frames {
+            if (start_frame..end_frame).contains(&position) {
+                writer.write_sample(frame.left).map_err(write_error)?;
+                if channels == 2 {
+                    writer.write_sample(frame.right).map_err(write_error)?;
+                }
+            }
+            position += 1;
+        }
+        if position >= end_frame {
+            break;
+        }
+    }
+    writer
+        .finalize()
+        .map_err(|error| format!("Unable to finalize trimmed WAV: {error}"))?;
+
+    Ok(json!({
+        "output_path": output.to_string_lossy(),
+        "source_duration_sec": scan.frame_count as f64 / rate,
+        "start_sec": start_frame as f64 / rate,
+        "end_sec": end_frame as f64 / rate,
+        "duration_sec": (end_frame - start_frame) as f64 / rate,
+        "removed_leading_sec": start_frame as f64 / rate,
+        "removed_trailing_sec": (scan.frame_count - end_frame) as f64 / rate
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        regions_from_events, split_segments, SpeechRegion, VadConfig, VoiceActivityDetector,
+    };
+
+    const RATE: u32 = 16_000;
+
+    /// Pseudo-random hiss plus, inside `voiced`, a 140 Hz harmonic complex.
+    fn scene(seconds: f64, voiced: &[(f64, f64)]) -> Vec<f32> {
+        let mut seed = 0x2545_f491u32;
+        (0..(seconds * f64::from(RATE)) as usize)
+            .map(|index| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let hiss = (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+                let time = index as f64 / f64::from(RATE);
+                let voice = if voiced
+                    .iter()
+                    .any(|(start, end)| (*start..*end).contains(&time))
+                {
+                    (1..=20)
+                        .map(|harmonic| {
+                            let phase = std::f64::consts::TAU * 140.0 * f64::from(harmonic) * time;
+                            (phase.sin() / f64::from(harmonic)) as f32
+                        })
+                        .sum::<f32>()
+                        * 0.05
+                } else {
+                    0.0
+                };
+                hiss * 0.004 + voice
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_voiced_regions_and_ignores_hiss() {
+        let voiced = [(1.0, 2.0), (2.4, 3.0), (4.5, 5.5)];
+        let audio = scene(6.5, &voiced);
+        let mut detector = VoiceActivityDetector::new(RATE, VadConfig::default()).unwrap();
+        let mut events = Vec::new();
+        for chunk in audio.chunks(333) {
+            detector.push(chunk, &mut events);
+        }
+        detector.finish(&mut events);
+        let regions = regions_from_events(&events);
+
+        assert_eq!(regions.len(), 3, "{regions:?}");
+        for (region, (start, end)) in regions.iter().zip(voiced) {
+            assert!((region.start_sec - start).abs() <= 0.04, "{region:?}");
+            assert!((region.end_sec - end).abs() <= 0.04, "{region:?}");
+        }
+        assert!(detector.noise_floor_db() < -45.0);
+
+        // The short pause merges, the long one splits.
+        let segments = split_segments(&regions, 0.7, 0.15, 6.5);
+        assert_eq!(segments.len(), 2);
+        assert!((segments[0].start_sec - 0.85).abs() <= 0.04);
+        assert_eq!(segments[1].end_sec, regions[2].end_sec + 0.15);
+    }
+
+    #[test]
+    fn segments_are_clamped_to_the_recording() {
+        let regions = [SpeechRegion {
+            start_sec: 0.05,
+            end_sec: 0.95,
+        }];
+        let segments = split_segments(&regions, 0.7, 0.15, 1.0);
+        assert_eq!(
+            segments,
+            vec![SpeechRegion {
+                start_sec: 0.0,
+                end_sec: 1.0
+            }]
+        );
+    }
+}
@@ -3,9 +3,12 @@ use serde_json::Value;
 #[cfg(feature = "bevy_backend")]
 mod native {
     use atome_bevy_renderer_core::{
-        apply_render_ops, AtomeEntityTable, AtomeLayerPatch, AtomeParentPatch, AtomeRenderNode,
-        AtomeRenderOp, AtomeRenderScene, AtomeRendererDiagnostics, AtomeResourcePatch,
-        AtomeSceneEffectsPatch, AtomeStylePatch, AtomeSurfacePatch, AtomeTextPatch, AtomeTransformPatch,
+        apply_render_ops,
+        picking::{pick_at, pick_in_rect},
+        AtomeAnimation, AtomeEntityTable, AtomeLayerPatch, AtomeParentPatch, AtomePathPatch,
+        AtomePickOptions, AtomeRenderNode, AtomeRenderOp, AtomeRenderScene,
+        AtomeRendererDiagnostics, AtomeResourcePatch, AtomeSceneEffectsPatch, AtomeStylePatch,
+        AtomeSurfacePatch, AtomeTextPatch, AtomeTransformPatch, AtomeViewportPatch,
         AtomeVisibilityPatch,
     };
     use bevy::prelude::*;
@@ -119,6 +122,17 @@ mod native {
                 "bevy_native_scene_effects_patch_required",
             )
             .map(AtomeRenderOp::SceneEffects),
+            "path" => parse_patch::<AtomePathPatch>(input.patch, "bevy_native_path_patch_required")
+                .map(AtomeRenderOp::Path),
+            "animate" => {
+                parse_patch::<AtomeAnimation>(input.patch, "bevy_native_animate_patch_required")
+                    .map(AtomeRenderOp::Animate)
+            }
+            "viewport" => parse_patch::<AtomeViewportPatch>(
+                input.patch,
+                "bevy_native_viewport_patch_required",
+            )
+            .map(AtomeRenderOp::Viewport),
             other => Err(format!("bevy_native_op_unsupported:{other}")),
         }
     }
@@ -183,11 +197,51 @@ mod native {
             }
             apply_render_ops(
                 state.app.world_mut(),
-                vec![AtomeRenderOp::Surface(AtomeSurfacePatch::logical(width, height))],
+                vec![AtomeRenderOp::Surface(AtomeSurfacePatch::logical(
+                    width, height,
+                ))],
             );
             Ok(renderer_summary(state))
         })
     }
+
+    fn with_surface_world<T>(
+        surface_id: &str,
+        read: impl FnOnce(&World) -> T,
+    ) -> Result<T, String> {
+        NATIVE_RENDERER.with(|slot| {
+            let guard = slot.borrow();
+            let state = guard
+                .as_ref()
+                .ok_or_else(|| "bevy_native_renderer_not_started".to_string())?;
+            if state.surface_id != surface_id {
+                return Err("bevy_native_surface_id_mismatch".to_string());
+            }
+            Ok(read(state.app.world()))
+        })
+    }
+
+    pub fn pick(
+        surface_id: String,
+        x: f32,
+        y: f32,
+        options: Option<Value>,
+    ) -> Result<Value, String> {
+        let options = match options {
+            Some(options) if !options.is_null() => {
+                serde_json::from_value::<AtomePickOptions>(options)
+                    .map_err(|error| format!("bevy_native_pick_options_decode_failed:{error}"))?
+            }
+            _ => AtomePickOptions::default(),
+        };
+        let ids = with_surface_world(&surface_id, |world| pick_at(world, x, y, options))?;
+        Ok(json!({ "ids": ids }))
+    }
+
+    pub fn pick_rect(surface_id: String, rect: [f32; 4]) -> Result<Value, String> {
+        let ids = with_surface_world(&surface_id, |world| pick_in_rect(world, rect))?;
+        Ok(json!({ "ids": ids }))
+    }
 }
 
 #[tauri::command]
@@ -234,6 +288,37 @@ pub fn bevy_native_resize(surface_id: String, width: f32, height: f32) -> Result
     }
 }
 
+#[tauri::command]
+pub fn bevy_native_pick(
+    surface_id: String,
+    x: f32,
+    y: f32,
+    options: Option<Value>,
+) -> Result<Value, String> {
+    #[cfg(feature = "bevy_backend")]
+    {
+        return native::pick(surface_id, x, y, options);
+    }
+    #[cfg(not(feature = "bevy_backend"))]
+    {
+        let _ = (surface_id, x, y, options);
+        Err("bevy_native_renderer_feature_required".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn bevy_native_pick_in_rect(surface_id: String, rect: [f32; 4]) -> Result<Value, String> {
+    #[cfg(feature = "bevy_backend")]
+    {
+        return native::pick_rect(surface_id, rect);
+    }
+    #[cfg(not(feature = "bevy_backend"))]
+    {
+        let _ = (surface_id, rect);
+        Err("bevy_native_renderer_feature_required".to_string())
+    }
+}
+
 #[cfg(all(test, feature = "bevy_renderer_core"))]
 mod tests {
     use super::*;
@@ -276,6 +361,37 @@ mod tests {
         }))
         .expect("native Bevy transform op should decode");
         assert!(matches!(transform, AtomeRenderOp::Transform(_)));
+
+        let path = native::parse_native_op(json!({
+            "type": "path",
+            "patch": {
+                "id": "arrow_native",
+                "path": {
+                    "d": "M0 0 L40 20",
+                    "stroke": [1.0, 0.0, 0.0, 1.0],
+                    "stroke_width": 2.0,
+                    "line_cap": "round"
+                }
+            }
+        }))
+        .expect("native Bevy path op should decode");
+        assert!(matches!(path, AtomeRenderOp::Path(_)));
+
+        let animate = native::parse_native_op(json!({
+            "type": "animate",
+            "patch": {
+                "id": "fade_native",
+                "target_id": "image_native",
+                "duration_ms": 250.0,
+                "easing": { "type": "ease_out" },
+                "keyframes": [
+                    { "offset": 0.0, "opacity": 0.0 },
+                    { "offset": 1.0, "opacity": 1.0 }
+                ]
+            }
+        }))
+        .expect("native Bevy animate op should decode");
+        assert!(matches!(animate, AtomeRenderOp::Animate(_)));
     }
 
     #[test]
@@ -314,5 +430,16 @@ mod tests {
         assert_eq!(result["renderer_mode"], "embedded_scene");
         assert_eq!(result["surface_id"], "eve_surface_project");
         assert_eq!(result["node_count"], 1);
+
+        let hit = native::pick("eve_surface_project".to_string(), 20.0, 20.0, None)
+            .expect("native pick should read the embedded scene");
+        assert_eq!(hit["ids"], json!(["image_native_start"]));
+        let marquee = native::pick_rect("eve_surface_project".to_string(), [0.0, 0.0, 10.0, 10.0])
+            .expect("native rect pick should read the embedded scene");
+        assert_eq!(marquee["ids"], json!([]));
+        assert_eq!(
+            native::pick("other_surface".to_string(), 20.0, 20.0, None).unwrap_err(),
+            "bevy_native_surface_id_mismatch"
+        );
     }
 }
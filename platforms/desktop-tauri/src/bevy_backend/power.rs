@@ -406,6 +406,28 @@ mod tests {
         assert_eq!(expected_updates_per_minute(settings.focused_mode), Some(12));
     }
 
+    #[test]
+    fn animation_raises_update_budget_until_it_ends() {
+        let mut settings = atome_winit_settings_for_profile(AtomePowerProfile::Balanced);
+
+        apply_atome_power_activity(
+            &mut settings,
+            AtomePowerProfile::Balanced,
+            AtomeRenderActivity::Animation,
+        );
+        assert_eq!(
+            expected_updates_per_minute(settings.focused_mode),
+            Some(3600)
+        );
+
+        apply_atome_power_activity(
+            &mut settings,
+            AtomePowerProfile::Balanced,
+            AtomeRenderActivity::Idle,
+        );
+        assert_eq!(expected_updates_per_minute(settings.focused_mode), Some(12));
+    }
+
     #[test]
     fn redraw_requests_are_explicit_and_counted() {
         let mut state = AtomeBevyPowerState::default();
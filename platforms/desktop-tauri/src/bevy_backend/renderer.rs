@@ -9,7 +9,12 @@ use bevy::prelude::*;
 use bevy::window::{Window, WindowPlugin};
 
 #[cfg(feature = "bevy_renderer_native")]
-use super::{bevy_present_mode_for_profile, bevy_winit_settings_for_profile, AtomePowerProfile};
+use super::{
+    atome_winit_settings_for_activity, atome_winit_settings_to_bevy, bevy_present_mode_for_profile,
+    bevy_winit_settings_for_profile, AtomeBevyPowerState, AtomePowerProfile, AtomeRenderActivity,
+};
+#[cfg(feature = "bevy_renderer_native")]
+use atome_bevy_renderer_core::{animation::animations_active, viewport::viewport_flying};
 
 #[cfg(feature = "bevy_renderer_core")]
 #[derive(Clone, Debug, Resource)]
@@ -80,11 +85,45 @@ pub fn build_atome_bevy_embedded_app(config: AtomeNativeBevyRendererConfig) -> A
     app
 }
 
+// Raises the update budget while renderer-side animations or a viewport
+// fly-to run and drops back to the profile's idle policy once they finish.
+// Other activities are owned by their own producers and left untouched.
+#[cfg(feature = "bevy_renderer_native")]
+fn sync_animation_power_activity(world: &mut World) {
+    let activity = if animations_active(world) || viewport_flying(world) {
+        AtomeRenderActivity::Animation
+    } else {
+        AtomeRenderActivity::Idle
+    };
+    let Some(mut state) = world.get_resource_mut::<AtomeBevyPowerState>() else {
+        return;
+    };
+    if state.activity == activity
+        || !matches!(
+            state.activity,
+            AtomeRenderActivity::Idle | AtomeRenderActivity::Animation
+        )
+    {
+        return;
+    }
+    state.activity = activity;
+    let profile = state.profile;
+    world.insert_resource(atome_winit_settings_to_bevy(
+        atome_winit_settings_for_activity(profile, activity),
+    ));
+}
+
 #[cfg(feature = "bevy_renderer_native")]
 pub fn run_atome_bevy_native(config: AtomeNativeBevyRendererConfig) {
     let settings = bevy_winit_settings_for_profile(config.power_profile);
+    let power_state = AtomeBevyPowerState {
+        profile: config.power_profile,
+        ..AtomeBevyPowerState::default()
+    };
     let mut app = build_atome_bevy_app(config);
-    app.insert_resource(settings);
+    app.insert_resource(settings)
+        .insert_resource(power_state)
+        .add_systems(Last, sync_animation_power_activity);
     app.run();
 }
 
@@ -92,7 +131,8 @@ pub fn run_atome_bevy_native(config: AtomeNativeBevyRendererConfig) {
 mod tests {
     use super::*;
     use atome_bevy_renderer_core::{
-        AtomeEntityId, AtomeLogicalSize, AtomeRenderNode, AtomeRenderScene,
+        AtomeBlendMode, AtomeEntityId, AtomeLogicalSize, AtomeMaskMode, AtomeRenderNode,
+        AtomeRenderScene,
     };
     use bevy::app::{App, Startup};
     use bevy::window::Window;
@@ -121,12 +161,19 @@ mod tests {
             texture_size: None,
             uv_rect: None,
             texture: None,
+            texture_hash: None,
             peaks: None,
             playback_progress: None,
             selected: None,
             filters: None,
             transition: None,
             procedural: None,
+            path: None,
+            fill: None,
+            text_style: None,
+            blend_mode: AtomeBlendMode::Normal,
+            mask_id: None,
+            mask_mode: AtomeMaskMode::Alpha,
         }
     }
 
@@ -190,13 +190,29 @@ pub fn run() {
             audio_engine::bridge::audio_set_pan,
             audio_engine::bridge::audio_set_playback_rate,
             audio_engine::bridge::audio_record_start,
+            audio_engine::bridge::audio_record_arm,
+            audio_engine::bridge::audio_record_arm_voice,
+            audio_engine::bridge::audio_record_status,
+            audio_engine::bridge::audio_record_pause,
+            audio_engine::bridge::audio_record_resume,
+            audio_engine::bridge::audio_record_marker,
             audio_engine::bridge::audio_record_stop,
+            audio_engine::bridge::audio_record_recovered,
+            audio_engine::bridge::audio_vad_segments,
+            audio_engine::bridge::audio_vad_trim,
+            audio_engine::bridge::audio_diarize,
+            audio_engine::bridge::audio_input_fx_get,
+            audio_engine::bridge::audio_input_fx_set,
+            audio_engine::bridge::audio_monitor_set,
             audio_engine::bridge::audio_get_levels,
             audio_engine::bridge::audio_get_scope,
+            audio_engine::bridge::audio_get_spectrum,
             audio_engine::bridge::audio_shutdown,
             bevy_backend::bevy_native_start,
             bevy_backend::bevy_native_apply_ops,
             bevy_backend::bevy_native_resize,
+            bevy_backend::bevy_native_pick,
+            bevy_backend::bevy_native_pick_in_rect,
             project_root
         ])
         .setup(|app| {
@@ -251,6 +267,19 @@ pub fn run() {
                 project_root: project_root.clone(),
             });
 
+            let recovery_root = project_root.join("data");
+            std::thread::spawn(move || {
+                let recovered =
+                    audio_engine::recorder::recover_orphaned_recordings(&recovery_root);
+                if !recovered.is_empty() {
+                    println!(
+                        "🎙️  Recovered {} interrupted recording(s) under {:?}",
+                        recovered.len(),
+                        recovery_root
+                    );
+                }
+            });
+
             let default_base = path_resolver
                 .download_dir()
                 .ok()
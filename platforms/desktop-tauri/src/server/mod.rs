@@ -97,6 +97,11 @@ struct LocalFileQuery {
     path: Option<String>,
 }
 
+#[derive(Deserialize, Default)]
+struct AudioPeaksQuery {
+    summary: Option<bool>,
+}
+
 #[derive(Deserialize, Default)]
 struct MailSyncRequest {
     initial: Option<bool>,
@@ -139,6 +144,9 @@ struct RemoteAudioRecordStartRequest {
     #[serde(rename = "sampleRate")]
     sample_rate_camel: Option<u32>,
     channels: Option<u16>,
+    output_format: Option<String>,
+    #[serde(rename = "outputFormat")]
+    output_format_camel: Option<String>,
 }
 
 #[derive(Deserialize, Default)]
@@ -156,6 +164,17 @@ struct RemoteAudioAnalyzeRequest {
     absolute_file_path: Option<String>,
     #[serde(rename = "absoluteFilePath")]
     absolute_file_path_camel: Option<String>,
+    normalize_lufs: Option<f64>,
+    #[serde(rename = "normalizeLufs")]
+    normalize_lufs_camel: Option<f64>,
+    true_peak_ceiling_dbtp: Option<f64>,
+    #[serde(rename = "truePeakCeilingDbtp")]
+    true_peak_ceiling_dbtp_camel: Option<f64>,
+    normalize_output_path: Option<String>,
+    #[serde(rename = "normalizeOutputPath")]
+    normalize_output_path_camel: Option<String>,
+    spectral: Option<bool>,
+    vad: Option<bool>,
 }
 
 #[derive(Deserialize, Default)]
@@ -835,102 +854,148 @@ fn resolve_remote_audio_path(
     Ok(resolved)
 }
 
-fn analyze_wav_file(path: &Path) -> Result<JsonValue, String> {
-    let metadata = stdfs::metadata(path)
-        .map_err(|error| format!("Unable to read audio file metadata: {error}"))?;
-    let mut reader =
-        hound::WavReader::open(path).map_err(|error| format!("Unable to open WAV: {error}"))?;
-    let spec = reader.spec();
-    let channels = usize::from(spec.channels.max(1));
-    let sample_rate = spec.sample_rate.max(1);
-    let total_samples = reader.duration() as usize;
-    let frame_count = total_samples / channels;
-    let duration_sec = frame_count as f64 / sample_rate as f64;
-    let window_frames = ((sample_rate as f64 * 0.1).round() as usize).max(1);
-    let silence_threshold = 0.001_f64;
-
-    let mut sample_count: usize = 0;
-    let mut frame_index: usize = 0;
-    let mut window_sum_sq = 0.0_f64;
-    let mut window_peak = 0.0_f64;
-    let mut window_sample_count = 0usize;
-    let mut sum_sq = 0.0_f64;
-    let mut peak = 0.0_f64;
-    let mut non_silent_window_count = 0usize;
-    let mut silent_window_count = 0usize;
-    let mut first_non_silent_sec: Option<f64> = None;
-    let mut last_non_silent_sec: Option<f64> = None;
-    let mut windows = Vec::new();
-
-    let push_window = |window_start_frame: usize,
-                       window_sample_count: usize,
-                       window_sum_sq: f64,
-                       window_peak: f64,
-                       windows: &mut Vec<JsonValue>,
-                       non_silent_window_count: &mut usize,
-                       silent_window_count: &mut usize,
-                       first_non_silent_sec: &mut Option<f64>,
-                       last_non_silent_sec: &mut Option<f64>| {
-        if window_sample_count == 0 {
+/// Running RMS/peak/silence statistics over interleaved samples, shared by
+/// the WAV (hound) and decoded (symphonia) analysis paths.
+struct AudioWindowStats {
+    sample_rate: u32,
+    channels: usize,
+    window_frames: usize,
+    silence_threshold: f64,
+    sample_count: usize,
+    frame_index: usize,
+    window_sum_sq: f64,
+    window_peak: f64,
+    window_sample_count: usize,
+    sum_sq: f64,
+    peak: f64,
+    non_silent_window_count: usize,
+    silent_window_count: usize,
+    first_non_silent_sec: Option<f64>,
+    last_non_silent_sec: Option<f64>,
+    windows: Vec<JsonValue>,
+}
+
+impl AudioWindowStats {
+    fn new(sample_rate: u32, channels: usize) -> Self {
+        let sample_rate = sample_rate.max(1);
+        Self {
+            sample_rate,
+            channels: channels.max(1),
+            window_frames: ((sample_rate as f64 * 0.1).round() as usize).max(1),
+            silence_threshold: 0.001,
+            sample_count: 0,
+            frame_index: 0,
+            window_sum_sq: 0.0,
+            window_peak: 0.0,
+            window_sample_count: 0,
+            sum_sq: 0.0,
+            peak: 0.0,
+            non_silent_window_count: 0,
+            silent_window_count: 0,
+            first_non_silent_sec: None,
+            last_non_silent_sec: None,
+            windows: Vec::new(),
+        }
+    }
+
+    fn push_window(&mut self, window_start_frame: usize) {
+        if self.window_sample_count == 0 {
             return;
         }
-        let rms = (window_sum_sq / window_sample_count as f64).sqrt();
-        let start_sec = window_start_frame as f64 / sample_rate as f64;
-        let frames_in_window = (window_sample_count / channels).max(1);
-        let end_sec = (window_start_frame + frames_in_window) as f64 / sample_rate as f64;
-        let non_silent = rms >= silence_threshold || window_peak >= silence_threshold;
+        let rms = (self.window_sum_sq / self.window_sample_count as f64).sqrt();
+        let start_sec = window_start_frame as f64 / self.sample_rate as f64;
+        let frames_in_window = (self.window_sample_count / self.channels).max(1);
+        let end_sec = (window_start_frame + frames_in_window) as f64 / self.sample_rate as f64;
+        let non_silent =
+            rms >= self.silence_threshold || self.window_peak >= self.silence_threshold;
         if non_silent {
-            *non_silent_window_count += 1;
-            if first_non_silent_sec.is_none() {
-                *first_non_silent_sec = Some(start_sec);
+            self.non_silent_window_count += 1;
+            if self.first_non_silent_sec.is_none() {
+                self.first_non_silent_sec = Some(start_sec);
             }
-            *last_non_silent_sec = Some(end_sec);
+            self.last_non_silent_sec = Some(end_sec);
         } else {
-            *silent_window_count += 1;
+            self.silent_window_count += 1;
         }
-        if windows.len() < 240 {
-            windows.push(json!({
+        if self.windows.len() < 240 {
+            self.windows.push(json!({
                 "start_sec": start_sec,
                 "end_sec": end_sec,
                 "rms": rms,
-                "peak": window_peak,
+                "peak": self.window_peak,
                 "non_silent": non_silent
             }));
         }
-    };
+        self.window_sum_sq = 0.0;
+        self.window_peak = 0.0;
+        self.window_sample_count = 0;
+    }
+
+    fn push_sample(&mut self, value: f64) {
+        let abs = value.abs();
+        self.peak = self.peak.max(abs);
+        self.sum_sq += value * value;
+        self.window_peak = self.window_peak.max(abs);
+        self.window_sum_sq += value * value;
+        self.sample_count += 1;
+        self.window_sample_count += 1;
+        if self.sample_count % self.channels == 0 {
+            self.frame_index += 1;
+            if self.frame_index % self.window_frames == 0 {
+                self.push_window(self.frame_index.saturating_sub(self.window_frames));
+            }
+        }
+    }
+
+    fn finish(mut self, path: &Path, file_size: u64, frame_count: usize) -> JsonValue {
+        if self.window_sample_count > 0 {
+            let start_frame = self
+                .frame_index
+                .saturating_sub(self.window_sample_count / self.channels);
+            self.push_window(start_frame);
+        }
+        let rms = if self.sample_count > 0 {
+            (self.sum_sq / self.sample_count as f64).sqrt()
+        } else {
+            0.0
+        };
+        json!({
+            "path": path.to_string_lossy(),
+            "file_size": file_size,
+            "sample_rate": self.sample_rate,
+            "channels": self.channels,
+            "sample_count": self.sample_count,
+            "frame_count": frame_count,
+            "duration_sec": frame_count as f64 / self.sample_rate as f64,
+            "rms": rms,
+            "peak": self.peak,
+            "silence_threshold": self.silence_threshold,
+            "non_silent_window_count": self.non_silent_window_count,
+            "silent_window_count": self.silent_window_count,
+            "first_non_silent_sec": self.first_non_silent_sec,
+            "last_non_silent_sec": self.last_non_silent_sec,
+            "windows_truncated": frame_count > self.window_frames * 240,
+            "windows": self.windows
+        })
+    }
+}
+
+fn analyze_wav_samples(
+    path: &Path,
+    file_size: u64,
+    mut reader: hound::WavReader<std::io::BufReader<stdfs::File>>,
+) -> Result<JsonValue, String> {
+    let spec = reader.spec();
+    let channels = usize::from(spec.channels.max(1));
+    let frame_count = reader.duration() as usize / channels;
+    let mut stats = AudioWindowStats::new(spec.sample_rate, channels);
 
     match spec.sample_format {
         hound::SampleFormat::Float => {
             for sample in reader.samples::<f32>() {
                 let value =
                     sample.map_err(|error| format!("Unable to read float sample: {error}"))? as f64;
-                let abs = value.abs();
-                peak = peak.max(abs);
-                sum_sq += value * value;
-                window_peak = window_peak.max(abs);
-                window_sum_sq += value * value;
-                sample_count += 1;
-                window_sample_count += 1;
-                if sample_count % channels == 0 {
-                    frame_index += 1;
-                    if frame_index % window_frames == 0 {
-                        let start_frame = frame_index.saturating_sub(window_frames);
-                        push_window(
-                            start_frame,
-                            window_sample_count,
-                            window_sum_sq,
-                            window_peak,
-                            &mut windows,
-                            &mut non_silent_window_count,
-                            &mut silent_window_count,
-                            &mut first_non_silent_sec,
-                            &mut last_non_silent_sec,
-                        );
-                        window_sum_sq = 0.0;
-                        window_peak = 0.0;
-                        window_sample_count = 0;
-                    }
-                }
+                stats.push_sample(value);
             }
         }
         hound::SampleFormat::Int => {
@@ -939,79 +1004,65 @@ fn analyze_wav_file(path: &Path) -> Result<JsonValue, String> {
             for sample in reader.samples::<i32>() {
                 let raw =
                     sample.map_err(|error| format!("Unable to read integer sample: {error}"))?;
-                let value = raw as f64 / scale;
-                let abs = value.abs();
-                peak = peak.max(abs);
-                sum_sq += value * value;
-                window_peak = window_peak.max(abs);
-                window_sum_sq += value * value;
-                sample_count += 1;
-                window_sample_count += 1;
-                if sample_count % channels == 0 {
-                    frame_index += 1;
-                    if frame_index % window_frames == 0 {
-                        let start_frame = frame_index.saturating_sub(window_frames);
-                        push_window(
-                            start_frame,
-                            window_sample_count,
-                            window_sum_sq,
-                            window_peak,
-                            &mut windows,
-                            &mut non_silent_window_count,
-                            &mut silent_window_count,
-                            &mut first_non_silent_sec,
-                            &mut last_non_silent_sec,
-                        );
-                        window_sum_sq = 0.0;
-                        window_peak = 0.0;
-                        window_sample_count = 0;
-                    }
-                }
+                stats.push_sample(raw as f64 / scale);
             }
         }
     }
 
-    if window_sample_count > 0 {
-        let start_frame = frame_index.saturating_sub(window_sample_count / channels);
-        push_window(
-            start_frame,
-            window_sample_count,
-            window_sum_sq,
-            window_peak,
-            &mut windows,
-            &mut non_silent_window_count,
-            &mut silent_window_count,
-            &mut first_non_silent_sec,
-            &mut last_non_silent_sec,
-        );
+    let mut analysis = stats.finish(path, file_size, frame_count);
+    analysis["channels"] = json!(spec.channels);
+    analysis["sample_rate"] = json!(spec.sample_rate);
+    analysis["bits_per_sample"] = json!(spec.bits_per_sample);
+    analysis["sample_format"] = json!(format!("{:?}", spec.sample_format));
+    analysis["decoder"] = json!("hound");
+    Ok(analysis)
+}
+
+fn analyze_decoded_samples(path: &Path, file_size: u64) -> Result<JsonValue, String> {
+    let mut decoder = crate::audio_engine::SymphoniaStreamDecoder::open_path(path)?;
+    let channels = decoder.channels().clamp(1, 2);
+    let mut stats = AudioWindowStats::new(decoder.sample_rate(), channels);
+    let mut frame_count = 0usize;
+    while let Some(frames) = decoder.next_frames()? {
+        frame_count += frames.len();
+        for frame in frames {
+            stats.push_sample(frame.left as f64);
+            if channels == 2 {
+                stats.push_sample(frame.right as f64);
+            }
+        }
     }
+    let mut analysis = stats.finish(path, file_size, frame_count);
+    analysis["source_channels"] = json!(decoder.channels());
+    analysis["bits_per_sample"] = JsonValue::Null;
+    analysis["sample_format"] = json!("Float");
+    analysis["decoder"] = json!("symphonia");
+    Ok(analysis)
+}
 
-    let rms = if sample_count > 0 {
-        (sum_sq / sample_count as f64).sqrt()
-    } else {
-        0.0
+/// RMS/peak/silence summary plus EBU R128 loudness for any file hound or
+/// symphonia can read.
+fn analyze_audio_file(path: &Path) -> Result<JsonValue, String> {
+    let metadata = stdfs::metadata(path)
+        .map_err(|error| format!("Unable to read audio file metadata: {error}"))?;
+    let mut analysis = match hound::WavReader::open(path) {
+        Ok(reader) => analyze_wav_samples(path, metadata.len(), reader)?,
+        Err(_) => analyze_decoded_samples(path, metadata.len())?,
     };
+    analysis["loudness"] = match crate::audio_engine::loudness::measure_file(path) {
+        Ok(report) => report.to_json(),
+        Err(error) => json!({ "error": error }),
+    };
+    Ok(analysis)
+}
 
-    Ok(json!({
-        "path": path.to_string_lossy(),
-        "file_size": metadata.len(),
-        "sample_rate": spec.sample_rate,
-        "channels": spec.channels,
-        "bits_per_sample": spec.bits_per_sample,
-        "sample_format": format!("{:?}", spec.sample_format),
-        "sample_count": sample_count,
-        "frame_count": frame_count,
-        "duration_sec": duration_sec,
-        "rms": rms,
-        "peak": peak,
-        "silence_threshold": silence_threshold,
-        "non_silent_window_count": non_silent_window_count,
-        "silent_window_count": silent_window_count,
-        "first_non_silent_sec": first_non_silent_sec,
-        "last_non_silent_sec": last_non_silent_sec,
-        "windows_truncated": frame_count > window_frames * 240,
-        "windows": windows
-    }))
+fn default_normalized_output_path(source: &Path) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .and_then(|value| value.to_str())
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or("audio");
+    source.with_file_name(format!("{stem}.normalized.wav"))
 }
 
 async fn remote_audio_record_start_handler(
@@ -1028,6 +1079,16 @@ async fn remote_audio_record_start_handler(
         .or(payload.session_id_camel)
         .map(|value| clean_remote_segment(&value, "remote_session"))
         .unwrap_or_else(|| format!("remote_{}", Uuid::new_v4()));
+    let output_format = match payload
+        .output_format
+        .or(payload.output_format_camel)
+        .as_deref()
+        .map(crate::audio_engine::recorder::OutputFormat::from_name)
+        .transpose()
+    {
+        Ok(format) => format.unwrap_or_default(),
+        Err(error) => return json_error(StatusCode::BAD_REQUEST, &error).into_response(),
+    };
     let requested_path = payload.file_path.or(payload.file_path_camel);
     let path = match resolve_remote_audio_path(
         &state,
@@ -1035,6 +1096,8 @@ async fn remote_audio_record_start_handler(
         Some(&session_id),
         false,
     ) {
+        // The default path is `<session>.wav`; keep it matching the encoder.
+        Ok(path) if requested_path.is_none() => path.with_extension(output_format.extension()),
         Ok(path) => path,
         Err(error) => return json_error(StatusCode::BAD_REQUEST, &error).into_response(),
     };
@@ -1046,11 +1109,18 @@ async fn remote_audio_record_start_handler(
 
     crate::audio_engine::metering::reset();
     let path_string = path.to_string_lossy().to_string();
-    match crate::audio_engine::recorder::start(&session_id, &path_string, sample_rate, channels) {
+    match crate::audio_engine::recorder::start_with_options(
+        &session_id,
+        &path_string,
+        sample_rate,
+        channels,
+        output_format,
+        crate::audio_engine::recorder::BufferSizeHint::default(),
+    ) {
         Ok(()) => {
             println!(
-                "[TauriRemote] audio record start session={} user={:?} path={} sr={} ch={}",
-                session_id, user_id, path_string, sample_rate, channels
+                "[TauriRemote] audio record start session={} user={:?} path={} sr={} ch={} format={:?}",
+                session_id, user_id, path_string, sample_rate, channels, output_format
             );
             Json(json!({
                 "success": true,
@@ -1059,6 +1129,7 @@ async fn remote_audio_record_start_handler(
                 "absolute_file_path": path_string,
                 "sample_rate_requested": sample_rate,
                 "channels_requested": channels,
+                "output_format": format!("{:?}", output_format),
                 "user_id": user_id
             }))
             .into_response()
@@ -1083,7 +1154,7 @@ async fn remote_audio_record_stop_handler(
         .unwrap_or_else(|| "remote_session".to_string());
     match crate::audio_engine::recorder::stop(&session_id) {
         Ok(result) => {
-            let analysis = analyze_wav_file(Path::new(&result.file_path))
+            let analysis = analyze_audio_file(Path::new(&result.file_path))
                 .unwrap_or_else(|error| json!({ "success": false, "error": error }));
             println!(
                 "[TauriRemote] audio record stop session={} user={:?} path={} duration={} frames={}",
@@ -1099,6 +1170,7 @@ async fn remote_audio_record_stop_handler(
                 "sample_rate": result.sample_rate,
                 "channels": result.channels,
                 "output_format": result.output_format,
+                "markers": result.markers,
                 "analysis": analysis,
                 "user_id": user_id
             }))
@@ -1126,11 +1198,60 @@ async fn remote_audio_analyze_handler(
         Ok(path) => path,
         Err(error) => return json_error(StatusCode::BAD_REQUEST, &error).into_response(),
     };
-    match analyze_wav_file(&path) {
-        Ok(analysis) => Json(json!({
+    let normalize_target = payload.normalize_lufs.or(payload.normalize_lufs_camel);
+    let normalize_output = match normalize_target {
+        Some(_) => {
+            let requested_output = payload
+                .normalize_output_path
+                .or(payload.normalize_output_path_camel);
+            let output = match requested_output {
+                Some(requested) => resolve_remote_audio_path(&state, Some(&requested), None, false),
+                None => Ok(default_normalized_output_path(&path)),
+            };
+            match output {
+                Ok(output) => Some(output),
+                Err(error) => return json_error(StatusCode::BAD_REQUEST, &error).into_response(),
+            }
+        }
+        None => None,
+    };
+    let ceiling = payload
+        .true_peak_ceiling_dbtp
+        .or(payload.true_peak_ceiling_dbtp_camel)
+        .unwrap_or(crate::audio_engine::loudness::DEFAULT_TRUE_PEAK_CEILING_DBTP);
+    let include_spectral = payload.spectral.unwrap_or(false);
+    let include_vad = payload.vad.unwrap_or(false);
+
+    let result = tokio::task::spawn_blocking(move || {
+        let mut analysis = analyze_audio_file(&path)?;
+        if include_spectral {
+            analysis["spectral"] = crate::audio_engine::spectral::analyze_file_cached(&path)?;
+        }
+        if include_vad {
+            analysis["vad"] = crate::audio_engine::vad::analyze_file(
+                &path,
+                crate::audio_engine::vad::VadConfig::default(),
+                crate::audio_engine::vad::DEFAULT_MIN_PAUSE_SEC,
+            )?;
+        }
+        let normalization = match (normalize_target, normalize_output) {
+            (Some(target), Some(output)) => Some(crate::audio_engine::loudness::normalize_file(
+                &path, &output, target, ceiling,
+            )?),
+            _ => None,
+        };
+        Ok::<_, String>((analysis, normalization))
+    })
+    .await
+    .map_err(|error| format!("audio_analyze_task_failed: {error}"))
+    .and_then(|inner| inner);
+
+    match result {
+        Ok((analysis, normalization)) => Json(json!({
             "success": true,
             "runtime": "tauri",
             "analysis": analysis,
+            "normalization": normalization,
             "user_id": user_id
         }))
         .into_response(),
@@ -3666,6 +3787,142 @@ async fn extract_audio_handler(
     }
 }
 
+async fn audio_peaks_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<MediaTokenQuery>,
+    Query(peaks_query): Query<AudioPeaksQuery>,
+    AxumPath(file): AxumPath<String>,
+) -> impl IntoResponse {
+    let auth_state = match &state.auth_state {
+        Some(s) => s,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "success": false, "error": "Auth state not initialized" })),
+            )
+                .into_response();
+        }
+    };
+
+    let user_id =
+        if let Some(value) = resolve_media_authenticated_user(&headers, &query, auth_state) {
+            value
+        } else {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "success": false, "error": "Unauthorized" })),
+            )
+                .into_response();
+        };
+
+    let safe_name = sanitize_file_name(&file);
+    let mut candidates = Vec::new();
+    for root in [LocalStorageRoot::Recordings, LocalStorageRoot::Downloads] {
+        match resolve_user_storage_dir(&state, &user_id, root).await {
+            Ok(dir) => candidates.push(dir.join(&safe_name)),
+            Err(err) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "success": false, "error": err.to_string() })),
+                )
+                    .into_response();
+            }
+        }
+    }
+    let mut source_path = None;
+    for candidate in &candidates {
+        if fs::metadata(candidate).await.is_ok() {
+            source_path = Some(candidate.clone());
+            break;
+        }
+    }
+    let Some(source_path) = source_path else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "success": false, "error": "Source file not found", "file": safe_name })),
+        )
+            .into_response();
+    };
+
+    // Peak generation decodes the whole file once; later zoom levels are
+    // served from the cached pyramid via range reads.
+    let peak_result = tokio::task::spawn_blocking(move || {
+        crate::audio_engine::peaks::ensure_peak_file(&source_path)
+    })
+    .await
+    .map_err(|err| format!("peak_task_failed: {err}"))
+    .and_then(|inner| inner);
+    let peak_path = match peak_result {
+        Ok(path) => path,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "success": false, "error": err })),
+            )
+                .into_response();
+        }
+    };
+
+    if peaks_query.summary.unwrap_or(false) {
+        return match crate::audio_engine::peaks::read_peak_summary(&peak_path) {
+            Ok(summary) => Json(json!({ "success": true, "peaks": summary })).into_response(),
+            Err(err) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "success": false, "error": err })),
+            )
+                .into_response(),
+        };
+    }
+
+    let metadata = match fs::metadata(&peak_path).await {
+        Ok(value) => value,
+        Err(err) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "success": false, "error": format!("Peak file missing: {err}") })),
+            )
+                .into_response();
+        }
+    };
+    let modified_epoch = metadata
+        .modified()
+        .ok()
+        .and_then(|value| value.duration_since(UNIX_EPOCH).ok())
+        .map(|value| value.as_secs())
+        .unwrap_or(0);
+    let etag_value = format!("W/\"{}-{}\"", metadata.len(), modified_epoch);
+    let if_none_match = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default();
+    if !if_none_match.is_empty() && if_none_match == etag_value {
+        let mut response_headers = HeaderMap::new();
+        if let Ok(header_value) = HeaderValue::from_str(&etag_value) {
+            response_headers.insert(header::ETAG, header_value);
+        }
+        return (StatusCode::NOT_MODIFIED, response_headers).into_response();
+    }
+
+    match serve_file_with_range(
+        &peak_path,
+        "application/octet-stream",
+        &etag_value,
+        &headers,
+        &format!("inline; filename=\"{}.peaks\"", safe_name),
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "success": false, "error": format!("Unable to serve peaks: {err}") })),
+        )
+            .into_response(),
+    }
+}
+
 async fn download_recording_handler(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -5332,6 +5589,7 @@ pub async fn start_server(static_dir: PathBuf, uploads_dir: PathBuf, data_dir: P
         )
         .route("/api/uploads/:file", get(download_upload_handler))
         .route("/api/extract-audio/:file", get(extract_audio_handler))
+        .route("/api/audio-peaks/:file", get(audio_peaks_handler))
         .route("/api/recordings/:id", get(download_recording_handler))
         .route("/api/user-recordings", post(user_recordings_upload_handler))
         .route("/api/admin/apply-update", post(update_file_handler))
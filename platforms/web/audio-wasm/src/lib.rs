@@ -22,6 +22,19 @@ use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 use wasm_bindgen::prelude::*;
 
+// Input metering (levels, scope, spectrum) is shared verbatim with the
+// native engine.
+#[path = "../../../desktop-tauri/src/audio_engine/fft.rs"]
+mod fft;
+// The native-only sample formats and scope reader go unused here.
+#[allow(dead_code)]
+#[path = "../../../desktop-tauri/src/audio_engine/metering.rs"]
+mod metering;
+#[path = "../../../desktop-tauri/src/audio_engine/metering_scope.rs"]
+mod metering_scope;
+#[path = "../../../desktop-tauri/src/audio_engine/metering_spectrum.rs"]
+mod metering_spectrum;
+
 struct ClipEntry {
     data: StaticSoundData,
 }
@@ -421,11 +434,39 @@ pub fn audio_destroy_clip(id: &str) -> Result<(), JsValue> {
     Ok(())
 }
 
+/// Feed interleaved capture samples (e.g. from the recording worklet) to the
+/// metering path, which updates levels, scope and spectrum like the native
+/// recorder's input callback.
+#[wasm_bindgen]
+pub fn audio_push_samples(samples: &[f32], sample_rate: u32, channels: u16) {
+    metering::configure_scope(sample_rate, channels);
+    metering::push_samples(samples);
+}
+
+#[wasm_bindgen]
+pub fn audio_get_levels() -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&metering::get_levels())
+        .map_err(|e| JsValue::from_str(&format!("Levels encode error: {e}")))
+}
+
+#[wasm_bindgen]
+pub fn audio_get_spectrum(
+    fft_size: Option<usize>,
+    window: Option<String>,
+    smoothing: Option<f32>,
+) -> Result<JsValue, JsValue> {
+    let spectrum = metering::get_spectrum(fft_size, window.as_deref(), smoothing)
+        .map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&spectrum)
+        .map_err(|e| JsValue::from_str(&format!("Spectrum encode error: {e}")))
+}
+
 #[wasm_bindgen]
 pub fn audio_shutdown() -> Result<(), JsValue> {
     let mut guard = ENGINE
         .lock()
         .map_err(|e| JsValue::from_str(&format!("Lock error: {e}")))?;
     *guard = None;
+    metering::reset();
     Ok(())
 }
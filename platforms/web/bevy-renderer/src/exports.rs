@@ -1,10 +1,14 @@
 use super::*;
+use atome_bevy_renderer_core::op_stream::decode_op_stream;
+use atome_bevy_renderer_core::raster::rasterize_scene;
 use atome_bevy_renderer_core::{
-    AtomeLayerPatch, AtomeParentPatch, AtomeRenderNode, AtomeRenderOp, AtomeRenderScene,
+    AtomeAnimation, AtomeAnimationEvent, AtomeLayerPatch, AtomeParentPatch, AtomePathPatch,
+    AtomePickOptions, AtomeRasterOptions, AtomeRenderNode, AtomeRenderOp, AtomeRenderScene,
     AtomeResourcePatch, AtomeSceneEffectsPatch, AtomeStylePatch, AtomeSurfaceBackgroundPatch,
-    AtomeSurfacePatch, AtomeTextPatch, AtomeTransformPatch, AtomeUiOp, AtomeVisibilityPatch,
+    AtomeSurfacePatch, AtomeTextPatch, AtomeTransformPatch, AtomeUiOp, AtomeViewportPatch,
+    AtomeVisibilityPatch,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 #[derive(Clone, Debug, Deserialize)]
@@ -29,6 +33,9 @@ enum WebAtomeRenderOp {
     Surface { patch: AtomeSurfacePatch },
     SurfaceBackground { patch: AtomeSurfaceBackgroundPatch },
     SceneEffects { patch: AtomeSceneEffectsPatch },
+    Path { patch: AtomePathPatch },
+    Animate { animation: AtomeAnimation },
+    Viewport { patch: AtomeViewportPatch },
 }
 
 impl From<WebAtomeRenderOp> for AtomeRenderOp {
@@ -48,6 +55,9 @@ impl From<WebAtomeRenderOp> for AtomeRenderOp {
                 AtomeRenderOp::SurfaceBackground(patch)
             }
             WebAtomeRenderOp::SceneEffects { patch } => AtomeRenderOp::SceneEffects(patch),
+            WebAtomeRenderOp::Path { patch } => AtomeRenderOp::Path(patch),
+            WebAtomeRenderOp::Animate { animation } => AtomeRenderOp::Animate(animation),
+            WebAtomeRenderOp::Viewport { patch } => AtomeRenderOp::Viewport(patch),
         }
     }
 }
@@ -140,6 +150,13 @@ pub fn apply_atome_bevy_ops(ops: JsValue) -> Result<(), JsValue> {
     Ok(())
 }
 
+#[wasm_bindgen]
+pub fn apply_atome_bevy_op_stream(bytes: &[u8]) -> Result<(), JsValue> {
+    let ops = decode_op_stream(bytes).map_err(|error| JsValue::from_str(&error))?;
+    queue_web_ops(ops);
+    Ok(())
+}
+
 #[wasm_bindgen]
 pub fn apply_atome_bevy_ui_ops(ops: JsValue) -> Result<(), JsValue> {
     let parsed: Vec<AtomeUiOp> = serde_wasm_bindgen::from_value(ops)
@@ -267,6 +284,121 @@ pub fn apply_atome_bevy_scene_effects(patch: JsValue) -> Result<(), JsValue> {
     Ok(())
 }
 
+#[wasm_bindgen]
+pub fn apply_atome_bevy_path(patch: JsValue) -> Result<(), JsValue> {
+    let parsed: AtomePathPatch = serde_wasm_bindgen::from_value(patch)
+        .map_err(|error| JsValue::from_str(&format!("bevy_path_decode_failed:{error}")))?;
+    queue_web_op(AtomeRenderOp::Path(parsed));
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn apply_atome_bevy_animate(animation: JsValue) -> Result<(), JsValue> {
+    let parsed: AtomeAnimation = serde_wasm_bindgen::from_value(animation)
+        .map_err(|error| JsValue::from_str(&format!("bevy_animate_decode_failed:{error}")))?;
+    queue_web_op(AtomeRenderOp::Animate(parsed));
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn apply_atome_bevy_viewport(patch: JsValue) -> Result<(), JsValue> {
+    let parsed: AtomeViewportPatch = serde_wasm_bindgen::from_value(patch)
+        .map_err(|error| JsValue::from_str(&format!("bevy_viewport_decode_failed:{error}")))?;
+    queue_web_op(AtomeRenderOp::Viewport(parsed));
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn drain_atome_bevy_animation_events() -> Result<JsValue, JsValue> {
+    let events: Vec<AtomeAnimationEvent> = drain_web_animation_events();
+    serde_wasm_bindgen::to_value(&events)
+        .map_err(|error| JsValue::from_str(&format!("bevy_animation_events_encode_failed:{error}")))
+}
+
+#[wasm_bindgen]
+pub fn pick_atome_bevy_at(x: f32, y: f32, options: JsValue) -> Result<JsValue, JsValue> {
+    let options = if options.is_undefined() || options.is_null() {
+        AtomePickOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options).map_err(|error| {
+            JsValue::from_str(&format!("bevy_pick_options_decode_failed:{error}"))
+        })?
+    };
+    serde_wasm_bindgen::to_value(&pick_web_at(x, y, options))
+        .map_err(|error| JsValue::from_str(&format!("bevy_pick_encode_failed:{error}")))
+}
+
+#[wasm_bindgen]
+pub fn pick_atome_bevy_in_rect(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&pick_web_in_rect([x, y, width, height]))
+        .map_err(|error| JsValue::from_str(&format!("bevy_pick_encode_failed:{error}")))
+}
+
+#[wasm_bindgen]
+pub fn read_atome_bevy_scene() -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&read_web_scene())
+        .map_err(|error| JsValue::from_str(&format!("bevy_scene_encode_failed:{error}")))
+}
+
+#[wasm_bindgen]
+pub fn diff_atome_bevy_scene(expected: JsValue) -> Result<JsValue, JsValue> {
+    let expected: AtomeRenderScene = serde_wasm_bindgen::from_value(expected)
+        .map_err(|error| JsValue::from_str(&format!("bevy_scene_decode_failed:{error}")))?;
+    serde_wasm_bindgen::to_value(&diff_web_scene(&expected))
+        .map_err(|error| JsValue::from_str(&format!("bevy_scene_diff_encode_failed:{error}")))
+}
+
+#[wasm_bindgen]
+pub fn resync_atome_bevy_scene(expected: JsValue) -> Result<JsValue, JsValue> {
+    let expected: AtomeRenderScene = serde_wasm_bindgen::from_value(expected)
+        .map_err(|error| JsValue::from_str(&format!("bevy_scene_decode_failed:{error}")))?;
+    serde_wasm_bindgen::to_value(&resync_web_scene(&expected))
+        .map_err(|error| JsValue::from_str(&format!("bevy_scene_diff_encode_failed:{error}")))
+}
+
+// PNG bytes serialize as a `Uint8Array` rather than an array of numbers.
+struct WebPngBytes(Vec<u8>);
+
+impl Serialize for WebPngBytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+#[derive(Serialize)]
+struct WebRasterOutput {
+    png: WebPngBytes,
+    skipped_nodes: Vec<String>,
+    skipped_effects: Vec<String>,
+}
+
+// Returns `{ png, skipped_nodes, skipped_effects }`: nodes and scene effects
+// the CPU rasterizer cannot draw are missing from `png` and listed by id.
+#[wasm_bindgen]
+pub fn rasterize_atome_bevy_scene(scene: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+    let scene: AtomeRenderScene = serde_wasm_bindgen::from_value(scene)
+        .map_err(|error| JsValue::from_str(&format!("bevy_scene_decode_failed:{error}")))?;
+    let options: AtomeRasterOptions = serde_wasm_bindgen::from_value(options).map_err(|error| {
+        JsValue::from_str(&format!("bevy_raster_options_decode_failed:{error}"))
+    })?;
+    let output = rasterize_scene(&scene, options).map_err(|error| JsValue::from_str(&error))?;
+    let png = output
+        .image
+        .to_png()
+        .map_err(|error| JsValue::from_str(&error))?;
+    serde_wasm_bindgen::to_value(&WebRasterOutput {
+        png: WebPngBytes(png),
+        skipped_nodes: output.skipped_nodes,
+        skipped_effects: output.skipped_effects,
+    })
+    .map_err(|error| JsValue::from_str(&format!("bevy_raster_encode_failed:{error}")))
+}
+
 #[wasm_bindgen]
 pub fn request_atome_bevy_redraw() {
     request_web_redraw();
@@ -277,6 +409,13 @@ pub fn register_atome_bevy_font(weight: u16, bytes: Vec<u8>) {
     queue_web_ui_font(weight, bytes);
 }
 
+// Registers a TTF as `family` at `weight` for scene text nodes whose
+// `text_style.font_family` names it.
+#[wasm_bindgen]
+pub fn register_atome_bevy_text_font(family: String, weight: u16, bytes: Vec<u8>) {
+    queue_web_text_font(family, weight, bytes);
+}
+
 #[wasm_bindgen]
 pub fn notify_atome_bevy_video_frame(id: String, frame_version: u32) {
     notify_web_video_frame(id, frame_version);
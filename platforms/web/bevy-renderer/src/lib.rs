@@ -1,9 +1,19 @@
+use atome_bevy_renderer_core::text_layout::register_text_font;
+use atome_bevy_renderer_core::{animation::drain_animation_events, AtomeAnimationEvent};
 use atome_bevy_renderer_core::{
     apply_render_ops, apply_surface, apply_ui_ops, register_ui_font, ui_viewport_size,
     AtomeBevyRendererConfig, AtomeBevyRendererPlugin, AtomeRenderOp, AtomeRenderScene,
     AtomeRendererDiagnostics, AtomeStylePatch, AtomeSurfacePatch, AtomeUiDiagnostics, AtomeUiEvent,
     AtomeUiOp,
 };
+use atome_bevy_renderer_core::{
+    picking::{pick_at, pick_in_rect},
+    AtomePickOptions,
+};
+use atome_bevy_renderer_core::{
+    readback::{diff_scene, read_scene, resync_ops},
+    AtomeSceneDiff,
+};
 use bevy::platform::time::Instant;
 use bevy::{
     log::{Level, LogPlugin},
@@ -24,8 +34,10 @@ thread_local! {
     static WEB_PENDING_OPS: RefCell<Vec<AtomeRenderOp>> = RefCell::new(Vec::new());
     static WEB_PENDING_UI_OPS: RefCell<Vec<AtomeUiOp>> = RefCell::new(Vec::new());
     static WEB_PENDING_UI_FONTS: RefCell<Vec<(u16, Vec<u8>)>> = const { RefCell::new(Vec::new()) };
+    static WEB_PENDING_TEXT_FONTS: RefCell<Vec<(String, u16, Vec<u8>)>> = const { RefCell::new(Vec::new()) };
     static WEB_LAST_UI_DIAGNOSTICS: RefCell<AtomeUiDiagnostics> = RefCell::new(AtomeUiDiagnostics::default());
     static WEB_DRAINED_UI_EVENTS: RefCell<Vec<AtomeUiEvent>> = const { RefCell::new(Vec::new()) };
+    static WEB_DRAINED_ANIMATION_EVENTS: RefCell<Vec<AtomeAnimationEvent>> = const { RefCell::new(Vec::new()) };
     static WEB_PENDING_VIDEO_FRAMES: RefCell<u32> = const { RefCell::new(0) };
     static WEB_EVENT_LOOP_PROXY: RefCell<Option<EventLoopProxy<WinitUserEvent>>> = const { RefCell::new(None) };
     static WEB_WAKE_PENDING: RefCell<bool> = const { RefCell::new(false) };
@@ -252,9 +264,12 @@ fn op_targets_id(op: &AtomeRenderOp, id: &str) -> bool {
         AtomeRenderOp::Visibility(patch) => patch.id == id,
         AtomeRenderOp::Text(patch) => patch.id == id,
         AtomeRenderOp::Resource(patch) => patch.id == id,
+        AtomeRenderOp::Path(patch) => patch.id == id,
+        AtomeRenderOp::Animate(animation) => animation.target_id == id,
         AtomeRenderOp::Surface(_)
         | AtomeRenderOp::SurfaceBackground(_)
-        | AtomeRenderOp::SceneEffects(_) => false,
+        | AtomeRenderOp::SceneEffects(_)
+        | AtomeRenderOp::Viewport(_) => false,
     }
 }
 
@@ -286,6 +301,18 @@ fn merge_style_patch_fields(existing: &mut AtomeStylePatch, next: &AtomeStylePat
     if next.procedural.is_some() {
         existing.procedural = next.procedural;
     }
+    if next.fill.is_some() {
+        existing.fill = next.fill.clone();
+    }
+    if next.blend_mode.is_some() {
+        existing.blend_mode = next.blend_mode;
+    }
+    if next.mask_id.is_some() {
+        existing.mask_id = next.mask_id.clone();
+    }
+    if next.mask_mode.is_some() {
+        existing.mask_mode = next.mask_mode;
+    }
 }
 
 // Coalesce per-atome style patches while they wait in the queue: applying
@@ -398,6 +425,51 @@ fn drain_web_ui_events() -> Vec<AtomeUiEvent> {
     WEB_DRAINED_UI_EVENTS.with(|cell| cell.borrow_mut().drain(..).collect())
 }
 
+fn drain_web_animation_events() -> Vec<AtomeAnimationEvent> {
+    WEB_DRAINED_ANIMATION_EVENTS.with(|cell| cell.borrow_mut().drain(..).collect())
+}
+
+// Picks read the scene as of the last applied frame; ops still queued for the
+// next frame are not reflected.
+fn pick_web_at(x: f32, y: f32, options: AtomePickOptions) -> Vec<String> {
+    WEB_RUNNING_APPS.with(|cell| {
+        cell.borrow()
+            .last()
+            .map(|app| pick_at(app.world(), x, y, options))
+            .unwrap_or_default()
+    })
+}
+
+fn pick_web_in_rect(rect: [f32; 4]) -> Vec<String> {
+    WEB_RUNNING_APPS.with(|cell| {
+        cell.borrow()
+            .last()
+            .map(|app| pick_in_rect(app.world(), rect))
+            .unwrap_or_default()
+    })
+}
+
+// Like picks, readback sees the scene as of the last applied frame, so a
+// resync issued while ops are still queued may repeat their work.
+fn read_web_scene() -> AtomeRenderScene {
+    WEB_RUNNING_APPS.with(|cell| {
+        cell.borrow()
+            .last()
+            .map(|app| read_scene(app.world()))
+            .unwrap_or_default()
+    })
+}
+
+fn diff_web_scene(expected: &AtomeRenderScene) -> AtomeSceneDiff {
+    diff_scene(expected, &read_web_scene())
+}
+
+fn resync_web_scene(expected: &AtomeRenderScene) -> AtomeSceneDiff {
+    let diff = diff_web_scene(expected);
+    queue_web_ops(resync_ops(expected, &diff));
+    diff
+}
+
 fn queue_web_ui_events(events: Vec<AtomeUiEvent>) {
     if events.is_empty() {
         return;
@@ -574,6 +646,7 @@ impl Plugin for WebBevyRendererPlugin {
                     apply_pending_web_ops,
                     apply_pending_web_ui_ops,
                     drain_ui_events_for_web,
+                    drain_animation_events_for_web,
                     apply_pending_video_frame_notifications,
                     apply_pending_web_redraw,
                 )
@@ -588,6 +661,7 @@ fn request_initial_web_redraw(world: &mut World) {
 }
 
 fn apply_pending_web_ops(world: &mut World) {
+    apply_pending_web_text_fonts(world);
     let ops = drain_web_ops();
     if ops.is_empty() {
         return;
@@ -611,6 +685,23 @@ fn apply_pending_web_ui_fonts(world: &mut World) {
     }
 }
 
+fn queue_web_text_font(family: String, weight: u16, bytes: Vec<u8>) {
+    WEB_PENDING_TEXT_FONTS.with(|cell| cell.borrow_mut().push((family, weight, bytes)));
+    request_web_redraw();
+}
+
+// Drained ahead of the scene ops so a text node spawned in the same batch
+// as its font registration resolves the registered face.
+fn apply_pending_web_text_fonts(world: &mut World) {
+    let fonts: Vec<(String, u16, Vec<u8>)> =
+        WEB_PENDING_TEXT_FONTS.with(|cell| cell.borrow_mut().drain(..).collect());
+    for (family, weight, bytes) in fonts {
+        if let Err(error) = register_text_font(world, &family, weight, bytes) {
+            world.resource_mut::<AtomeRendererDiagnostics>().last_error = Some(error);
+        }
+    }
+}
+
 fn apply_pending_web_ui_ops(world: &mut World) {
     apply_pending_web_ui_fonts(world);
     let ops = drain_web_ui_ops();
@@ -645,6 +736,14 @@ fn drain_ui_events_for_web(world: &mut World) {
     WEB_DRAINED_UI_EVENTS.with(|cell| cell.borrow_mut().extend(drained));
 }
 
+fn drain_animation_events_for_web(world: &mut World) {
+    let drained = drain_animation_events(world);
+    if drained.is_empty() {
+        return;
+    }
+    WEB_DRAINED_ANIMATION_EVENTS.with(|cell| cell.borrow_mut().extend(drained));
+}
+
 fn apply_browser_window_resize_to_surface(world: &mut World) {
     let has_valid_resize = world
         .resource::<Messages<WindowResized>>()
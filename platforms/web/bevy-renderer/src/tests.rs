@@ -1,7 +1,8 @@
 use atome_bevy_renderer_core::{
-    render_math::atome_camera_projection, AtomeBevyRendererConfig, AtomeEntityTable,
-    AtomeRenderNode, AtomeRenderOp, AtomeRenderScene, AtomeRendererDiagnostics, AtomeStylePatch,
-    AtomeSurfaceBackgroundPatch, AtomeTransformPatch, AtomeTransition,
+    render_math::atome_camera_projection, AtomeBevyRendererConfig, AtomeBlendMode,
+    AtomeEntityTable, AtomeMaskMode, AtomeRenderNode, AtomeRenderOp, AtomeRenderScene,
+    AtomeRendererDiagnostics, AtomeStylePatch, AtomeSurfaceBackgroundPatch, AtomeTransformPatch,
+    AtomeTransition,
 };
 use bevy::prelude::*;
 use bevy::window::{CompositeAlphaMode, RequestRedraw, WindowResized, WindowResolution};
@@ -30,11 +31,18 @@ fn shape_node(id: &str) -> AtomeRenderNode {
         backdrop: None,
         presentation: false,
         procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
         text: None,
         source: None,
         texture_size: None,
         uv_rect: None,
         texture: None,
+        texture_hash: None,
         peaks: None,
         playback_progress: None,
         filters: None,
@@ -375,6 +383,10 @@ fn queued_audio_progress_styles_are_coalesced_per_atome() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
     queue_web_op(AtomeRenderOp::Style(AtomeStylePatch {
         id: "waveform_1".to_string(),
@@ -387,6 +399,10 @@ fn queued_audio_progress_styles_are_coalesced_per_atome() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
     queue_web_op(AtomeRenderOp::Style(AtomeStylePatch {
         id: "waveform_2".to_string(),
@@ -399,6 +415,10 @@ fn queued_audio_progress_styles_are_coalesced_per_atome() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
 
     let ops = drain_web_ops();
@@ -430,6 +450,10 @@ fn queued_opacity_styles_are_merged_per_atome() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
     queue_web_op(AtomeRenderOp::Style(AtomeStylePatch {
         id: "video_1".to_string(),
@@ -442,6 +466,10 @@ fn queued_opacity_styles_are_merged_per_atome() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
 
     let ops = drain_web_ops();
@@ -468,6 +496,10 @@ fn queued_styles_are_not_merged_across_non_style_ops_for_same_atome() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
     queue_web_op(AtomeRenderOp::Despawn("atom_1".to_string()));
     queue_web_op(AtomeRenderOp::Style(AtomeStylePatch {
@@ -481,6 +513,10 @@ fn queued_styles_are_not_merged_across_non_style_ops_for_same_atome() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
 
     let ops = drain_web_ops();
@@ -502,6 +538,10 @@ fn queued_styles_with_transition_are_not_merged() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
     queue_web_op(AtomeRenderOp::Style(AtomeStylePatch {
         id: "atom_t".to_string(),
@@ -514,6 +554,10 @@ fn queued_styles_with_transition_are_not_merged() {
         shadow: None,
         backdrop: None,
         procedural: None,
+        fill: None,
+        blend_mode: None,
+        mask_id: None,
+        mask_mode: None,
     }));
 
     let ops = drain_web_ops();
@@ -10,7 +10,8 @@ fn web_event_loop_start_can_only_be_claimed_once() {
     );
 }
 use atome_bevy_renderer_core::{
-    AtomeRenderNode, AtomeRenderOp, AtomeRenderScene, AtomeRendererDiagnostics, AtomeTransformPatch,
+    AtomeBlendMode, AtomeMaskMode, AtomeRenderNode, AtomeRenderOp, AtomeRenderScene,
+    AtomeRendererDiagnostics, AtomeTransformPatch,
 };
 use bevy::window::RequestRedraw;
 
@@ -34,11 +35,18 @@ fn shape_node(id: &str) -> AtomeRenderNode {
         backdrop: None,
         presentation: false,
         procedural: None,
+        path: None,
+        fill: None,
+        text_style: None,
+        blend_mode: AtomeBlendMode::Normal,
+        mask_id: None,
+        mask_mode: AtomeMaskMode::Alpha,
         text: None,
         source: None,
         texture_size: None,
         uv_rect: None,
         texture: None,
+        texture_hash: None,
         peaks: None,
         playback_progress: None,
         filters: None,